@@ -141,6 +141,7 @@ pub mod align_kind {
 pub struct Aligner {
     pub additions: HashMap<Location, u32>,
     pub aligns: [Align; 11],
+    disable_count: usize,
 }
 
 impl Aligner {
@@ -204,6 +205,21 @@ impl Aligner {
     pub fn any_enabled(&self) -> bool {
         self.aligns.iter().any(|x| x.enable)
     }
+
+    pub fn start_item(&mut self, kind: usize) {
+        if self.disable_count == 0 {
+            self.aligns[kind].start_item();
+        }
+    }
+
+    /// Items started between `disable` and `enable` are not aligned (e.g. lists placed in a single line)
+    pub fn disable(&mut self) {
+        self.disable_count += 1;
+    }
+
+    pub fn enable(&mut self) {
+        self.disable_count = self.disable_count.saturating_sub(1);
+    }
 }
 
 #[derive(Clone, Debug, Default)]
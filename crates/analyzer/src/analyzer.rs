@@ -1,6 +1,7 @@
 use crate::analyzer_error::AnalyzerError;
 use crate::attribute_table;
 use crate::conv::{Context, Conv};
+use crate::doc_coverage;
 use crate::handlers::*;
 use crate::ir::{Ir, IrResult};
 use crate::msb_table;
@@ -73,6 +74,7 @@ impl Analyzer {
                 }
             }
         }
+        doc_coverage::set_missing_docs(&metadata.project.name, metadata.lint.missing_docs);
         Analyzer {
             project_name: metadata.project.name.clone(),
             build_opt: metadata.build.clone(),
@@ -151,6 +153,7 @@ impl Analyzer {
 
         ret.append(&mut symbol_table::check_unused_variable());
         ret.append(&mut symbol_table::check_wavedrom());
+        ret.append(&mut doc_coverage::check_missing_docs());
 
         ret
     }
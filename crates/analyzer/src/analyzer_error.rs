@@ -864,6 +864,10 @@ pub enum AnalyzerError {
         token_source: TokenSource,
     },
 
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    MissingDocs(Box<MissingDocs>),
+
     #[diagnostic(
         severity(Error),
         code(missing_if_reset),
@@ -1613,6 +1617,7 @@ impl AnalyzerError {
             AnalyzerError::MissingClockDomain { token_source, .. } => *token_source,
             AnalyzerError::MissingClockSignal { token_source, .. } => *token_source,
             AnalyzerError::MissingDefaultArgument { token_source, .. } => *token_source,
+            AnalyzerError::MissingDocs(x) => x.token_source,
             AnalyzerError::MissingIfReset { token_source, .. } => *token_source,
             AnalyzerError::MissingPort { token_source, .. } => *token_source,
             AnalyzerError::MissingResetSignal { token_source, .. } => *token_source,
@@ -2151,6 +2156,18 @@ impl AnalyzerError {
             token_source: token.source(),
         }
     }
+    /// `deny` reports it as error instead of warning
+    pub fn missing_docs(kind: &str, identifier: &str, deny: bool, token: &TokenRange) -> Self {
+        AnalyzerError::MissingDocs(Box::new(MissingDocs {
+            kind: kind.into(),
+            identifier: identifier.into(),
+            deny,
+            input: source(token),
+            error_location: token.into(),
+            token_source: token.source(),
+        }))
+    }
+
     pub fn missing_if_reset(token: &TokenRange) -> Self {
         AnalyzerError::MissingIfReset {
             input: source(token),
@@ -2632,6 +2649,9 @@ pub enum InvalidWavedromKind {
     JavaScriptInTestBlock,
     PipeSeparatorInTest { signal: String },
     NoMatchingPorts,
+    InvalidTiming { signal: String },
+    InvalidData { signal: String, data: String },
+    MissingData { signal: String },
 }
 
 impl fmt::Display for InvalidWavedromKind {
@@ -2662,6 +2682,24 @@ impl fmt::Display for InvalidWavedromKind {
             InvalidWavedromKind::NoMatchingPorts => {
                 write!(f, "WaveDrom test has no signals matching module ports")
             }
+            InvalidWavedromKind::InvalidTiming { signal } => {
+                write!(
+                    f,
+                    "WaveDrom signal '{signal}' has invalid timing ('period' should be a positive number, 'phase' should be a number)"
+                )
+            }
+            InvalidWavedromKind::InvalidData { signal, data } => {
+                write!(
+                    f,
+                    "WaveDrom signal '{signal}' has data '{data}' which is not a value"
+                )
+            }
+            InvalidWavedromKind::MissingData { signal } => {
+                write!(
+                    f,
+                    "WaveDrom signal '{signal}' has fewer 'data' items than data characters in 'wave'"
+                )
+            }
         }
     }
 }
@@ -2798,6 +2836,56 @@ impl fmt::Display for DuplicatedIdentifierKind {
     }
 }
 
+/// Severity of `missing_docs` lint depends on its lint level,
+/// so `Diagnostic` is implemented manually instead of derived.
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("missing documentation for {kind} \"{identifier}\"")]
+pub struct MissingDocs {
+    pub kind: String,
+    pub identifier: String,
+    pub deny: bool,
+    input: MultiSources,
+    error_location: SourceSpan,
+    token_source: TokenSource,
+}
+
+impl Diagnostic for MissingDocs {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new("missing_docs"))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        if self.deny {
+            Some(Severity::Error)
+        } else {
+            Some(Severity::Warning)
+        }
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new("add doc comment"))
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(
+            "https://doc.veryl-lang.org/book/07_appendix/02_semantic_error.html#missing_docs",
+        ))
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.input)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(
+            miette::LabeledSpan::new_with_span(
+                Some("Error location".to_string()),
+                self.error_location,
+            ),
+        )))
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum UnevaluableValueKind {
     CaseCondition,
@@ -1,3 +1,4 @@
+use crate::wavedrom::{CycleRange, parse_cycle_range};
 use std::cell::RefCell;
 use std::fmt;
 use strum::IntoEnumIterator;
@@ -15,12 +16,14 @@ pub enum Attribute {
     Allow(AllowItem),
     EnumEncoding(EnumEncodingItem),
     EnumMemberPrefix(StrId),
-    Test(Token, Option<StrId>),
+    Test(Token, Option<StrId>, TestLimit),
     CondType(CondTypeItem),
     Align(Vec<AlignItem>),
     Format(Vec<FormatItem>),
     Expand(Vec<ExpandItem>),
     Ignore,
+    Wavedrom(Vec<StrId>, Option<CycleRange>),
+    Seed(StrId),
 }
 
 impl Attribute {
@@ -70,7 +73,7 @@ impl fmt::Display for Attribute {
             Attribute::Allow(x) => format!("allow({x})"),
             Attribute::EnumEncoding(x) => format!("enum_encoding({x})"),
             Attribute::EnumMemberPrefix(x) => format!("enum_member_prefix({x})"),
-            Attribute::Test(x, _) => format!("test({})", x.text),
+            Attribute::Test(x, _, _) => format!("test({})", x.text),
             Attribute::CondType(x) => format!("cond_type({x})"),
             Attribute::Align(x) => {
                 let mut arg = String::new();
@@ -94,6 +97,14 @@ impl fmt::Display for Attribute {
                 format!("expand({arg})")
             }
             Attribute::Ignore => String::from("ignore"),
+            Attribute::Wavedrom(x, _) => {
+                let mut arg = String::new();
+                for x in x {
+                    arg.push_str(&format!("{x}, "));
+                }
+                format!("wavedrom({arg})")
+            }
+            Attribute::Seed(x) => format!("seed({x})"),
         };
         text.fmt(f)
     }
@@ -168,6 +179,7 @@ struct Pattern {
     pub r#else: StrId,
     pub sv: StrId,
     pub allow: StrId,
+    pub missing_docs: StrId,
     pub missing_port: StrId,
     pub missing_reset_statement: StrId,
     pub unused_variable: StrId,
@@ -192,6 +204,8 @@ struct Pattern {
     pub expand: StrId,
     pub modport: StrId,
     pub ignore: StrId,
+    pub wavedrom: StrId,
+    pub seed: StrId,
 }
 
 impl Pattern {
@@ -203,6 +217,7 @@ impl Pattern {
             r#else: resource_table::insert_str("else"),
             sv: resource_table::insert_str("sv"),
             allow: resource_table::insert_str("allow"),
+            missing_docs: resource_table::insert_str("missing_docs"),
             missing_port: resource_table::insert_str("missing_port"),
             missing_reset_statement: resource_table::insert_str("missing_reset_statement"),
             unused_variable: resource_table::insert_str("unused_variable"),
@@ -227,6 +242,8 @@ impl Pattern {
             expand: resource_table::insert_str("expand"),
             modport: resource_table::insert_str("modport"),
             ignore: resource_table::insert_str("ignore"),
+            wavedrom: resource_table::insert_str("wavedrom"),
+            seed: resource_table::insert_str("seed"),
         }
     }
 }
@@ -278,6 +295,7 @@ impl TryFrom<&veryl_parser::veryl_grammar_trait::Attribute> for Attribute {
 
                 if let Some(arg) = arg {
                     match arg.text {
+                        x if x == pat.missing_docs => Ok(Attribute::Allow(AllowItem::MissingDocs)),
                         x if x == pat.missing_port => Ok(Attribute::Allow(AllowItem::MissingPort)),
                         x if x == pat.missing_reset_statement => {
                             Ok(Attribute::Allow(AllowItem::MissingResetStatement))
@@ -331,14 +349,36 @@ impl TryFrom<&veryl_parser::veryl_grammar_trait::Attribute> for Attribute {
             x if x == pat.test => {
                 let arg = get_arg_ident(&value.attribute_opt, 0);
                 let top = get_arg_ident(&value.attribute_opt, 1);
+                let num_args = value.attribute_opt.as_ref().map_or(0, |x| {
+                    let args: Vec<_> = x.attribute_list.as_ref().into();
+                    args.len()
+                });
+                let limit = get_arg_string(&value.attribute_opt, 1 + top.is_some() as usize);
+
+                let err = AttributeError::MismatchArgs(
+                    "test identifier, optional top module identifier and optional limit string (e.g. \"timeout=10, max_cycles=1000\")"
+                        .to_string(),
+                );
+
+                let Some(arg) = arg else {
+                    return Err(err);
+                };
+
+                if num_args != 1 + top.is_some() as usize + limit.is_some() as usize {
+                    return Err(err);
+                }
 
-                if let Some(arg) = arg {
-                    Ok(Attribute::Test(arg, top.map(|x| x.text)))
+                let limit = if let Some(limit) = limit {
+                    let text = limit.text.to_string();
+                    match parse_test_limit(text.trim_matches('"')) {
+                        Some(limit) => limit,
+                        None => return Err(err),
+                    }
                 } else {
-                    Err(AttributeError::MismatchArgs(
-                        "single identifier".to_string(),
-                    ))
-                }
+                    TestLimit::default()
+                };
+
+                Ok(Attribute::Test(arg, top.map(|x| x.text), limit))
             }
             x if x == pat.cond_type => {
                 let arg = get_arg_ident(&value.attribute_opt, 0);
@@ -435,13 +475,88 @@ impl TryFrom<&veryl_parser::veryl_grammar_trait::Attribute> for Attribute {
                     Ok(Attribute::Ignore)
                 }
             }
+            x if x == pat.wavedrom => {
+                let signals: Vec<_> = get_args_ident(&value.attribute_opt)
+                    .iter()
+                    .map(|x| x.text)
+                    .collect();
+                let num_args = value.attribute_opt.as_ref().map_or(0, |x| {
+                    let args: Vec<_> = x.attribute_list.as_ref().into();
+                    args.len()
+                });
+                let range = get_arg_string(&value.attribute_opt, signals.len());
+
+                let err = AttributeError::MismatchArgs(
+                    "signal identifiers and optional cycle range string (e.g. \"0..16\")"
+                        .to_string(),
+                );
+
+                if num_args != signals.len() + range.is_some() as usize {
+                    return Err(err);
+                }
+
+                if let Some(range) = range {
+                    let text = range.text.to_string();
+                    match parse_cycle_range(text.trim_matches('"')) {
+                        Some(range) => Ok(Attribute::Wavedrom(signals, Some(range))),
+                        None => Err(err),
+                    }
+                } else {
+                    Ok(Attribute::Wavedrom(signals, None))
+                }
+            }
+            x if x == pat.seed => {
+                let arg = get_arg_ident(&value.attribute_opt, 0);
+                let num_args = value.attribute_opt.as_ref().map_or(0, |x| {
+                    let args: Vec<_> = x.attribute_list.as_ref().into();
+                    args.len()
+                });
+
+                match arg {
+                    Some(arg) if num_args == 1 => Ok(Attribute::Seed(arg.text)),
+                    _ => Err(AttributeError::MismatchArgs(
+                        "single variable identifier".to_string(),
+                    )),
+                }
+            }
             _ => Err(AttributeError::UnknownAttribute),
         })
     }
 }
 
+/// Execution limits of a native test given by `#[test(name, "...")]`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TestLimit {
+    /// Wall-clock timeout in seconds
+    pub timeout: Option<u64>,
+    /// Maximum number of cycles of each clock, excluding reset
+    pub max_cycles: Option<u64>,
+    /// Maximum simulation time
+    pub max_time: Option<u64>,
+}
+
+/// Parse comma-separated `key=value` limits (e.g. "timeout=10, max_cycles=1000").
+pub fn parse_test_limit(text: &str) -> Option<TestLimit> {
+    let mut ret = TestLimit::default();
+    for item in text.split(',') {
+        let (key, value) = item.split_once('=')?;
+        let value: u64 = value.trim().parse().ok()?;
+        let target = match key.trim() {
+            "timeout" => &mut ret.timeout,
+            "max_cycles" => &mut ret.max_cycles,
+            "max_time" => &mut ret.max_time,
+            _ => return None,
+        };
+        if target.replace(value).is_some() {
+            return None;
+        }
+    }
+    Some(ret)
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter)]
 pub enum AllowItem {
+    MissingDocs,
     MissingPort,
     MissingResetStatement,
     UnusedVariable,
@@ -464,6 +579,7 @@ impl AllowItem {
 impl fmt::Display for AllowItem {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let text = match self {
+            AllowItem::MissingDocs => "missing_docs",
             AllowItem::MissingPort => "missing_port",
             AllowItem::MissingResetStatement => "missing_reset_statement",
             AllowItem::UnusedVariable => "unused_variable",
@@ -8,6 +8,7 @@ use crate::ir::{self, IrResult, VarPath};
 use crate::symbol::SymbolKind;
 use crate::symbol_table;
 use crate::{HashMap, ir_error};
+use std::sync::Arc;
 use veryl_parser::token_range::TokenRange;
 use veryl_parser::veryl_grammar_trait::*;
 
@@ -39,7 +40,7 @@ impl Conv<&Veryl> for ir::Ir {
                                         component.suppress_unassigned = true;
                                     }
 
-                                    components.push(ir::Component::Module(component));
+                                    components.push(ir::Component::Module(Arc::new(component)));
                                 }
                             }
                             PublicDescriptionItem::InterfaceDeclaration(x) => {
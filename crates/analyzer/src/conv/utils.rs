@@ -18,6 +18,7 @@ use crate::symbol_path::{GenericSymbolPath, GenericSymbolPathKind};
 use crate::symbol_table::{self, ResolveResult};
 use crate::value::Value;
 use crate::{HashMap, ir_error};
+use std::sync::Arc;
 use veryl_parser::resource_table::{self, StrId};
 use veryl_parser::token_range::TokenRange;
 use veryl_parser::veryl_grammar_trait::*;
@@ -2063,7 +2064,7 @@ pub fn get_component(
                             component.declarations.clear();
                         }
 
-                        let component = ir::Component::Module(component);
+                        let component = ir::Component::Module(Arc::new(component));
                         c.set_instance_history(sig, component.clone());
                         c.pop_instance_history();
                         Ok(component)
@@ -2110,7 +2111,7 @@ pub fn get_component(
                             component.declarations.clear();
                         }
 
-                        let component = ir::Component::Module(component);
+                        let component = ir::Component::Module(Arc::new(component));
                         c.set_instance_history(sig, component.clone());
                         c.pop_instance_history();
                         Ok(component)
@@ -2820,6 +2821,7 @@ pub fn tb_method_call(
     Ok(Some(ir::Statement::TbMethodCall(TbMethodCall {
         inst: inst_name,
         method,
+        token,
     })))
 }
 
@@ -0,0 +1,193 @@
+use crate::AnalyzerError;
+use crate::attribute::{AllowItem, Attribute};
+use crate::attribute_table;
+use crate::symbol::{Parameter, Port, Symbol, SymbolId, SymbolKind};
+use crate::symbol_table;
+use std::cell::RefCell;
+use std::fmt;
+use veryl_metadata::LintLevel;
+use veryl_parser::resource_table::PathId;
+use veryl_parser::veryl_token::{Token, TokenSource};
+
+/// Kind of items counted in documentation coverage
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DocItemKind {
+    Module,
+    Interface,
+    Package,
+    Port,
+    Parameter,
+    Function,
+    PackageMember,
+}
+
+impl fmt::Display for DocItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            DocItemKind::Module => "module",
+            DocItemKind::Interface => "interface",
+            DocItemKind::Package => "package",
+            DocItemKind::Port => "port",
+            DocItemKind::Parameter => "parameter",
+            DocItemKind::Function => "function",
+            DocItemKind::PackageMember => "package member",
+        };
+        text.fmt(f)
+    }
+}
+
+/// Public item which should be documented
+#[derive(Clone, Debug)]
+pub struct DocItem {
+    pub kind: DocItemKind,
+    /// Name qualified by the enclosing module, interface or package
+    pub name: String,
+    pub token: Token,
+    pub documented: bool,
+}
+
+impl DocItem {
+    fn new(kind: DocItemKind, name: String, symbol: &Symbol) -> Self {
+        Self {
+            kind,
+            name,
+            token: symbol.token,
+            documented: !symbol.doc_comment.is_empty(),
+        }
+    }
+
+    pub fn path(&self) -> Option<PathId> {
+        if let TokenSource::File { path, .. } = self.token.source {
+            Some(path)
+        } else {
+            None
+        }
+    }
+}
+
+struct Collector {
+    items: Vec<DocItem>,
+}
+
+impl Collector {
+    fn push(&mut self, kind: DocItemKind, parent: &str, symbol: &Symbol) {
+        let name = format!("{parent}::{}", symbol.token);
+        self.items.push(DocItem::new(kind, name, symbol));
+    }
+
+    fn parameters(&mut self, parent: &str, parameters: &[Parameter]) {
+        for x in parameters {
+            if let Some(symbol) = symbol_table::get(x.symbol) {
+                self.push(DocItemKind::Parameter, parent, &symbol);
+            }
+        }
+    }
+
+    fn ports(&mut self, parent: &str, ports: &[Port]) {
+        for x in ports {
+            self.push(DocItemKind::Port, parent, &x.symbol());
+        }
+    }
+
+    fn members(&mut self, parent: &str, members: &[SymbolId], is_package: bool) {
+        for x in members {
+            let Some(symbol) = symbol_table::get(*x) else {
+                continue;
+            };
+            let kind = match symbol.kind {
+                SymbolKind::Function(_) | SymbolKind::ProtoFunction(_) => DocItemKind::Function,
+                SymbolKind::Parameter(_)
+                | SymbolKind::ProtoConst(_)
+                | SymbolKind::TypeDef(_)
+                | SymbolKind::ProtoTypeDef(_)
+                | SymbolKind::Struct(_)
+                | SymbolKind::Union(_)
+                | SymbolKind::Enum(_)
+                    if is_package =>
+                {
+                    DocItemKind::PackageMember
+                }
+                _ => continue,
+            };
+            self.push(kind, parent, &symbol);
+        }
+    }
+
+    fn component(&mut self, symbol: &Symbol) {
+        let name = symbol.token.to_string();
+        let kind = match &symbol.kind {
+            SymbolKind::Module(_) | SymbolKind::ProtoModule(_) => DocItemKind::Module,
+            SymbolKind::Interface(_) | SymbolKind::ProtoInterface(_) => DocItemKind::Interface,
+            SymbolKind::Package(_) | SymbolKind::ProtoPackage(_) => DocItemKind::Package,
+            _ => return,
+        };
+        self.items.push(DocItem::new(kind, name.clone(), symbol));
+
+        match &symbol.kind {
+            SymbolKind::Module(x) => {
+                self.parameters(&name, &x.parameters);
+                self.ports(&name, &x.ports);
+            }
+            SymbolKind::ProtoModule(x) => {
+                self.parameters(&name, &x.parameters);
+                self.ports(&name, &x.ports);
+            }
+            SymbolKind::Interface(x) => {
+                self.parameters(&name, &x.parameters);
+                self.members(&name, &x.members, false);
+            }
+            SymbolKind::ProtoInterface(x) => {
+                self.parameters(&name, &x.parameters);
+                self.members(&name, &x.members, false);
+            }
+            SymbolKind::Package(x) => self.members(&name, &x.members, true),
+            SymbolKind::ProtoPackage(x) => self.members(&name, &x.members, true),
+            _ => (),
+        }
+    }
+}
+
+/// Public modules, interfaces and packages of `project`, and their ports, parameters,
+/// functions and package members in source order.
+pub fn collect_doc_items(project: &str) -> Vec<DocItem> {
+    let mut symbols: Vec<_> = symbol_table::get_all()
+        .into_iter()
+        .filter(|x| {
+            x.public && x.namespace.depth() == 1 && x.namespace.paths[0].to_string() == project
+        })
+        .collect();
+    symbols.sort_by_key(|x| (x.token.source.to_string(), x.token.line, x.token.column));
+
+    let mut collector = Collector { items: Vec::new() };
+    for symbol in &symbols {
+        collector.component(symbol);
+    }
+    collector.items
+}
+
+// Project name and level of `missing_docs` lint
+thread_local!(static MISSING_DOCS: RefCell<Option<(String, LintLevel)>> = const { RefCell::new(None) });
+
+pub fn set_missing_docs(project: &str, level: LintLevel) {
+    MISSING_DOCS.with(|f| *f.borrow_mut() = Some((project.to_string(), level)));
+}
+
+/// Check public items of the project set by `set_missing_docs` for `missing_docs` lint
+pub fn check_missing_docs() -> Vec<AnalyzerError> {
+    let Some((project, level)) = MISSING_DOCS.with(|f| f.borrow().clone()) else {
+        return Vec::new();
+    };
+    if level == LintLevel::Allow {
+        return Vec::new();
+    }
+
+    let deny = level == LintLevel::Deny;
+    collect_doc_items(&project)
+        .into_iter()
+        .filter(|x| {
+            !x.documented
+                && !attribute_table::contains(&x.token, Attribute::Allow(AllowItem::MissingDocs))
+        })
+        .map(|x| AnalyzerError::missing_docs(&x.kind.to_string(), &x.name, deny, &x.token.into()))
+        .collect()
+}
@@ -23,7 +23,7 @@ use crate::symbol::{
     ProtoInterfaceProperty, ProtoModuleProperty, ProtoPackageProperty, ProtoTypeDefProperty,
     StructMemberProperty, StructProperty, Symbol, SymbolId, SymbolKind, TestProperty, TestType,
     TypeDefProperty, TypeKind, TypeModifierKind, UnionMemberProperty, UnionProperty,
-    VariableProperty,
+    VariableProperty, WavedromProperty,
 };
 use crate::symbol_path::{GenericSymbolPath, GenericSymbolPathNamespace, SymbolPathNamespace};
 use crate::symbol_table;
@@ -1993,15 +1993,26 @@ impl VerylGrammarTrait for CreateSymbolTable {
                     let attrs = attribute_table::get(&arg.module.module_token.token);
                     let mut test_attr = None;
                     let mut ignored = false;
+                    let mut wavedrom = None;
+                    let mut seed = None;
                     for attr in &attrs {
-                        if let Attr::Test(_, top) = attr {
-                            test_attr = Some(*top);
+                        if let Attr::Test(_, top, limit) = attr {
+                            test_attr = Some((*top, *limit));
                         }
                         if matches!(attr, Attr::Ignore) {
                             ignored = true;
                         }
+                        if let Attr::Wavedrom(signals, cycles) = attr {
+                            wavedrom = Some(WavedromProperty {
+                                signals: signals.clone(),
+                                cycles: *cycles,
+                            });
+                        }
+                        if let Attr::Seed(x) = attr {
+                            seed = Some(*x);
+                        }
                     }
-                    if let Some(top) = test_attr {
+                    if let Some((top, limit)) = test_attr {
                         let path = if let TokenSource::File { path, .. } =
                             arg.identifier.identifier_token.token.source
                         {
@@ -2015,6 +2026,9 @@ impl VerylGrammarTrait for CreateSymbolTable {
                             path,
                             top,
                             ignored,
+                            wavedrom,
+                            limit,
+                            seed,
                         });
                     }
                     None
@@ -2515,8 +2529,8 @@ impl VerylGrammarTrait for CreateSymbolTable {
 
                 let attrs = attribute_table::get(&arg.embed.embed_token.token);
                 for attr in &attrs {
-                    if let Attr::Test(x, y) = attr {
-                        test_attr = Some((*x, *y));
+                    if let Attr::Test(x, y, z) = attr {
+                        test_attr = Some((*x, *y, *z));
                     }
                     if matches!(attr, Attr::Ignore) {
                         ignored = true;
@@ -2530,7 +2544,8 @@ impl VerylGrammarTrait for CreateSymbolTable {
                     _ => None,
                 };
 
-                let (token, kind) = if let (Some((token, top)), Some(r#type)) = (test_attr, r#type)
+                let (token, kind) = if let (Some((token, top, limit)), Some(r#type)) =
+                    (test_attr, r#type)
                 {
                     let content_source = content.triple_l_brace.triple_l_brace_token.token.source;
                     let path = if let TokenSource::File { path, .. } = content_source {
@@ -2551,6 +2566,9 @@ impl VerylGrammarTrait for CreateSymbolTable {
                         path,
                         top,
                         ignored,
+                        wavedrom: None,
+                        limit,
+                        seed: None,
                     };
                     (token, SymbolKind::Test(property))
                 } else {
@@ -2578,8 +2596,8 @@ impl VerylGrammarTrait for CreateSymbolTable {
 
             let attrs = attribute_table::get(&arg.include.include_token.token);
             for attr in &attrs {
-                if let Attr::Test(x, y) = attr {
-                    test_attr = Some((*x, *y));
+                if let Attr::Test(x, y, z) = attr {
+                    test_attr = Some((*x, *y, *z));
                 }
                 if matches!(attr, Attr::Ignore) {
                     ignored = true;
@@ -2593,7 +2611,7 @@ impl VerylGrammarTrait for CreateSymbolTable {
                 _ => None,
             };
 
-            if let (Some((token, top)), Some(r#type)) = (test_attr, r#type) {
+            if let (Some((token, top, limit)), Some(r#type)) = (test_attr, r#type) {
                 let path = if let TokenSource::File { path, .. } = content.source {
                     path
                 } else {
@@ -2612,6 +2630,9 @@ impl VerylGrammarTrait for CreateSymbolTable {
                     path,
                     top,
                     ignored,
+                    wavedrom: None,
+                    limit,
+                    seed: None,
                 };
                 self.insert_symbol(&token, SymbolKind::Test(property), false);
             }
@@ -1,6 +1,7 @@
 use crate::conv::Context;
 use crate::ir::{AssignDestination, Interface, Module};
 use std::fmt;
+use std::sync::Arc;
 use veryl_parser::resource_table::StrId;
 use veryl_parser::token_range::TokenRange;
 
@@ -51,7 +52,8 @@ pub type IrResult<T> = Result<T, Box<IrError>>;
 
 #[derive(Clone)]
 pub enum Component {
-    Module(Module),
+    /// Shared because modules are cloned into instance histories and simulator IRs
+    Module(Arc<Module>),
     Interface(Interface),
     SystemVerilog(SystemVerilog),
 }
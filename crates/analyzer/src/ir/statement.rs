@@ -67,6 +67,7 @@ pub enum ForRange {
 pub struct TbMethodCall {
     pub inst: StrId,
     pub method: TbMethod,
+    pub token: TokenRange,
 }
 
 #[derive(Clone)]
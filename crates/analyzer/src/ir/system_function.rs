@@ -57,6 +57,9 @@ pub enum SystemFunctionKind {
     Write(Vec<Input>),
     Assert(Input, Option<Input>),
     Finish,
+    Force(Input, Input),
+    Release(Input),
+    Deposit(Input, Input),
     Signed(Input),
     Unsigned(Input),
 }
@@ -231,6 +234,29 @@ impl SystemFunctionCall {
                     comptime,
                 })
             }
+            "$force" | "$deposit" => {
+                if args.len() != 2 {
+                    return Err(ir_error!(token));
+                }
+                let arg0 = create_input(context, name, None, args.remove(0));
+                let arg1 = create_input(context, name, None, args.remove(0));
+                let kind = if name.to_string() == "$force" {
+                    SystemFunctionKind::Force(arg0, arg1)
+                } else {
+                    SystemFunctionKind::Deposit(arg0, arg1)
+                };
+                Ok(SystemFunctionCall { kind, comptime })
+            }
+            "$release" => {
+                if args.len() != 1 {
+                    return Err(ir_error!(token));
+                }
+                let arg0 = create_input(context, name, None, args.remove(0));
+                Ok(SystemFunctionCall {
+                    kind: SystemFunctionKind::Release(arg0),
+                    comptime,
+                })
+            }
             "$signed" => {
                 if args.len() != 1 {
                     return Err(ir_error!(token));
@@ -309,6 +335,9 @@ impl SystemFunctionCall {
             SystemFunctionKind::Write(_) => None,
             SystemFunctionKind::Assert(_, _) => None,
             SystemFunctionKind::Finish => None,
+            SystemFunctionKind::Force(_, _) => None,
+            SystemFunctionKind::Release(_) => None,
+            SystemFunctionKind::Deposit(_, _) => None,
             SystemFunctionKind::Signed(x) | SystemFunctionKind::Unsigned(x) => {
                 x.0.eval_value(context)
             }
@@ -333,6 +362,9 @@ impl SystemFunctionCall {
             SystemFunctionKind::Write(_) => self.comptime.clone(),
             SystemFunctionKind::Assert(_, _) => self.comptime.clone(),
             SystemFunctionKind::Finish => self.comptime.clone(),
+            SystemFunctionKind::Force(_, _) => self.comptime.clone(),
+            SystemFunctionKind::Release(_) => self.comptime.clone(),
+            SystemFunctionKind::Deposit(_, _) => self.comptime.clone(),
             SystemFunctionKind::Signed(_) | SystemFunctionKind::Unsigned(_) => {
                 let mut ret = self.comptime.clone();
                 if let Some(x) = value {
@@ -381,6 +413,9 @@ impl fmt::Display for SystemFunctionCall {
                 }
             }
             SystemFunctionKind::Finish => "$finish()".fmt(f),
+            SystemFunctionKind::Force(x, y) => format!("$force({x}, {y})").fmt(f),
+            SystemFunctionKind::Release(x) => format!("$release({x})").fmt(f),
+            SystemFunctionKind::Deposit(x, y) => format!("$deposit({x}, {y})").fmt(f),
             SystemFunctionKind::Signed(x) => format!("$signed({x})").fmt(f),
             SystemFunctionKind::Unsigned(x) => format!("$unsigned({x})").fmt(f),
         }
@@ -5,6 +5,7 @@ pub mod attribute_table;
 pub mod connect_operation_table;
 pub mod conv;
 pub mod definition_table;
+pub mod doc_coverage;
 pub mod handlers;
 pub mod ir;
 pub mod literal;
@@ -103,6 +103,27 @@ pub fn sv_system_functions() -> Vec<SvSystemFunction> {
             // condition, optional message
             &[("condition", Direction::Input)],
         ),
+        SvSystemFunction::new(
+            "$force",
+            // hierarchical path, value
+            &[
+                ("variable", Direction::Input),
+                ("expression", Direction::Input),
+            ],
+        ),
+        SvSystemFunction::new(
+            "$release",
+            // hierarchical path
+            &[("variable", Direction::Input)],
+        ),
+        SvSystemFunction::new(
+            "$deposit",
+            // hierarchical path, value
+            &[
+                ("variable", Direction::Input),
+                ("expression", Direction::Input),
+            ],
+        ),
         // Simulation time system functions
         SvSystemFunction::new("$time", &[]),
         SvSystemFunction::new("$stime", &[]),
@@ -1,5 +1,5 @@
 use crate::HashMap;
-use crate::attribute::EnumEncodingItem;
+use crate::attribute::{EnumEncodingItem, TestLimit};
 use crate::conv::Context;
 use crate::conv::utils::{TypePosition, eval_generic_expr, eval_size, eval_type};
 use crate::definition_table::DefinitionId;
@@ -11,6 +11,7 @@ use crate::namespace_table;
 use crate::symbol_path::{GenericSymbolPath, GenericSymbolPathKind, SymbolPath};
 use crate::symbol_table::{self, Import};
 use crate::value::Value;
+use crate::wavedrom::CycleRange;
 use std::cell::RefCell;
 use std::fmt;
 use std::hash::{DefaultHasher, Hash, Hasher};
@@ -2679,6 +2680,16 @@ pub struct TestProperty {
     pub path: PathId,
     pub top: Option<StrId>,
     pub ignored: bool,
+    pub wavedrom: Option<WavedromProperty>,
+    pub limit: TestLimit,
+    /// Variable receiving the seed of each lane given by `#[seed(var)]`
+    pub seed: Option<StrId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WavedromProperty {
+    pub signals: Vec<StrId>,
+    pub cycles: Option<CycleRange>,
 }
 
 #[derive(Debug, Clone)]
@@ -2,7 +2,7 @@ use crate::conv::Context;
 use crate::ir::Ir;
 use crate::{Analyzer, AnalyzerError, attribute_table, symbol_table};
 use std::thread;
-use veryl_metadata::Metadata;
+use veryl_metadata::{Lint, LintLevel, Metadata};
 use veryl_parser::Parser;
 
 #[track_caller]
@@ -24,6 +24,26 @@ fn analyze(code: &str) -> Vec<AnalyzerError> {
     errors
 }
 
+#[track_caller]
+fn analyze_with_lint(code: &str, lint: Lint) -> Vec<AnalyzerError> {
+    symbol_table::clear();
+    attribute_table::clear();
+
+    let mut metadata = Metadata::create_default("prj").unwrap();
+    metadata.lint = lint;
+    let parser = Parser::parse(&code, &"").unwrap();
+    let analyzer = Analyzer::new(&metadata);
+    let mut context = Context::default();
+
+    let mut errors = vec![];
+    errors.append(&mut analyzer.analyze_pass1(&"prj", &parser.veryl));
+    errors.append(&mut Analyzer::analyze_post_pass1());
+    errors.append(&mut analyzer.analyze_pass2(&"prj", &parser.veryl, &mut context, None));
+    errors.append(&mut Analyzer::analyze_post_pass2());
+    dbg!(&errors);
+    errors
+}
+
 #[track_caller]
 fn analyze_with_ir(code: &str) -> Vec<AnalyzerError> {
     symbol_table::clear();
@@ -2022,6 +2042,48 @@ fn mismatch_attribute_args() {
         AnalyzerError::MismatchAttributeArgs { .. }
     ));
 
+    let code = r#"
+    #[test(test_a, "max_cycle=10")]
+    module test_a {
+        var a: logic;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::MismatchAttributeArgs { .. }
+    ));
+
+    let code = r#"
+    #[test(test_a)]
+    #[wavedrom(a, "8..4")]
+    module test_a {
+        var a: logic;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::MismatchAttributeArgs { .. }
+    ));
+
+    let code = r#"
+    #[test(test_a)]
+    #[seed(a, b)]
+    module test_a {
+        var a: logic;
+        var b: logic;
+    }
+    "#;
+
+    let errors = analyze(code);
+    assert!(matches!(
+        errors[0],
+        AnalyzerError::MismatchAttributeArgs { .. }
+    ));
+
     let code = r#"
     module ModuleA {
         #[allow(dummy_name)]
@@ -6859,6 +6921,75 @@ fn unused_variable() {
     assert!(errors.is_empty());
 }
 
+#[test]
+fn missing_docs() {
+    let code = r#"
+    /// Documented module
+    pub module ModuleA #(
+        /// Width
+        param W: u32 = 8,
+    ) (
+        /// Input
+        i_a: input logic<W>,
+        o_b: output logic<W>,
+    ) {
+        assign o_b = i_a;
+    }
+
+    pub package PackageA {
+        /// Constant
+        const C: u32 = 1;
+        function f () -> u32 {
+            return C;
+        }
+    }
+
+    module ModuleB {}
+    "#;
+
+    let errors = analyze(code);
+    assert!(errors.is_empty());
+
+    let lint = Lint {
+        missing_docs: LintLevel::Warn,
+        ..Default::default()
+    };
+    let errors = analyze_with_lint(code, lint);
+    let missing: Vec<_> = errors
+        .iter()
+        .filter_map(|x| match x {
+            AnalyzerError::MissingDocs(x) => Some(x.identifier.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(missing, ["ModuleA::o_b", "PackageA", "PackageA::f"]);
+    assert!(errors.iter().all(|x| !x.is_error()));
+
+    let lint = Lint {
+        missing_docs: LintLevel::Deny,
+        ..Default::default()
+    };
+    let errors = analyze_with_lint(code, lint.clone());
+    assert!(matches!(errors[0], AnalyzerError::MissingDocs(_)));
+    assert!(errors[0].is_error());
+
+    let code = r#"
+    #[allow(missing_docs)]
+    pub module ModuleA (
+        i_a: input logic,
+    ) {}
+
+    /// Documented package
+    pub package PackageA {
+        #[allow(missing_docs)]
+        const C: u32 = 1;
+    }
+    "#;
+
+    let errors = analyze_with_lint(code, lint);
+    assert!(errors.is_empty());
+}
+
 #[test]
 fn unused_return() {
     let code = r#"
@@ -484,6 +484,28 @@ impl TypeDag {
         ret
     }
 
+    fn dependencies(&self, id: SymbolId) -> Vec<Symbol> {
+        let mut ret = Vec::new();
+        let Some(node) = self.nodes.get_by_left(&id) else {
+            return ret;
+        };
+
+        let mut graph = self.dag.graph().clone();
+
+        // Reverse edge to traverse nodes which are called from the given node
+        graph.reverse();
+
+        let mut dfs = Dfs::new(&graph, (*node).into());
+        while let Some(x) = dfs.next(&graph) {
+            let index = x.index() as u32;
+            if self.paths.contains_key(&index) {
+                ret.push(self.get_symbol(index));
+            }
+        }
+
+        ret
+    }
+
     fn dependent_files(&self) -> HashMap<PathId, Vec<PathId>> {
         let mut ret = HashMap::default();
         let graph = self.file_dag.graph().clone();
@@ -609,6 +631,10 @@ pub fn connected_components() -> Vec<Vec<Symbol>> {
     TYPE_DAG.with(|f| f.borrow().connected_components())
 }
 
+pub fn dependencies(id: SymbolId) -> Vec<Symbol> {
+    TYPE_DAG.with(|f| f.borrow().dependencies(id))
+}
+
 pub fn dependent_files() -> HashMap<PathId, Vec<PathId>> {
     TYPE_DAG.with(|f| f.borrow().dependent_files())
 }
@@ -130,6 +130,144 @@ pub fn strip_port_prefix(name: &str) -> &str {
     }
 }
 
+/// Value of a WaveDrom data label.
+/// Bits set in `mask` are don't-care (`x`, `z` or `?` digits).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WaveData {
+    pub value: u128,
+    pub mask: u128,
+}
+
+impl WaveData {
+    pub fn new(value: u128) -> Self {
+        Self { value, mask: 0 }
+    }
+
+    pub fn dont_care() -> Self {
+        Self {
+            value: 0,
+            mask: u128::MAX,
+        }
+    }
+}
+
+fn parse_wave_digits(digits: &str, radix: u32) -> Option<WaveData> {
+    let bits = match radix {
+        2 => 1,
+        8 => 3,
+        16 => 4,
+        _ => {
+            let value = digits.parse::<u128>().ok()?;
+            return Some(WaveData::new(value));
+        }
+    };
+
+    if digits.is_empty() {
+        return None;
+    }
+
+    let mut ret = WaveData::default();
+    for c in digits.chars() {
+        if (ret.value | ret.mask) >> (128 - bits) != 0 {
+            return None;
+        }
+        ret.value <<= bits;
+        ret.mask <<= bits;
+        if matches!(c, 'x' | 'X' | 'z' | 'Z' | '?') {
+            ret.mask |= (1 << bits) - 1;
+        } else {
+            ret.value |= c.to_digit(radix)? as u128;
+        }
+    }
+    Some(ret)
+}
+
+/// Parse a WaveDrom data label as a value.
+///
+/// Decimal (`42`, `-1`), prefixed (`0xff`, `0b1010`, `0o17`) and
+/// Veryl/SystemVerilog based literals (`8'hff`, `'b1x0x`) are accepted.
+/// `x` alone means that all bits are don't-care.
+pub fn parse_wave_data(text: &str) -> Option<WaveData> {
+    let text: String = text.trim().chars().filter(|x| *x != '_').collect();
+
+    if matches!(text.as_str(), "x" | "X" | "?") {
+        return Some(WaveData::dont_care());
+    }
+
+    let based = if let Some((_, x)) = text.split_once('\'') {
+        let x = x.strip_prefix(['s', 'S']).unwrap_or(x);
+        let mut chars = x.chars();
+        let radix = chars.next()?;
+        Some((radix.to_ascii_lowercase(), chars.as_str()))
+    } else if text.len() > 2 && text.starts_with('0') {
+        let mut chars = text[1..].chars();
+        let radix = chars.next()?.to_ascii_lowercase();
+        matches!(radix, 'x' | 'b' | 'o').then_some((radix, chars.as_str()))
+    } else {
+        None
+    };
+
+    match based {
+        Some(('h' | 'x', x)) => parse_wave_digits(x, 16),
+        Some(('o', x)) => parse_wave_digits(x, 8),
+        Some(('b', x)) => parse_wave_digits(x, 2),
+        Some(('d', x)) => parse_wave_digits(x, 10),
+        Some(_) => None,
+        None => {
+            if let Some(x) = text.strip_prefix('-') {
+                let value = x.parse::<i128>().ok()?;
+                Some(WaveData::new((-value) as u128))
+            } else {
+                parse_wave_digits(&text, 10)
+            }
+        }
+    }
+}
+
+/// Whether the wave character refers an item of `data`
+pub fn is_data_wave_char(c: char) -> bool {
+    matches!(c, '2'..='9' | '=')
+}
+
+/// Range of clock cycles written to a generated WaveDrom diagram.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CycleRange {
+    pub start: u64,
+    /// Exclusive end. `None` means the end of simulation.
+    pub end: Option<u64>,
+}
+
+impl CycleRange {
+    pub fn contains(&self, cycle: u64) -> bool {
+        cycle >= self.start && self.end.is_none_or(|x| cycle < x)
+    }
+}
+
+impl std::str::FromStr for CycleRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_cycle_range(s).ok_or_else(|| format!("invalid cycle range: {s}"))
+    }
+}
+
+/// Parse a cycle range like `4..16`, `4..` or `..16`.
+pub fn parse_cycle_range(text: &str) -> Option<CycleRange> {
+    let (start, end) = text.trim().split_once("..")?;
+    let start = match start.trim() {
+        "" => 0,
+        x => x.parse().ok()?,
+    };
+    let end = match end.trim() {
+        "" => None,
+        x => Some(x.parse().ok()?),
+    };
+    if end.is_some_and(|x| x <= start) {
+        return None;
+    }
+    Some(CycleRange { start, end })
+}
+
 pub struct DocTestTarget {
     pub module_name: StrId,
     pub wavedrom_json: String,
@@ -169,6 +307,9 @@ fn signal_hint(kind: &InvalidWavedromKind) -> Option<&str> {
     match kind {
         InvalidWavedromKind::UnknownWaveChar { signal, .. } => Some(signal),
         InvalidWavedromKind::PipeSeparatorInTest { signal } => Some(signal),
+        InvalidWavedromKind::InvalidTiming { signal } => Some(signal),
+        InvalidWavedromKind::InvalidData { signal, .. } => Some(signal),
+        InvalidWavedromKind::MissingData { signal } => Some(signal),
         _ => None,
     }
 }
@@ -219,6 +360,10 @@ fn check_wave_chars(signal_array: &[serde_json::Value]) -> Result<(), InvalidWav
                     'p' | 'P'
                         | 'n'
                         | 'N'
+                        | 'h'
+                        | 'H'
+                        | 'l'
+                        | 'L'
                         | '0'
                         | '1'
                         | 'x'
@@ -247,6 +392,62 @@ fn check_wave_chars(signal_array: &[serde_json::Value]) -> Result<(), InvalidWav
     Ok(())
 }
 
+/// `period` should be positive and `phase` should be a number
+fn check_timing(
+    obj: &serde_json::Map<String, serde_json::Value>,
+    name: &str,
+) -> Result<(), InvalidWavedromKind> {
+    let err = || InvalidWavedromKind::InvalidTiming {
+        signal: name.to_string(),
+    };
+    if let Some(x) = obj.get("period") {
+        let period = x.as_f64().ok_or_else(err)?;
+        if period <= 0.0 {
+            return Err(err());
+        }
+    }
+    if let Some(x) = obj.get("phase") {
+        x.as_f64().ok_or_else(err)?;
+    }
+    Ok(())
+}
+
+/// Labels in `data` should be values, and be enough for the data characters in `wave`
+fn check_data(
+    obj: &serde_json::Map<String, serde_json::Value>,
+    name: &str,
+) -> Result<(), InvalidWavedromKind> {
+    let data: Vec<String> = match obj.get("data") {
+        Some(serde_json::Value::Array(x)) => x
+            .iter()
+            .map(|x| match x {
+                serde_json::Value::String(x) => x.clone(),
+                x => x.to_string(),
+            })
+            .collect(),
+        Some(serde_json::Value::String(x)) => x.split_whitespace().map(String::from).collect(),
+        _ => vec![],
+    };
+
+    for x in &data {
+        if parse_wave_data(x).is_none() {
+            return Err(InvalidWavedromKind::InvalidData {
+                signal: name.to_string(),
+                data: x.clone(),
+            });
+        }
+    }
+
+    let wave = obj.get("wave").and_then(|v| v.as_str()).unwrap_or("");
+    let required = wave.chars().filter(|x| is_data_wave_char(*x)).count();
+    if data.len() < required {
+        return Err(InvalidWavedromKind::MissingData {
+            signal: name.to_string(),
+        });
+    }
+    Ok(())
+}
+
 fn is_javascript_expression(content: &str) -> bool {
     let trimmed = content.trim();
     !trimmed.starts_with('{') && !trimmed.starts_with('[')
@@ -292,6 +493,20 @@ fn validate_wavedrom_test(
         }
     }
 
+    for item in signal_array {
+        if let Some(obj) = item.as_object()
+            && let Some(name) = obj.get("name").and_then(|v| v.as_str())
+        {
+            check_timing(obj, name)?;
+            if port_names
+                .iter()
+                .any(|port_name| port_name == name || strip_port_prefix(port_name) == name)
+            {
+                check_data(obj, name)?;
+            }
+        }
+    }
+
     let has_output_match = signal_array.iter().any(|item| {
         let Some(obj) = item.as_object() else {
             return false;
@@ -388,6 +603,89 @@ mod tests {
         assert!(serde_json::from_str::<serde_json::Value>(&result).is_ok());
     }
 
+    #[test]
+    fn parse_wave_data_formats() {
+        assert_eq!(parse_wave_data("42"), Some(WaveData::new(42)));
+        assert_eq!(parse_wave_data("0xff"), Some(WaveData::new(255)));
+        assert_eq!(parse_wave_data("0b1010"), Some(WaveData::new(10)));
+        assert_eq!(parse_wave_data("0o17"), Some(WaveData::new(15)));
+        assert_eq!(parse_wave_data("8'hA5"), Some(WaveData::new(0xa5)));
+        assert_eq!(parse_wave_data("'d1_000"), Some(WaveData::new(1000)));
+        assert_eq!(parse_wave_data("-1"), Some(WaveData::new(u128::MAX)));
+        assert_eq!(parse_wave_data("x"), Some(WaveData::dont_care()));
+        assert_eq!(
+            parse_wave_data("0x1x"),
+            Some(WaveData {
+                value: 0x10,
+                mask: 0x0f
+            })
+        );
+        assert_eq!(
+            parse_wave_data("4'b1?0x"),
+            Some(WaveData {
+                value: 0b1000,
+                mask: 0b0101
+            })
+        );
+        assert_eq!(
+            parse_wave_data("0xffffffffffffffffffffffffffffffff"),
+            Some(WaveData::new(u128::MAX))
+        );
+        assert_eq!(parse_wave_data("0x1ffffffffffffffffffffffffffffffff"), None);
+        assert_eq!(parse_wave_data("IDLE"), None);
+        assert_eq!(parse_wave_data("0x"), None);
+        assert_eq!(parse_wave_data("0é1"), None);
+    }
+
+    #[test]
+    fn parse_cycle_range_formats() {
+        assert_eq!(
+            parse_cycle_range("4..16"),
+            Some(CycleRange {
+                start: 4,
+                end: Some(16)
+            })
+        );
+        assert_eq!(
+            parse_cycle_range("4.."),
+            Some(CycleRange {
+                start: 4,
+                end: None
+            })
+        );
+        assert_eq!(
+            parse_cycle_range("..8"),
+            Some(CycleRange {
+                start: 0,
+                end: Some(8)
+            })
+        );
+        assert_eq!(parse_cycle_range("8..4"), None);
+        assert_eq!(parse_cycle_range("8"), None);
+        assert_eq!(parse_cycle_range("a..b"), None);
+    }
+
+    #[test]
+    fn validate_wavedrom_test_data() {
+        let ports = vec!["i_clk".to_string(), "o_cnt".to_string()];
+        let json = "{signal: [{name: 'clk', wave: 'p...'}, {name: 'cnt', wave: '==.=', data: ['1', '2']}]}";
+        let err = validate_wavedrom_test(json, &ports).unwrap_err();
+        assert!(matches!(err, InvalidWavedromKind::MissingData { .. }));
+
+        let json = "{signal: [{name: 'clk', wave: 'p...'}, {name: 'cnt', wave: '=.=.', data: ['1', 'A']}]}";
+        let err = validate_wavedrom_test(json, &ports).unwrap_err();
+        assert!(matches!(err, InvalidWavedromKind::InvalidData { .. }));
+
+        let json =
+            "{signal: [{name: 'clk', wave: 'p...', period: 0}, {name: 'cnt', wave: '0...'}]}";
+        let err = validate_wavedrom_test(json, &ports).unwrap_err();
+        assert!(matches!(err, InvalidWavedromKind::InvalidTiming { .. }));
+
+        // labels of signals which are not ports are not checked
+        let json = "{signal: [{name: 'clk', wave: 'p...', period: 2, phase: 0.5}, {name: 'cnt', wave: '=.=.', data: ['1', '0x2']}, {name: 'state', wave: '=.=.', data: 'IDLE BUSY'}]}";
+        assert!(validate_wavedrom_test(json, &ports).is_ok());
+    }
+
     #[test]
     fn javascript_expression_detected() {
         assert!(is_javascript_expression(
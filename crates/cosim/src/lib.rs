@@ -95,6 +95,51 @@ pub unsafe extern "C" fn cosim_set(
     sim.set(name, value);
 }
 
+#[unsafe(no_mangle)]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn cosim_force(
+    handle: NonNull<Simulator>,
+    path: *const c_char,
+    value: &[SvLogicVecVal; 4],
+) {
+    let sim = unsafe { &mut *handle.as_ptr() };
+
+    let path = unsafe { CStr::from_ptr(path) };
+    let path = path.to_str().unwrap();
+
+    let value: Value = value.as_slice().into();
+
+    sim.force(path, value);
+}
+
+#[unsafe(no_mangle)]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn cosim_release(handle: NonNull<Simulator>, path: *const c_char) {
+    let sim = unsafe { &mut *handle.as_ptr() };
+
+    let path = unsafe { CStr::from_ptr(path) };
+    let path = path.to_str().unwrap();
+
+    sim.release(path);
+}
+
+#[unsafe(no_mangle)]
+#[allow(clippy::missing_safety_doc)]
+pub unsafe extern "C" fn cosim_deposit(
+    handle: NonNull<Simulator>,
+    path: *const c_char,
+    value: &[SvLogicVecVal; 4],
+) {
+    let sim = unsafe { &mut *handle.as_ptr() };
+
+    let path = unsafe { CStr::from_ptr(path) };
+    let path = path.to_str().unwrap();
+
+    let value: Value = value.as_slice().into();
+
+    sim.deposit(path, value);
+}
+
 #[unsafe(no_mangle)]
 #[allow(clippy::missing_safety_doc)]
 pub unsafe extern "C" fn cosim_get(
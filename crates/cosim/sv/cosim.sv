@@ -4,3 +4,6 @@ import "DPI-C" function void    cosim_step_reset (input chandle handle, input st
 import "DPI-C" function void    cosim_step_clock (input chandle handle, input string name);
 import "DPI-C" function void    cosim_set        (input chandle handle, input string name, input  logic [127:0] value);
 import "DPI-C" function void    cosim_get        (input chandle handle, input string name, output logic [127:0] value);
+import "DPI-C" function void    cosim_force      (input chandle handle, input string path, input  logic [127:0] value);
+import "DPI-C" function void    cosim_release    (input chandle handle, input string path);
+import "DPI-C" function void    cosim_deposit    (input chandle handle, input string path, input  logic [127:0] value);
@@ -1603,6 +1603,51 @@ impl Emitter {
         self.generic_map.pop();
     }
 
+    /// Emit `$force`, `$release` and `$deposit` of native testbenches as
+    /// procedural `force`, `release` and assignment of SystemVerilog.
+    fn emit_force_statement(&mut self, arg: &IdentifierStatement) -> bool {
+        let IdentifierStatementGroup::FunctionCall(x) = &*arg.identifier_statement_group else {
+            return false;
+        };
+        let token = arg.expression_identifier.identifier();
+        let keyword = match token.to_string().as_str() {
+            "$force" => Some("force"),
+            "$release" => Some("release"),
+            "$deposit" => None,
+            _ => return false,
+        };
+        let Some(list) = &x.function_call.function_call_opt else {
+            return false;
+        };
+        let list = &list.argument_list;
+        let Some(Factor::StringLiteral(path)) = list
+            .argument_item
+            .argument_expression
+            .expression
+            .unwrap_factor()
+        else {
+            return false;
+        };
+        let path_token = &path.string_literal.string_literal_token;
+        let path = path_token.to_string();
+        let path = path.trim_matches('"');
+
+        match keyword {
+            Some(keyword) => {
+                self.token(&token.replace(keyword));
+                self.space(1);
+                self.token(&path_token.replace(path));
+            }
+            None => self.token(&path_token.replace(path)),
+        }
+        if let Some(value) = list.argument_list_list.first() {
+            self.str(" = ");
+            self.expression(&value.argument_item.argument_expression.expression);
+        }
+        self.semicolon(&arg.semicolon);
+        true
+    }
+
     fn emit_connect_statement(&mut self, arg: &IdentifierStatement) -> bool {
         let (identifier, operator, expression) = {
             let IdentifierStatementGroup::Assignment(x) = &*arg.identifier_statement_group else {
@@ -3499,8 +3544,8 @@ impl VerylWalker for Emitter {
 
     /// Semantic action for non-terminal 'IdentifierStatement'
     fn identifier_statement(&mut self, arg: &IdentifierStatement) {
-        let connect_statement_emitted = self.emit_connect_statement(arg);
-        if !connect_statement_emitted {
+        let emitted = self.emit_force_statement(arg) || self.emit_connect_statement(arg);
+        if !emitted {
             self.align_start(align_kind::IDENTIFIER);
             self.expression_identifier(&arg.expression_identifier);
             self.assignment_lefthand_side = Some(*arg.expression_identifier.clone());
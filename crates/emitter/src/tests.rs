@@ -2999,3 +2999,43 @@ endmodule
     println!("ret\n{}exp\n{}", ret, expect);
     assert_eq!(ret, expect);
 }
+
+#[test]
+fn force_release_deposit() {
+    let code = r#"module ModuleA {
+    var a: logic<8>;
+    var b: logic<8>;
+
+    initial {
+        $force("u.r", 8'd40);
+        $release("u.r");
+        $deposit("u.w", a + b);
+    }
+}
+"#;
+
+    let expect = r#"module ModuleA;
+    logic [8-1:0] a;
+    logic [8-1:0] b;
+
+    initial begin
+        force u.r = 8'd40;
+        release u.r;
+        u.w = a + b;
+    end
+endmodule
+//# sourceMappingURL=test.sv.map
+"#;
+
+    let mut metadata = Metadata::create_default("prj").unwrap();
+
+    metadata.build.omit_project_prefix = true;
+
+    let ret = if cfg!(windows) {
+        emit(&metadata, code).replace("\r\n", "\n")
+    } else {
+        emit(&metadata, code)
+    };
+
+    assert_eq!(ret, expect);
+}
@@ -1,10 +1,13 @@
+use crate::partial::{self, LineEdit};
+use std::collections::HashMap;
 use veryl_aligner::{Aligner, Location, Measure, align_kind};
 use veryl_analyzer::attribute::{AlignItem, FormatItem};
 use veryl_analyzer::attribute_table;
-use veryl_metadata::{Format, Metadata};
+use veryl_metadata::{BraceStyle, Format, Metadata, TrailingComma};
 use veryl_parser::resource_table;
+use veryl_parser::resource_table::TokenId;
 use veryl_parser::token_collector::TokenCollector;
-use veryl_parser::token_range::TokenExt;
+use veryl_parser::token_range::{TokenExt, TokenRange};
 use veryl_parser::veryl_grammar_trait::*;
 use veryl_parser::veryl_token::{Token, VerylToken};
 use veryl_parser::veryl_walker::VerylWalker;
@@ -15,6 +18,17 @@ enum Mode {
     Align,
 }
 
+/// Import declaration in a run of consecutive import declarations sorted by `sort_imports`
+#[derive(Clone, Copy, Debug)]
+struct SortedImport {
+    /// The first token of the run in the original order
+    run_beg: Token,
+    /// The last line of the run
+    run_end_line: u32,
+    /// Whether the item is emitted at the beginning of the run
+    first: bool,
+}
+
 pub struct Formatter {
     mode: Mode,
     format_opt: Format,
@@ -34,6 +48,9 @@ pub struct Formatter {
     in_expression: Vec<()>,
     in_attribute: bool,
     in_named_argument: Vec<bool>,
+    output_line: u32,
+    token_lines: Option<HashMap<TokenId, u32>>,
+    blank_lines_after: Option<(TokenId, usize)>,
 }
 
 impl Default for Formatter {
@@ -57,6 +74,9 @@ impl Default for Formatter {
             in_expression: Vec::new(),
             in_attribute: false,
             in_named_argument: Vec::new(),
+            output_line: 1,
+            token_lines: None,
+            blank_lines_after: None,
         }
     }
 }
@@ -81,6 +101,24 @@ impl Formatter {
         self.veryl(input);
     }
 
+    /// Format only declarations intersecting any of the line ranges (1-origin, inclusive).
+    /// The other lines are kept as is, and `as_str` returns the whole text after the edits.
+    pub fn format_lines(
+        &mut self,
+        input: &Veryl,
+        raw_input: &str,
+        lines: &[(u32, u32)],
+    ) -> Vec<LineEdit> {
+        self.token_lines = Some(HashMap::new());
+        self.format(input, raw_input);
+        let token_lines = self.token_lines.take().unwrap_or_default();
+
+        let edits = partial::line_edits(input, raw_input, &self.string, &token_lines, lines)
+            .unwrap_or_default();
+        self.string = partial::apply_line_edits(raw_input, &edits);
+        edits
+    }
+
     pub fn as_str(&self) -> &str {
         &self.string
     }
@@ -89,6 +127,7 @@ impl Formatter {
         match self.mode {
             Mode::Emit => {
                 self.string.push_str(x);
+                self.output_line += x.matches('\n').count() as u32;
             }
             Mode::Align => {
                 self.aligner.space(x.len());
@@ -202,6 +241,94 @@ impl Formatter {
         }
     }
 
+    fn trailing_comma(&mut self, comma: Option<&Comma>) {
+        match (self.format_opt.trailing_comma, comma) {
+            // keep comments attached to the removed comma
+            (TrailingComma::Never, Some(x)) => self.token(&x.comma_token.replace("")),
+            (TrailingComma::Never, None) => (),
+            (_, Some(x)) => self.comma(x),
+            (_, None) => self.str(","),
+        }
+    }
+
+    /// Trailing comma of expression lists which is kept as is by default
+    fn expression_trailing_comma(&mut self, comma: Option<&Comma>, multi_line: bool) {
+        match (self.format_opt.trailing_comma, comma) {
+            (TrailingComma::Never, Some(x)) => self.token(&x.comma_token.replace("")),
+            (TrailingComma::Always, None) if multi_line => self.str(","),
+            (_, Some(x)) => self.comma(x),
+            (_, None) => (),
+        }
+    }
+
+    /// Move the following brace of declaration to the next line if `brace_style` is `next_line`
+    fn declaration_brace(&mut self) {
+        if self.format_opt.brace_style == BraceStyle::NextLine && self.mode == Mode::Emit {
+            if self.string.ends_with(' ') {
+                self.string.pop();
+            }
+            self.newline();
+            self.adjust_line = false;
+        }
+    }
+
+    /// Emitting order of items.
+    /// Consecutive import declarations are sorted if `sort_imports` is enabled.
+    fn item_order(
+        &self,
+        imports: &[Option<&ImportDeclaration>],
+    ) -> (Vec<usize>, Vec<Option<SortedImport>>) {
+        let mut order: Vec<usize> = (0..imports.len()).collect();
+        let mut sorted = vec![None; imports.len()];
+        if !self.format_opt.sort_imports {
+            return (order, sorted);
+        }
+
+        let mut i = 0;
+        while i < imports.len() {
+            let Some(first) = imports[i] else {
+                i += 1;
+                continue;
+            };
+            let mut end_line = first.semicolon.semicolon_token.token.line;
+            let mut j = i + 1;
+            while let Some(Some(x)) = imports.get(j)
+                && x.import.import_token.token.line == end_line + 1
+            {
+                end_line = x.semicolon.semicolon_token.token.line;
+                j += 1;
+            }
+
+            order[i..j].sort_by_cached_key(|x| import_key(imports[*x].unwrap()));
+            for (k, x) in sorted[i..j].iter_mut().enumerate() {
+                *x = Some(SortedImport {
+                    run_beg: first.import.import_token.token,
+                    run_end_line: end_line,
+                    first: k == 0,
+                });
+            }
+            i = j;
+        }
+        (order, sorted)
+    }
+
+    fn sorted_import_start(&mut self, x: Option<SortedImport>) {
+        if let Some(x) = x {
+            // keep blank lines around the run, and remove them inside the run
+            if x.first {
+                self.consume_adjust_line(&x.run_beg);
+            } else {
+                self.adjust_line = false;
+            }
+        }
+    }
+
+    fn sorted_import_finish(&mut self, x: Option<SortedImport>) {
+        if let Some(x) = x {
+            self.line = self.line.max(x.run_end_line);
+        }
+    }
+
     fn space(&mut self, repeat: usize) {
         self.str(&" ".repeat(repeat));
     }
@@ -223,6 +350,9 @@ impl Formatter {
             &text
         };
         let newlines_in_text = text.matches('\n').count() as u32;
+        if let Some(token_lines) = &mut self.token_lines {
+            token_lines.entry(x.id).or_insert(self.output_line);
+        }
         self.str(text);
         self.line = x.line + newlines_in_text;
     }
@@ -243,12 +373,27 @@ impl Formatter {
                 }
                 // detect line comment newline which will consume the next newline
                 self.consumed_next_newline = false;
+                let mut blank_lines = None;
+                if let Some((id, n)) = self.blank_lines_after
+                    && id == x.token.id
+                {
+                    blank_lines = Some(n);
+                }
                 for x in &x.comments {
                     // insert space between comments in the same line
                     if x.line == self.line && !self.in_start_token {
                         self.space(1);
                     }
-                    for _ in 0..x.line - self.line {
+                    let newlines = if x.line > self.line
+                        && let Some(n) = blank_lines.take()
+                    {
+                        // the first comment in the next line is placed after the blank lines
+                        self.blank_lines_after = None;
+                        n as u32 + 1
+                    } else {
+                        x.line - self.line
+                    };
+                    for _ in 0..newlines {
                         self.unindent();
                         self.str(self.newline);
                         self.indent();
@@ -282,7 +427,7 @@ impl Formatter {
 
     fn align_start(&mut self, kind: usize) {
         if self.mode == Mode::Align {
-            self.aligner.aligns[kind].start_item();
+            self.aligner.start_item(kind);
         }
     }
 
@@ -301,8 +446,11 @@ impl Formatter {
     }
 
     fn align_dummy_location(&mut self, kind: usize, loc: Option<Location>) {
-        if self.mode == Mode::Align {
-            self.aligner.aligns[kind].dummy_location(loc.unwrap());
+        // no location if alignment is disabled
+        if self.mode == Mode::Align
+            && let Some(loc) = loc
+        {
+            self.aligner.aligns[kind].dummy_location(loc);
         }
     }
 
@@ -718,9 +866,10 @@ impl VerylWalker for Formatter {
             }
             self.argument_item(&x.argument_item);
         }
-        if let Some(ref x) = arg.argument_list_opt {
-            self.comma(&x.comma);
-        }
+        self.expression_trailing_comma(
+            arg.argument_list_opt.as_ref().map(|x| x.comma.as_ref()),
+            *self.in_named_argument.last().unwrap(),
+        );
     }
 
     /// Semantic action for non-terminal 'ArgumentItem'
@@ -783,9 +932,12 @@ impl VerylWalker for Formatter {
             }
             self.struct_constructor_item(&x.struct_constructor_item);
         }
-        if let Some(ref x) = arg.struct_constructor_list_opt {
-            self.comma(&x.comma);
-        }
+        self.expression_trailing_comma(
+            arg.struct_constructor_list_opt
+                .as_ref()
+                .map(|x| x.comma.as_ref()),
+            self.multi_line(),
+        );
     }
 
     /// Semantic action for non-terminal 'StructConstructorItem'
@@ -815,9 +967,12 @@ impl VerylWalker for Formatter {
             }
             self.concatenation_item(&x.concatenation_item);
         }
-        if let Some(ref x) = arg.concatenation_list_opt {
-            self.comma(&x.comma);
-        }
+        self.expression_trailing_comma(
+            arg.concatenation_list_opt
+                .as_ref()
+                .map(|x| x.comma.as_ref()),
+            self.multi_line(),
+        );
         if self.multi_line() {
             self.newline_pop();
             self.align_reset();
@@ -850,9 +1005,12 @@ impl VerylWalker for Formatter {
             }
             self.array_literal_item(&x.array_literal_item);
         }
-        if let Some(ref x) = arg.array_literal_list_opt {
-            self.comma(&x.comma);
-        }
+        self.expression_trailing_comma(
+            arg.array_literal_list_opt
+                .as_ref()
+                .map(|x| x.comma.as_ref()),
+            self.multi_line(),
+        );
         if self.multi_line() {
             self.newline_pop();
             self.align_reset();
@@ -913,11 +1071,7 @@ impl VerylWalker for Formatter {
         self.colon(&arg.colon0);
         self.space(1);
         self.expression(&arg.expression1);
-        if let Some(ref x) = arg.case_expression_opt {
-            self.comma(&x.comma);
-        } else {
-            self.str(",");
-        }
+        self.trailing_comma(arg.case_expression_opt.as_ref().map(|x| x.comma.as_ref()));
         self.newline_pop();
         self.r_brace(&arg.r_brace);
     }
@@ -1699,11 +1853,7 @@ impl VerylWalker for Formatter {
             self.newline();
             self.modport_group(&x.modport_group);
         }
-        if let Some(ref x) = arg.modport_list_opt {
-            self.comma(&x.comma);
-        } else {
-            self.str(",");
-        }
+        self.trailing_comma(arg.modport_list_opt.as_ref().map(|x| x.comma.as_ref()));
     }
 
     /// Semantic action for non-terminal 'ModportGroup'
@@ -1773,11 +1923,7 @@ impl VerylWalker for Formatter {
             self.newline();
             self.enum_group(&x.enum_group);
         }
-        if let Some(ref x) = arg.enum_list_opt {
-            self.comma(&x.comma);
-        } else {
-            self.str(",");
-        }
+        self.trailing_comma(arg.enum_list_opt.as_ref().map(|x| x.comma.as_ref()));
     }
 
     /// Semantic action for non-terminal 'EnumGroup'
@@ -1835,11 +1981,7 @@ impl VerylWalker for Formatter {
             self.newline();
             self.struct_union_group(&x.struct_union_group);
         }
-        if let Some(ref x) = arg.struct_union_list_opt {
-            self.comma(&x.comma);
-        } else {
-            self.str(",");
-        }
+        self.trailing_comma(arg.struct_union_list_opt.as_ref().map(|x| x.comma.as_ref()));
     }
 
     /// Semantic action for non-terminal 'StructUnionGroup'
@@ -1928,11 +2070,11 @@ impl VerylWalker for Formatter {
         }
         // Omit trailing comma at single_line
         if !self.single_line() {
-            if let Some(ref x) = arg.inst_parameter_list_opt {
-                self.comma(&x.comma);
-            } else {
-                self.str(",");
-            }
+            self.trailing_comma(
+                arg.inst_parameter_list_opt
+                    .as_ref()
+                    .map(|x| x.comma.as_ref()),
+            );
         }
     }
 
@@ -1985,11 +2127,7 @@ impl VerylWalker for Formatter {
         }
         // Omit trailing comma at single_line
         if !self.single_line() {
-            if let Some(ref x) = arg.inst_port_list_opt {
-                self.comma(&x.comma);
-            } else {
-                self.str(",");
-            }
+            self.trailing_comma(arg.inst_port_list_opt.as_ref().map(|x| x.comma.as_ref()));
         }
     }
 
@@ -2054,11 +2192,11 @@ impl VerylWalker for Formatter {
             self.newline();
             self.with_parameter_group(&x.with_parameter_group);
         }
-        if let Some(ref x) = arg.with_parameter_list_opt {
-            self.comma(&x.comma);
-        } else {
-            self.str(",");
-        }
+        self.trailing_comma(
+            arg.with_parameter_list_opt
+                .as_ref()
+                .map(|x| x.comma.as_ref()),
+        );
     }
 
     /// Semantic action for non-terminal 'WithParameterGroup'
@@ -2169,11 +2307,11 @@ impl VerylWalker for Formatter {
             self.with_generic_parameter_item(&x.with_generic_parameter_item);
         }
         if self.multi_line() {
-            if let Some(ref x) = arg.with_generic_parameter_list_opt {
-                self.comma(&x.comma);
-            } else {
-                self.str(",");
-            }
+            self.trailing_comma(
+                arg.with_generic_parameter_list_opt
+                    .as_ref()
+                    .map(|x| x.comma.as_ref()),
+            );
         }
     }
 
@@ -2249,11 +2387,11 @@ impl VerylWalker for Formatter {
             self.with_generic_argument_item(&x.with_generic_argument_item);
         }
         if self.multi_line() {
-            if let Some(ref x) = arg.with_generic_argument_list_opt {
-                self.comma(&x.comma);
-            } else {
-                self.str(",");
-            }
+            self.trailing_comma(
+                arg.with_generic_argument_list_opt
+                    .as_ref()
+                    .map(|x| x.comma.as_ref()),
+            );
         }
     }
 
@@ -2283,7 +2421,17 @@ impl VerylWalker for Formatter {
 
     /// Semantic action for non-terminal 'PortDeclaration'
     fn port_declaration(&mut self, arg: &PortDeclaration) {
-        if let Some(ref x) = arg.port_declaration_opt {
+        if let Some(ref x) = arg.port_declaration_opt
+            && is_single_line_port_declaration(arg, self.format_opt.single_line_ports)
+        {
+            self.single_line_start();
+            self.aligner.disable();
+            self.l_paren(&arg.l_paren);
+            self.port_declaration_list(&x.port_declaration_list);
+            self.r_paren(&arg.r_paren);
+            self.aligner.enable();
+            self.single_line_finish();
+        } else if let Some(ref x) = arg.port_declaration_opt {
             self.token_will_push(&arg.l_paren.l_paren_token);
             self.newline_push();
             self.port_declaration_list(&x.port_declaration_list);
@@ -2303,10 +2451,16 @@ impl VerylWalker for Formatter {
             self.newline();
             self.port_declaration_group(&x.port_declaration_group);
         }
-        if let Some(ref x) = arg.port_declaration_list_opt {
-            self.comma(&x.comma);
+        let comma = arg
+            .port_declaration_list_opt
+            .as_ref()
+            .map(|x| x.comma.as_ref());
+        if self.single_line() {
+            if let Some(x) = comma {
+                self.token(&x.comma_token.replace(""));
+            }
         } else {
-            self.str(",");
+            self.trailing_comma(comma);
         }
     }
 
@@ -2432,6 +2586,7 @@ impl VerylWalker for Formatter {
             self.space(1);
             self.align_reset();
         }
+        self.declaration_brace();
         self.statement_block(&arg.statement_block);
         self.align_reset();
     }
@@ -2491,10 +2646,19 @@ impl VerylWalker for Formatter {
             self.space(1);
             self.align_reset();
         }
+        self.declaration_brace();
         self.token_will_push(&arg.l_brace.l_brace_token);
-        for (i, x) in arg.module_declaration_list.iter().enumerate() {
+        let imports: Vec<_> = arg
+            .module_declaration_list
+            .iter()
+            .map(|x| module_group_import(&x.module_group))
+            .collect();
+        let (order, sorted) = self.item_order(&imports);
+        for (i, j) in order.into_iter().enumerate() {
             self.newline_list(i);
-            self.module_group(&x.module_group);
+            self.sorted_import_start(sorted[i]);
+            self.module_group(&arg.module_declaration_list[j].module_group);
+            self.sorted_import_finish(sorted[i]);
         }
         self.newline_list_post(
             arg.module_declaration_list.is_empty(),
@@ -2547,10 +2711,19 @@ impl VerylWalker for Formatter {
             self.with_parameter(&x.with_parameter);
             self.space(1);
         }
+        self.declaration_brace();
         self.token_will_push(&arg.l_brace.l_brace_token);
-        for (i, x) in arg.interface_declaration_list.iter().enumerate() {
+        let imports: Vec<_> = arg
+            .interface_declaration_list
+            .iter()
+            .map(|x| interface_group_import(&x.interface_group))
+            .collect();
+        let (order, sorted) = self.item_order(&imports);
+        for (i, j) in order.into_iter().enumerate() {
             self.newline_list(i);
-            self.interface_group(&x.interface_group);
+            self.sorted_import_start(sorted[i]);
+            self.interface_group(&arg.interface_declaration_list[j].interface_group);
+            self.sorted_import_finish(sorted[i]);
         }
         self.newline_list_post(
             arg.interface_declaration_list.is_empty(),
@@ -2708,10 +2881,19 @@ impl VerylWalker for Formatter {
             self.scoped_identifier(&x.scoped_identifier);
             self.space(1);
         }
+        self.declaration_brace();
         self.token_will_push(&arg.l_brace.l_brace_token);
-        for (i, x) in arg.package_declaration_list.iter().enumerate() {
+        let imports: Vec<_> = arg
+            .package_declaration_list
+            .iter()
+            .map(|x| package_group_import(&x.package_group))
+            .collect();
+        let (order, sorted) = self.item_order(&imports);
+        for (i, j) in order.into_iter().enumerate() {
             self.newline_list(i);
-            self.package_group(&x.package_group);
+            self.sorted_import_start(sorted[i]);
+            self.package_group(&arg.package_declaration_list[j].package_group);
+            self.sorted_import_finish(sorted[i]);
         }
         self.newline_list_post(
             arg.package_declaration_list.is_empty(),
@@ -3027,12 +3209,51 @@ impl VerylWalker for Formatter {
         if !arg.start.start_token.comments.is_empty() {
             self.newline();
         }
-        for (i, x) in arg.veryl_list.iter().enumerate() {
+        let imports: Vec<_> = arg
+            .veryl_list
+            .iter()
+            .map(|x| description_group_import(&x.description_group))
+            .collect();
+        let (order, sorted) = self.item_order(&imports);
+        let blank_lines = self
+            .format_opt
+            .top_level_blank_lines
+            .filter(|_| self.mode == Mode::Emit);
+        // consecutive import declarations keep the original blank lines
+        let apply_blank_lines: Vec<_> = (0..order.len())
+            .map(|i| {
+                i + 1 < order.len()
+                    && (imports[order[i]].is_none() || imports[order[i + 1]].is_none())
+            })
+            .collect();
+
+        for (i, j) in order.iter().enumerate() {
+            let group = &arg.veryl_list[*j].description_group;
             if i != 0 {
                 self.newline();
+                if let Some(n) = blank_lines
+                    && apply_blank_lines[i - 1]
+                {
+                    // blank lines have been inserted before the trailing comments if exist
+                    if self.blank_lines_after.take().is_some() {
+                        for _ in 0..n {
+                            self.str(self.newline);
+                        }
+                    }
+                    self.adjust_line = false;
+                }
+            }
+            if let Some(n) = blank_lines
+                && apply_blank_lines[i]
+            {
+                let end = TokenRange::from(group.as_ref()).end;
+                self.blank_lines_after = Some((end.id, n));
             }
-            self.description_group(&x.description_group);
+            self.sorted_import_start(sorted[i]);
+            self.description_group(group);
+            self.sorted_import_finish(sorted[i]);
         }
+        self.blank_lines_after = None;
         self.newline();
     }
 }
@@ -3052,6 +3273,110 @@ fn is_single_line_inst_declaration(arg: &ComponentInstantiation) -> bool {
     true
 }
 
+fn is_single_line_port_declaration(arg: &PortDeclaration, max_ports: usize) -> bool {
+    let Some(ref x) = arg.port_declaration_opt else {
+        return true;
+    };
+
+    let list = &x.port_declaration_list;
+    let ports = 1 + list.port_declaration_list_list.len();
+    let is_flat = std::iter::once(&list.port_declaration_group)
+        .chain(
+            list.port_declaration_list_list
+                .iter()
+                .map(|x| &x.port_declaration_group),
+        )
+        .all(|x| {
+            matches!(
+                *x.port_declaration_group_group,
+                PortDeclarationGroupGroup::PortDeclarationItem(_)
+            ) && x.port_declaration_group_list.is_empty()
+        });
+
+    // Ports including comments are kept in multi lines
+    let mut with_comments = TokenCollector::new(true);
+    with_comments.port_declaration(arg);
+    let mut without_comments = TokenCollector::new(false);
+    without_comments.port_declaration(arg);
+    let has_comments = with_comments.tokens.len() != without_comments.tokens.len();
+
+    ports <= max_ports && is_flat && !has_comments
+}
+
+fn import_key(arg: &ImportDeclaration) -> String {
+    let mut collector = TokenCollector::new(false);
+    collector.import_declaration(arg);
+    collector
+        .tokens
+        .iter()
+        .map(|x| resource_table::get_str_value(x.text).unwrap())
+        .collect()
+}
+
+/// Import declaration which can be sorted.
+/// Imports with attributes or followed by comments in the next lines are not sorted.
+fn sortable_import(arg: &ImportDeclaration) -> Option<&ImportDeclaration> {
+    let semicolon = &arg.semicolon.semicolon_token;
+    semicolon
+        .comments
+        .iter()
+        .all(|x| x.line == semicolon.token.line)
+        .then_some(arg)
+}
+
+fn description_group_import(arg: &DescriptionGroup) -> Option<&ImportDeclaration> {
+    if !arg.description_group_list.is_empty() {
+        return None;
+    }
+    if let DescriptionGroupGroup::DescriptionItem(x) = arg.description_group_group.as_ref()
+        && let DescriptionItem::ImportDeclaration(x) = x.description_item.as_ref()
+    {
+        sortable_import(&x.import_declaration)
+    } else {
+        None
+    }
+}
+
+fn module_group_import(arg: &ModuleGroup) -> Option<&ImportDeclaration> {
+    if !arg.module_group_list.is_empty() {
+        return None;
+    }
+    if let ModuleGroupGroup::ModuleItem(x) = arg.module_group_group.as_ref()
+        && let GenerateItem::ImportDeclaration(x) = x.module_item.generate_item.as_ref()
+    {
+        sortable_import(&x.import_declaration)
+    } else {
+        None
+    }
+}
+
+fn interface_group_import(arg: &InterfaceGroup) -> Option<&ImportDeclaration> {
+    if !arg.interface_group_list.is_empty() {
+        return None;
+    }
+    if let InterfaceGroupGroup::InterfaceItem(x) = arg.interface_group_group.as_ref()
+        && let InterfaceItem::GenerateItem(x) = x.interface_item.as_ref()
+        && let GenerateItem::ImportDeclaration(x) = x.generate_item.as_ref()
+    {
+        sortable_import(&x.import_declaration)
+    } else {
+        None
+    }
+}
+
+fn package_group_import(arg: &PackageGroup) -> Option<&ImportDeclaration> {
+    if !arg.package_group_list.is_empty() {
+        return None;
+    }
+    if let PackageGroupGroup::PackageItem(x) = arg.package_group_group.as_ref()
+        && let PackageItem::ImportDeclaration(x) = x.package_item.as_ref()
+    {
+        sortable_import(&x.import_declaration)
+    } else {
+        None
+    }
+}
+
 fn skip_formatting(arg: &DescriptionItem) -> bool {
     let Some(identifier) = arg.identifier_token() else {
         return false;
@@ -1,5 +1,7 @@
 pub mod formatter;
+pub mod partial;
 pub use formatter::Formatter;
+pub use partial::LineEdit;
 
 #[cfg(test)]
 mod tests;
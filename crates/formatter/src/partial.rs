@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use veryl_parser::resource_table::TokenId;
+use veryl_parser::token_range::TokenRange;
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::Token;
+
+/// Replacement of original lines by formatted text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineEdit {
+    /// The first replaced line (1-origin)
+    pub beg: u32,
+    /// The last replaced line (1-origin, inclusive)
+    pub end: u32,
+    /// Formatted text including the last newline
+    pub text: String,
+}
+
+/// Declaration which can be formatted independently.
+///
+/// A unit spans until the line before the next unit because comments are attached to
+/// the preceding token.
+struct Unit {
+    beg: Token,
+    body: Option<Body>,
+}
+
+/// Items between braces of module, interface and package
+struct Body {
+    l_brace: Token,
+    r_brace: Token,
+    items: Vec<Token>,
+}
+
+fn body(arg: &DescriptionGroup) -> Option<Body> {
+    let DescriptionGroupGroup::DescriptionItem(x) = arg.description_group_group.as_ref() else {
+        return None;
+    };
+    let DescriptionItem::DescriptionItemOptPublicDescriptionItem(x) = x.description_item.as_ref()
+    else {
+        return None;
+    };
+    let (l_brace, r_brace, items): (_, _, Vec<Token>) = match x.public_description_item.as_ref() {
+        PublicDescriptionItem::ModuleDeclaration(x) => {
+            let x = &x.module_declaration;
+            let items = x
+                .module_declaration_list
+                .iter()
+                .map(|x| TokenRange::from(x.module_group.as_ref()).beg)
+                .collect();
+            (&x.l_brace, &x.r_brace, items)
+        }
+        PublicDescriptionItem::InterfaceDeclaration(x) => {
+            let x = &x.interface_declaration;
+            let items = x
+                .interface_declaration_list
+                .iter()
+                .map(|x| TokenRange::from(x.interface_group.as_ref()).beg)
+                .collect();
+            (&x.l_brace, &x.r_brace, items)
+        }
+        PublicDescriptionItem::PackageDeclaration(x) => {
+            let x = &x.package_declaration;
+            let items = x
+                .package_declaration_list
+                .iter()
+                .map(|x| TokenRange::from(x.package_group.as_ref()).beg)
+                .collect();
+            (&x.l_brace, &x.r_brace, items)
+        }
+        _ => return None,
+    };
+
+    if items.is_empty() {
+        None
+    } else {
+        Some(Body {
+            l_brace: l_brace.l_brace_token.token,
+            r_brace: r_brace.r_brace_token.token,
+            items,
+        })
+    }
+}
+
+fn units(input: &Veryl) -> Vec<Unit> {
+    input
+        .veryl_list
+        .iter()
+        .map(|x| {
+            let group = x.description_group.as_ref();
+            Unit {
+                beg: TokenRange::from(group).beg,
+                body: body(group),
+            }
+        })
+        .collect()
+}
+
+/// Line layout of the original text and the formatted text
+struct Layout<'a> {
+    lines: Vec<&'a str>,
+    formatted_lines: Vec<&'a str>,
+    formatted_token_lines: &'a HashMap<TokenId, u32>,
+}
+
+impl Layout<'_> {
+    fn formatted_line(&self, x: &Token) -> Option<u32> {
+        self.formatted_token_lines.get(&x.id).copied()
+    }
+
+    /// Whether the token is the first token of the line in the original text
+    fn at_line_start(&self, x: &Token) -> bool {
+        let Some(line) = self.lines.get(x.line as usize - 1) else {
+            return false;
+        };
+        line.chars()
+            .take(x.column as usize - 1)
+            .all(|x| x.is_whitespace())
+    }
+}
+
+fn intersects(beg: u32, end: u32, lines: &[(u32, u32)]) -> bool {
+    lines.iter().any(|x| beg <= x.1 && x.0 <= end)
+}
+
+/// Original lines and formatted lines to be replaced
+#[derive(Clone, Copy)]
+struct Replace {
+    beg: u32,
+    end: u32,
+    formatted_beg: u32,
+    formatted_end: u32,
+}
+
+/// Select items intersecting any of `lines`.
+/// Items which are not separated by newline are selected together.
+fn select(
+    layout: &Layout,
+    items: &[Token],
+    next: Option<&Token>,
+    lines: &[(u32, u32)],
+) -> Vec<bool> {
+    let last_line = layout.lines.len() as u32;
+    let mut ret: Vec<_> = items
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let beg = x.line;
+            let end = items
+                .get(i + 1)
+                .or(next)
+                .map(|x| x.line.saturating_sub(1))
+                .unwrap_or(last_line);
+            intersects(beg, end.max(beg), lines)
+        })
+        .collect();
+
+    for _ in 0..2 {
+        for i in 1..items.len() {
+            if !layout.at_line_start(&items[i]) {
+                let x = ret[i - 1] | ret[i];
+                ret[i - 1] = x;
+                ret[i] = x;
+            }
+        }
+        for i in (1..items.len()).rev() {
+            if !layout.at_line_start(&items[i]) {
+                let x = ret[i - 1] | ret[i];
+                ret[i - 1] = x;
+                ret[i] = x;
+            }
+        }
+    }
+    ret
+}
+
+/// Convert selected items to replaced lines
+fn replaces(
+    layout: &Layout,
+    items: &[Token],
+    selected: &[bool],
+    next: Option<&Token>,
+    formatted_next: Option<u32>,
+) -> Option<Vec<Replace>> {
+    let last_line = layout.lines.len() as u32;
+    let formatted_last_line = layout.formatted_lines.len() as u32;
+
+    let mut ret = Vec::new();
+    for (i, x) in items.iter().enumerate() {
+        if !selected[i] {
+            continue;
+        }
+        let (end, formatted_end) = if let Some(next) = items.get(i + 1) {
+            (next.line - 1, layout.formatted_line(next)? - 1)
+        } else if let Some(next) = next {
+            (next.line - 1, formatted_next? - 1)
+        } else {
+            (last_line, formatted_last_line)
+        };
+        ret.push(Replace {
+            beg: x.line,
+            end,
+            formatted_beg: layout.formatted_line(x)?,
+            formatted_end,
+        });
+    }
+    Some(ret)
+}
+
+/// Calculate edits which replace units intersecting any of `lines` by the formatted text.
+/// Returns `None` if the formatted text can't be mapped to the original text.
+pub fn line_edits(
+    input: &Veryl,
+    raw_input: &str,
+    formatted: &str,
+    formatted_token_lines: &HashMap<TokenId, u32>,
+    lines: &[(u32, u32)],
+) -> Option<Vec<LineEdit>> {
+    let layout = Layout {
+        lines: raw_input.split_inclusive('\n').collect(),
+        formatted_lines: formatted.split_inclusive('\n').collect(),
+        formatted_token_lines,
+    };
+
+    let units = units(input);
+    let begs: Vec<_> = units.iter().map(|x| x.beg).collect();
+    let selected = select(&layout, &begs, None, lines);
+
+    let mut whole = vec![false; units.len()];
+    let mut replace = Vec::new();
+    for (i, unit) in units.iter().enumerate() {
+        if !selected[i] {
+            continue;
+        }
+        let Some(body) = &unit.body else {
+            whole[i] = true;
+            continue;
+        };
+
+        // header and footer lines require formatting the whole unit
+        let header = (unit.beg.line, body.l_brace.line);
+        let footer = body.r_brace.line;
+        let in_header = intersects(header.0, header.1, lines);
+        let in_footer = intersects(footer, footer, lines);
+        if in_header || in_footer {
+            whole[i] = true;
+            continue;
+        }
+
+        let selected = select(&layout, &body.items, Some(&body.r_brace), lines);
+        let first_shared = !layout.at_line_start(&body.items[0]) && selected[0];
+        let last_shared = !layout.at_line_start(&body.r_brace) && selected[selected.len() - 1];
+        if first_shared || last_shared {
+            whole[i] = true;
+            continue;
+        }
+
+        let formatted_r_brace = layout.formatted_line(&body.r_brace);
+        replace.append(&mut replaces(
+            &layout,
+            &body.items,
+            &selected,
+            Some(&body.r_brace),
+            formatted_r_brace,
+        )?);
+    }
+
+    // units not separated by newline should be formatted together
+    for _ in 0..2 {
+        for i in 1..units.len() {
+            if !layout.at_line_start(&units[i].beg) {
+                let x = whole[i - 1] | whole[i];
+                whole[i - 1] = x;
+                whole[i] = x;
+            }
+        }
+    }
+    replace.append(&mut replaces(&layout, &begs, &whole, None, None)?);
+    replace.sort_by_key(|x| x.beg);
+
+    // merge adjacent replaces
+    let mut merged: Vec<Replace> = Vec::new();
+    for x in replace {
+        if let Some(last) = merged.last_mut()
+            && last.end + 1 >= x.beg
+        {
+            last.end = last.end.max(x.end);
+            last.formatted_end = last.formatted_end.max(x.formatted_end);
+        } else {
+            merged.push(x);
+        }
+    }
+
+    let mut ret = Vec::new();
+    for x in merged {
+        let original: String = layout
+            .lines
+            .get(x.beg as usize - 1..x.end as usize)?
+            .concat();
+        let text: String = layout
+            .formatted_lines
+            .get(x.formatted_beg as usize - 1..x.formatted_end as usize)?
+            .concat();
+        if original != text {
+            ret.push(LineEdit {
+                beg: x.beg,
+                end: x.end,
+                text,
+            });
+        }
+    }
+    Some(ret)
+}
+
+/// Apply edits to the original text
+pub fn apply_line_edits(raw_input: &str, edits: &[LineEdit]) -> String {
+    let lines: Vec<_> = raw_input.split_inclusive('\n').collect();
+    let mut ret = String::new();
+    let mut line = 1;
+    for edit in edits {
+        while line < edit.beg {
+            ret.push_str(lines[line as usize - 1]);
+            line += 1;
+        }
+        ret.push_str(&edit.text);
+        line = edit.end + 1;
+    }
+    while (line as usize) <= lines.len() {
+        ret.push_str(lines[line as usize - 1]);
+        line += 1;
+    }
+    ret
+}
@@ -1,6 +1,6 @@
 use crate::Formatter;
 use veryl_analyzer::{Analyzer, Context};
-use veryl_metadata::Metadata;
+use veryl_metadata::{BraceStyle, Metadata, TrailingComma};
 use veryl_parser::Parser;
 
 #[track_caller]
@@ -264,3 +264,316 @@ fn format_generic_list() {
     let ret = format(&metadata, &code);
     assert_eq!(ret, expect);
 }
+
+#[track_caller]
+fn format_lines(metadata: &Metadata, code: &str, beg: u32, end: u32) -> String {
+    let parser = Parser::parse(&code, &"").unwrap();
+
+    let mut formatter = Formatter::new(metadata);
+    formatter.format_lines(&parser.veryl, code, &[(beg, end)]);
+    formatter.as_str().to_string()
+}
+
+#[test]
+fn range_formatting() {
+    let code = r#"module ModuleA {
+    let a:logic=1;
+    let b:logic=1;
+}
+module ModuleB {
+  let c:logic=1;
+}
+module ModuleC { let d:logic=1; }
+"#;
+
+    let metadata = Metadata::create_default("prj").unwrap();
+
+    // only the intersecting declaration is formatted
+    let expect = r#"module ModuleA {
+    let a:logic=1;
+    let b: logic = 1;
+}
+module ModuleB {
+  let c:logic=1;
+}
+module ModuleC { let d:logic=1; }
+"#;
+    let ret = format_lines(&metadata, &code, 3, 3);
+    assert_eq!(ret, expect);
+
+    // header line requires formatting the whole module
+    let expect = r#"module ModuleA {
+    let a:logic=1;
+    let b:logic=1;
+}
+module ModuleB {
+    let c: logic = 1;
+}
+module ModuleC { let d:logic=1; }
+"#;
+    let ret = format_lines(&metadata, &code, 5, 5);
+    assert_eq!(ret, expect);
+
+    // declarations not separated by newline are formatted together
+    let expect = r#"module ModuleA {
+    let a:logic=1;
+    let b:logic=1;
+}
+module ModuleB {
+  let c:logic=1;
+}
+module ModuleC {
+    let d: logic = 1;
+}
+"#;
+    let ret = format_lines(&metadata, &code, 8, 8);
+    assert_eq!(ret, expect);
+
+    // lines without declaration are kept
+    let ret = format_lines(&metadata, &code, 20, 30);
+    assert_eq!(ret, code);
+}
+
+#[track_caller]
+fn format_idempotent(metadata: &Metadata, code: &str) -> String {
+    let ret = format(metadata, code);
+    assert_eq!(format(metadata, &ret), ret);
+    ret
+}
+
+#[test]
+fn brace_style_next_line() {
+    let code = r#"module ModuleA (
+    i_a: input logic,
+) {
+    function FuncA () -> logic {
+        return 1;
+    }
+    always_comb {
+        a = 1;
+    }
+}
+package PackageA {
+    const A: u32 = 1;
+}
+interface InterfaceA {
+    var a: logic;
+}
+"#;
+    let expect = r#"module ModuleA (
+    i_a: input logic,
+)
+{
+    function FuncA () -> logic
+    {
+        return 1;
+    }
+    always_comb {
+        a = 1;
+    }
+}
+package PackageA
+{
+    const A: u32 = 1;
+}
+interface InterfaceA
+{
+    var a: logic;
+}
+"#;
+
+    let mut metadata = Metadata::create_default("prj").unwrap();
+    metadata.format.brace_style = BraceStyle::NextLine;
+
+    let ret = format_idempotent(&metadata, &code);
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn sort_imports() {
+    let code = r#"import PkgC::*;
+import PkgA::*;
+
+import PkgB::*;
+module ModuleA {
+    import PkgZ::b;
+    import PkgZ::a; // a
+    #[allow(unused_variable)]
+    import PkgY::*;
+
+    let a: logic = 1;
+}
+"#;
+    let expect = r#"import PkgA::*;
+import PkgC::*;
+
+import PkgB::*;
+module ModuleA {
+    import PkgZ::a; // a
+    import PkgZ::b;
+    #[allow(unused_variable)]
+    import PkgY::*;
+
+    let a: logic = 1;
+}
+"#;
+
+    let mut metadata = Metadata::create_default("prj").unwrap();
+    metadata.format.sort_imports = true;
+
+    let ret = format_idempotent(&metadata, &code);
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn top_level_blank_lines() {
+    let code = r#"import PkgA::*;
+import PkgB::*;
+module ModuleA {
+    let a: logic = 1;
+}
+
+
+
+// ModuleB
+module ModuleB {
+    let a: logic = 1;
+} // ModuleB
+package PackageA {
+    const A: u32 = 1;
+}
+"#;
+    let expect = r#"import PkgA::*;
+import PkgB::*;
+
+module ModuleA {
+    let a: logic = 1;
+}
+
+// ModuleB
+module ModuleB {
+    let a: logic = 1;
+} // ModuleB
+
+package PackageA {
+    const A: u32 = 1;
+}
+"#;
+
+    let mut metadata = Metadata::create_default("prj").unwrap();
+    metadata.format.top_level_blank_lines = Some(1);
+
+    let ret = format_idempotent(&metadata, &code);
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn trailing_comma_never() {
+    let code = r#"module ModuleA #(
+    param A: u32 = 1,
+    param B: u32 = 1
+) (
+    i_a: input logic, // a
+    i_b: input logic,
+) {
+    inst u: ModuleB (
+        a: i_a,
+        b: i_b,
+    );
+}
+"#;
+    let expect = r#"module ModuleA #(
+    param A: u32 = 1,
+    param B: u32 = 1
+) (
+    i_a: input logic, // a
+    i_b: input logic
+) {
+    inst u: ModuleB (
+        a: i_a,
+        b: i_b
+    );
+}
+"#;
+
+    let mut metadata = Metadata::create_default("prj").unwrap();
+    metadata.format.trailing_comma = TrailingComma::Never;
+
+    let ret = format_idempotent(&metadata, &code);
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn trailing_comma_always() {
+    let code = r#"module ModuleA {
+    let a: logic<2> = {
+        i_a,
+        i_b
+    };
+    let b: logic<2> = {i_a, i_b};
+    let c: logic    = FuncA(
+        x: i_a,
+        y: i_b
+    );
+}
+"#;
+    let vertical = format_idempotent(&Metadata::create_default("prj").unwrap(), &code);
+    assert_eq!(vertical, code);
+
+    let expect = r#"module ModuleA {
+    let a: logic<2> = {
+        i_a,
+        i_b,
+    };
+    let b: logic<2> = {i_a, i_b};
+    let c: logic    = FuncA(
+        x: i_a,
+        y: i_b,
+    );
+}
+"#;
+
+    let mut metadata = Metadata::create_default("prj").unwrap();
+    metadata.format.trailing_comma = TrailingComma::Always;
+
+    let ret = format_idempotent(&metadata, &code);
+    assert_eq!(ret, expect);
+}
+
+#[test]
+fn single_line_ports() {
+    let code = r#"module ModuleA (
+    i_a    : input  logic,
+    o_b: output logic,
+) {
+    function FuncA (
+        a: input logic,
+    ) {}
+}
+module ModuleB (i_a: input logic, i_b: input logic, o_c: output logic) {}
+module ModuleC (
+    i_a: input logic, // a
+) {}
+"#;
+    let expect = r#"module ModuleA (i_a: input logic, o_b: output logic) {
+    function FuncA (a: input logic) {}
+}
+module ModuleB (
+    i_a: input  logic,
+    i_b: input  logic,
+    o_c: output logic,
+) {}
+module ModuleC (
+    i_a: input logic, // a
+) {}
+"#;
+
+    let mut metadata = Metadata::create_default("prj").unwrap();
+    metadata.format.single_line_ports = 2;
+
+    let ret = format_idempotent(&metadata, &code);
+    assert_eq!(ret, expect);
+
+    let ret = format_idempotent(&Metadata::create_default("prj").unwrap(), &expect);
+    assert_ne!(ret, expect);
+}
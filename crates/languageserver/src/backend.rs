@@ -105,6 +105,11 @@ impl LanguageServer for Backend {
                 }),
                 definition_provider: Some(OneOf::Left(true)),
                 document_formatting_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
+                document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                    first_trigger_character: ";".to_string(),
+                    more_trigger_character: Some(vec!["}".to_string()]),
+                }),
                 workspace_symbol_provider: Some(OneOf::Left(true)),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 references_provider: Some(OneOf::Left(true)),
@@ -362,6 +367,59 @@ impl LanguageServer for Backend {
         Ok(None)
     }
 
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let url = params.text_document.uri;
+        let range = params.range;
+        // the end position at the head of line doesn't include the line
+        let end = if range.end.character == 0 && range.end.line > range.start.line {
+            range.end.line
+        } else {
+            range.end.line + 1
+        };
+
+        self.send(MsgToServer::RangeFormatting {
+            url,
+            beg: range.start.line + 1,
+            end,
+        })
+        .await;
+
+        // Dispose unexpected messages
+        while let Some(x) = self.recv().await {
+            if let MsgFromServer::Formatting(x) = x {
+                return Ok(x);
+            }
+        }
+        Ok(None)
+    }
+
+    async fn on_type_formatting(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let url = params.text_document_position.text_document.uri;
+        let line = params.text_document_position.position.line + 1;
+
+        // format the declaration which is terminated by the typed character
+        self.send(MsgToServer::RangeFormatting {
+            url,
+            beg: line,
+            end: line,
+        })
+        .await;
+
+        // Dispose unexpected messages
+        while let Some(x) = self.recv().await {
+            if let MsgFromServer::Formatting(x) = x {
+                return Ok(x);
+            }
+        }
+        Ok(None)
+    }
+
     async fn shutdown(&self) -> Result<()> {
         Ok(())
     }
@@ -1,7 +1,6 @@
 #![recursion_limit = "256"]
 
 mod backend;
-mod keyword;
 mod server;
 #[cfg(test)]
 mod tests;
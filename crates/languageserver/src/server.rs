@@ -1,4 +1,3 @@
-use crate::keyword::KEYWORDS;
 use async_channel::{Receiver, Sender};
 use dashmap::DashMap;
 use futures::executor::block_on;
@@ -19,6 +18,7 @@ use veryl_analyzer::{
 };
 use veryl_formatter::Formatter;
 use veryl_metadata::Metadata;
+use veryl_parser::keyword::KEYWORDS;
 use veryl_parser::resource_table::{self, PathId};
 use veryl_parser::text_table;
 use veryl_parser::veryl_token::Token;
@@ -97,6 +97,11 @@ pub enum MsgToServer {
     Formatting {
         url: Url,
     },
+    RangeFormatting {
+        url: Url,
+        beg: u32,
+        end: u32,
+    },
 }
 
 pub enum MsgFromServer {
@@ -203,6 +208,9 @@ impl Server {
                     }
                     MsgToServer::SemanticTokens { url } => self.semantic_tokens(&url),
                     MsgToServer::Formatting { url } => self.formatting(&url),
+                    MsgToServer::RangeFormatting { url, beg, end } => {
+                        self.range_formatting(&url, beg, end)
+                    }
                 }
             }
 
@@ -616,6 +624,35 @@ impl Server {
             .send_blocking(MsgFromServer::Formatting(None))
             .unwrap();
     }
+
+    fn range_formatting(&mut self, url: &Url, beg: u32, end: u32) {
+        if let Some(path) = url.to_file_path()
+            && let Some(metadata) = self.get_metadata(url)
+            && let Some(rope) = self.document_map.get(path.as_ref())
+            && let Some(parser) = self.parser_map.get(path.as_ref())
+        {
+            let mut formatter = Formatter::new(&metadata);
+            let raw_input: String = String::from(&*rope);
+            let edits = formatter.format_lines(&parser.veryl, &raw_input, &[(beg, end)]);
+
+            let text_edits = edits
+                .into_iter()
+                .map(|x| TextEdit {
+                    range: Range::new(Position::new(x.beg - 1, 0), Position::new(x.end, 0)),
+                    new_text: x.text,
+                })
+                .collect();
+
+            self.snd
+                .send_blocking(MsgFromServer::Formatting(Some(text_edits)))
+                .unwrap();
+            return;
+        }
+
+        self.snd
+            .send_blocking(MsgFromServer::Formatting(None))
+            .unwrap();
+    }
 }
 
 impl Server {
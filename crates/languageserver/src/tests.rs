@@ -16,6 +16,7 @@ struct TestServer {
     req_stream: DuplexStream,
     res_stream: DuplexStream,
     responses: VecDeque<String>,
+    buffer: Vec<u8>,
 }
 
 impl TestServer {
@@ -35,6 +36,7 @@ impl TestServer {
             req_stream: req_client,
             res_stream: res_client,
             responses: VecDeque::new(),
+            buffer: Vec::new(),
         }
     }
 
@@ -42,25 +44,38 @@ impl TestServer {
         format!("Content-Length: {}\r\n\r\n{}", payload.len(), payload)
     }
 
-    fn decode(text: &str) -> Vec<String> {
+    /// Decode complete messages, and return the length of decoded bytes
+    fn decode(text: &[u8]) -> (Vec<String>, usize) {
         let mut ret = Vec::new();
-        let mut temp = text;
-
-        while !temp.is_empty() {
-            let p = temp.find("\r\n\r\n").unwrap();
-            let (header, body) = temp.split_at(p + 4);
-            let len = header
-                .strip_prefix("Content-Length: ")
-                .unwrap()
-                .strip_suffix("\r\n\r\n")
-                .unwrap();
+        let mut pos = 0;
+
+        while let Some(p) = text[pos..].windows(4).position(|x| x == b"\r\n\r\n") {
+            let header = std::str::from_utf8(&text[pos..pos + p]).unwrap();
+            let len = header.strip_prefix("Content-Length: ").unwrap();
             let len: usize = len.parse().unwrap();
-            let (body, rest) = body.split_at(len);
-            ret.push(body.to_string());
-            temp = rest;
+            let body = pos + p + 4;
+            if text.len() < body + len {
+                break;
+            }
+            ret.push(String::from_utf8(text[body..body + len].to_vec()).unwrap());
+            pos = body + len;
         }
 
-        ret
+        (ret, pos)
+    }
+
+    async fn fill_responses(&mut self) {
+        while self.responses.is_empty() {
+            let mut buf = vec![0; 1024];
+            let n = self.res_stream.read(&mut buf).await.unwrap();
+            assert!(n > 0, "server closed the stream");
+            self.buffer.extend_from_slice(&buf[..n]);
+            let (messages, len) = Self::decode(&self.buffer);
+            self.buffer.drain(..len);
+            for x in messages {
+                self.responses.push_front(x);
+            }
+        }
     }
 
     async fn send_request(&mut self, req: Request) {
@@ -77,27 +92,13 @@ impl TestServer {
     }
 
     async fn recv_response(&mut self) -> Response {
-        if self.responses.is_empty() {
-            let mut buf = vec![0; 1024];
-            let n = self.res_stream.read(&mut buf).await.unwrap();
-            let ret = String::from_utf8(buf[..n].to_vec()).unwrap();
-            for x in Self::decode(&ret) {
-                self.responses.push_front(x);
-            }
-        }
+        self.fill_responses().await;
         let res = self.responses.pop_back().unwrap();
         serde_json::from_str(&res).unwrap()
     }
 
     async fn recv_notification(&mut self) -> Request {
-        if self.responses.is_empty() {
-            let mut buf = vec![0; 1024];
-            let n = self.res_stream.read(&mut buf).await.unwrap();
-            let ret = String::from_utf8(buf[..n].to_vec()).unwrap();
-            for x in Self::decode(&ret) {
-                self.responses.push_front(x);
-            }
-        }
+        self.fill_responses().await;
         let res = self.responses.pop_back().unwrap();
         serde_json::from_str(&res).unwrap()
     }
@@ -258,3 +259,56 @@ async fn progress() {
     }
     assert_eq!(percentage, 100);
 }
+
+#[tokio::test]
+#[ntest::timeout(60000)]
+async fn range_formatting() {
+    let mut server = TestServer::new(Backend::new);
+
+    let req = build_initialize(1);
+    server.send_request(req).await;
+    let res = server.recv_response().await;
+    assert!(res.is_ok());
+
+    let req = build_initialized();
+    server.send_request(req).await;
+    let res = server.recv_notification().await;
+    assert_eq!(res.method(), "window/logMessage");
+
+    let req = build_did_open("module A {\nlet a:logic=1;\nlet b:logic=1;\n}\n");
+    server.send_request(req).await;
+
+    let res = server.recv_notification().await;
+    assert_eq!(res.method(), "window/logMessage");
+    let res = server.recv_notification().await;
+    assert_eq!(res.method(), "textDocument/publishDiagnostics");
+
+    let mut path = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    path.pop();
+    path.pop();
+    path.push("test.veryl");
+    let params = DocumentRangeFormattingParams {
+        text_document: TextDocumentIdentifier {
+            uri: Url::from_file_path(path).unwrap(),
+        },
+        range: Range::new(Position::new(2, 0), Position::new(2, 5)),
+        options: FormattingOptions::default(),
+        work_done_progress_params: WorkDoneProgressParams::default(),
+    };
+    let req = Request::build("textDocument/rangeFormatting")
+        .params(json!(params))
+        .id(2)
+        .finish();
+    server.send_request(req).await;
+
+    let res = server.recv_response().await;
+    dbg!(&res);
+    let (_, result) = res.into_parts();
+    let edits: Vec<TextEdit> = serde_json::from_value(result.unwrap()).unwrap();
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0].range,
+        Range::new(Position::new(2, 0), Position::new(3, 0))
+    );
+    assert_eq!(edits[0].new_text, "    let b: logic = 1;\n");
+}
@@ -18,11 +18,15 @@ pub struct Build {
     pub reset_low_suffix: Option<String>,
     #[serde(default)]
     pub filelist_type: FilelistType,
+    #[serde(default)]
+    pub filelist_types: Vec<FilelistType>,
     #[serde(default = "default_source")]
     pub source: PathBuf,
     #[serde(default = "default_sources")]
     pub sources: Vec<PathBuf>,
     #[serde(default)]
+    pub tops: Vec<String>,
+    #[serde(default)]
     pub target: Target,
     #[serde(default)]
     pub implicit_parameter_types: Vec<BuiltinType>,
@@ -81,8 +85,7 @@ fn default_evaluate_array_limit() -> usize {
 }
 
 fn default_incremental() -> bool {
-    // TODO false by default until stabilizing incremental
-    false
+    true
 }
 
 impl Default for Build {
@@ -122,6 +125,42 @@ pub enum FilelistType {
     Relative,
     #[serde(rename = "flgen")]
     Flgen,
+    #[serde(rename = "vivado")]
+    Vivado,
+    #[serde(rename = "quartus")]
+    Quartus,
+    #[serde(rename = "verilator")]
+    Verilator,
+    #[serde(rename = "fusesoc")]
+    Fusesoc,
+}
+
+impl FilelistType {
+    pub fn extension(self) -> &'static str {
+        match self {
+            FilelistType::Absolute => "f",
+            FilelistType::Relative => "f",
+            FilelistType::Flgen => "list.rb",
+            FilelistType::Vivado => "tcl",
+            FilelistType::Quartus => "qsf",
+            FilelistType::Verilator => "verilator.f",
+            FilelistType::Fusesoc => "core",
+        }
+    }
+}
+
+impl Build {
+    /// Filelist types to be generated.
+    /// `filelist_type` comes first, and `filelist_types` are appended without duplication.
+    pub fn all_filelist_types(&self) -> Vec<FilelistType> {
+        let mut ret = vec![self.filelist_type];
+        for x in &self.filelist_types {
+            if !ret.contains(x) {
+                ret.push(*x);
+            }
+        }
+        ret
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
@@ -38,6 +38,26 @@ fn native_newline_str() -> &'static str {
     }
 }
 
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BraceStyle {
+    #[default]
+    SameLine,
+    NextLine,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrailingComma {
+    /// Add trailing commas to multi-line declaration lists
+    #[default]
+    Vertical,
+    /// Add trailing commas to multi-line expression lists too
+    Always,
+    /// Remove trailing commas
+    Never,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Format {
@@ -52,6 +72,21 @@ pub struct Format {
 
     #[serde(default)]
     pub newline_style: NewlineStyle,
+
+    #[serde(default)]
+    pub brace_style: BraceStyle,
+
+    #[serde(default)]
+    pub sort_imports: bool,
+
+    #[serde(default)]
+    pub top_level_blank_lines: Option<usize>,
+
+    #[serde(default)]
+    pub trailing_comma: TrailingComma,
+
+    #[serde(default)]
+    pub single_line_ports: usize,
 }
 
 impl Default for Format {
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Ipxact {
+    #[serde(default = "default_path")]
+    pub path: PathBuf,
+    #[serde(default)]
+    pub vendor: Option<String>,
+    #[serde(default)]
+    pub library: Option<String>,
+    #[serde(default)]
+    pub bus_interfaces: Vec<IpxactBusInterface>,
+}
+
+impl Default for Ipxact {
+    fn default() -> Self {
+        toml::from_str("").unwrap()
+    }
+}
+
+fn default_path() -> PathBuf {
+    "ipxact".into()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IpxactBusInterface {
+    pub interface: String,
+    pub modport: String,
+    pub mode: IpxactBusMode,
+    pub bus_type: String,
+    #[serde(default)]
+    pub abstraction_type: Option<String>,
+    #[serde(default)]
+    pub port_maps: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IpxactBusMode {
+    Master,
+    Slave,
+    MirroredMaster,
+    MirroredSlave,
+}
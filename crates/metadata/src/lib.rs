@@ -3,6 +3,7 @@ mod build_info;
 mod doc;
 mod format;
 mod git;
+mod ipxact;
 mod lint;
 mod lockfile;
 mod lockfile_compat;
@@ -11,21 +12,24 @@ mod metadata_error;
 mod project;
 mod pubfile;
 mod publish;
+mod regmap;
 mod test;
 #[cfg(test)]
 mod tests;
 pub use build::{Build, BuiltinType, ClockType, FilelistType, ResetType, SourceMapTarget, Target};
 pub use build_info::BuildInfo;
 pub use doc::Doc;
-pub use format::{Format, NewlineStyle};
+pub use format::{BraceStyle, Format, NewlineStyle, TrailingComma};
 pub use git::Git;
-pub use lint::{Case, Lint};
+pub use ipxact::{Ipxact, IpxactBusInterface, IpxactBusMode};
+pub use lint::{Case, Lint, LintLevel};
 pub use lockfile::{LockSource, Lockfile};
 pub use metadata::{BumpKind, Metadata, UrlPath};
 pub use metadata_error::MetadataError;
 pub use project::Project;
 pub use pubfile::{Pubfile, Release};
 pub use publish::Publish;
+pub use regmap::Regmap;
 pub use semver;
 pub use test::{SimType, Test, WaveFormFormat, WaveFormTarget};
 
@@ -7,6 +7,19 @@ use std::fmt;
 pub struct Lint {
     #[serde(default)]
     pub naming: LintNaming,
+    #[serde(default)]
+    pub missing_docs: LintLevel,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LintLevel {
+    #[default]
+    #[serde(rename = "allow")]
+    Allow,
+    #[serde(rename = "warn")]
+    Warn,
+    #[serde(rename = "deny")]
+    Deny,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -38,6 +38,8 @@ pub struct Lock {
     pub name: String,
     pub source: LockSource,
     pub dependencies: Vec<LockDependency>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
     #[serde(skip)]
     pub visible: bool,
 }
@@ -212,13 +214,26 @@ impl Lockfile {
         let mut modified = false;
 
         for lock in &locks {
-            let add = if let Some(old_locks) = old_table.get(&lock.source.to_url()) {
-                !old_locks.iter().any(|x| x.source == lock.source)
+            let old_lock = old_table
+                .get(&lock.source.to_url())
+                .and_then(|x| x.iter().find(|x| x.source == lock.source));
+
+            if let Some(old_lock) = old_lock {
+                match (&old_lock.checksum, &lock.checksum) {
+                    (Some(expected), Some(actual)) if expected != actual => {
+                        return Err(MetadataError::ChecksumMismatch {
+                            name: lock.name.clone(),
+                            expected: expected.clone(),
+                            actual: actual.clone(),
+                        });
+                    }
+                    (None, Some(_)) => {
+                        info!("Recording checksum ({})", lock.source);
+                        modified = true;
+                    }
+                    _ => (),
+                }
             } else {
-                true
-            };
-
-            if add {
                 info!("Adding dependency ({})", lock.source);
                 modified = true;
             }
@@ -378,10 +393,12 @@ impl Lockfile {
                     });
                 }
             } else {
+                let checksum = Self::get_checksum(&dependency.source)?;
                 let lock = Lock {
                     name: name.clone(),
                     source: dependency.source.clone(),
                     dependencies,
+                    checksum,
                     visible: root,
                 };
 
@@ -544,6 +561,27 @@ impl Lockfile {
         project: &str,
         version_req: &VersionReq,
     ) -> Result<(Release, PathBuf), MetadataError> {
+        let (mut pubfile, prj_path) = self.fetch_pubfile(url, project)?;
+
+        pubfile.releases.sort_by(|a, b| b.version.cmp(&a.version));
+
+        for release in &pubfile.releases {
+            if version_req.matches(&release.version) {
+                return Ok((release.clone(), prj_path));
+            }
+        }
+
+        Err(MetadataError::VersionNotFound {
+            url: url.clone(),
+            version: version_req.to_string(),
+        })
+    }
+
+    fn fetch_pubfile(
+        &mut self,
+        url: &UrlPath,
+        project: &str,
+    ) -> Result<(Pubfile, PathBuf), MetadataError> {
         let resolve_dir = veryl_path::cache_path().join("resolve");
 
         if !resolve_dir.exists() {
@@ -566,20 +604,9 @@ impl Lockfile {
         };
 
         let toml = path.join(&prj_path).join("Veryl.pub");
-        let mut pubfile = Pubfile::load(toml)?;
-
-        pubfile.releases.sort_by(|a, b| b.version.cmp(&a.version));
-
-        for release in &pubfile.releases {
-            if version_req.matches(&release.version) {
-                return Ok((release.clone(), prj_path));
-            }
-        }
+        let pubfile = Pubfile::load(toml)?;
 
-        Err(MetadataError::VersionNotFound {
-            url: url.clone(),
-            version: version_req.to_string(),
-        })
+        Ok((pubfile, prj_path))
     }
 
     fn dependency_path(
@@ -592,6 +619,100 @@ impl Lockfile {
         Ok(dependencies_dir.join(uuid.simple().encode_lower(&mut Uuid::encode_buffer())))
     }
 
+    fn get_checksum(source: &LockSource) -> Result<Option<String>, MetadataError> {
+        match source {
+            LockSource::Repository(x) if x.r#override.is_none() => {
+                let path = Self::dependency_path(&x.url, &x.path, &x.revision)?;
+                Ok(Some(Self::gen_checksum(&path.join(&x.path))?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Calculate the hash of the source tree except `.git`
+    fn gen_checksum(path: &Path) -> Result<String, MetadataError> {
+        let mut hasher = blake3::Hasher::new();
+
+        let entries = WalkDir::new(path)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|x| x.file_name() != ".git");
+
+        for entry in entries {
+            let entry = entry.map_err(|x| {
+                let path = x.path().unwrap_or(path).to_path_buf();
+                MetadataError::file_io(x.into(), &path)
+            })?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let rel = entry.path().strip_prefix(path).unwrap();
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            let text =
+                fs::read(entry.path()).map_err(|x| MetadataError::file_io(x, entry.path()))?;
+
+            hasher.update(rel.as_bytes());
+            hasher.update(&[0]);
+            hasher.update(&(text.len() as u64).to_le_bytes());
+            hasher.update(&text);
+        }
+
+        Ok(hasher.finalize().to_hex().to_string())
+    }
+
+    /// Check that locked dependencies are not modified from the recorded state.
+    ///
+    /// The returned errors are reported by the caller without stopping at the first one.
+    pub fn verify(&mut self) -> Result<Vec<MetadataError>, MetadataError> {
+        let mut locks: Vec<_> = self.lock_table.values().flatten().cloned().collect();
+        locks.sort_by(|x, y| x.name.cmp(&y.name));
+
+        let mut ret = Vec::new();
+
+        for lock in &locks {
+            let LockSource::Repository(x) = &lock.source else {
+                continue;
+            };
+
+            let (pubfile, _) = self.fetch_pubfile(&x.url, &x.project)?;
+            let release = pubfile.releases.iter().find(|r| r.version == x.version);
+
+            match release {
+                Some(release) if release.revision != x.revision => {
+                    ret.push(MetadataError::RevisionChanged {
+                        name: lock.name.clone(),
+                        version: x.version.clone(),
+                        locked: x.revision.clone(),
+                        upstream: release.revision.clone(),
+                    });
+                }
+                None => {
+                    ret.push(MetadataError::VersionNotFound {
+                        url: x.url.clone(),
+                        version: x.version.to_string(),
+                    });
+                }
+                _ => (),
+            }
+
+            if let Some(expected) = &lock.checksum {
+                self.get_metadata(&lock.source)?;
+                if let Some(actual) = Self::get_checksum(&lock.source)?
+                    && &actual != expected
+                {
+                    ret.push(MetadataError::ChecksumMismatch {
+                        name: lock.name.clone(),
+                        expected: expected.clone(),
+                        actual,
+                    });
+                }
+            }
+        }
+
+        Ok(ret)
+    }
+
     fn get_metadata(&self, source: &LockSource) -> Result<Metadata, MetadataError> {
         // try to load from local path
         let path = match source {
@@ -765,6 +886,7 @@ impl Lockfile {
                 name: lock.name,
                 source,
                 dependencies,
+                checksum: None,
                 visible: false,
             };
 
@@ -3,11 +3,13 @@ use crate::build_info::BuildInfo;
 use crate::doc::Doc;
 use crate::format::Format;
 use crate::git::Git;
+use crate::ipxact::Ipxact;
 use crate::lint::Lint;
 use crate::lockfile::Lockfile;
 use crate::project::Project;
 use crate::pubfile::{Pubfile, Release};
 use crate::publish::Publish;
+use crate::regmap::Regmap;
 use crate::test::Test;
 use crate::{FilelistType, MetadataError, SourceMapTarget};
 use log::{debug, info, warn};
@@ -48,6 +50,10 @@ pub struct Metadata {
     #[serde(default)]
     pub doc: Doc,
     #[serde(default)]
+    pub regmap: Regmap,
+    #[serde(default)]
+    pub ipxact: Ipxact,
+    #[serde(default)]
     pub test: Test,
     #[serde(default)]
     pub dependencies: HashMap<String, Dependency>,
@@ -203,6 +209,15 @@ impl Metadata {
             let _ = Expression::parse(license)?;
         }
 
+        let filelist_types = self.build.all_filelist_types();
+        for (i, x) in filelist_types.iter().enumerate() {
+            for y in &filelist_types[i + 1..] {
+                if x.extension() == y.extension() {
+                    return Err(MetadataError::FilelistConflict(*x, *y));
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -430,18 +445,29 @@ obj_dir/
     }
 
     pub fn filelist_path(&self) -> PathBuf {
-        let filelist_name = match self.build.filelist_type {
-            FilelistType::Absolute => format!("{}.f", self.project.name),
-            FilelistType::Relative => format!("{}.f", self.project.name),
-            FilelistType::Flgen => format!("{}.list.rb", self.project.name),
-        };
+        self.filelist_path_of(self.build.filelist_type)
+    }
 
+    pub fn filelist_path_of(&self, filelist_type: FilelistType) -> PathBuf {
+        let filelist_name = format!("{}.{}", self.project.name, filelist_type.extension());
         self.metadata_path.with_file_name(filelist_name)
     }
 
+    pub fn filelist_paths(&self) -> Vec<(FilelistType, PathBuf)> {
+        self.build
+            .all_filelist_types()
+            .into_iter()
+            .map(|x| (x, self.filelist_path_of(x)))
+            .collect()
+    }
+
     pub fn doc_path(&self) -> PathBuf {
         self.metadata_path.parent().unwrap().join(&self.doc.path)
     }
+
+    pub fn ipxact_path(&self) -> PathBuf {
+        self.metadata_path.parent().unwrap().join(&self.ipxact.path)
+    }
 }
 
 impl FromStr for Metadata {
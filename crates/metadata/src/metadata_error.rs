@@ -1,3 +1,4 @@
+use crate::FilelistType;
 use crate::metadata::UrlPath;
 use miette::{self, Diagnostic};
 use semver::Version;
@@ -89,6 +90,40 @@ pub enum MetadataError {
     #[error("path error")]
     Path(#[from] PathError),
 
+    #[diagnostic(
+        code(MetadataError::FilelistConflict),
+        help("these filelist types can't be generated at once")
+    )]
+    #[error("filelist types \"{0:?}\" and \"{1:?}\" are generated to the same path")]
+    FilelistConflict(FilelistType, FilelistType),
+
+    #[diagnostic(
+        code(MetadataError::ChecksumMismatch),
+        help("the dependency cache may be modified; remove it and retry")
+    )]
+    #[error(
+        "checksum of dependency \"{name}\" is mismatched (expected: {expected}, actual: {actual})"
+    )]
+    ChecksumMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[diagnostic(
+        code(MetadataError::RevisionChanged),
+        help("the release tag may be force-pushed in the upstream repository")
+    )]
+    #[error(
+        "revision of dependency \"{name}\" @ {version} is changed (locked: {locked}, upstream: {upstream})"
+    )]
+    RevisionChanged {
+        name: String,
+        version: Version,
+        locked: String,
+        upstream: String,
+    },
+
     #[diagnostic(code(MetadataError::MissingVersion), help(""))]
     #[error("Version field is required in Veryl.toml to publish")]
     MissingVersion,
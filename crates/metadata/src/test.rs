@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Test {
     #[serde(default)]
@@ -20,6 +20,21 @@ pub struct Test {
     pub waveform_format: WaveFormFormat,
     #[serde(default)]
     pub include_files: Vec<PathBuf>,
+    /// Wall-clock timeout of each native test in seconds; 0 disables it
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    pub max_cycles: Option<u64>,
+    pub max_time: Option<u64>,
+}
+
+fn default_timeout() -> u64 {
+    600
+}
+
+impl Default for Test {
+    fn default() -> Self {
+        toml::from_str("").unwrap()
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
@@ -23,6 +23,9 @@ target = {type = "source"}
 
 [format]
 indent_width = 4
+
+[test]
+max_cycles = 100000
 "#;
 
 const MAIN_TOML: &'static str = r#"
@@ -182,7 +185,11 @@ fn check_toml() {
     assert!(metadata.build.reset_high_suffix.is_none());
     assert!(metadata.build.reset_low_prefix.is_none());
     assert_eq!(metadata.build.reset_low_suffix.unwrap(), "_n");
+    assert!(metadata.build.incremental);
     assert_eq!(metadata.format.indent_width, 4);
+    assert_eq!(metadata.test.timeout, 600);
+    assert_eq!(metadata.test.max_cycles, Some(100000));
+    assert!(metadata.test.max_time.is_none());
 }
 
 #[test]
@@ -359,3 +366,76 @@ fn lockfile() {
 
     let _ = lockfile.clear_cache();
 }
+
+#[test]
+fn lockfile_checksum() {
+    let (metadata, _tempdir) = create_metadata_multi();
+    let mut lockfile = Lockfile::new(&metadata).unwrap();
+
+    for locks in lockfile.lock_table.values() {
+        for lock in locks {
+            match lock.source {
+                LockSource::Repository(_) => assert!(lock.checksum.is_some()),
+                LockSource::Path(_) => assert!(lock.checksum.is_none()),
+            }
+        }
+    }
+
+    assert!(lockfile.verify().unwrap().is_empty());
+
+    let sub1 = lockfile
+        .lock_table
+        .values_mut()
+        .find_map(|x| x.iter_mut().find(|x| x.name == "sub1"))
+        .unwrap();
+    sub1.checksum = Some("0".repeat(64));
+
+    let errors = lockfile.verify().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        errors[0],
+        MetadataError::ChecksumMismatch { ref name, .. } if name == "sub1"
+    ));
+
+    let _ = lockfile.clear_cache();
+}
+
+#[test]
+fn filelist_types() {
+    let (mut metadata, _tempdir) = create_metadata_simple();
+    metadata.build.filelist_type = FilelistType::Relative;
+    metadata.build.filelist_types = vec![
+        FilelistType::Vivado,
+        FilelistType::Quartus,
+        FilelistType::Fusesoc,
+        FilelistType::Vivado,
+    ];
+    assert!(metadata.check().is_ok());
+
+    let paths: Vec<_> = metadata
+        .filelist_paths()
+        .into_iter()
+        .map(|(_, x)| x.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(paths, ["test.f", "test.tcl", "test.qsf", "test.core"]);
+
+    metadata.build.filelist_type = FilelistType::Absolute;
+    metadata.build.filelist_types = vec![FilelistType::Verilator, FilelistType::Flgen];
+    assert!(metadata.check().is_ok());
+
+    let paths: Vec<_> = metadata
+        .filelist_paths()
+        .into_iter()
+        .map(|(_, x)| x.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(paths, ["test.f", "test.verilator.f", "test.list.rb"]);
+
+    metadata.build.filelist_types = vec![FilelistType::Relative];
+    assert!(matches!(
+        metadata.check(),
+        Err(MetadataError::FilelistConflict(
+            FilelistType::Absolute,
+            FilelistType::Relative
+        ))
+    ));
+}
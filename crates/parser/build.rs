@@ -165,6 +165,22 @@ fn generate_token_type() {
     fs::write(&out_path, code).expect("Failed to write generated file");
 }
 
+#[cfg(feature = "build")]
+fn generate_keyword() {
+    use std::fs;
+
+    let text = fs::read_to_string("veryl.par").unwrap();
+    let mut keywords = "pub const KEYWORDS: &[&str] = &[\n".to_string();
+    for line in text.lines() {
+        if line.contains("Keyword:") {
+            let keyword = line.split("'").nth(1).unwrap();
+            keywords.push_str(&format!("    \"{keyword}\",\n"));
+        }
+    }
+    keywords.push_str("];\n");
+    fs::write("src/keyword.rs", keywords).unwrap();
+}
+
 #[cfg(feature = "build")]
 fn main() {
     use parol::parol_runtime::Report;
@@ -197,6 +213,7 @@ fn main() {
     if generate_parser {
         println!("cargo:warning=veryl.par was changed");
         generate_token_type();
+        generate_keyword();
 
         let now = Instant::now();
 
@@ -0,0 +1,445 @@
+//! Ahead-of-time compilation of a simulation model into a native object.
+//!
+//! The object contains the JIT functions of a top module compiled as position
+//! independent code, and entry functions evaluating comb logic and events.
+//! It is linked with [`RUNTIME`], which provides the helper functions of wide
+//! operations, and is driven through the generated C header.
+
+use crate::HashMap;
+use crate::cranelift::{RelocatableCode, host_isa};
+use crate::ir::{Config, Event, Ir, Statement, VarId, build_ir_relocatable};
+use crate::simulator_error::SimulatorError;
+use cranelift::codegen::ir::{AbiParam, FuncRef, Function, MemFlags, Signature, UserFuncName};
+use cranelift::frontend::{FunctionBuilder, FunctionBuilderContext};
+use cranelift::prelude::types::{I8, I16, I32, I64};
+use cranelift::prelude::{InstBuilder, Value};
+use cranelift_module::{
+    DataDescription, FuncId, Linkage, Module, ModuleError, ModuleReloc, ModuleRelocTarget,
+    default_libcall_names,
+};
+use cranelift_object::{ObjectBuilder, ObjectModule};
+use std::fmt::Write;
+use veryl_analyzer::ir as air;
+use veryl_parser::resource_table::StrId;
+
+/// C source of the runtime linked with native objects.
+pub const RUNTIME: &str = include_str!("aot/runtime.c");
+
+/// File name of [`RUNTIME`]
+pub const RUNTIME_FILE: &str = "veryl_runtime.c";
+
+/// A native simulation model of a top module
+pub struct NativeModel {
+    /// Prefix of symbols and C declarations (e.g. `veryl_Top`)
+    pub prefix: String,
+    /// Relocatable object file
+    pub object: Vec<u8>,
+    /// C header declaring the model
+    pub header: String,
+}
+
+/// A call of a compiled function with ff/comb base pointers adjusted by offsets
+struct Call {
+    callee: usize,
+    ff_offset: i64,
+    comb_offset: i64,
+}
+
+/// An entry function exported from the object
+struct Entry {
+    name: String,
+    calls: Vec<Call>,
+    /// Number of times `calls` are evaluated
+    repeat: usize,
+    /// FF commit entries performed after `calls`
+    ff_commit: Vec<(usize, usize)>,
+}
+
+/// Compile `top` into a native object.
+pub fn emit_object(
+    ir: &air::Ir,
+    top: StrId,
+    config: &Config,
+) -> Result<NativeModel, SimulatorError> {
+    let unsupported = |reason: String| SimulatorError::NativeObjectUnsupported {
+        module_name: top.to_string(),
+        reason,
+    };
+
+    let (sim_ir, code) = build_ir_relocatable(ir, top, config)?;
+    let prefix = format!("veryl_{}", identifier(&top.to_string()));
+
+    let ff_words = sim_ir.ff_values.len().div_ceil(8).max(1);
+    let comb_words = sim_ir.comb_values.len().div_ceil(8).max(1);
+    let comb_base = (ff_words * 8) as i64;
+
+    let funcs: HashMap<usize, usize> = code.iter().enumerate().map(|(i, x)| (x.func, i)).collect();
+    let calls = |stmts: &[Statement], name: &str| -> Result<Vec<Call>, SimulatorError> {
+        let ff_base = sim_ir.ff_values.as_ptr() as i64;
+        let comb_base_ptr = sim_ir.comb_values.as_ptr() as i64;
+        let mut ret = vec![];
+        for stmt in stmts {
+            let (func, ptrs) = match stmt {
+                Statement::Binary(func, ff, comb) => (func, vec![(*ff, *comb)]),
+                Statement::BinaryBatch(func, ptrs) => (func, ptrs.clone()),
+                _ => {
+                    return Err(unsupported(format!(
+                        "{} statement in {name} can't be compiled",
+                        stmt.type_name()
+                    )));
+                }
+            };
+            let Some(callee) = funcs.get(&(*func as usize)) else {
+                return Err(unsupported(format!(
+                    "compiled function in {name} is not relocatable"
+                )));
+            };
+            for (ff, comb) in ptrs {
+                ret.push(Call {
+                    callee: *callee,
+                    ff_offset: ff as i64 - ff_base,
+                    comb_offset: comb as i64 - comb_base_ptr + comb_base,
+                });
+            }
+        }
+        Ok(ret)
+    };
+
+    let mut entries = vec![Entry {
+        name: format!("{prefix}_eval_comb"),
+        calls: calls(&sim_ir.comb_statements, "comb")?,
+        repeat: sim_ir.required_comb_passes.max(1),
+        ff_commit: vec![],
+    }];
+    let mut events = vec![];
+    for (event, stmts) in &sim_ir.event_statements {
+        let name = event_name(&sim_ir, event);
+        events.push((name.clone(), format!("{prefix}_{name}")));
+        entries.push(Entry {
+            name: format!("{prefix}_{name}"),
+            calls: calls(stmts, &name)?,
+            repeat: 1,
+            ff_commit: sim_ir.ff_commit_entries.clone(),
+        });
+    }
+    events.sort();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let object = write_object(&prefix, &sim_ir, &code, &entries, ff_words).map_err(unsupported)?;
+    let header = header(&prefix, &sim_ir, &events, ff_words, comb_words);
+
+    Ok(NativeModel {
+        prefix,
+        object,
+        header,
+    })
+}
+
+/// Replace characters which can't be used in C identifiers.
+fn identifier(x: &str) -> String {
+    x.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn event_name(ir: &Ir, event: &Event) -> String {
+    let var_name = |id: &VarId| {
+        let path = ir
+            .ports
+            .iter()
+            .find(|(_, x)| *x == id)
+            .map(|(x, _)| x.to_string())
+            .or_else(|| {
+                ir.module_variables
+                    .variables
+                    .get(id)
+                    .map(|x| x.path.to_string())
+            })
+            .unwrap_or_else(|| format!("{id}"));
+        identifier(&path)
+    };
+    match event {
+        Event::Clock(id) => format!("clock_{}", var_name(id)),
+        Event::Reset(id) => format!("reset_{}", var_name(id)),
+        Event::Initial => "initial".to_string(),
+        Event::Final => "final".to_string(),
+    }
+}
+
+fn write_object(
+    prefix: &str,
+    ir: &Ir,
+    code: &[RelocatableCode],
+    entries: &[Entry],
+    ff_words: usize,
+) -> Result<Vec<u8>, String> {
+    let err = |x: ModuleError| x.to_string();
+    let builder =
+        ObjectBuilder::new(host_isa(false, true), prefix, default_libcall_names()).map_err(err)?;
+    let mut module = ObjectModule::new(builder);
+    let ptr_type = module.isa().pointer_type();
+    let call_conv = module.isa().default_call_conv();
+
+    // compiled functions take ff/comb base pointers
+    let mut func_sig = Signature::new(call_conv);
+    func_sig.params.push(AbiParam::new(ptr_type));
+    func_sig.params.push(AbiParam::new(ptr_type));
+    // signatures of helpers are not recorded in the object
+    let helper_sig = Signature::new(call_conv);
+
+    let mut helpers: HashMap<&str, FuncId> = HashMap::default();
+    let mut funcs = vec![];
+    for (i, x) in code.iter().enumerate() {
+        let id = module
+            .declare_function(&format!("{prefix}_func{i}"), Linkage::Local, &func_sig)
+            .map_err(err)?;
+        let mut relocs = vec![];
+        for r in &x.relocs {
+            let helper = match helpers.get(r.symbol) {
+                Some(x) => *x,
+                None => {
+                    let helper = module
+                        .declare_function(r.symbol, Linkage::Import, &helper_sig)
+                        .map_err(err)?;
+                    helpers.insert(r.symbol, helper);
+                    helper
+                }
+            };
+            relocs.push(ModuleReloc {
+                offset: r.offset,
+                kind: r.kind,
+                name: ModuleRelocTarget::user(0, helper.as_u32()),
+                addend: r.addend,
+            });
+        }
+        module
+            .define_function_bytes(id, 16, &x.code, &relocs)
+            .map_err(err)?;
+        funcs.push(id);
+    }
+
+    let mut entry_sig = Signature::new(call_conv);
+    entry_sig.params.push(AbiParam::new(ptr_type));
+    for entry in entries {
+        let id = module
+            .declare_function(&entry.name, Linkage::Export, &entry_sig)
+            .map_err(err)?;
+        let mut ctx = module.make_context();
+        ctx.func = build_entry(&mut module, &entry_sig, entry, &funcs);
+        module.define_function(id, &mut ctx).map_err(err)?;
+    }
+
+    for (name, values, words) in [
+        ("ff_init", &ir.ff_values, ff_words),
+        (
+            "comb_init",
+            &ir.comb_values,
+            ir.comb_values.len().div_ceil(8).max(1),
+        ),
+    ] {
+        let mut data = values.to_vec();
+        data.resize(words * 8, 0);
+        let id = module
+            .declare_data(&format!("{prefix}_{name}"), Linkage::Export, false, false)
+            .map_err(err)?;
+        let mut desc = DataDescription::new();
+        desc.define(data.into_boxed_slice());
+        desc.set_align(8);
+        module.define_data(id, &desc).map_err(err)?;
+    }
+
+    module.finish().emit().map_err(|x| x.to_string())
+}
+
+/// Build an entry function taking the pointer of a model.
+/// FF values are placed at the beginning of the model, followed by comb values.
+fn build_entry(
+    module: &mut ObjectModule,
+    sig: &Signature,
+    entry: &Entry,
+    funcs: &[FuncId],
+) -> Function {
+    let mut func = Function::with_name_signature(UserFuncName::default(), sig.clone());
+    let mut func_ctx = FunctionBuilderContext::new();
+    let mut builder = FunctionBuilder::new(&mut func, &mut func_ctx);
+
+    let block = builder.create_block();
+    builder.append_block_params_for_function_params(block);
+    builder.switch_to_block(block);
+    let ff = builder.block_params(block)[0];
+
+    let mut callees: HashMap<usize, FuncRef> = HashMap::default();
+    for _ in 0..entry.repeat {
+        for call in &entry.calls {
+            let func_ref = *callees
+                .entry(call.callee)
+                .or_insert_with(|| module.declare_func_in_func(funcs[call.callee], builder.func));
+            let ff_ptr = builder.ins().iadd_imm(ff, call.ff_offset);
+            let comb_ptr = builder.ins().iadd_imm(ff, call.comb_offset);
+            builder.ins().call(func_ref, &[ff_ptr, comb_ptr]);
+        }
+    }
+
+    for &(current, size) in &entry.ff_commit {
+        copy_bytes(&mut builder, ff, current + size, current, size);
+    }
+
+    builder.ins().return_(&[]);
+    builder.seal_all_blocks();
+    builder.finalize();
+    func
+}
+
+/// Copy `size` bytes from `src` to `dst` offsets of `base`.
+fn copy_bytes(builder: &mut FunctionBuilder, base: Value, src: usize, dst: usize, size: usize) {
+    let flags = MemFlags::new().with_notrap();
+    let mut pos = 0;
+    for (ty, bytes) in [(I64, 8), (I32, 4), (I16, 2), (I8, 1)] {
+        while size - pos >= bytes {
+            let value = builder.ins().load(ty, flags, base, (src + pos) as i32);
+            builder.ins().store(flags, value, base, (dst + pos) as i32);
+            pos += bytes;
+        }
+    }
+}
+
+/// Generate the C header of a native model.
+fn header(
+    prefix: &str,
+    ir: &Ir,
+    events: &[(String, String)],
+    ff_words: usize,
+    comb_words: usize,
+) -> String {
+    let guard = format!("{}_H", prefix.to_uppercase());
+    let macro_prefix = prefix.to_uppercase();
+    let ff_range = ir.ff_values.as_ptr_range();
+    let comb_ptr = ir.comb_values.as_ptr();
+
+    let mut ret = String::new();
+    let _ = writeln!(
+        ret,
+        "/* Native simulation model of {} generated by Veryl. Do not edit.\n \
+         *\n \
+         * Link the object with {RUNTIME_FILE}.\n \
+         * After changing inputs, call {prefix}_eval_comb before evaluating an\n \
+         * event or reading outputs. Evaluating an event updates FFs, so call\n \
+         * {prefix}_eval_comb again to update comb logic depending on them.\n \
+         */",
+        ir.name
+    );
+    let _ = writeln!(ret, "#ifndef {guard}\n#define {guard}\n");
+    let _ = writeln!(
+        ret,
+        "#include <stddef.h>\n#include <stdint.h>\n#include <string.h>\n"
+    );
+    let _ = writeln!(ret, "#ifdef __cplusplus\nextern \"C\" {{\n#endif\n");
+
+    let _ = writeln!(
+        ret,
+        "typedef struct {{\n    uint64_t ff[{ff_words}];\n    uint64_t comb[{comb_words}];\n}} {prefix};\n"
+    );
+    let _ = writeln!(
+        ret,
+        "extern const uint8_t {prefix}_ff_init[{}];",
+        ff_words * 8
+    );
+    let _ = writeln!(
+        ret,
+        "extern const uint8_t {prefix}_comb_init[{}];\n",
+        comb_words * 8
+    );
+    let _ = writeln!(ret, "/* Settle comb logic */");
+    let _ = writeln!(ret, "void {prefix}_eval_comb({prefix} *m);");
+    for (event, symbol) in events {
+        let _ = writeln!(ret, "/* Evaluate event `{event}` */");
+        let _ = writeln!(ret, "void {symbol}({prefix} *m);");
+    }
+    let _ = writeln!(
+        ret,
+        "\n/* Initialize a model */\n\
+         static inline void {prefix}_init({prefix} *m) {{\n    \
+         memcpy(m->ff, {prefix}_ff_init, sizeof(m->ff));\n    \
+         memcpy(m->comb, {prefix}_comb_init, sizeof(m->comb));\n}}"
+    );
+
+    let mut ports: Vec<_> = ir.ports.iter().collect();
+    ports.sort_by_key(|(path, _)| path.to_string());
+    for (path, id) in ports {
+        let Some(var) = ir.module_variables.variables.get(id) else {
+            continue;
+        };
+        let name = identifier(&path.to_string());
+        let offsets: Vec<_> = var
+            .current_values
+            .iter()
+            .map(|x| {
+                let x = *x as *const u8;
+                if ff_range.contains(&x) {
+                    ("ff", x as usize - ff_range.start as usize)
+                } else {
+                    ("comb", x as usize - comb_ptr as usize)
+                }
+            })
+            .collect();
+        let Some((buffer, offset)) = offsets.first() else {
+            continue;
+        };
+        let (index_param, offset) = if offsets.len() == 1 {
+            ("", offset.to_string())
+        } else {
+            let list: Vec<_> = offsets.iter().map(|(_, x)| x.to_string()).collect();
+            let _ = writeln!(
+                ret,
+                "\nstatic const size_t {prefix}_{name}_offsets[{}] = {{{}}};",
+                list.len(),
+                list.join(", ")
+            );
+            (", size_t i", format!("{prefix}_{name}_offsets[i]"))
+        };
+        let index_arg = if index_param.is_empty() { "" } else { ", i" };
+        let nb = var.native_bytes;
+
+        let _ = writeln!(ret, "\n/* {} {name} */", var.r#type);
+        let _ = writeln!(
+            ret,
+            "#define {macro_prefix}_{}_WIDTH {}",
+            name.to_uppercase(),
+            var.width
+        );
+        let _ = writeln!(
+            ret,
+            "static inline uint8_t *{prefix}_ptr_{name}({prefix} *m{index_param}) {{\n    \
+             return (uint8_t *)m->{buffer} + {offset};\n}}"
+        );
+        if var.width <= 64 {
+            let mask = if var.width == 64 {
+                u64::MAX
+            } else {
+                (1u64 << var.width) - 1
+            };
+            let clear_mask = if ir.use_4state {
+                format!("\n    memset(p + {nb}, 0, {nb});")
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                ret,
+                "static inline uint64_t {prefix}_get_{name}({prefix} *m{index_param}) {{\n    \
+                 uint64_t v = 0;\n    \
+                 memcpy(&v, {prefix}_ptr_{name}(m{index_arg}), {nb});\n    \
+                 return v;\n}}"
+            );
+            let _ = writeln!(
+                ret,
+                "static inline void {prefix}_set_{name}({prefix} *m{index_param}, uint64_t v) {{\n    \
+                 uint8_t *p = {prefix}_ptr_{name}(m{index_arg});\n    \
+                 v &= 0x{mask:x}ull;\n    \
+                 memcpy(p, &v, {nb});{clear_mask}\n}}"
+            );
+        }
+    }
+
+    let _ = writeln!(ret, "\n#ifdef __cplusplus\n}}\n#endif\n\n#endif");
+    ret
+}
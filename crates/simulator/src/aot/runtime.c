@@ -0,0 +1,275 @@
+/* Runtime of native simulation models generated by Veryl.
+ *
+ * Helper functions for wide (>128-bit) arithmetic operations called from
+ * the model. All data is stored as little-endian uint64_t chunks which may
+ * not be 8-byte aligned. This is a port of `wide_ops.rs` of the simulator,
+ * and the `runtime_parity` test checks that both give the same results.
+ */
+
+#include <stdint.h>
+#include <string.h>
+
+static inline uint32_t nw(uint32_t nb) { return nb / 8; }
+
+static inline uint64_t rd(const uint8_t *ptr, uint32_t i) {
+    uint64_t v;
+    memcpy(&v, ptr + (uint64_t)i * 8, 8);
+    return v;
+}
+
+static inline void wr(uint8_t *ptr, uint32_t i, uint64_t v) {
+    memcpy(ptr + (uint64_t)i * 8, &v, 8);
+}
+
+static inline uint32_t unpack_nb(uint32_t packed) { return packed & 0xFFFF; }
+static inline uint32_t unpack_width(uint32_t packed) { return packed >> 16; }
+
+/* ── Bitwise binary ops ───────────────────────────────────────────── */
+
+void veryl_wide_band(uint8_t *dst, const uint8_t *a, const uint8_t *b, uint32_t nb) {
+    for (uint32_t i = 0; i < nw(nb); i++) wr(dst, i, rd(a, i) & rd(b, i));
+}
+
+void veryl_wide_bor(uint8_t *dst, const uint8_t *a, const uint8_t *b, uint32_t nb) {
+    for (uint32_t i = 0; i < nw(nb); i++) wr(dst, i, rd(a, i) | rd(b, i));
+}
+
+void veryl_wide_bxor(uint8_t *dst, const uint8_t *a, const uint8_t *b, uint32_t nb) {
+    for (uint32_t i = 0; i < nw(nb); i++) wr(dst, i, rd(a, i) ^ rd(b, i));
+}
+
+void veryl_wide_bxor_not(uint8_t *dst, const uint8_t *a, const uint8_t *b, uint32_t nb) {
+    for (uint32_t i = 0; i < nw(nb); i++) wr(dst, i, ~(rd(a, i) ^ rd(b, i)));
+}
+
+void veryl_wide_band_not(uint8_t *dst, const uint8_t *a, const uint8_t *b, uint32_t nb) {
+    for (uint32_t i = 0; i < nw(nb); i++) wr(dst, i, rd(a, i) & ~rd(b, i));
+}
+
+/* ── Bitwise unary ops ────────────────────────────────────────────── */
+
+void veryl_wide_bnot(uint8_t *dst, const uint8_t *a, uint32_t nb) {
+    for (uint32_t i = 0; i < nw(nb); i++) wr(dst, i, ~rd(a, i));
+}
+
+/* ── Arithmetic ───────────────────────────────────────────────────── */
+
+void veryl_wide_add(uint8_t *dst, const uint8_t *a, const uint8_t *b, uint32_t nb) {
+    uint64_t carry = 0;
+    for (uint32_t i = 0; i < nw(nb); i++) {
+        uint64_t x = rd(a, i);
+        uint64_t sum1 = x + rd(b, i);
+        uint64_t c1 = sum1 < x;
+        uint64_t sum2 = sum1 + carry;
+        uint64_t c2 = sum2 < sum1;
+        wr(dst, i, sum2);
+        carry = c1 + c2;
+    }
+}
+
+void veryl_wide_sub(uint8_t *dst, const uint8_t *a, const uint8_t *b, uint32_t nb) {
+    uint64_t borrow = 0;
+    for (uint32_t i = 0; i < nw(nb); i++) {
+        uint64_t x = rd(a, i);
+        uint64_t y = rd(b, i);
+        uint64_t diff1 = x - y;
+        uint64_t b1 = x < y;
+        uint64_t diff2 = diff1 - borrow;
+        uint64_t b2 = diff1 < borrow;
+        wr(dst, i, diff2);
+        borrow = b1 + b2;
+    }
+}
+
+void veryl_wide_mul(uint8_t *dst, const uint8_t *a, const uint8_t *b, uint32_t nb) {
+    uint32_t n = nw(nb);
+    for (uint32_t i = 0; i < n; i++) wr(dst, i, 0);
+    for (uint32_t i = 0; i < n; i++) {
+        uint64_t ai = rd(a, i);
+        if (ai == 0) continue;
+        unsigned __int128 carry = 0;
+        for (uint32_t j = 0; i + j < n; j++) {
+            unsigned __int128 prod =
+                (unsigned __int128)ai * rd(b, j) + rd(dst, i + j) + carry;
+            wr(dst, i + j, (uint64_t)prod);
+            carry = prod >> 64;
+        }
+    }
+}
+
+void veryl_wide_negate(uint8_t *dst, const uint8_t *a, uint32_t nb) {
+    uint64_t carry = 1;
+    for (uint32_t i = 0; i < nw(nb); i++) {
+        uint64_t sum = ~rd(a, i) + carry;
+        carry = carry && sum == 0;
+        wr(dst, i, sum);
+    }
+}
+
+/* ── Memory ───────────────────────────────────────────────────────── */
+
+void veryl_wide_copy(uint8_t *dst, const uint8_t *src, uint32_t nb) {
+    for (uint32_t i = 0; i < nw(nb); i++) wr(dst, i, rd(src, i));
+}
+
+/* ── Comparisons ──────────────────────────────────────────────────── */
+
+int64_t veryl_wide_eq(const uint8_t *a, const uint8_t *b, uint32_t nb) {
+    for (uint32_t i = 0; i < nw(nb); i++) {
+        if (rd(a, i) != rd(b, i)) return 0;
+    }
+    return 1;
+}
+
+int64_t veryl_wide_ne(const uint8_t *a, const uint8_t *b, uint32_t nb) {
+    for (uint32_t i = 0; i < nw(nb); i++) {
+        if (rd(a, i) != rd(b, i)) return 1;
+    }
+    return 0;
+}
+
+/* Unsigned compare: returns -1 if a < b, 0 if a == b, 1 if a > b. */
+int64_t veryl_wide_ucmp(const uint8_t *a, const uint8_t *b, uint32_t nb) {
+    for (uint32_t i = nw(nb); i-- > 0;) {
+        uint64_t ai = rd(a, i);
+        uint64_t bi = rd(b, i);
+        if (ai < bi) return -1;
+        if (ai > bi) return 1;
+    }
+    return 0;
+}
+
+/* Signed compare using the sign bit at position (width-1). */
+int64_t veryl_wide_scmp(const uint8_t *a, const uint8_t *b, uint32_t packed_nb_width) {
+    uint32_t nb = unpack_nb(packed_nb_width);
+    uint32_t width = unpack_width(packed_nb_width);
+    if (width == 0 || nb == 0) return 0;
+    uint32_t sign_word = (width - 1) / 64;
+    uint32_t sign_bit = (width - 1) % 64;
+    uint64_t a_sign = (rd(a, sign_word) >> sign_bit) & 1;
+    uint64_t b_sign = (rd(b, sign_word) >> sign_bit) & 1;
+    if (a_sign != b_sign) return a_sign == 1 ? -1 : 1;
+    return veryl_wide_ucmp(a, b, nb);
+}
+
+/* ── Shifts ───────────────────────────────────────────────────────── */
+
+void veryl_wide_shl(uint8_t *dst, const uint8_t *a, uint64_t amount, uint32_t nb) {
+    uint32_t n = nw(nb);
+    uint64_t word_shift = amount / 64;
+    uint32_t bit_shift = amount % 64;
+
+    if (word_shift >= n) {
+        for (uint32_t i = 0; i < n; i++) wr(dst, i, 0);
+        return;
+    }
+
+    for (uint32_t i = n; i-- > 0;) {
+        int64_t src_idx = (int64_t)i - (int64_t)word_shift;
+        uint64_t lo = src_idx >= 0 ? rd(a, (uint32_t)src_idx) : 0;
+        uint64_t hi = src_idx > 0 ? rd(a, (uint32_t)src_idx - 1) : 0;
+        wr(dst, i, bit_shift == 0 ? lo : (lo << bit_shift) | (hi >> (64 - bit_shift)));
+    }
+}
+
+void veryl_wide_lshr(uint8_t *dst, const uint8_t *a, uint64_t amount, uint32_t nb) {
+    uint32_t n = nw(nb);
+    uint64_t word_shift = amount / 64;
+    uint32_t bit_shift = amount % 64;
+
+    if (word_shift >= n) {
+        for (uint32_t i = 0; i < n; i++) wr(dst, i, 0);
+        return;
+    }
+
+    for (uint32_t i = 0; i < n; i++) {
+        uint64_t src_idx = i + word_shift;
+        uint64_t lo = src_idx < n ? rd(a, (uint32_t)src_idx) : 0;
+        uint64_t hi = src_idx + 1 < n ? rd(a, (uint32_t)src_idx + 1) : 0;
+        wr(dst, i, bit_shift == 0 ? lo : (lo >> bit_shift) | (hi << (64 - bit_shift)));
+    }
+}
+
+/* Arithmetic shift right: fills with sign bit at position (width-1). */
+void veryl_wide_ashr(uint8_t *dst, const uint8_t *a, uint64_t amount, uint32_t packed_nb_width) {
+    uint32_t nb = unpack_nb(packed_nb_width);
+    uint32_t width = unpack_width(packed_nb_width);
+    if (nb == 0 || width == 0) return;
+    uint32_t n = nw(nb);
+    uint32_t sign_word = (width - 1) / 64;
+    uint32_t sign_bit = (width - 1) % 64;
+    uint64_t sign = (rd(a, sign_word) >> sign_bit) & 1;
+
+    veryl_wide_lshr(dst, a, amount, nb);
+
+    if (sign == 1 && amount > 0) {
+        uint64_t fill_start = amount >= width ? 0 : width - amount;
+        for (uint64_t bit_pos = fill_start; bit_pos < width; bit_pos++) {
+            uint32_t word = (uint32_t)(bit_pos / 64);
+            uint32_t bit = bit_pos % 64;
+            if (word < n) wr(dst, word, rd(dst, word) | ((uint64_t)1 << bit));
+        }
+    }
+}
+
+/* ── Reductions ───────────────────────────────────────────────────── */
+
+int64_t veryl_wide_is_nonzero(const uint8_t *a, uint32_t nb) {
+    for (uint32_t i = 0; i < nw(nb); i++) {
+        if (rd(a, i) != 0) return 1;
+    }
+    return 0;
+}
+
+/* Check if all bits in [0..width) are set. */
+int64_t veryl_wide_is_all_ones(const uint8_t *a, uint32_t packed_nb_width) {
+    uint32_t width = unpack_width(packed_nb_width);
+    if (width == 0) return 1;
+    uint32_t full_words = width / 64;
+    uint32_t remaining = width % 64;
+    for (uint32_t i = 0; i < full_words; i++) {
+        if (rd(a, i) != UINT64_MAX) return 0;
+    }
+    if (remaining > 0) {
+        uint64_t mask = ((uint64_t)1 << remaining) - 1;
+        if ((rd(a, full_words) & mask) != mask) return 0;
+    }
+    return 1;
+}
+
+/* Parity of popcount (reduction XOR): returns 0 or 1. */
+int64_t veryl_wide_popcnt_parity(const uint8_t *a, uint32_t nb) {
+    uint32_t total = 0;
+    for (uint32_t i = 0; i < nw(nb); i++) total ^= (uint32_t)__builtin_popcountll(rd(a, i));
+    return total & 1;
+}
+
+/* Apply a width mask: clear bits >= width in dst. */
+void veryl_wide_apply_mask(uint8_t *dst, const uint8_t *unused, uint32_t packed_nb_width) {
+    (void)unused;
+    uint32_t nb = unpack_nb(packed_nb_width);
+    uint32_t width = unpack_width(packed_nb_width);
+    if (width == 0 || nb == 0) return;
+    uint32_t n = nw(nb);
+    uint32_t full_words = width / 64;
+    uint32_t remaining = width % 64;
+    if (remaining > 0 && full_words < n) {
+        uint64_t mask = ((uint64_t)1 << remaining) - 1;
+        wr(dst, full_words, rd(dst, full_words) & mask);
+    }
+    for (uint32_t i = full_words + (remaining > 0 ? 1 : 0); i < n; i++) wr(dst, i, 0);
+}
+
+/* Fill dst with all-ones for width bits, zero above. */
+void veryl_wide_fill_ones(uint8_t *dst, const uint8_t *unused, uint32_t packed_nb_width) {
+    (void)unused;
+    uint32_t nb = unpack_nb(packed_nb_width);
+    uint32_t width = unpack_width(packed_nb_width);
+    if (nb == 0) return;
+    uint32_t n = nw(nb);
+    uint32_t full_words = width / 64;
+    uint32_t remaining = width % 64;
+    for (uint32_t i = 0; i < full_words && i < n; i++) wr(dst, i, UINT64_MAX);
+    if (remaining > 0 && full_words < n) wr(dst, full_words, ((uint64_t)1 << remaining) - 1);
+    for (uint32_t i = full_words + (remaining > 0 ? 1 : 0); i < n; i++) wr(dst, i, 0);
+}
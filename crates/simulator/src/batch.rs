@@ -0,0 +1,229 @@
+//! Batched simulation of independent lanes.
+//!
+//! Each lane is a separate `Simulator` instantiated from a single
+//! `ProtoModule`, so the design is analyzed and JIT-compiled once and each
+//! lane only owns its value buffers. Values of lanes are not interleaved in a
+//! SIMD layout; lanes are evaluated one after another, or distributed among
+//! the persistent worker threads. This is intended for regressions which run
+//! the same design with many stimulus sets, e.g. the seed sweep of
+//! `veryl test --seeds`, which runs a test with `#[seed(var)]` on one lane per
+//! seed.
+
+use crate::ir::{
+    Config, Event, Ir, ProtoModuleCache, Stages, Value, VarPath, WorkerPool, build_ir_cached,
+    write_native_value,
+};
+use crate::simulator::Simulator;
+use crate::simulator_error::SimulatorError;
+use crate::testbench::{TestLimits, TestResult, run_simulator_testbench};
+use crate::wave_dumper::WaveDumper;
+use veryl_analyzer::ir as air;
+use veryl_analyzer::value::MaskCache;
+use veryl_parser::resource_table::StrId;
+
+pub struct BatchSimulator {
+    lanes: Vec<Simulator>,
+    workers: Option<WorkerPool>,
+    /// A single stage whose chunk `i` is the lanes stepped by thread `i`
+    stages: Stages,
+    mask_cache: MaskCache,
+}
+
+impl BatchSimulator {
+    /// Build `lanes` independent simulations of `top`.
+    ///
+    /// If `config.threads` is greater than 1, lanes are distributed among the
+    /// threads instead of partitioning the design.
+    pub fn new(
+        ir: &air::Ir,
+        top: StrId,
+        config: &Config,
+        lanes: usize,
+    ) -> Result<Self, SimulatorError> {
+        let lane_config = Config {
+            threads: 1,
+            ..config.clone()
+        };
+        let mut cache = ProtoModuleCache::default();
+        let irs = (0..lanes.max(1))
+            .map(|_| build_ir_cached(ir, top, &lane_config, &mut cache))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::from_irs(irs, None, config.threads))
+    }
+
+    /// Build a batch whose lanes are simulations of `irs`.
+    ///
+    /// `irs` should be built from the same top module, e.g. by
+    /// `build_ir_cached` with a shared cache. The waveform is dumped from the
+    /// first lane only.
+    pub fn from_irs(irs: Vec<Ir>, dump: Option<WaveDumper>, threads: usize) -> Self {
+        let mut dump = dump;
+        let lanes: Vec<_> = irs
+            .into_iter()
+            .map(|x| Simulator::new(x, dump.take()))
+            .collect();
+
+        let threads = threads.min(lanes.len());
+        let workers = (threads > 1).then(|| WorkerPool::new(threads));
+        let chunk = lanes.len().div_ceil(threads.max(1));
+        let stages = vec![
+            (0..lanes.len())
+                .step_by(chunk.max(1))
+                .map(|x| x..(x + chunk).min(lanes.len()))
+                .collect(),
+        ];
+
+        Self {
+            lanes,
+            workers,
+            stages,
+            mask_cache: MaskCache::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lanes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lanes.is_empty()
+    }
+
+    pub fn lane(&mut self, index: usize) -> &mut Simulator {
+        &mut self.lanes[index]
+    }
+
+    pub fn lanes(&mut self) -> &mut [Simulator] {
+        &mut self.lanes
+    }
+
+    pub fn get_clock(&self, port: &str) -> Option<Event> {
+        self.lanes[0].get_clock(port)
+    }
+
+    pub fn get_reset(&self, port: &str) -> Option<Event> {
+        self.lanes[0].get_reset(port)
+    }
+
+    /// Drive `port` of each lane by the corresponding value of `values`.
+    pub fn set(&mut self, port: &str, values: &[Value]) {
+        for (sim, value) in self.lanes.iter_mut().zip(values) {
+            sim.set(port, value.clone());
+        }
+    }
+
+    /// Drive `port` of all lanes by the same value.
+    pub fn set_all(&mut self, port: &str, value: Value) {
+        for sim in &mut self.lanes {
+            sim.set(port, value.clone());
+        }
+    }
+
+    /// Get values of `port` of all lanes.
+    pub fn get(&mut self, port: &str) -> Option<Vec<Value>> {
+        self.lanes.iter_mut().map(|x| x.get(port)).collect()
+    }
+
+    /// Set the seed variable `var` of lane `i` to `base + i`.
+    /// Returns false if the design doesn't have the variable.
+    pub fn set_seeds(&mut self, var: StrId, base: u64) -> bool {
+        let mut ret = true;
+        for (i, sim) in self.lanes.iter_mut().enumerate() {
+            ret &= set_seed(&mut sim.ir, var, base.wrapping_add(i as u64));
+            sim.mark_comb_dirty();
+        }
+        ret
+    }
+
+    /// Advance all lanes by `event`.
+    pub fn step(&mut self, event: &Event) {
+        let Some(workers) = &self.workers else {
+            for sim in &mut self.lanes {
+                sim.step(event);
+            }
+            return;
+        };
+
+        let lanes = self.lanes.as_mut_ptr();
+        let eval = |i: usize, _: &mut MaskCache| {
+            // SAFETY: chunks of the stage are disjoint, so each lane is
+            // borrowed by one thread, and `run_with` returns after all
+            // threads finish while `self.lanes` is borrowed mutably.
+            unsafe { (*lanes.add(i)).step(event) };
+        };
+        workers.run_with(&eval, &self.stages, &mut self.mask_cache);
+    }
+
+    /// Run the native testbench of `module_name` with `seed + lane index`
+    /// written to the seed variable `var` given by `#[seed(var)]`.
+    ///
+    /// Each lane follows its own control flow of the testbench, so lanes are
+    /// run one after another. Returns the result of the first failed lane.
+    pub fn run_seed_sweep(
+        &mut self,
+        module_name: &str,
+        var: StrId,
+        seed: u64,
+        limits: &TestLimits,
+    ) -> Result<TestResult, SimulatorError> {
+        if !self.set_seeds(var, seed) {
+            return Err(SimulatorError::SeedVariableNotFound {
+                module_name: module_name.to_string(),
+                var_name: var.to_string(),
+            });
+        }
+
+        for (i, sim) in self.lanes.iter_mut().enumerate() {
+            let lane_seed = seed.wrapping_add(i as u64);
+            let seed_error = |msg| format!("{var} = {lane_seed}: {msg}");
+            match run_simulator_testbench(sim, module_name, limits) {
+                Ok(TestResult::Pass) => (),
+                Ok(TestResult::Fail(msg)) => return Ok(TestResult::Fail(seed_error(msg))),
+                Err(SimulatorError::TestFailed { message }) => {
+                    return Err(SimulatorError::TestFailed {
+                        message: seed_error(message),
+                    });
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(TestResult::Pass)
+    }
+}
+
+/// Whether the top module of `ir` has the seed variable `var`.
+pub fn has_seed(ir: &Ir, var: StrId) -> bool {
+    let path = VarPath::new(var);
+    ir.module_variables
+        .variables
+        .values()
+        .any(|x| x.path == path)
+}
+
+/// Write `seed` to the variable `var` of the top module of `ir`.
+/// Returns false if there is no such variable.
+pub fn set_seed(ir: &mut Ir, var: StrId, seed: u64) -> bool {
+    let path = VarPath::new(var);
+    let Some(var) = ir
+        .module_variables
+        .variables
+        .values()
+        .find(|x| x.path == path)
+    else {
+        return false;
+    };
+
+    let mut value = Value::new(seed, 64, false);
+    value.trunc(var.width);
+    for ptr in var
+        .current_values
+        .first()
+        .into_iter()
+        .chain(var.next_values.first())
+    {
+        unsafe {
+            write_native_value(*ptr, var.native_bytes, ir.use_4state, &value);
+        }
+    }
+    true
+}
@@ -22,6 +22,8 @@ pub struct Opt {
     pub dump_asm: bool,
     #[arg(long)]
     pub disable_ff_opt: bool,
+    #[arg(long, default_value_t = 1)]
+    pub threads: usize,
 }
 
 impl From<Opt> for Config {
@@ -32,6 +34,8 @@ impl From<Opt> for Config {
             dump_cranelift: value.dump_cranelift,
             dump_asm: value.dump_asm,
             disable_ff_opt: value.disable_ff_opt,
+            threads: value.threads,
+            ..Default::default()
         }
     }
 }
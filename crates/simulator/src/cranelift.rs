@@ -1,11 +1,17 @@
 use crate::ir::Context as ConvContext;
 use crate::ir::ProtoStatement;
 use crate::ir::VarOffset;
+use crate::ir::wide_fn_addrs;
+use crate::jit_cache;
 use crate::{HashMap, HashSet};
+use cranelift::codegen::binemit::Reloc;
 use cranelift::codegen::control::ControlPlane;
-use cranelift::codegen::ir::{AbiParam, Function, SigRef, Signature, StackSlotData, UserFuncName};
-use cranelift::codegen::isa::{self, CallConv};
-use cranelift::codegen::{self, settings};
+use cranelift::codegen::ir::{
+    AbiParam, ExtFuncData, ExternalName, FuncRef, Function, SigRef, Signature, StackSlotData,
+    UserExternalName, UserFuncName,
+};
+use cranelift::codegen::isa::{self, CallConv, OwnedTargetIsa};
+use cranelift::codegen::{self, FinalizedMachReloc, FinalizedRelocTarget, settings};
 use cranelift::frontend::{FunctionBuilder, FunctionBuilderContext};
 use cranelift::prelude::types::{I32, I64};
 use cranelift::prelude::*;
@@ -47,6 +53,31 @@ pub struct Context {
     /// Used for unified comb where helper functions may modify values
     /// between cached loads.
     pub disable_load_cache: bool,
+    /// Call helper functions by symbol instead of absolute address, so that
+    /// the code can be emitted as a native object.
+    pub relocatable: bool,
+    /// Imported helper functions (cached per address) if `relocatable`.
+    pub helper_funcs: HashMap<usize, FuncRef>,
+}
+
+/// Relocation of a call to a helper function.
+#[derive(Clone, Debug)]
+pub struct HelperReloc {
+    pub offset: u32,
+    pub kind: Reloc,
+    /// Symbol name of the helper function
+    pub symbol: &'static str,
+    pub addend: i64,
+}
+
+/// Position independent machine code of a JIT function, kept to emit a
+/// native object.
+#[derive(Clone, Debug)]
+pub struct RelocatableCode {
+    /// Address of the JIT-compiled copy of the function in this process
+    pub func: usize,
+    pub code: Vec<u8>,
+    pub relocs: Vec<HelperReloc>,
 }
 
 /// Get or create a SigRef for the given helper signature kind.
@@ -99,8 +130,13 @@ pub fn call_helper_void(
     args: &[Value],
 ) {
     let sig_ref = get_or_create_sig(context, builder, kind);
-    let ptr = builder.ins().iconst(I64, func_addr as i64);
-    builder.ins().call_indirect(sig_ref, ptr, args);
+    if context.relocatable {
+        let func_ref = import_helper(context, builder, sig_ref, func_addr);
+        builder.ins().call(func_ref, args);
+    } else {
+        let ptr = builder.ins().iconst(I64, func_addr as i64);
+        builder.ins().call_indirect(sig_ref, ptr, args);
+    }
 }
 
 /// Call a helper function that returns an I64 value.
@@ -112,11 +148,85 @@ pub fn call_helper_ret(
     args: &[Value],
 ) -> Value {
     let sig_ref = get_or_create_sig(context, builder, kind);
-    let ptr = builder.ins().iconst(I64, func_addr as i64);
-    let call = builder.ins().call_indirect(sig_ref, ptr, args);
+    let call = if context.relocatable {
+        let func_ref = import_helper(context, builder, sig_ref, func_addr);
+        builder.ins().call(func_ref, args)
+    } else {
+        let ptr = builder.ins().iconst(I64, func_addr as i64);
+        builder.ins().call_indirect(sig_ref, ptr, args)
+    };
     builder.inst_results(call)[0]
 }
 
+/// Import a helper function as an external symbol.
+/// The symbol is identified by its index in `wide_fn_addrs::symbols()`.
+fn import_helper(
+    context: &mut Context,
+    builder: &mut FunctionBuilder,
+    sig_ref: SigRef,
+    func_addr: usize,
+) -> FuncRef {
+    if let Some(&func_ref) = context.helper_funcs.get(&func_addr) {
+        return func_ref;
+    }
+
+    let index = wide_fn_addrs::symbols()
+        .iter()
+        .position(|(addr, _)| *addr == func_addr)
+        .expect("unknown helper function");
+    let name = builder
+        .func
+        .declare_imported_user_function(UserExternalName::new(0, index as u32));
+    let func_ref = builder.import_function(ExtFuncData {
+        name: ExternalName::user(name),
+        signature: sig_ref,
+        colocated: false,
+        patchable: false,
+    });
+    context.helper_funcs.insert(func_addr, func_ref);
+    func_ref
+}
+
+/// Look up the host ISA.
+pub fn host_isa(verifier: bool, pic: bool) -> OwnedTargetIsa {
+    let mut settings_builder = settings::builder();
+    settings_builder.set("opt_level", "speed").unwrap();
+    if !verifier {
+        settings_builder.set("enable_verifier", "false").unwrap();
+    }
+    if pic {
+        settings_builder.set("is_pic", "true").unwrap();
+    }
+    let flags = settings::Flags::new(settings_builder);
+
+    match isa::lookup(Triple::host()) {
+        Err(err) => panic!("Error looking up target: {}", err),
+        Ok(isa_builder) => isa_builder.finish(flags).unwrap(),
+    }
+}
+
+/// Resolve relocations of helper calls into their symbol names.
+/// Returns `None` if there are relocations to anything else.
+pub fn helper_relocs(func: &Function, relocs: &[FinalizedMachReloc]) -> Option<Vec<HelperReloc>> {
+    let symbols = wide_fn_addrs::symbols();
+    relocs
+        .iter()
+        .map(|x| {
+            let FinalizedRelocTarget::ExternalName(ExternalName::User(name)) = &x.target else {
+                return None;
+            };
+            let name = &func.params.user_named_funcs()[*name];
+            let (_, symbol) = symbols.get(name.index as usize)?;
+            Some(HelperReloc {
+                offset: x.offset,
+                kind: x.kind,
+                symbol,
+                addend: x.addend,
+            })
+        })
+        .collect()
+}
+
 /// Allocate a stack slot of `nb` bytes and return its address as an I64 value.
 pub fn alloc_wide_slot(builder: &mut FunctionBuilder, nb: usize) -> Value {
     let slot = builder.create_sized_stack_slot(StackSlotData::new(
@@ -166,18 +276,16 @@ fn build_binary_inner(
     disable_load_cache: bool,
 ) -> Option<FuncPtr> {
     let config = &context.config;
-
-    let mut settings_builder = settings::builder();
-    settings_builder.set("opt_level", "speed").unwrap();
-    if !config.dump_cranelift {
-        settings_builder.set("enable_verifier", "false").unwrap();
-    }
-    let flags = settings::Flags::new(settings_builder);
-
-    let isa = match isa::lookup(Triple::host()) {
-        Err(err) => panic!("Error looking up target: {}", err),
-        Ok(isa_builder) => isa_builder.finish(flags).unwrap(),
+    let relocatable = config.emit_object;
+    // Assembly dump needs compilation, so the disk cache is bypassed
+    let cache_dir = if relocatable || config.dump_asm {
+        None
+    } else {
+        config.jit_cache_dir.clone()
     };
+    let named_helpers = relocatable || cache_dir.is_some();
+
+    let isa = host_isa(config.dump_cranelift, false);
 
     let ptr_type = isa.pointer_type();
     let call_conv = CallConv::triple_default(&Triple::host());
@@ -213,6 +321,8 @@ fn build_binary_inner(
         helper_sigs: HashMap::default(),
         call_conv,
         disable_load_cache,
+        relocatable: named_helpers,
+        helper_funcs: HashMap::default(),
     };
 
     let len = proto.len();
@@ -230,38 +340,95 @@ fn build_binary_inner(
         println!("{}", indent_all_by(2, func.display().to_string()));
     }
 
-    let mut ctx = codegen::Context::for_function(func);
-    if config.dump_asm {
-        ctx.set_disasm(true);
-    }
+    let cache_key = cache_dir.map(|dir| {
+        let key = jit_cache::key(&*isa, &func);
+        (dir, key)
+    });
+    let cached = cache_key
+        .as_ref()
+        .and_then(|(dir, key)| jit_cache::load(dir, key));
+
+    let pic_func = relocatable.then(|| func.clone());
+    let (code, relocs) = if let Some(cached) = cached {
+        (cached.code, cached.relocs)
+    } else {
+        let named_func = named_helpers.then(|| func.clone());
+        let mut ctx = codegen::Context::for_function(func);
+        if config.dump_asm {
+            ctx.set_disasm(true);
+        }
+
+        let mut control_plane = ControlPlane::default();
+        let code = match ctx.compile(&*isa, &mut control_plane) {
+            Ok(code) => code,
+            Err(err) => {
+                log::warn!("JIT compilation failed, falling back to interpreter: {err:?}");
+                return None;
+            }
+        };
+
+        if config.dump_asm
+            && let Some(disasm) = &code.vcode
+        {
+            println!("Assembly of {}", isa.name());
+            println!("{}", indent_all_by(2, disasm.to_string()));
+        }
 
-    let mut control_plane = ControlPlane::default();
-    let code = match ctx.compile(&*isa, &mut control_plane) {
-        Ok(code) => code,
-        Err(err) => {
-            log::warn!("JIT compilation failed, falling back to interpreter: {err:?}");
+        let relocs = if let Some(func) = &named_func {
+            helper_relocs(func, code.buffer.relocs())?
+        } else {
+            Vec::new()
+        };
+        if let Some(x) = relocs.iter().find(|x| x.kind != Reloc::Abs8) {
+            log::warn!("JIT compilation failed, unsupported relocation: {}", x.kind);
             return None;
         }
-    };
 
-    if config.dump_asm
-        && let Some(disasm) = &code.vcode
-    {
-        println!("Assembly of {}", isa.name());
-        println!("{}", indent_all_by(2, disasm.to_string()));
-    }
+        let code = code.code_buffer().to_vec();
+        if let Some((dir, key)) = &cache_key {
+            jit_cache::store(dir, key, &code, &relocs);
+        }
+        (code, relocs)
+    };
 
     let mut buffer = memmap2::MmapOptions::new()
-        .len(code.code_buffer().len())
+        .len(code.len())
         .map_anon()
         .unwrap();
 
-    buffer.copy_from_slice(code.code_buffer());
+    buffer.copy_from_slice(&code);
+
+    // Helper calls by symbol are resolved to the helpers of this process
+    let symbols = wide_fn_addrs::symbols();
+    for x in relocs {
+        let (addr, _) = symbols.iter().find(|(_, s)| *s == x.symbol).unwrap();
+        let value = (*addr as i64).wrapping_add(x.addend);
+        let offset = x.offset as usize;
+        buffer[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    }
+
     let buffer = buffer.make_exec().unwrap();
 
     let func_ptr: FuncPtr = unsafe { std::mem::transmute(buffer.as_ptr()) };
 
     context.binary.push(buffer);
 
+    if let Some(func) = pic_func {
+        let pic_isa = host_isa(false, true);
+        let mut ctx = codegen::Context::for_function(func.clone());
+        let code = match ctx.compile(&*pic_isa, &mut ControlPlane::default()) {
+            Ok(code) => code,
+            Err(err) => {
+                log::warn!("JIT compilation failed, falling back to interpreter: {err:?}");
+                return None;
+            }
+        };
+        context.relocatable_code.push(RelocatableCode {
+            func: func_ptr as usize,
+            code: code.code_buffer().to_vec(),
+            relocs: helper_relocs(&func, code.buffer.relocs())?,
+        });
+    }
+
     Some(func_ptr)
 }
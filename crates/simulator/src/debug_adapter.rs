@@ -0,0 +1,891 @@
+//! Debug Adapter Protocol server for native testbenches.
+//!
+//! The simulation can stop before clock/reset statements of the testbench and
+//! after each clock edge driven by them, so breakpoints in `always_ff` and
+//! `always_comb` and stepping work at clock-edge granularity.
+//!
+//! A breakpoint in `always_ff` or in a branch of `always_comb` stops only
+//! after an edge at which the statement was executed, which is recorded by
+//! `statement_trace`. Top-level statements of `always_comb` are executed
+//! after every edge. The simulation should be built with
+//! `Config::trace_statements`.
+
+use crate::ir::{Event, ModuleVariables};
+use crate::output_buffer;
+use crate::signal_expr::{SignalExpr, read_element};
+use crate::simulator::Simulator;
+use crate::statement_trace;
+use crate::testbench::{TestResult, TestbenchHook, TestbenchStatement, run_testbench_with_hook};
+use serde_json::{Value as Json, json};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use veryl_analyzer::ir as air;
+use veryl_parser::resource_table::{self, PathId, StrId};
+use veryl_parser::token_range::TokenRange;
+use veryl_parser::veryl_token::TokenSource;
+
+const THREAD_ID: i64 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockKind {
+    AlwaysFf,
+    AlwaysComb,
+    Initial,
+}
+
+impl fmt::Display for BlockKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            BlockKind::AlwaysFf => "always_ff",
+            BlockKind::AlwaysComb => "always_comb",
+            BlockKind::Initial => "initial",
+        };
+        text.fmt(f)
+    }
+}
+
+/// Source lines of a statement where a breakpoint can be placed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StatementLocation {
+    pub path: PathId,
+    pub beg: u32,
+    pub end: u32,
+    /// Whether execution of the statement is recorded by `statement_trace`
+    pub traced: bool,
+}
+
+impl StatementLocation {
+    fn new(token: &TokenRange, traced: bool) -> Option<Self> {
+        let TokenSource::File { path, .. } = token.beg.source else {
+            return None;
+        };
+        Some(Self {
+            path,
+            beg: token.beg.line,
+            end: token.end.line.max(token.beg.line),
+            traced,
+        })
+    }
+}
+
+/// `always_ff`, `always_comb` or `initial` block of a module
+#[derive(Clone, Debug)]
+pub struct SourceBlock {
+    pub kind: BlockKind,
+    pub module: StrId,
+    pub statements: Vec<StatementLocation>,
+}
+
+fn statement_locations(stmts: &[air::Statement], traced: bool, ret: &mut Vec<StatementLocation>) {
+    for x in stmts {
+        match x {
+            air::Statement::If(x) => {
+                statement_locations(&x.true_side, true, ret);
+                statement_locations(&x.false_side, true, ret);
+            }
+            air::Statement::IfReset(x) => {
+                statement_locations(&x.true_side, true, ret);
+                statement_locations(&x.false_side, true, ret);
+            }
+            air::Statement::For(x) => statement_locations(&x.body, true, ret),
+            _ => (),
+        }
+        if let Some(token) = statement_trace::statement_token(x) {
+            ret.extend(StatementLocation::new(&token, traced));
+        }
+    }
+}
+
+/// Testbench statements can stop only before clock and reset statements
+fn testbench_locations(stmts: &[air::Statement], ret: &mut Vec<StatementLocation>) {
+    for x in stmts {
+        match x {
+            air::Statement::If(x) => {
+                testbench_locations(&x.true_side, ret);
+                testbench_locations(&x.false_side, ret);
+            }
+            air::Statement::For(x) => testbench_locations(&x.body, ret),
+            air::Statement::TbMethodCall(x) => {
+                ret.extend(StatementLocation::new(&x.token, false));
+            }
+            _ => (),
+        }
+    }
+}
+
+fn module_blocks(module: &air::Module, ret: &mut Vec<SourceBlock>) {
+    for decl in &module.declarations {
+        let mut statements = Vec::new();
+        let kind = match decl {
+            air::Declaration::Ff(x) => {
+                statement_locations(&x.statements, true, &mut statements);
+                BlockKind::AlwaysFf
+            }
+            air::Declaration::Comb(x) => {
+                statement_locations(&x.statements, false, &mut statements);
+                BlockKind::AlwaysComb
+            }
+            air::Declaration::Initial(x) => {
+                testbench_locations(&x.statements, &mut statements);
+                BlockKind::Initial
+            }
+            _ => continue,
+        };
+        if statements.is_empty() {
+            continue;
+        }
+        let block = SourceBlock {
+            kind,
+            module: module.name,
+            statements,
+        };
+        if !ret
+            .iter()
+            .any(|x| x.kind == block.kind && x.statements == block.statements)
+        {
+            ret.push(block);
+        }
+    }
+}
+
+/// Blocks of all modules in `ir`
+pub fn collect_source_blocks(ir: &air::Ir) -> Vec<SourceBlock> {
+    let mut ret = Vec::new();
+    for x in &ir.components {
+        if let air::Component::Module(x) = x {
+            module_blocks(x, &mut ret);
+        }
+    }
+    ret
+}
+
+fn hierarchy_blocks(module: &air::Module, ret: &mut Vec<SourceBlock>) {
+    module_blocks(module, ret);
+    for decl in &module.declarations {
+        if let air::Declaration::Inst(x) = decl
+            && let air::Component::Module(x) = &x.component
+        {
+            hierarchy_blocks(x, ret);
+        }
+    }
+}
+
+/// Blocks of modules instantiated under `top`
+pub fn collect_hierarchy_blocks(ir: &air::Ir, top: StrId) -> Vec<SourceBlock> {
+    let mut ret = Vec::new();
+    for x in &ir.components {
+        if let air::Component::Module(x) = x
+            && x.name == top
+        {
+            hierarchy_blocks(x, &mut ret);
+        }
+    }
+    ret
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    if a == b {
+        return true;
+    }
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn source_json(path: PathId) -> Json {
+    let path = resource_table::get_path_value(path).unwrap_or_default();
+    let name = path
+        .file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+    json!({ "name": name, "path": path.to_string_lossy() })
+}
+
+struct Breakpoint {
+    id: i64,
+    source: PathBuf,
+    line: u32,
+    condition: Option<SignalExpr>,
+    /// Block index and statement where the breakpoint is placed
+    location: Option<(usize, StatementLocation)>,
+    error: Option<String>,
+}
+
+impl Breakpoint {
+    fn verified(&self) -> bool {
+        self.location.is_some() && self.error.is_none()
+    }
+
+    fn to_json(&self) -> Json {
+        let mut ret = json!({
+            "id": self.id,
+            "verified": self.verified(),
+            "line": self.line,
+            "source": { "path": self.source.to_string_lossy() },
+        });
+        if let Some(x) = &self.error {
+            ret["message"] = json!(x);
+        } else if self.location.is_none() {
+            ret["message"] = json!(
+                "breakpoints can be placed on statements in always_ff and always_comb, \
+                 and on clock/reset statements in initial"
+            );
+        }
+        ret
+    }
+}
+
+fn resolve_location(
+    blocks: &[SourceBlock],
+    source: &Path,
+    line: u32,
+) -> Option<(usize, StatementLocation)> {
+    let mut ret: Option<(usize, StatementLocation)> = None;
+    for (i, block) in blocks.iter().enumerate() {
+        for x in &block.statements {
+            if x.beg > line || x.end < line {
+                continue;
+            }
+            let Some(path) = resource_table::get_path_value(x.path) else {
+                continue;
+            };
+            if !same_path(&path, source) {
+                continue;
+            }
+            // the innermost statement is stopped at
+            if ret.is_none_or(|(_, y)| x.end - x.beg < y.end - y.beg) {
+                ret = Some((i, *x));
+            }
+        }
+    }
+    ret
+}
+
+struct Frame {
+    name: String,
+    path: Option<PathId>,
+    line: u32,
+}
+
+enum Reference {
+    Simulation,
+    Module(Vec<usize>),
+    Array(Vec<usize>, String),
+}
+
+fn module_at<'a>(root: &'a ModuleVariables, path: &[usize]) -> &'a ModuleVariables {
+    path.iter().fold(root, |x, i| &x.children[*i])
+}
+
+fn module_references(module: &ModuleVariables, path: Vec<usize>, ret: &mut Vec<Reference>) {
+    for (i, x) in module.children.iter().enumerate() {
+        let mut path = path.clone();
+        path.push(i);
+        ret.push(Reference::Module(path.clone()));
+        module_references(x, path, ret);
+    }
+}
+
+/// DAP session driving one native testbench.
+///
+/// Requests are read from `reader` and responses and events are written to `writer`
+/// with `Content-Length` framing, so stdin/stdout can be used as transport.
+pub struct DebugSession<R, W> {
+    reader: R,
+    writer: W,
+    seq: i64,
+    blocks: Vec<SourceBlock>,
+    breakpoints: Vec<Breakpoint>,
+    next_breakpoint_id: i64,
+    launch: Option<Json>,
+    configured: bool,
+    stepping: bool,
+    disconnected: bool,
+    current: Option<TokenRange>,
+    frames: Vec<Frame>,
+    references: Vec<Reference>,
+    edges: u64,
+}
+
+impl<R: BufRead, W: Write> DebugSession<R, W> {
+    /// `blocks` are used to verify breakpoints until a test is launched
+    pub fn new(reader: R, writer: W, blocks: Vec<SourceBlock>) -> Self {
+        Self {
+            reader,
+            writer,
+            seq: 1,
+            blocks,
+            breakpoints: Vec::new(),
+            next_breakpoint_id: 1,
+            launch: None,
+            configured: false,
+            stepping: false,
+            disconnected: false,
+            current: None,
+            frames: Vec::new(),
+            references: Vec::new(),
+            edges: 0,
+        }
+    }
+
+    fn read_message(&mut self) -> io::Result<Option<Json>> {
+        let mut length = None;
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let line = line.trim();
+            if line.is_empty() {
+                if length.is_some() {
+                    break;
+                }
+            } else if let Some(x) = line.strip_prefix("Content-Length:") {
+                length = x.trim().parse::<usize>().ok();
+            }
+        }
+
+        let mut body = vec![0; length.unwrap_or_default()];
+        self.reader.read_exact(&mut body)?;
+        serde_json::from_slice(&body)
+            .map(Some)
+            .map_err(io::Error::other)
+    }
+
+    fn send(&mut self, mut message: Json) {
+        message["seq"] = json!(self.seq);
+        self.seq += 1;
+        let body = message.to_string();
+        let ret = write!(self.writer, "Content-Length: {}\r\n\r\n{body}", body.len())
+            .and_then(|_| self.writer.flush());
+        if ret.is_err() {
+            self.disconnected = true;
+        }
+    }
+
+    fn respond(&mut self, request: &Json, body: Json) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": true,
+            "body": body,
+        }));
+    }
+
+    fn respond_error(&mut self, request: &Json, message: &str) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": false,
+            "message": message,
+        }));
+    }
+
+    fn event(&mut self, event: &str, body: Json) {
+        self.send(json!({ "type": "event", "event": event, "body": body }));
+    }
+
+    fn flush_output(&mut self) {
+        let text = output_buffer::take();
+        output_buffer::enable();
+        if !text.is_empty() {
+            self.event("output", json!({ "category": "stdout", "output": text }));
+        }
+    }
+
+    fn set_breakpoints(&mut self, request: &Json) {
+        let args = &request["arguments"];
+        let Some(source) = args["source"]["path"].as_str() else {
+            self.respond_error(request, "source path is required");
+            return;
+        };
+        let source = PathBuf::from(source);
+        self.breakpoints.retain(|x| x.source != source);
+
+        let mut ret = Vec::new();
+        for x in args["breakpoints"].as_array().into_iter().flatten() {
+            let line = x["line"].as_u64().unwrap_or_default() as u32;
+            let mut breakpoint = Breakpoint {
+                id: self.next_breakpoint_id,
+                source: source.clone(),
+                line,
+                condition: None,
+                location: resolve_location(&self.blocks, &source, line),
+                error: None,
+            };
+            self.next_breakpoint_id += 1;
+
+            if let Some(condition) = x["condition"].as_str().filter(|x| !x.trim().is_empty()) {
+                match SignalExpr::parse(condition) {
+                    Ok(x) => breakpoint.condition = Some(x),
+                    Err(x) => breakpoint.error = Some(format!("invalid condition: {x}")),
+                }
+            }
+
+            ret.push(breakpoint.to_json());
+            self.breakpoints.push(breakpoint);
+        }
+        self.respond(request, json!({ "breakpoints": ret }));
+    }
+
+    fn initialize(&mut self, request: &Json) {
+        self.respond(
+            request,
+            json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsConditionalBreakpoints": true,
+                "supportsEvaluateForHovers": true,
+                "supportsTerminateRequest": true,
+            }),
+        );
+        self.event("initialized", json!({}));
+    }
+
+    /// Handle requests until `launch` is received, and return its arguments.
+    ///
+    /// `None` is returned if the client disconnects before launch.
+    pub fn wait_launch(&mut self) -> io::Result<Option<Json>> {
+        while let Some(request) = self.read_message()? {
+            match request["command"].as_str().unwrap_or_default() {
+                "initialize" => self.initialize(&request),
+                "setBreakpoints" => self.set_breakpoints(&request),
+                "configurationDone" => {
+                    self.configured = true;
+                    self.respond(&request, json!({}));
+                }
+                "threads" => self.threads(&request),
+                "launch" => {
+                    let args = request["arguments"].clone();
+                    self.launch = Some(request);
+                    return Ok(Some(args));
+                }
+                "disconnect" | "terminate" => {
+                    self.respond(&request, json!({}));
+                    return Ok(None);
+                }
+                _ => self.respond_error(&request, "no test is launched"),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Report that the launched test can't be started
+    pub fn fail_launch(&mut self, message: &str) {
+        if let Some(request) = self.launch.take() {
+            self.respond_error(&request, message);
+        }
+        self.event("terminated", json!({}));
+    }
+
+    fn threads(&mut self, request: &Json) {
+        self.respond(
+            request,
+            json!({ "threads": [{ "id": THREAD_ID, "name": "testbench" }] }),
+        );
+    }
+
+    fn stack_trace(&mut self, request: &Json) {
+        let frames: Vec<_> = self
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let mut ret = json!({
+                    "id": i + 1,
+                    "name": x.name,
+                    "line": x.line,
+                    "column": 1,
+                });
+                if let Some(path) = x.path {
+                    ret["source"] = source_json(path);
+                }
+                ret
+            })
+            .collect();
+        let total = frames.len();
+        self.respond(
+            request,
+            json!({ "stackFrames": frames, "totalFrames": total }),
+        );
+    }
+
+    fn scopes(&mut self, request: &Json) {
+        self.respond(
+            request,
+            json!({ "scopes": [
+                { "name": "Signals", "variablesReference": 2, "expensive": false },
+                { "name": "Simulation", "variablesReference": 1, "expensive": false },
+            ]}),
+        );
+    }
+
+    fn reference(&mut self, reference: Reference) -> usize {
+        self.references.push(reference);
+        self.references.len()
+    }
+
+    fn variables(&mut self, request: &Json, sim: &mut Simulator) {
+        sim.ensure_comb_updated();
+        let index = request["arguments"]["variablesReference"]
+            .as_u64()
+            .unwrap_or_default() as usize;
+        let use_4state = sim.ir.use_4state;
+
+        let mut ret = Vec::new();
+        let mut arrays = Vec::new();
+        match self.references.get(index.wrapping_sub(1)) {
+            Some(Reference::Simulation) => {
+                ret.push(json!({ "name": "time", "value": sim.time.to_string(), "variablesReference": 0 }));
+                ret.push(json!({ "name": "edges", "value": self.edges.to_string(), "variablesReference": 0 }));
+            }
+            Some(Reference::Module(path)) => {
+                let module = module_at(&sim.ir.module_variables, path);
+                let mut variables: Vec<_> = module.variables.values().collect();
+                variables.sort_by_key(|x| x.path.to_string());
+                for x in variables {
+                    let name = x.path.to_string();
+                    let r#type = x.r#type.to_string();
+                    if x.current_values.len() > 1 {
+                        arrays.push((path.clone(), name, r#type, x.current_values.len()));
+                    } else if let Some(value) = read_element(x, 0, use_4state) {
+                        ret.push(json!({
+                            "name": name,
+                            "value": format!("{value:x}"),
+                            "type": r#type,
+                            "evaluateName": name,
+                            "variablesReference": 0,
+                        }));
+                    }
+                }
+                for (i, x) in module.children.iter().enumerate() {
+                    let mut child = path.clone();
+                    child.push(i);
+                    let reference = self
+                        .references
+                        .iter()
+                        .position(|x| matches!(x, Reference::Module(x) if *x == child))
+                        .map(|x| x + 1)
+                        .unwrap_or_default();
+                    ret.push(json!({
+                        "name": x.name.to_string(),
+                        "value": "instance",
+                        "variablesReference": reference,
+                    }));
+                }
+            }
+            Some(Reference::Array(path, name)) => {
+                let module = module_at(&sim.ir.module_variables, path);
+                if let Some(x) = module
+                    .variables
+                    .values()
+                    .find(|x| x.path.to_string() == *name)
+                {
+                    for i in 0..x.current_values.len() {
+                        if let Some(value) = read_element(x, i, use_4state) {
+                            ret.push(json!({
+                                "name": format!("[{i}]"),
+                                "value": format!("{value:x}"),
+                                "variablesReference": 0,
+                            }));
+                        }
+                    }
+                }
+            }
+            None => (),
+        }
+
+        for (path, name, r#type, len) in arrays {
+            let reference = self
+                .references
+                .iter()
+                .position(|x| matches!(x, Reference::Array(x, y) if *x == path && *y == name))
+                .map(|x| x + 1)
+                .unwrap_or_else(|| self.reference(Reference::Array(path, name.clone())));
+            ret.push(json!({
+                "name": name,
+                "value": format!("[{len}]"),
+                "type": r#type,
+                "variablesReference": reference,
+            }));
+        }
+
+        self.respond(request, json!({ "variables": ret }));
+    }
+
+    fn evaluate(&mut self, request: &Json, sim: &mut Simulator) {
+        let expression = request["arguments"]["expression"]
+            .as_str()
+            .unwrap_or_default();
+        match SignalExpr::parse(expression).and_then(|x| x.evaluate(sim)) {
+            Ok(x) => self.respond(request, json!({ "result": x, "variablesReference": 0 })),
+            Err(x) => self.respond_error(request, &x),
+        }
+    }
+
+    /// Handle requests while the simulation is stopped
+    fn serve(&mut self, sim: &mut Simulator) -> ControlFlow<()> {
+        loop {
+            let Ok(Some(request)) = self.read_message() else {
+                self.disconnected = true;
+                return ControlFlow::Break(());
+            };
+            match request["command"].as_str().unwrap_or_default() {
+                "continue" => {
+                    self.stepping = false;
+                    self.respond(&request, json!({ "allThreadsContinued": true }));
+                    return ControlFlow::Continue(());
+                }
+                "next" | "stepIn" | "stepOut" => {
+                    self.stepping = true;
+                    self.respond(&request, json!({}));
+                    return ControlFlow::Continue(());
+                }
+                "disconnect" | "terminate" => {
+                    self.disconnected = true;
+                    self.respond(&request, json!({}));
+                    return ControlFlow::Break(());
+                }
+                "configurationDone" => {
+                    self.configured = true;
+                    self.respond(&request, json!({}));
+                }
+                "setBreakpoints" => self.set_breakpoints(&request),
+                "threads" => self.threads(&request),
+                "stackTrace" => self.stack_trace(&request),
+                "scopes" => self.scopes(&request),
+                "variables" => self.variables(&request, sim),
+                "evaluate" => self.evaluate(&request, sim),
+                "pause" => self.respond(&request, json!({})),
+                x => self.respond_error(&request, &format!("unsupported request '{x}'")),
+            }
+        }
+    }
+
+    fn testbench_frame(&self) -> Frame {
+        if let Some(token) = &self.current
+            && let Some(location) = StatementLocation::new(token, false)
+        {
+            let module = self
+                .blocks
+                .iter()
+                .find(|x| x.kind == BlockKind::Initial && x.statements.contains(&location))
+                .map(|x| x.module.to_string())
+                .unwrap_or_default();
+            Frame {
+                name: format!("initial ({module})"),
+                path: Some(location.path),
+                line: location.beg,
+            }
+        } else {
+            Frame {
+                name: "testbench".to_string(),
+                path: None,
+                line: 0,
+            }
+        }
+    }
+
+    fn stop(&mut self, sim: &mut Simulator, reason: &str, hits: Vec<usize>) -> ControlFlow<()> {
+        self.frames.clear();
+        if let Some(x) = hits.first() {
+            let breakpoint = &self.breakpoints[*x];
+            if let Some((block, location)) = &breakpoint.location {
+                let block = &self.blocks[*block];
+                if block.kind != BlockKind::Initial {
+                    self.frames.push(Frame {
+                        name: format!("{} ({})", block.kind, block.module),
+                        path: Some(location.path),
+                        line: breakpoint.line,
+                    });
+                }
+            }
+        }
+        self.frames.push(self.testbench_frame());
+
+        let ids: Vec<_> = hits.iter().map(|x| self.breakpoints[*x].id).collect();
+        self.flush_output();
+        self.event(
+            "stopped",
+            json!({
+                "reason": reason,
+                "threadId": THREAD_ID,
+                "allThreadsStopped": true,
+                "hitBreakpointIds": ids,
+            }),
+        );
+        self.serve(sim)
+    }
+
+    fn hit(&self, sim: &mut Simulator, index: usize) -> bool {
+        match &self.breakpoints[index].condition {
+            // condition errors stop the simulation so that the user can notice them
+            Some(x) => x.eval(sim).map(|x| x.unwrap_or(0) != 0).unwrap_or(true),
+            None => true,
+        }
+    }
+
+    /// Run the launched testbench until it finishes or the client disconnects.
+    ///
+    /// `blocks` should be collected from the hierarchy of the test so that
+    /// breakpoints in modules which are not instantiated are not verified.
+    pub fn run(
+        &mut self,
+        mut sim: Simulator,
+        stmts: &[TestbenchStatement],
+        blocks: Vec<SourceBlock>,
+    ) -> TestResult {
+        self.blocks = blocks;
+        for i in 0..self.breakpoints.len() {
+            let x = &self.breakpoints[i];
+            let location = resolve_location(&self.blocks, &x.source, x.line);
+            let changed = location.map(|x| x.1) != x.location.map(|x| x.1);
+            self.breakpoints[i].location = location;
+            if changed {
+                let breakpoint = self.breakpoints[i].to_json();
+                self.event(
+                    "breakpoint",
+                    json!({ "reason": "changed", "breakpoint": breakpoint }),
+                );
+            }
+        }
+
+        let stop_on_entry = if let Some(request) = self.launch.take() {
+            self.respond(&request, json!({}));
+            request["arguments"]["stopOnEntry"]
+                .as_bool()
+                .unwrap_or_default()
+        } else {
+            false
+        };
+
+        self.references = vec![Reference::Simulation, Reference::Module(Vec::new())];
+        module_references(&sim.ir.module_variables, Vec::new(), &mut self.references);
+
+        output_buffer::enable();
+        statement_trace::enable();
+        let mut flow = ControlFlow::Continue(());
+        while !self.configured && flow.is_continue() {
+            flow = self.serve_configuration();
+        }
+        if stop_on_entry && flow.is_continue() {
+            flow = self.stop(&mut sim, "entry", Vec::new());
+        }
+
+        let result = if flow.is_break() {
+            TestResult::Fail("terminated by debugger".to_string())
+        } else {
+            run_testbench_with_hook(&mut sim, stmts, self)
+        };
+        self.flush_output();
+        output_buffer::take();
+
+        if !self.disconnected {
+            let (message, code) = match &result {
+                TestResult::Pass => ("test passed\n".to_string(), 0),
+                TestResult::Fail(x) => (format!("test failed: {x}\n"), 1),
+            };
+            self.event(
+                "output",
+                json!({ "category": "console", "output": message }),
+            );
+            self.event("exited", json!({ "exitCode": code }));
+            self.event("terminated", json!({}));
+
+            // variables can be inspected until the client disconnects
+            self.current = None;
+            self.frames.clear();
+            let _ = self.serve(&mut sim);
+        }
+
+        result
+    }
+
+    /// Handle one request before `configurationDone`
+    fn serve_configuration(&mut self) -> ControlFlow<()> {
+        let Ok(Some(request)) = self.read_message() else {
+            self.disconnected = true;
+            return ControlFlow::Break(());
+        };
+        match request["command"].as_str().unwrap_or_default() {
+            "configurationDone" => {
+                self.configured = true;
+                self.respond(&request, json!({}));
+            }
+            "setBreakpoints" => self.set_breakpoints(&request),
+            "threads" => self.threads(&request),
+            "disconnect" | "terminate" => {
+                self.disconnected = true;
+                self.respond(&request, json!({}));
+                return ControlFlow::Break(());
+            }
+            x => self.respond_error(&request, &format!("unsupported request '{x}'")),
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl<R: BufRead, W: Write> TestbenchHook for DebugSession<R, W> {
+    fn before_statement(&mut self, sim: &mut Simulator, token: &TokenRange) -> ControlFlow<()> {
+        if self.disconnected {
+            return ControlFlow::Break(());
+        }
+        self.current = Some(*token);
+
+        let Some(location) = StatementLocation::new(token, false) else {
+            return ControlFlow::Continue(());
+        };
+        let hits: Vec<_> = (0..self.breakpoints.len())
+            .filter(|x| {
+                self.breakpoints[*x].location.is_some_and(|(block, y)| {
+                    self.blocks[block].kind == BlockKind::Initial && y == location
+                })
+            })
+            .collect();
+        let hits: Vec<_> = hits.into_iter().filter(|x| self.hit(sim, *x)).collect();
+        if hits.is_empty() {
+            ControlFlow::Continue(())
+        } else {
+            self.stop(sim, "breakpoint", hits)
+        }
+    }
+
+    fn after_edge(&mut self, sim: &mut Simulator, _event: &Event) -> ControlFlow<()> {
+        if self.disconnected {
+            return ControlFlow::Break(());
+        }
+        self.edges += 1;
+
+        // always_comb is evaluated here so that its statements are traced
+        sim.ensure_comb_updated();
+        let executed: Vec<_> = statement_trace::take()
+            .iter()
+            .filter_map(|x| StatementLocation::new(x, true))
+            .collect();
+
+        let hits: Vec<_> = (0..self.breakpoints.len())
+            .filter(|x| {
+                self.breakpoints[*x].location.is_some_and(|(block, y)| {
+                    match self.blocks[block].kind {
+                        BlockKind::AlwaysFf | BlockKind::AlwaysComb => {
+                            !y.traced || executed.contains(&y)
+                        }
+                        BlockKind::Initial => false,
+                    }
+                })
+            })
+            .collect();
+        let hits: Vec<_> = hits.into_iter().filter(|x| self.hit(sim, *x)).collect();
+        if !hits.is_empty() {
+            self.stop(sim, "breakpoint", hits)
+        } else if self.stepping {
+            self.stop(sim, "step", Vec::new())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
@@ -5,12 +5,16 @@ mod expression;
 mod module;
 #[cfg(not(target_family = "wasm"))]
 mod optimize;
+mod partition;
 mod statement;
 mod variable;
+mod worker_pool;
 
 pub use context::{Context, Conv};
 pub use declaration::ProtoDeclaration;
 pub use event::Event;
+#[cfg(not(target_family = "wasm"))]
+pub(crate) use expression::wide_fn_addrs;
 pub use expression::{Expression, ProtoExpression};
 pub use module::{Module, ProtoModule};
 pub use statement::{
@@ -24,12 +28,15 @@ pub use variable::{
 };
 pub use veryl_analyzer::ir::{Op, Type, VarId, VarPath};
 pub use veryl_analyzer::value::Value;
+pub use worker_pool::{Stages, WorkerPool};
 
 use crate::HashMap;
 use crate::simulator::SimProfile;
 use crate::simulator_error::SimulatorError;
 #[cfg(not(target_family = "wasm"))]
 use memmap2::Mmap;
+use std::ops::Range;
+use std::path::PathBuf;
 use std::sync::Arc;
 use veryl_analyzer::ir as air;
 use veryl_analyzer::value::MaskCache;
@@ -61,6 +68,21 @@ pub struct Ir {
     pub ff_commit_entries: Vec<(usize, usize)>,
     /// Whether FF classification optimization is disabled.
     pub disable_ff_opt: bool,
+    /// Whether registers which are still X after reset are reported.
+    pub report_x_registers: bool,
+    /// Variables held by `Simulator::force` until released.
+    pub forces: Vec<Force>,
+    /// Parallel stages of `comb_statements`; empty if comb is evaluated serially.
+    pub comb_stages: Stages,
+    /// Concurrent chunks of `event_statements`; events not included are evaluated serially.
+    pub event_chunks: HashMap<Event, Vec<Range<usize>>>,
+    /// Worker threads used to evaluate `comb_stages` and `event_chunks`.
+    workers: Option<WorkerPool>,
+    /// Module converted again without JIT by the first `force`, because
+    /// JIT-compiled functions don't observe forced values until they return.
+    interpreter_source: Option<(Arc<air::Module>, Config)>,
+    /// Interpreted statements evaluated instead while `forces` is not empty.
+    interpreted: Option<Interpreted>,
     /// Keeps JIT-compiled code alive. Wrapped in `Arc` so that multiple `Ir`
     /// instances created from the same cached `ProtoModule` can share the binary.
     _binary: Arc<Vec<BinaryStorage>>,
@@ -82,6 +104,8 @@ impl Ir {
         config: &Config,
         token: TokenRange,
     ) -> Ir {
+        let parallel = !module.comb_stages.is_empty() || !module.event_chunks.is_empty();
+        let workers = (config.threads > 1 && parallel).then(|| WorkerPool::new(config.threads));
         Ir {
             name: module.name,
             token,
@@ -95,10 +119,55 @@ impl Ir {
             required_comb_passes: module.required_comb_passes,
             ff_commit_entries: module.ff_commit_entries,
             disable_ff_opt: config.disable_ff_opt,
+            report_x_registers: config.report_x_registers,
+            forces: Vec::new(),
+            comb_stages: module.comb_stages,
+            event_chunks: module.event_chunks,
+            workers,
+            interpreter_source: None,
+            interpreted: None,
             _binary: binary,
         }
     }
 
+    /// Build interpreted statements evaluated while variables are forced.
+    pub fn prepare_forces(&mut self) {
+        let Some((module, config)) = self.interpreter_source.take() else {
+            return;
+        };
+        let mut context = context::Context {
+            config,
+            ..Default::default()
+        };
+        let proto: Option<ProtoModule> = Conv::conv(&mut context, module.as_ref()).ok();
+        self.interpreted = proto.and_then(|proto| {
+            let (comb_statements, event_statements) = proto.instantiate_statements(
+                &mut self.ff_values,
+                &mut self.comb_values,
+                &self.module_variables,
+            )?;
+            Some(Interpreted {
+                comb_statements,
+                event_statements,
+                required_comb_passes: proto.required_comb_passes,
+            })
+        });
+        if self.interpreted.is_none() {
+            log::warn!(
+                "forced values may not be observed by JIT-compiled statements of \"{}\"",
+                self.name
+            );
+        }
+    }
+
+    /// Comb statements evaluated with the current forces.
+    fn forced_comb_statements(&self) -> &[Statement] {
+        match &self.interpreted {
+            Some(x) => &x.comb_statements,
+            None => &self.comb_statements,
+        }
+    }
+
     /// Evaluate comb until convergence.
     /// Returns true if converged on the first extra pass (or no extra needed).
     pub fn settle_comb(
@@ -114,7 +183,10 @@ impl Ir {
         }
         let _ = profile; // suppress unused warning when profile feature is off
 
-        let min_passes = self.required_comb_passes;
+        let min_passes = match &self.interpreted {
+            Some(x) if !self.forces.is_empty() => x.required_comb_passes,
+            _ => self.required_comb_passes,
+        };
         self.eval_comb_full(mask_cache, profile);
         #[cfg(feature = "profile")]
         {
@@ -182,8 +254,21 @@ impl Ir {
         #[cfg(feature = "profile")]
         let start = std::time::Instant::now();
 
-        for x in &self.comb_statements {
-            x.eval_step(mask_cache);
+        if !self.forces.is_empty() {
+            // Forced values are restored after each statement so that
+            // subsequent readers never observe the value of the drivers.
+            for x in self.forced_comb_statements() {
+                x.eval_step(mask_cache);
+                self.apply_forces();
+            }
+        } else if let Some(workers) = &self.workers
+            && !self.comb_stages.is_empty()
+        {
+            workers.run(&self.comb_statements, &self.comb_stages, mask_cache);
+        } else {
+            for x in &self.comb_statements {
+                x.eval_step(mask_cache);
+            }
         }
 
         #[cfg(feature = "profile")]
@@ -192,6 +277,49 @@ impl Ir {
         }
     }
 
+    /// Evaluate statements triggered by `event`.
+    pub fn eval_event(&self, event: &Event, mask_cache: &mut MaskCache) {
+        if !self.forces.is_empty()
+            && let Some(interpreted) = &self.interpreted
+        {
+            for x in interpreted
+                .event_statements
+                .get(event)
+                .into_iter()
+                .flatten()
+            {
+                x.eval_step(mask_cache);
+                self.apply_forces();
+            }
+            return;
+        }
+
+        let Some(statements) = self.event_statements.get(event) else {
+            return;
+        };
+        if let Some(workers) = &self.workers
+            && let Some(chunks) = self.event_chunks.get(event)
+        {
+            workers.run(statements, std::slice::from_ref(chunks), mask_cache);
+        } else {
+            for x in statements {
+                x.eval_step(mask_cache);
+            }
+        }
+        self.apply_forces();
+    }
+
+    /// Write forced values to their variables.
+    pub fn apply_forces(&self) {
+        for x in &self.forces {
+            for &ptr in &x.ptrs {
+                unsafe {
+                    write_native_value(ptr, x.native_bytes, self.use_4state, &x.value);
+                }
+            }
+        }
+    }
+
     /// Number of statements in comb_statements (for profiling).
     pub fn comb_stmt_count(&self) -> (usize, usize, usize) {
         let mut binary = 0;
@@ -212,6 +340,23 @@ impl Ir {
         format!("{}", self.module_variables)
     }
 
+    /// Whether pessimistic X-propagation treated an unknown `if` condition
+    /// as false because both sides couldn't be merged.
+    pub fn merge_fallback(&self) -> bool {
+        let interpreted = self.interpreted.iter().flat_map(|x| {
+            x.event_statements
+                .values()
+                .flatten()
+                .chain(&x.comb_statements)
+        });
+        self.event_statements
+            .values()
+            .flatten()
+            .chain(&self.comb_statements)
+            .chain(interpreted)
+            .any(|x| x.merge_fallback())
+    }
+
     /// Returns (jit_count, total_count) of top-level statements across all events and comb.
     pub fn jit_stats(&self) -> (usize, usize) {
         let mut jit = 0;
@@ -259,6 +404,21 @@ impl Ir {
     }
 }
 
+/// Statements of `Ir` built without JIT, which share its buffers.
+struct Interpreted {
+    comb_statements: Vec<Statement>,
+    event_statements: HashMap<Event, Vec<Statement>>,
+    required_comb_passes: usize,
+}
+
+/// A variable held at `value` regardless of its drivers.
+pub struct Force {
+    /// Current and next values of all elements of the variable
+    pub ptrs: Vec<*mut u8>,
+    pub native_bytes: usize,
+    pub value: Value,
+}
+
 // SAFETY: Each Ir exclusively owns its ff_values/comb_values buffers.
 // Raw pointers in Statements point into these buffers — no cross-Ir aliasing.
 // _binary (Arc<Vec<BinaryStorage>>) keeps JIT code pages alive.
@@ -267,6 +427,31 @@ impl Ir {
 unsafe impl Send for Ir {}
 
 pub fn build_ir(ir: &air::Ir, top: StrId, config: &Config) -> Result<Ir, SimulatorError> {
+    build_ir_with_context(ir, top, config).map(|(ir, _)| ir)
+}
+
+/// Build IR of `top` with position independent code of its JIT functions,
+/// which is used to emit a native object.
+#[cfg(not(target_family = "wasm"))]
+pub fn build_ir_relocatable(
+    ir: &air::Ir,
+    top: StrId,
+    config: &Config,
+) -> Result<(Ir, Vec<crate::cranelift::RelocatableCode>), SimulatorError> {
+    let config = Config {
+        use_jit: true,
+        emit_object: true,
+        threads: 1,
+        ..config.clone()
+    };
+    build_ir_with_context(ir, top, &config).map(|(ir, context)| (ir, context.relocatable_code))
+}
+
+fn build_ir_with_context(
+    ir: &air::Ir,
+    top: StrId,
+    config: &Config,
+) -> Result<(Ir, context::Context), SimulatorError> {
     for x in &ir.components {
         if let air::Component::Module(x) = x
             && top == x.name
@@ -276,9 +461,12 @@ pub fn build_ir(ir: &air::Ir, top: StrId, config: &Config) -> Result<Ir, Simulat
                 config: config.clone(),
                 ..Default::default()
             };
-            let proto: ProtoModule = Conv::conv(&mut context, x)?;
+            let proto: ProtoModule = Conv::conv(&mut context, x.as_ref())?;
             let module = proto.instantiate();
-            return Ok(Ir::from_module(module, context.binary, config, token));
+            let binary = std::mem::take(&mut context.binary);
+            let mut ir = Ir::from_module(module, binary, config, token);
+            ir.interpreter_source = interpreter_source(x, config);
+            return Ok((ir, context));
         }
     }
     Err(SimulatorError::TopModuleNotFound {
@@ -290,6 +478,26 @@ struct CacheEntry {
     proto: ProtoModule,
     binary: Arc<Vec<BinaryStorage>>,
     token: TokenRange,
+    interpreter_source: Option<(Arc<air::Module>, Config)>,
+}
+
+/// Source of `Ir::prepare_forces`, which is necessary only with JIT.
+fn interpreter_source(
+    module: &Arc<air::Module>,
+    config: &Config,
+) -> Option<(Arc<air::Module>, Config)> {
+    config.use_jit.then(|| {
+        let config = Config {
+            use_jit: false,
+            dump_cranelift: false,
+            dump_asm: false,
+            threads: 1,
+            emit_object: false,
+            jit_cache_dir: None,
+            ..config.clone()
+        };
+        (Arc::clone(module), config)
+    })
 }
 
 /// Cache for `ProtoModule` and JIT binaries keyed by top module name.
@@ -309,12 +517,9 @@ pub fn build_ir_cached(
     // Cache hit: reuse ProtoModule, just instantiate with fresh buffers
     if let Some(entry) = cache.entries.get(&top) {
         let module = entry.proto.instantiate();
-        return Ok(Ir::from_module_arc(
-            module,
-            Arc::clone(&entry.binary),
-            config,
-            entry.token,
-        ));
+        let mut ir = Ir::from_module_arc(module, Arc::clone(&entry.binary), config, entry.token);
+        ir.interpreter_source = entry.interpreter_source.clone();
+        return Ok(ir);
     }
 
     // Cache miss: run Conv::conv
@@ -328,11 +533,13 @@ pub fn build_ir_cached(
                 ..Default::default()
             };
 
-            let proto: ProtoModule = Conv::conv(&mut context, x)?;
+            let proto: ProtoModule = Conv::conv(&mut context, x.as_ref())?;
             let module = proto.instantiate();
             let binary = Arc::new(context.binary);
 
-            let result = Ir::from_module_arc(module, Arc::clone(&binary), config, token);
+            let interpreter_source = interpreter_source(x, config);
+            let mut result = Ir::from_module_arc(module, Arc::clone(&binary), config, token);
+            result.interpreter_source = interpreter_source.clone();
 
             cache.shared_binaries.push(Arc::clone(&binary));
 
@@ -342,6 +549,7 @@ pub fn build_ir_cached(
                     proto,
                     binary,
                     token,
+                    interpreter_source,
                 },
             );
 
@@ -361,6 +569,54 @@ pub struct Config {
     pub dump_asm: bool,
     /// Force all always_ff variables to FF (disable is_ff refinement).
     pub disable_ff_opt: bool,
+    /// Number of threads evaluating independent parts of the design.
+    /// 0 and 1 mean single-threaded simulation.
+    pub threads: usize,
+    /// Keep position independent code of JIT functions to emit a native object.
+    pub emit_object: bool,
+    /// Directory of the persistent cache of JIT-compiled functions.
+    pub jit_cache_dir: Option<PathBuf>,
+    /// Treatment of unknown conditions of `if` and `case` in 4-state simulation.
+    pub xprop: XProp,
+    /// Initial value of registers.
+    pub register_init: RegisterInit,
+    /// Report registers which are still X after reset in native testbenches.
+    pub report_x_registers: bool,
+    /// Record executed statements of `always_ff` and nested statements of
+    /// `always_comb` to `statement_trace`. Traced statements are not JIT-compiled.
+    pub trace_statements: bool,
+}
+
+/// X-propagation mode, which decides the branch taken by a condition
+/// containing X/Z without any known 1 bit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum XProp {
+    /// Take the false branch as SystemVerilog
+    #[default]
+    Standard,
+    /// Take both branches and merge the results (T-merge).
+    /// Bits which differ between the branches become X.
+    /// Branches with side effects (e.g. system function calls) can't be
+    /// merged, and are evaluated as `Standard` with a warning.
+    Pessimistic,
+    /// Take the true branch by treating X/Z bits as 1
+    Optimistic,
+}
+
+/// Initialization policy of registers, which are variables assigned in `always_ff`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RegisterInit {
+    /// Initial value of the declaration
+    #[default]
+    Declared,
+    /// All bits are X (0 in 2-state simulation)
+    X,
+    /// All bits are 0
+    Zero,
+    /// All bits are 1
+    One,
+    /// Pseudo-random bits generated from the seed
+    Random(u64),
 }
 
 impl Config {
@@ -372,15 +628,23 @@ impl Config {
         #[cfg(target_family = "wasm")]
         let jit_options = [false];
 
+        #[cfg(not(target_family = "wasm"))]
+        let thread_options = [1, 4];
+        #[cfg(target_family = "wasm")]
+        let thread_options = [1];
+
         for use_4state in [false, true] {
             for use_jit in jit_options {
                 for disable_ff_opt in [false, true] {
-                    ret.push(Config {
-                        use_4state,
-                        use_jit,
-                        disable_ff_opt,
-                        ..Default::default()
-                    });
+                    for threads in thread_options {
+                        ret.push(Config {
+                            use_4state,
+                            use_jit,
+                            disable_ff_opt,
+                            threads,
+                            ..Default::default()
+                        });
+                    }
                 }
             }
         }
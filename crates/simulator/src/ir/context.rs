@@ -51,6 +51,9 @@ pub struct Context {
     pub config: Config,
     pub scope_contexts: Vec<ScopeContext>,
     pub binary: Vec<super::BinaryStorage>,
+    /// Position independent code of JIT functions if `config.emit_object`
+    #[cfg(not(target_family = "wasm"))]
+    pub relocatable_code: Vec<crate::cranelift::RelocatableCode>,
     pub ff_total_bytes: usize,
     pub comb_total_bytes: usize,
     pub pending_statements: Vec<ProtoStatement>,
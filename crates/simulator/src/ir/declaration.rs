@@ -9,6 +9,7 @@ use crate::ir::expression::{ExpressionContext, build_dynamic_bit_select};
 #[cfg(not(target_family = "wasm"))]
 use crate::ir::statement::CompiledBlockStatement;
 use crate::ir::statement::ProtoAssignStatement;
+use crate::ir::statement::trace_statement;
 use crate::ir::variable::{ModuleVariableMeta, VarOffset, create_variable_meta};
 use crate::ir::{Event, ProtoExpression, ProtoStatement};
 use crate::simulator_error::SimulatorError;
@@ -203,6 +204,10 @@ impl Conv<&air::Declaration> for ProtoDeclaration {
                 let mut statements = vec![];
                 for stmt in &x.statements {
                     let stmts: Vec<ProtoStatement> = Conv::conv(context, stmt)?;
+                    // `if_reset` is traced after it is split into events
+                    if x.reset.is_none() {
+                        statements.extend(trace_statement(context, stmt));
+                    }
                     statements.extend(stmts);
                 }
 
@@ -213,8 +218,11 @@ impl Conv<&air::Declaration> for ProtoDeclaration {
                     let reset_event = Event::Reset(reset.id);
                     let head = statements.remove(0);
                     let (true_side, false_side) = head.split_if_reset().unwrap();
-                    event_statements.insert(reset_event, true_side);
-                    event_statements.insert(clock_event, false_side);
+                    let trace: Vec<_> = trace_statement(context, &x.statements[0])
+                        .into_iter()
+                        .collect();
+                    event_statements.insert(reset_event, [trace.clone(), true_side].concat());
+                    event_statements.insert(clock_event, [trace, false_side].concat());
                 } else {
                     event_statements.insert(clock_event, statements);
                 }
@@ -309,7 +317,7 @@ impl Conv<&air::InstDeclaration> for ProtoDeclaration {
         let (child_variable_meta, child_ff_count, child_comb_count) = create_variable_meta(
             &child_module.variables,
             &child_ff_table,
-            context.config.use_4state,
+            &context.config,
             ff_start,
             comb_start,
         )
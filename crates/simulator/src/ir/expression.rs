@@ -320,6 +320,35 @@ pub(crate) mod wide_fn_addrs {
     pub fn fill_ones() -> usize {
         fn_addr!(wide_ops::wide_fill_ones)
     }
+
+    /// Symbol names of the helpers in the runtime of native objects.
+    pub fn symbols() -> [(usize, &'static str); 23] {
+        [
+            (band(), "veryl_wide_band"),
+            (bor(), "veryl_wide_bor"),
+            (bxor(), "veryl_wide_bxor"),
+            (bxor_not(), "veryl_wide_bxor_not"),
+            (band_not(), "veryl_wide_band_not"),
+            (bnot(), "veryl_wide_bnot"),
+            (add(), "veryl_wide_add"),
+            (sub(), "veryl_wide_sub"),
+            (mul(), "veryl_wide_mul"),
+            (negate(), "veryl_wide_negate"),
+            (copy(), "veryl_wide_copy"),
+            (eq(), "veryl_wide_eq"),
+            (ne(), "veryl_wide_ne"),
+            (ucmp(), "veryl_wide_ucmp"),
+            (scmp(), "veryl_wide_scmp"),
+            (shl(), "veryl_wide_shl"),
+            (lshr(), "veryl_wide_lshr"),
+            (ashr(), "veryl_wide_ashr"),
+            (is_nonzero(), "veryl_wide_is_nonzero"),
+            (is_all_ones(), "veryl_wide_is_all_ones"),
+            (popcnt_parity(), "veryl_wide_popcnt_parity"),
+            (apply_mask(), "veryl_wide_apply_mask"),
+            (fill_ones(), "veryl_wide_fill_ones"),
+        ]
+    }
 }
 
 /// Emit a wide bitwise binary op via helper call.
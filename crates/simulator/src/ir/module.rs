@@ -4,22 +4,27 @@ use crate::HashSet;
 use crate::cranelift;
 use crate::ir::context::{Context, Conv, ScopeContext};
 use crate::ir::declaration::stable_topo_sort;
+use crate::ir::partition::{StorageUnits, comb_stages, split_independent};
 use crate::ir::variable::{
     ModuleVariableMeta, ModuleVariables, VarOffset, Variable, create_variable_meta, value_size,
     write_native_value,
 };
 use crate::ir::{
-    Event, ProtoDeclaration, ProtoStatement, ProtoStatementBlock, ProtoStatements, Statement,
-    VarId, VarPath,
+    Event, ProtoDeclaration, ProtoStatement, ProtoStatementBlock, ProtoStatements, Stages,
+    Statement, VarId, VarPath,
 };
 use crate::simulator_error::SimulatorError;
 use daggy::Dag;
 use daggy::petgraph::Direction::Outgoing;
 use daggy::petgraph::algo;
 use std::collections::VecDeque;
+use std::ops::Range;
 use veryl_analyzer::ir as air;
 use veryl_parser::resource_table::StrId;
 
+/// Statements triggered by each event.
+pub type EventStatements = HashMap<Event, Vec<Statement>>;
+
 pub struct Module {
     pub name: StrId,
     pub ports: HashMap<VarPath, VarId>,
@@ -36,6 +41,10 @@ pub struct Module {
     pub required_comb_passes: usize,
     /// FF commit entries: (current_offset, value_size) pairs.
     pub ff_commit_entries: Vec<(usize, usize)>,
+    /// Parallel stages of `comb_statements` (empty if serial).
+    pub comb_stages: Stages,
+    /// Concurrent chunks of `event_statements` per event.
+    pub event_chunks: HashMap<Event, Vec<Range<usize>>>,
 }
 
 pub struct ProtoModule {
@@ -52,6 +61,36 @@ pub struct ProtoModule {
     pub comb_statements: ProtoStatements,
     /// Number of eval_comb passes needed for full convergence.
     pub required_comb_passes: usize,
+    /// Comb statements split into stages of concurrent chunks for
+    /// multi-threaded simulation. When not empty, `comb_statements` is empty.
+    pub comb_stages: Vec<Vec<ProtoStatements>>,
+    /// Event statements split into concurrent chunks for multi-threaded
+    /// simulation. The corresponding entries of `event_statements` are empty.
+    pub event_chunks: HashMap<Event, Vec<ProtoStatements>>,
+}
+
+/// Instantiate stages of chunks into a flat statement list and the ranges of each chunk.
+fn instantiate_stages(
+    stages: &[Vec<ProtoStatements>],
+    ff_ptr: *mut u8,
+    ff_len: usize,
+    comb_ptr: *mut u8,
+    comb_len: usize,
+    use_4state: bool,
+) -> (Vec<Statement>, Stages) {
+    let mut statements = vec![];
+    let mut ranges = vec![];
+    for stage in stages {
+        let mut chunks = vec![];
+        for chunk in stage {
+            let s = chunk.to_statements(ff_ptr, ff_len, comb_ptr, comb_len, use_4state);
+            let beg = statements.len();
+            statements.extend(batch_binary_statements(s));
+            chunks.push(beg..statements.len());
+        }
+        ranges.push(chunks);
+    }
+    (statements, ranges)
 }
 
 fn create_buffers(
@@ -127,6 +166,21 @@ fn fill_buffers_recursive(
     }
 }
 
+/// Whether all variables of both trees point to the same values.
+fn same_variables(a: &ModuleVariables, b: &ModuleVariables) -> bool {
+    a.variables.len() == b.variables.len()
+        && a.variables.iter().all(|(id, x)| {
+            b.variables.get(id).is_some_and(|y| {
+                x.current_values == y.current_values && x.next_values == y.next_values
+            })
+        })
+        && a.children.len() == b.children.len()
+        && a.children
+            .iter()
+            .zip(&b.children)
+            .all(|(a, b)| same_variables(a, b))
+}
+
 fn create_variables_recursive(
     module_meta: &ModuleVariableMeta,
     ff_base: *mut u8,
@@ -158,6 +212,7 @@ fn create_variables_recursive(
                 r#type: meta.r#type.clone(),
                 width: meta.width,
                 native_bytes: meta.native_bytes,
+                is_register: meta.is_register,
                 current_values,
                 next_values,
             },
@@ -199,6 +254,46 @@ fn collect_ff_commit_entries(
 }
 
 impl ProtoModule {
+    /// Instantiate statements on buffers created by another instance of the same layout.
+    /// Returns `None` if the buffers don't match, or the module is split for multi-threading.
+    pub fn instantiate_statements(
+        &self,
+        ff_values: &mut [u8],
+        comb_values: &mut [u8],
+        module_variables: &ModuleVariables,
+    ) -> Option<(Vec<Statement>, EventStatements)> {
+        if ff_values.len() != self.ff_bytes
+            || comb_values.len() != self.comb_bytes
+            || !self.comb_stages.is_empty()
+            || !self.event_chunks.is_empty()
+        {
+            return None;
+        }
+
+        let ff_ptr = ff_values.as_mut_ptr();
+        let comb_ptr = comb_values.as_mut_ptr();
+
+        let variables = create_variables_recursive(&self.module_variable_meta, ff_ptr, comb_ptr);
+        if !same_variables(&variables, module_variables) {
+            return None;
+        }
+        let ff_len = self.ff_bytes;
+        let comb_len = self.comb_bytes;
+
+        let comb_statements =
+            self.comb_statements
+                .to_statements(ff_ptr, ff_len, comb_ptr, comb_len, self.use_4state);
+        let event_statements = self
+            .event_statements
+            .iter()
+            .map(|(event, stmts)| {
+                let s = stmts.to_statements(ff_ptr, ff_len, comb_ptr, comb_len, self.use_4state);
+                (event.clone(), s)
+            })
+            .collect();
+        Some((comb_statements, event_statements))
+    }
+
     pub fn instantiate(&self) -> Module {
         log::trace!(
             "instantiate: module={}, ff_bytes={}, comb_bytes={}",
@@ -225,22 +320,47 @@ impl ProtoModule {
         let ff_len = self.ff_bytes;
         let comb_len = self.comb_bytes;
 
+        let mut event_chunks = HashMap::default();
         let event_statements = self
             .event_statements
             .iter()
             .map(|(event, stmts)| {
+                if let Some(chunks) = self.event_chunks.get(event) {
+                    let (s, mut ranges) = instantiate_stages(
+                        std::slice::from_ref(chunks),
+                        ff_ptr,
+                        ff_len,
+                        comb_ptr,
+                        comb_len,
+                        self.use_4state,
+                    );
+                    event_chunks.insert(event.clone(), ranges.pop().unwrap());
+                    return (event.clone(), s);
+                }
                 let s = stmts.to_statements(ff_ptr, ff_len, comb_ptr, comb_len, self.use_4state);
                 (event.clone(), batch_binary_statements(s))
             })
             .collect();
 
-        let comb_statements = batch_binary_statements(self.comb_statements.to_statements(
-            ff_ptr,
-            ff_len,
-            comb_ptr,
-            comb_len,
-            self.use_4state,
-        ));
+        let (comb_statements, comb_stages) = if self.comb_stages.is_empty() {
+            let s = self.comb_statements.to_statements(
+                ff_ptr,
+                ff_len,
+                comb_ptr,
+                comb_len,
+                self.use_4state,
+            );
+            (batch_binary_statements(s), vec![])
+        } else {
+            instantiate_stages(
+                &self.comb_stages,
+                ff_ptr,
+                ff_len,
+                comb_ptr,
+                comb_len,
+                self.use_4state,
+            )
+        };
 
         let ff_commit_entries =
             collect_ff_commit_entries(&self.module_variable_meta, self.use_4state);
@@ -259,6 +379,8 @@ impl ProtoModule {
             comb_statements,
             required_comb_passes: self.required_comb_passes,
             ff_commit_entries,
+            comb_stages,
+            event_chunks,
         }
     }
 
@@ -310,7 +432,15 @@ impl ProtoModule {
         for (event, stmts) in &self.event_statements {
             validate_stmts(stmts, &format!("event {event:?}"));
         }
+        for (event, chunks) in &self.event_chunks {
+            for stmts in chunks {
+                validate_stmts(stmts, &format!("event {event:?}"));
+            }
+        }
         validate_stmts(&self.comb_statements, "comb");
+        for stmts in self.comb_stages.iter().flatten() {
+            validate_stmts(stmts, "comb");
+        }
 
         // Validate variable metadata offsets
         validate_meta_offsets(&self.module_variable_meta, ff_bytes, comb_bytes, use_4state);
@@ -902,7 +1032,8 @@ fn compute_required_passes(sorted: &[ProtoStatement]) -> usize {
 /// Compute dependency levels for sorted ProtoStatements and reorder within
 /// each level so that CompiledBlocks with the same func pointer are adjacent.
 /// This enables batching of same-function JIT calls.
-fn reorder_by_level(sorted: Vec<ProtoStatement>) -> Vec<ProtoStatement> {
+/// Returns statements grouped by level.
+fn level_groups(sorted: Vec<ProtoStatement>) -> Vec<Vec<ProtoStatement>> {
     // Level = max(var_level[input]) + 1. For CBs, use all offsets
     // (including FF) since gather_variable_offsets filters FF for DAG.
     let mut var_level: HashMap<VarOffset, usize> = HashMap::default();
@@ -961,7 +1092,7 @@ fn reorder_by_level(sorted: Vec<ProtoStatement>) -> Vec<ProtoStatement> {
         *group = topo_sort_within_level(std::mem::take(group));
     }
 
-    groups.into_iter().flatten().collect()
+    groups
 }
 
 /// Local topological sort within a single level group.
@@ -1115,7 +1246,7 @@ impl Conv<&air::Module> for ProtoModule {
         let (variable_meta, ff_bytes, comb_bytes) = create_variable_meta(
             &src.variables,
             &ff_table,
-            context.config.use_4state,
+            &context.config,
             ff_start,
             comb_start,
         )
@@ -1167,18 +1298,10 @@ impl Conv<&air::Module> for ProtoModule {
 
         let unified_sorted = analyze_dependency(unified)?;
         // No DCE/inlining: unified list includes internal child comb that would be incorrectly removed.
-        let unified_sorted = reorder_by_level(unified_sorted);
+        let levels = level_groups(unified_sorted);
+        let level_lens: Vec<_> = levels.iter().map(|x| x.len()).collect();
+        let unified_sorted: Vec<_> = levels.into_iter().flatten().collect();
         let required_comb_passes = compute_required_passes(&unified_sorted);
-        let comb_statements = try_jit_no_cache(context, unified_sorted);
-
-        // Event statements preserve source order (no topological sorting).
-        // NBA semantics: reads come from current, writes go to next, then
-        // ff_commit copies next → current. Source order must be preserved
-        // for sequential writes to the same variable.
-        let event_statements: HashMap<Event, ProtoStatements> = all_event_statements
-            .into_iter()
-            .map(|(event, stmts)| (event, try_jit(context, stmts)))
-            .collect();
 
         let module_variable_meta = ModuleVariableMeta {
             name: src.name,
@@ -1186,6 +1309,58 @@ impl Conv<&air::Module> for ProtoModule {
             children: all_child_modules,
         };
 
+        // Split independent statements into chunks for multi-threaded simulation.
+        // Chunks are JIT-compiled separately so that they can run concurrently.
+        let threads = context.config.threads;
+        let mut units = StorageUnits::new(&module_variable_meta);
+
+        let mut comb_stages_proto = vec![];
+        let comb_statements = if threads > 1 {
+            let mut rest = unified_sorted.into_iter();
+            let levels = level_lens
+                .iter()
+                .map(|x| rest.by_ref().take(*x).collect())
+                .collect();
+            let stages = comb_stages(levels, &mut units, threads);
+            if stages.iter().any(|x| x.len() > 1) {
+                for stage in stages {
+                    let chunks = stage
+                        .into_iter()
+                        .map(|x| try_jit_no_cache(context, x))
+                        .collect();
+                    comb_stages_proto.push(chunks);
+                }
+                ProtoStatements(vec![])
+            } else {
+                let stmts = stages.into_iter().flatten().flatten().collect();
+                try_jit_no_cache(context, stmts)
+            }
+        } else {
+            try_jit_no_cache(context, unified_sorted)
+        };
+
+        // Event statements preserve source order (no topological sorting).
+        // NBA semantics: reads come from current, writes go to next, then
+        // ff_commit copies next → current. Source order must be preserved
+        // for sequential writes to the same variable.
+        let mut event_chunks = HashMap::default();
+        let mut event_statements: HashMap<Event, ProtoStatements> = HashMap::default();
+        for (event, stmts) in all_event_statements {
+            let chunks = if matches!(event, Event::Clock(_) | Event::Reset(_)) {
+                split_independent(stmts, &mut units, threads)
+            } else {
+                vec![stmts]
+            };
+            if chunks.len() > 1 {
+                let chunks = chunks.into_iter().map(|x| try_jit(context, x)).collect();
+                event_chunks.insert(event.clone(), chunks);
+                event_statements.insert(event, ProtoStatements(vec![]));
+            } else {
+                let stmts = chunks.into_iter().flatten().collect();
+                event_statements.insert(event, try_jit(context, stmts));
+            }
+        }
+
         Ok(ProtoModule {
             name: src.name,
             ports: src.ports.clone(),
@@ -1196,6 +1371,8 @@ impl Conv<&air::Module> for ProtoModule {
             event_statements,
             comb_statements,
             required_comb_passes,
+            comb_stages: comb_stages_proto,
+            event_chunks,
         })
     }
 }
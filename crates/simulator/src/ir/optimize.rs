@@ -78,7 +78,13 @@ fn count_stmt_reads(stmt: &ProtoStatement, counts: &mut HashMap<CombKey, usize>)
             ProtoSystemFunctionCall::Assert { condition, .. } => {
                 count_expr_reads(condition, counts);
             }
-            ProtoSystemFunctionCall::Finish => {}
+            ProtoSystemFunctionCall::Force { value, .. }
+            | ProtoSystemFunctionCall::Deposit { value, .. } => {
+                count_expr_reads(value, counts);
+            }
+            ProtoSystemFunctionCall::Finish
+            | ProtoSystemFunctionCall::Release { .. }
+            | ProtoSystemFunctionCall::Trace(_) => {}
         },
         ProtoStatement::CompiledBlock(x) => {
             for off in &x.input_offsets {
@@ -226,6 +232,7 @@ fn substitute_stmt(
                 .into_iter()
                 .map(|s| substitute_stmt(s, inline_map))
                 .collect(),
+            xprop: x.xprop,
         }),
         ProtoStatement::AssignDynamic(x) => {
             let mut x = x;
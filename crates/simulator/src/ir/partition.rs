@@ -0,0 +1,241 @@
+//! Partitioning of statements into independent chunks for multi-threaded simulation.
+//!
+//! Two statements are independent if neither writes a storage unit the other
+//! reads or writes. A storage unit is a whole variable (all array elements)
+//! in one slot: comb value, FF current value or FF next value. Because FF
+//! reads go to current and event writes go to next, `always_ff` blocks of
+//! different instances are usually independent of each other.
+
+use crate::ir::{ModuleVariableMeta, ProtoStatement, VarOffset};
+use crate::{HashMap, HashSet};
+
+/// Minimum number of statements in a comb level to evaluate it in parallel.
+/// Smaller levels are merged into a serial stage to save synchronization.
+pub(crate) const COMB_PARALLEL_MIN: usize = 16;
+
+/// Mapping from variable offsets to storage units
+pub(crate) struct StorageUnits {
+    units: HashMap<VarOffset, usize>,
+    next: usize,
+}
+
+impl StorageUnits {
+    pub fn new(meta: &ModuleVariableMeta) -> Self {
+        let mut ret = Self {
+            units: HashMap::default(),
+            next: 0,
+        };
+        ret.add_module(meta);
+        ret
+    }
+
+    fn add_module(&mut self, meta: &ModuleVariableMeta) {
+        let mut ids: Vec<_> = meta.variable_meta.keys().collect();
+        ids.sort();
+        for id in ids {
+            let current = self.next;
+            let next = self.next + 1;
+            self.next += 2;
+            for element in &meta.variable_meta[id].elements {
+                self.units.insert(element.current, current);
+                if element.is_ff() {
+                    self.units.insert(VarOffset::Ff(element.next_offset), next);
+                }
+            }
+        }
+        for child in &meta.children {
+            self.add_module(child);
+        }
+    }
+
+    /// Offsets not belonging to any variable (e.g. the middle of an array
+    /// slot) get a unit of their own.
+    fn unit(&mut self, offset: VarOffset) -> usize {
+        if let Some(x) = self.units.get(&offset) {
+            *x
+        } else {
+            let ret = self.next;
+            self.next += 1;
+            self.units.insert(offset, ret);
+            ret
+        }
+    }
+}
+
+/// Gather offsets read and written by a statement, including FF offsets
+/// which `gather_variable_offsets` omits for compiled blocks.
+fn footprint(stmt: &ProtoStatement, reads: &mut Vec<VarOffset>, writes: &mut Vec<VarOffset>) {
+    match stmt {
+        ProtoStatement::CompiledBlock(x) => {
+            reads.extend_from_slice(&x.input_offsets);
+            writes.extend_from_slice(&x.output_offsets);
+        }
+        ProtoStatement::If(x) => {
+            if let Some(cond) = &x.cond {
+                cond.gather_variable_offsets(reads);
+            }
+            for s in x.true_side.iter().chain(&x.false_side) {
+                footprint(s, reads, writes);
+            }
+        }
+        ProtoStatement::For(x) => {
+            writes.push(x.var_offset);
+            for s in &x.body {
+                footprint(s, reads, writes);
+            }
+        }
+        _ => stmt.gather_variable_offsets(reads, writes),
+    }
+}
+
+/// Whether the statement has effects outside of variable storage, which must
+/// keep the source order and run on the simulator thread.
+fn is_ordered(stmt: &ProtoStatement) -> bool {
+    match stmt {
+        ProtoStatement::SystemFunctionCall(_) | ProtoStatement::TbMethodCall { .. } => true,
+        ProtoStatement::If(x) => x.true_side.iter().chain(&x.false_side).any(is_ordered),
+        ProtoStatement::For(x) => x.body.iter().any(is_ordered),
+        _ => false,
+    }
+}
+
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
+
+fn union(parent: &mut [usize], x: usize, y: usize) {
+    let x = find(parent, x);
+    let y = find(parent, y);
+    if x != y {
+        parent[x.max(y)] = x.min(y);
+    }
+}
+
+/// Split statements into at most `threads` chunks which can be evaluated
+/// concurrently. Dependent statements are kept in the same chunk in the
+/// original order, and ordered statements are gathered into the first chunk.
+pub(crate) fn split_independent(
+    stmts: Vec<ProtoStatement>,
+    units: &mut StorageUnits,
+    threads: usize,
+) -> Vec<Vec<ProtoStatement>> {
+    let n = stmts.len();
+    if threads <= 1 || n <= 1 {
+        return vec![stmts];
+    }
+
+    let mut stmt_reads = Vec::with_capacity(n);
+    let mut stmt_writes = Vec::with_capacity(n);
+    let mut written = HashSet::default();
+    for stmt in &stmts {
+        let mut reads = vec![];
+        let mut writes = vec![];
+        footprint(stmt, &mut reads, &mut writes);
+        let reads: Vec<_> = reads.into_iter().map(|x| units.unit(x)).collect();
+        let writes: Vec<_> = writes.into_iter().map(|x| units.unit(x)).collect();
+        for x in &writes {
+            written.insert(*x);
+        }
+        stmt_reads.push(reads);
+        stmt_writes.push(writes);
+    }
+
+    // nodes: statements, then the ordered node, then storage units
+    let ordered = n;
+    let unit_base = n + 1;
+    let mut parent: Vec<usize> = (0..unit_base + units.next).collect();
+    for i in 0..n {
+        for x in &stmt_writes[i] {
+            union(&mut parent, i, unit_base + x);
+        }
+        for x in &stmt_reads[i] {
+            if written.contains(x) {
+                union(&mut parent, i, unit_base + x);
+            }
+        }
+        if is_ordered(&stmts[i]) {
+            union(&mut parent, i, ordered);
+        }
+    }
+
+    // components in the order of their first statement
+    let mut components: Vec<Vec<usize>> = vec![];
+    let mut component_index = HashMap::default();
+    let mut ordered_component = None;
+    for i in 0..n {
+        let root = find(&mut parent, i);
+        let index = *component_index.entry(root).or_insert_with(|| {
+            components.push(vec![]);
+            components.len() - 1
+        });
+        components[index].push(i);
+        if root == find(&mut parent, ordered) {
+            ordered_component = Some(index);
+        }
+    }
+    if components.len() == 1 {
+        return vec![stmts];
+    }
+
+    // ordered component goes to the first chunk, the others to the lightest chunk
+    let threads = threads.min(components.len());
+    let mut chunk_of = vec![0; n];
+    let mut sizes = vec![0; threads];
+    if let Some(x) = ordered_component {
+        sizes[0] = components[x].len();
+    }
+    for (index, component) in components.iter().enumerate() {
+        let chunk = if Some(index) == ordered_component {
+            0
+        } else {
+            let chunk = (0..threads).min_by_key(|x| (sizes[*x], *x)).unwrap();
+            sizes[chunk] += component.len();
+            chunk
+        };
+        for i in component {
+            chunk_of[*i] = chunk;
+        }
+    }
+
+    let mut ret = vec![vec![]; threads];
+    for (i, stmt) in stmts.into_iter().enumerate() {
+        ret[chunk_of[i]].push(stmt);
+    }
+    ret.retain(|x| !x.is_empty());
+    ret
+}
+
+/// Build stages of comb evaluation from dependency levels.
+/// Each stage is a list of chunks which can be evaluated concurrently, and
+/// stages are separated by barriers.
+pub(crate) fn comb_stages(
+    levels: Vec<Vec<ProtoStatement>>,
+    units: &mut StorageUnits,
+    threads: usize,
+) -> Vec<Vec<Vec<ProtoStatement>>> {
+    let mut ret = vec![];
+    let mut serial = vec![];
+    for level in levels {
+        let chunks = if level.len() >= COMB_PARALLEL_MIN {
+            split_independent(level, units, threads)
+        } else {
+            vec![level]
+        };
+        if chunks.len() > 1 {
+            if !serial.is_empty() {
+                ret.push(vec![std::mem::take(&mut serial)]);
+            }
+            ret.push(chunks);
+        } else {
+            serial.extend(chunks.into_iter().flatten());
+        }
+    }
+    if !serial.is_empty() {
+        ret.push(vec![serial]);
+    }
+    ret
+}
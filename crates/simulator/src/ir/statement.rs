@@ -12,15 +12,17 @@ use crate::ir::expression::{
     band_const, build_dynamic_select_shift, gen_mask_for_width, gen_mask_range_128, iconst_128,
 };
 use crate::ir::variable::{
-    VarOffset, native_bytes as calc_native_bytes, read_native_value, write_native_value,
+    VarOffset, native_bytes as calc_native_bytes, read_native_value, value_size, write_native_value,
 };
-use crate::ir::{Expression, ProtoExpression, Value};
+use crate::ir::{Expression, ProtoExpression, Value, XProp};
 use crate::output_buffer;
 use crate::simulator_error::SimulatorError;
+use crate::statement_trace;
 #[cfg(not(target_family = "wasm"))]
 use cranelift::prelude::types::{I32, I64, I128};
 #[cfg(not(target_family = "wasm"))]
 use cranelift::prelude::{FunctionBuilder, InstBuilder, IntCC, MemFlags};
+use std::sync::atomic::{AtomicBool, Ordering};
 use veryl_analyzer::conv::utils::eval_array_literal;
 use veryl_analyzer::ir as air;
 use veryl_analyzer::ir::FunctionCall;
@@ -102,6 +104,19 @@ pub enum SystemFunctionCall {
         message: Option<String>,
     },
     Finish,
+    Force {
+        path: String,
+        value: Expression,
+    },
+    Release {
+        path: String,
+    },
+    Deposit {
+        path: String,
+        value: Expression,
+    },
+    /// Record execution of the statement at the token for the debugger
+    Trace(TokenRange),
 }
 
 #[derive(Clone)]
@@ -163,7 +178,11 @@ pub enum Statement {
     Binary(FuncPtr, *const u8, *const u8),
     BinaryBatch(FuncPtr, Vec<(*const u8, *const u8)>),
     SystemFunctionCall(SystemFunctionCall),
-    TbMethodCall { inst: StrId, method: TbMethodKind },
+    TbMethodCall {
+        inst: StrId,
+        method: TbMethodKind,
+        token: TokenRange,
+    },
 }
 
 // SAFETY: Raw pointers point into the owning Ir's exclusively-owned buffers.
@@ -253,6 +272,56 @@ impl Statement {
         }
     }
 
+    /// Gather (pointer, native bytes, use_4state) of all values written by the statement.
+    /// Returns false if they can't be determined.
+    pub fn gather_destination(&self, dsts: &mut Vec<(*mut u8, usize, bool)>) -> bool {
+        match self {
+            Statement::Assign(x) => {
+                dsts.push((x.dst, x.dst_native_bytes, x.dst_use_4state));
+                true
+            }
+            Statement::AssignDynamic(x) => {
+                for i in 0..x.dst_num_elements {
+                    let ptr = unsafe { x.dst_base_ptr.offset(i as isize * x.dst_stride) };
+                    dsts.push((ptr, x.dst_native_bytes, x.dst_use_4state));
+                }
+                true
+            }
+            Statement::If(x) => x
+                .true_side
+                .iter()
+                .chain(&x.false_side)
+                .all(|x| x.gather_destination(dsts)),
+            Statement::For(x) => {
+                dsts.push((x.var_ptr, x.var_native_bytes, x.var_use_4state));
+                x.body.iter().all(|x| x.gather_destination(dsts))
+            }
+            Statement::SystemFunctionCall(SystemFunctionCall::Trace(_)) => true,
+            // system functions have side effects other than the destinations
+            // (e.g. output of `$display`), so they can't be merged
+            Statement::SystemFunctionCall(_)
+            | Statement::Binary(_, _, _)
+            | Statement::BinaryBatch(_, _)
+            | Statement::TbMethodCall { .. } => false,
+        }
+    }
+
+    /// Whether an unknown condition of pessimistic X-propagation was
+    /// treated as false because both sides couldn't be merged.
+    pub fn merge_fallback(&self) -> bool {
+        match self {
+            Statement::If(x) => {
+                x.merge_fallback.load(Ordering::Relaxed)
+                    || x.true_side
+                        .iter()
+                        .chain(&x.false_side)
+                        .any(|x| x.merge_fallback())
+            }
+            Statement::For(x) => x.body.iter().any(|x| x.merge_fallback()),
+            _ => false,
+        }
+    }
+
     pub fn gather_variable(&self, inputs: &mut Vec<*const u8>, outputs: &mut Vec<*const u8>) {
         match self {
             Statement::Assign(x) => x.gather_variable(inputs, outputs),
@@ -388,9 +457,13 @@ impl SystemFunctionCall {
                     panic!("$assert failed: {msg}");
                 }
             }
-            SystemFunctionCall::Finish => {
+            SystemFunctionCall::Finish
+            | SystemFunctionCall::Force { .. }
+            | SystemFunctionCall::Release { .. }
+            | SystemFunctionCall::Deposit { .. } => {
                 // Handled by testbench driver
             }
+            SystemFunctionCall::Trace(x) => statement_trace::record(x),
         }
     }
 
@@ -407,7 +480,13 @@ impl SystemFunctionCall {
                 let mut dummy_outputs = vec![];
                 condition.gather_variable(inputs, &mut dummy_outputs);
             }
-            SystemFunctionCall::Finish => {}
+            SystemFunctionCall::Force { value, .. } | SystemFunctionCall::Deposit { value, .. } => {
+                let mut dummy_outputs = vec![];
+                value.gather_variable(inputs, &mut dummy_outputs);
+            }
+            SystemFunctionCall::Finish
+            | SystemFunctionCall::Release { .. }
+            | SystemFunctionCall::Trace(_) => {}
         }
     }
 }
@@ -432,6 +511,18 @@ pub enum ProtoSystemFunctionCall {
         message: Option<String>,
     },
     Finish,
+    Force {
+        path: String,
+        value: ProtoExpression,
+    },
+    Release {
+        path: String,
+    },
+    Deposit {
+        path: String,
+        value: ProtoExpression,
+    },
+    Trace(TokenRange),
 }
 
 #[derive(Clone, Debug)]
@@ -714,6 +805,7 @@ pub enum ProtoStatement {
     TbMethodCall {
         inst: StrId,
         method: ProtoTbMethodKind,
+        token: TokenRange,
     },
 }
 
@@ -776,7 +868,13 @@ impl ProtoStatement {
                 ProtoSystemFunctionCall::Assert { condition, .. } => {
                     condition.adjust_offsets(ff_delta, comb_delta);
                 }
-                ProtoSystemFunctionCall::Finish => {}
+                ProtoSystemFunctionCall::Force { value, .. }
+                | ProtoSystemFunctionCall::Deposit { value, .. } => {
+                    value.adjust_offsets(ff_delta, comb_delta);
+                }
+                ProtoSystemFunctionCall::Finish
+                | ProtoSystemFunctionCall::Release { .. }
+                | ProtoSystemFunctionCall::Trace(_) => {}
             },
             ProtoStatement::CompiledBlock(_) => {
                 // CompiledBlocks use ff_delta_bytes/comb_delta_bytes at runtime.
@@ -891,7 +989,13 @@ impl ProtoStatement {
                 ProtoSystemFunctionCall::Assert { condition, .. } => {
                     condition.gather_variable_offsets(inputs);
                 }
-                ProtoSystemFunctionCall::Finish => {}
+                ProtoSystemFunctionCall::Force { value, .. }
+                | ProtoSystemFunctionCall::Deposit { value, .. } => {
+                    value.gather_variable_offsets(inputs);
+                }
+                ProtoSystemFunctionCall::Finish
+                | ProtoSystemFunctionCall::Release { .. }
+                | ProtoSystemFunctionCall::Trace(_) => {}
             },
             ProtoStatement::CompiledBlock(x) => {
                 // Only include comb (non-FF) offsets for dependency analysis.
@@ -1132,6 +1236,40 @@ impl ProtoStatement {
                     ProtoSystemFunctionCall::Finish => {
                         Statement::SystemFunctionCall(SystemFunctionCall::Finish)
                     }
+                    ProtoSystemFunctionCall::Trace(x) => {
+                        Statement::SystemFunctionCall(SystemFunctionCall::Trace(*x))
+                    }
+                    ProtoSystemFunctionCall::Force { path, value } => {
+                        let value = value.apply_values_ptr(
+                            ff_values_ptr,
+                            ff_len,
+                            comb_values_ptr,
+                            comb_len,
+                            use_4state,
+                        );
+                        Statement::SystemFunctionCall(SystemFunctionCall::Force {
+                            path: path.clone(),
+                            value,
+                        })
+                    }
+                    ProtoSystemFunctionCall::Release { path } => {
+                        Statement::SystemFunctionCall(SystemFunctionCall::Release {
+                            path: path.clone(),
+                        })
+                    }
+                    ProtoSystemFunctionCall::Deposit { path, value } => {
+                        let value = value.apply_values_ptr(
+                            ff_values_ptr,
+                            ff_len,
+                            comb_values_ptr,
+                            comb_len,
+                            use_4state,
+                        );
+                        Statement::SystemFunctionCall(SystemFunctionCall::Deposit {
+                            path: path.clone(),
+                            value,
+                        })
+                    }
                 },
                 ProtoStatement::CompiledBlock(x) => {
                     // Use wrapping_offset because the adjusted pointer may temporarily
@@ -1173,7 +1311,11 @@ impl ProtoStatement {
                         body,
                     })
                 }
-                ProtoStatement::TbMethodCall { inst, method } => {
+                ProtoStatement::TbMethodCall {
+                    inst,
+                    method,
+                    token,
+                } => {
                     let method = match method {
                         ProtoTbMethodKind::ClockNext { count, period } => {
                             let count = count.as_ref().map(|e| {
@@ -1215,6 +1357,7 @@ impl ProtoStatement {
                     Statement::TbMethodCall {
                         inst: *inst,
                         method,
+                        token: *token,
                     }
                 }
             }
@@ -1944,24 +2087,53 @@ impl ProtoAssignStatement {
     }
 }
 
-#[derive(Clone)]
 pub struct IfStatement {
     pub cond: Option<Expression>,
     pub true_side: Vec<Statement>,
     pub false_side: Vec<Statement>,
+    pub xprop: XProp,
+    /// Set when both sides couldn't be merged for an unknown condition
+    pub merge_fallback: AtomicBool,
+}
+
+impl Clone for IfStatement {
+    fn clone(&self) -> Self {
+        Self {
+            cond: self.cond.clone(),
+            true_side: self.true_side.clone(),
+            false_side: self.false_side.clone(),
+            xprop: self.xprop,
+            merge_fallback: AtomicBool::new(self.merge_fallback.load(Ordering::Relaxed)),
+        }
+    }
 }
 
 impl IfStatement {
     pub fn eval_step(&self, mask_cache: &mut MaskCache) {
         let cond = if let Some(x) = &self.cond {
             let cond = x.eval(mask_cache);
-            match &cond {
-                Value::U64(x) => (x.payload & !x.mask_xz) != 0,
+            let (known_one, unknown) = match &cond {
+                Value::U64(x) => ((x.payload & !x.mask_xz) != 0, x.mask_xz != 0),
                 Value::BigUint(x) => {
                     use veryl_analyzer::value::biguint_to_u128;
                     let payload = biguint_to_u128(&x.payload);
                     let mask_xz = biguint_to_u128(&x.mask_xz);
-                    (payload & !mask_xz) != 0
+                    ((payload & !mask_xz) != 0, mask_xz != 0)
+                }
+            };
+            if known_one || !unknown {
+                known_one
+            } else {
+                match self.xprop {
+                    XProp::Standard => false,
+                    XProp::Optimistic => true,
+                    XProp::Pessimistic => {
+                        if self.eval_merge(mask_cache) {
+                            return;
+                        }
+                        self.merge_fallback.store(true, Ordering::Relaxed);
+                        false
+                    }
                 }
             }
         } else {
@@ -1979,6 +2151,56 @@ impl IfStatement {
         }
     }
 
+    /// Evaluate both sides for an unknown condition, and merge their results.
+    /// Bits which differ between the sides become X.
+    /// Returns false if destinations of the sides can't be determined.
+    fn eval_merge(&self, mask_cache: &mut MaskCache) -> bool {
+        let mut dsts = vec![];
+        for x in self.true_side.iter().chain(&self.false_side) {
+            if !x.gather_destination(&mut dsts) {
+                return false;
+            }
+        }
+        dsts.sort_unstable();
+        dsts.dedup();
+
+        let save = |dsts: &[(*mut u8, usize, bool)]| -> Vec<Vec<u8>> {
+            dsts.iter()
+                .map(|&(ptr, nb, use_4state)| unsafe {
+                    std::slice::from_raw_parts(ptr, value_size(nb, use_4state)).to_vec()
+                })
+                .collect()
+        };
+
+        let original = save(&dsts);
+        for x in &self.true_side {
+            x.eval_step(mask_cache);
+        }
+        let taken = save(&dsts);
+        for (&(ptr, _, _), original) in dsts.iter().zip(&original) {
+            unsafe {
+                std::ptr::copy_nonoverlapping(original.as_ptr(), ptr, original.len());
+            }
+        }
+        for x in &self.false_side {
+            x.eval_step(mask_cache);
+        }
+
+        for (&(ptr, nb, use_4state), taken) in dsts.iter().zip(&taken) {
+            if !use_4state {
+                continue;
+            }
+            let dst = unsafe { std::slice::from_raw_parts_mut(ptr, nb * 2) };
+            let (payload, mask_xz) = dst.split_at_mut(nb);
+            for i in 0..nb {
+                let x = mask_xz[i] | taken[nb + i] | (payload[i] ^ taken[i]);
+                payload[i] &= !x;
+                mask_xz[i] = x;
+            }
+        }
+        true
+    }
+
     pub fn gather_variable(&self, inputs: &mut Vec<*const u8>, outputs: &mut Vec<*const u8>) {
         if let Some(x) = &self.cond {
             x.gather_variable(inputs, outputs);
@@ -1998,11 +2220,16 @@ pub struct ProtoIfStatement {
     pub cond: Option<ProtoExpression>,
     pub true_side: Vec<ProtoStatement>,
     pub false_side: Vec<ProtoStatement>,
+    pub xprop: XProp,
 }
 
 impl ProtoIfStatement {
     #[cfg(not(target_family = "wasm"))]
     pub fn can_build_binary(&self) -> bool {
+        // Merging both sides is supported by the interpreter only
+        if self.xprop == XProp::Pessimistic {
+            return false;
+        }
         if let Some(cond) = &self.cond
             && !cond.can_build_binary()
         {
@@ -2045,6 +2272,8 @@ impl ProtoIfStatement {
                 cond,
                 true_side,
                 false_side,
+                xprop: self.xprop,
+                merge_fallback: AtomicBool::new(false),
             }
         }
     }
@@ -2056,6 +2285,10 @@ impl ProtoIfStatement {
         builder: &mut FunctionBuilder,
         is_last: bool,
     ) -> Option<()> {
+        if self.xprop == XProp::Pessimistic {
+            return None;
+        }
+
         let true_block = builder.create_block();
         let false_block = builder.create_block();
         let final_block = builder.create_block();
@@ -2063,10 +2296,10 @@ impl ProtoIfStatement {
         // Evaluate condition
         if let Some(x) = &self.cond {
             let (cond_payload, cond_mask_xz) = x.build_binary(context, builder)?;
-            let effective_cond = if let Some(mask_xz) = cond_mask_xz {
-                builder.ins().band_not(cond_payload, mask_xz)
-            } else {
-                cond_payload
+            let effective_cond = match (cond_mask_xz, self.xprop) {
+                (Some(mask_xz), XProp::Optimistic) => builder.ins().bor(cond_payload, mask_xz),
+                (Some(mask_xz), _) => builder.ins().band_not(cond_payload, mask_xz),
+                (None, _) => cond_payload,
             };
             builder
                 .ins()
@@ -2242,6 +2475,29 @@ impl Conv<&air::Statement> for Vec<ProtoStatement> {
                         ProtoSystemFunctionCall::Finish,
                     )]
                 }
+                SystemFunctionKind::Force(path, value)
+                | SystemFunctionKind::Deposit(path, value) => {
+                    let Some(path) = extract_string_value(&path.0) else {
+                        return Err(SimulatorError::unsupported_description(&x.comptime.token));
+                    };
+                    let path = path.trim_matches('"').to_string();
+                    let value: ProtoExpression = Conv::conv(context, &value.0)?;
+                    let call = if matches!(x.kind, SystemFunctionKind::Force(_, _)) {
+                        ProtoSystemFunctionCall::Force { path, value }
+                    } else {
+                        ProtoSystemFunctionCall::Deposit { path, value }
+                    };
+                    vec![ProtoStatement::SystemFunctionCall(call)]
+                }
+                SystemFunctionKind::Release(path) => {
+                    let Some(path) = extract_string_value(&path.0) else {
+                        return Err(SimulatorError::unsupported_description(&x.comptime.token));
+                    };
+                    let path = path.trim_matches('"').to_string();
+                    vec![ProtoStatement::SystemFunctionCall(
+                        ProtoSystemFunctionCall::Release { path },
+                    )]
+                }
                 _ => {
                     return Err(SimulatorError::unsupported_description(&x.comptime.token));
                 }
@@ -2276,6 +2532,7 @@ impl Conv<&air::Statement> for Vec<ProtoStatement> {
                 vec![ProtoStatement::TbMethodCall {
                     inst: x.inst,
                     method,
+                    token: x.token,
                 }]
             }
             air::Statement::For(x) => {
@@ -2292,6 +2549,7 @@ impl Conv<&air::Statement> for Vec<ProtoStatement> {
                 let mut body = vec![];
                 for stmt in &x.body {
                     let stmts: Vec<ProtoStatement> = Conv::conv(context, stmt)?;
+                    body.extend(trace_statement(context, stmt));
                     body.extend(stmts);
                 }
 
@@ -2733,6 +2991,24 @@ impl Conv<&air::AssignStatement> for ProtoAssignStatement {
     }
 }
 
+/// X-propagation mode of `if`, which is meaningful in 4-state simulation only.
+/// Statement recording execution of `src` if `Config::trace_statements` is set
+pub fn trace_statement(context: &ConvContext, src: &air::Statement) -> Option<ProtoStatement> {
+    if !context.config.trace_statements || context.in_initial {
+        return None;
+    }
+    statement_trace::statement_token(src)
+        .map(|x| ProtoStatement::SystemFunctionCall(ProtoSystemFunctionCall::Trace(x)))
+}
+
+fn xprop(context: &ConvContext) -> XProp {
+    if context.config.use_4state {
+        context.config.xprop
+    } else {
+        XProp::Standard
+    }
+}
+
 impl Conv<&air::IfStatement> for ProtoIfStatement {
     fn conv(context: &mut ConvContext, src: &air::IfStatement) -> Result<Self, SimulatorError> {
         let cond: ProtoExpression = Conv::conv(context, &src.cond)?;
@@ -2740,12 +3016,14 @@ impl Conv<&air::IfStatement> for ProtoIfStatement {
         let mut true_side = vec![];
         for x in &src.true_side {
             let stmts: Vec<ProtoStatement> = Conv::conv(context, x)?;
+            true_side.extend(trace_statement(context, x));
             true_side.extend(stmts);
         }
 
         let mut false_side = vec![];
         for x in &src.false_side {
             let stmts: Vec<ProtoStatement> = Conv::conv(context, x)?;
+            false_side.extend(trace_statement(context, x));
             false_side.extend(stmts);
         }
 
@@ -2753,6 +3031,7 @@ impl Conv<&air::IfStatement> for ProtoIfStatement {
             cond: Some(cond),
             true_side,
             false_side,
+            xprop: xprop(context),
         })
     }
 }
@@ -2765,12 +3044,14 @@ impl Conv<&air::IfResetStatement> for ProtoIfStatement {
         let mut true_side = vec![];
         for x in &src.true_side {
             let stmts: Vec<ProtoStatement> = Conv::conv(context, x)?;
+            true_side.extend(trace_statement(context, x));
             true_side.extend(stmts);
         }
 
         let mut false_side = vec![];
         for x in &src.false_side {
             let stmts: Vec<ProtoStatement> = Conv::conv(context, x)?;
+            false_side.extend(trace_statement(context, x));
             false_side.extend(stmts);
         }
 
@@ -2778,6 +3059,7 @@ impl Conv<&air::IfResetStatement> for ProtoIfStatement {
             cond: None,
             true_side,
             false_side,
+            xprop: xprop(context),
         })
     }
 }
@@ -1,4 +1,5 @@
 use crate::HashMap;
+use crate::ir::{Config, RegisterInit};
 use std::fmt;
 use veryl_analyzer::ir as air;
 use veryl_analyzer::ir::{Type, VarId, VarPath};
@@ -193,6 +194,8 @@ pub struct Variable {
     pub r#type: Type,
     pub width: usize,
     pub native_bytes: usize,
+    /// Whether the variable is assigned in `always_ff`
+    pub is_register: bool,
     pub current_values: Vec<*mut u8>,
     pub next_values: Vec<*mut u8>,
 }
@@ -242,6 +245,8 @@ pub struct VariableMeta {
     pub elements: Vec<VariableElement>,
     /// initial value for each element; used when instantiating
     pub initial_values: Vec<Value>,
+    /// Whether the variable is assigned in `always_ff`
+    pub is_register: bool,
 }
 
 impl VariableMeta {
@@ -282,6 +287,41 @@ impl VariableMeta {
     }
 }
 
+/// Initial value of a register element according to `policy`.
+/// `salt` distinguishes elements so that random values differ among them.
+fn register_initial_value(
+    declared: &Value,
+    width: usize,
+    policy: RegisterInit,
+    salt: u64,
+) -> Value {
+    let signed = declared.signed();
+    let nb = native_bytes(width).max(8);
+    let mut ret = match policy {
+        RegisterInit::Declared => return declared.clone(),
+        RegisterInit::X => Value::new_x(width, signed),
+        RegisterInit::Zero => Value::new(0, width, signed),
+        RegisterInit::One => Value::from_le_bytes(&vec![0xff; nb], &vec![0; nb], width, signed),
+        RegisterInit::Random(seed) => {
+            let mut state = seed ^ salt.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            let payload: Vec<u8> = (0..nb.div_ceil(8))
+                .flat_map(|_| splitmix64(&mut state).to_le_bytes())
+                .collect();
+            Value::from_le_bytes(&payload, &vec![0; payload.len()], width, signed)
+        }
+    };
+    ret.trunc(width);
+    ret
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
 /// Compute offset-based VariableMeta for each variable using native-width byte storage.
 ///
 /// Returns `(variable_meta, ff_bytes, comb_bytes)`.
@@ -292,10 +332,11 @@ impl VariableMeta {
 pub fn create_variable_meta(
     src: &HashMap<VarId, air::Variable>,
     ff_table: &air::FfTable,
-    use_4state: bool,
+    config: &Config,
     ff_start_bytes: isize,
     comb_start_bytes: isize,
 ) -> Option<(HashMap<VarId, VariableMeta>, usize, usize)> {
+    let use_4state = config.use_4state;
     let mut ff_pos: isize = ff_start_bytes;
     let mut comb_pos: isize = comb_start_bytes;
 
@@ -326,12 +367,27 @@ pub fn create_variable_meta(
             .enumerate()
             .any(|(i, _)| ff_table.is_ff(v.id, i));
         let force_ff = any_ff && v.value.len() > 1;
+        let is_register = (0..v.value.len()).any(|i| {
+            ff_table
+                .table
+                .get(&(v.id, i))
+                .is_some_and(|x| x.assigned.is_some())
+        });
 
         let mut elements = vec![];
         let mut initial_values = vec![];
 
         for (i, val) in v.value.iter().enumerate() {
-            let mut val = val.clone();
+            let mut val = if is_register {
+                register_initial_value(
+                    val,
+                    width,
+                    config.register_init,
+                    ((ff_pos as u64) << 32) ^ comb_pos as u64,
+                )
+            } else {
+                val.clone()
+            };
             if !use_4state {
                 val.clear_xz();
             }
@@ -365,6 +421,7 @@ pub fn create_variable_meta(
             native_bytes: nb,
             elements,
             initial_values,
+            is_register,
         };
         variables.insert(*k, meta);
     }
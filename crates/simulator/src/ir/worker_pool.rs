@@ -0,0 +1,198 @@
+//! Persistent worker threads for multi-threaded simulation.
+//!
+//! The simulator thread and workers evaluate one chunk of each stage and
+//! wait on a barrier between stages, so a stage starts only after all writes
+//! of the previous stage are visible.
+//!
+//! A panic on any thread is caught so that the other threads are not left
+//! waiting on the barrier. It poisons the pool and is re-raised on the
+//! simulator thread after the last barrier.
+
+use crate::ir::Statement;
+use std::any::Any;
+use std::ops::Range;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Barrier, Condvar, Mutex};
+use std::thread::JoinHandle;
+use veryl_analyzer::value::MaskCache;
+
+/// Stages of a parallel evaluation. Each stage is a list of chunks given as
+/// ranges of a statement list, and chunk `i` is evaluated by thread `i`.
+pub type Stages = Vec<Vec<Range<usize>>>;
+
+type EvalFn = dyn Fn(usize, &mut MaskCache);
+
+#[derive(Clone, Copy)]
+struct Job {
+    eval: *const EvalFn,
+    stages: *const Vec<Range<usize>>,
+    stages_len: usize,
+}
+
+// SAFETY: the pointers are dereferenced only while `WorkerPool::run` keeps
+// the borrowed evaluator and stages alive and waits for all workers.
+unsafe impl Send for Job {}
+
+struct State {
+    generation: u64,
+    job: Option<Job>,
+    quit: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    start: Condvar,
+    barrier: Barrier,
+    poisoned: AtomicBool,
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+}
+
+pub struct WorkerPool {
+    shared: Arc<Shared>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+/// Evaluate the chunks assigned to `index` with a barrier after each stage.
+///
+/// # Safety
+/// `job` must point to a live evaluator and stages.
+unsafe fn run_job(shared: &Shared, job: Job, index: usize, mask_cache: &mut MaskCache) {
+    let eval = unsafe { &*job.eval };
+    let stages = unsafe { std::slice::from_raw_parts(job.stages, job.stages_len) };
+    for stage in stages {
+        // every thread waits on every barrier even after a panic, and only
+        // the evaluation of the remaining stages is skipped
+        if let Some(range) = stage.get(index)
+            && !shared.poisoned.load(Ordering::Relaxed)
+        {
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                for i in range.clone() {
+                    eval(i, mask_cache);
+                }
+            }));
+            if let Err(payload) = result {
+                shared.panic.lock().unwrap().get_or_insert(payload);
+                shared.poisoned.store(true, Ordering::Relaxed);
+            }
+        }
+        shared.barrier.wait();
+    }
+}
+
+impl WorkerPool {
+    /// Spawn `threads - 1` workers; the simulator thread is the first thread.
+    pub fn new(threads: usize) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                generation: 0,
+                job: None,
+                quit: false,
+            }),
+            start: Condvar::new(),
+            barrier: Barrier::new(threads),
+            poisoned: AtomicBool::new(false),
+            panic: Mutex::new(None),
+        });
+
+        let handles = (1..threads)
+            .map(|index| {
+                let shared = Arc::clone(&shared);
+                std::thread::Builder::new()
+                    .name(format!("veryl-sim-{index}"))
+                    .spawn(move || worker(shared, index))
+                    .unwrap()
+            })
+            .collect();
+
+        Self { shared, handles }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.handles.len() + 1
+    }
+
+    /// Evaluate `stages` of `statements` on all threads and return after
+    /// every stage is finished.
+    pub fn run(
+        &self,
+        statements: &[Statement],
+        stages: &[Vec<Range<usize>>],
+        mask_cache: &mut MaskCache,
+    ) {
+        self.run_with(
+            &|i, mask_cache| statements[i].eval_step(mask_cache),
+            stages,
+            mask_cache,
+        );
+    }
+
+    /// Evaluate `stages` by calling `eval` with each index of the chunks.
+    ///
+    /// # Panics
+    /// Re-raises a panic of `eval` on any thread. The pool is poisoned
+    /// after that, and later calls panic immediately.
+    pub(crate) fn run_with(
+        &self,
+        eval: &dyn Fn(usize, &mut MaskCache),
+        stages: &[Vec<Range<usize>>],
+        mask_cache: &mut MaskCache,
+    ) {
+        if self.shared.poisoned.load(Ordering::Relaxed) {
+            panic!("worker pool is poisoned by a previous panic");
+        }
+
+        // SAFETY: only the lifetime is erased; see below
+        let eval: *const EvalFn = unsafe { std::mem::transmute(eval) };
+        let job = Job {
+            eval,
+            stages: stages.as_ptr(),
+            stages_len: stages.len(),
+        };
+        {
+            let mut state = self.shared.state.lock().unwrap();
+            state.generation += 1;
+            state.job = Some(job);
+        }
+        self.shared.start.notify_all();
+
+        // SAFETY: eval and stages are borrowed until the last barrier,
+        // which every worker passes only after finishing its chunks.
+        unsafe { run_job(&self.shared, job, 0, mask_cache) };
+
+        let payload = self.shared.panic.lock().unwrap().take();
+        if let Some(payload) = payload {
+            std::panic::resume_unwind(payload);
+        }
+    }
+}
+
+fn worker(shared: Arc<Shared>, index: usize) {
+    let mut mask_cache = MaskCache::default();
+    let mut generation = 0;
+    loop {
+        let job = {
+            let mut state = shared.state.lock().unwrap();
+            while state.generation == generation && !state.quit {
+                state = shared.start.wait(state).unwrap();
+            }
+            if state.quit {
+                return;
+            }
+            generation = state.generation;
+            state.job.unwrap()
+        };
+        // SAFETY: see `WorkerPool::run`
+        unsafe { run_job(&shared, job, index, &mut mask_cache) };
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().quit = true;
+        self.shared.start.notify_all();
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::cranelift::HelperReloc;
+use crate::ir::wide_fn_addrs;
+use cranelift::codegen::binemit::Reloc;
+use cranelift::codegen::ir::Function;
+use cranelift::codegen::isa::TargetIsa;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+
+const MAGIC: &[u8; 8] = b"VERYLJIT";
+const EXTENSION: &str = "bin";
+
+/// Machine code of a JIT function stored in the cache.
+///
+/// All relocations are `Abs8` to helper functions, which are resolved to the
+/// helpers of the current process when loaded.
+pub struct CacheEntry {
+    pub code: Vec<u8>,
+    pub relocs: Vec<HelperReloc>,
+}
+
+/// Calculate the cache key of a JIT function.
+///
+/// `func` must be built with helper calls by symbol, so that its IR doesn't
+/// contain addresses of the current process. Since the IR is generated from
+/// the proto statements and the simulator configuration, the key covers both.
+pub fn key(isa: &dyn TargetIsa, func: &Function) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    hasher.update(isa.triple().to_string().as_bytes());
+    hasher.update(isa.flags().to_string().as_bytes());
+    hasher.update(func.display().to_string().as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+fn entry_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(key).with_extension(EXTENSION)
+}
+
+/// Load a cache entry. Returns `None` if it doesn't exist or is broken.
+pub fn load(dir: &Path, key: &str) -> Option<CacheEntry> {
+    let path = entry_path(dir, key);
+    let data = fs::read(&path).ok()?;
+    let entry = decode(&data)?;
+
+    // Update mtime for least-recently-used eviction
+    if let Ok(file) = fs::File::options().append(true).open(&path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+
+    Some(entry)
+}
+
+/// Store a cache entry. Failures are ignored because the cache is optional.
+pub fn store(dir: &Path, key: &str, code: &[u8], relocs: &[HelperReloc]) {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let Some(data) = encode(code, relocs) else {
+        return;
+    };
+    if fs::create_dir_all(dir).is_err() {
+        return;
+    }
+
+    // Write to a temporary file first so that concurrent readers never see a partial entry
+    let count = COUNT.fetch_add(1, Ordering::Relaxed);
+    let temp = dir.join(format!("{key}.{}.{count}.tmp", std::process::id()));
+    if fs::write(&temp, data).is_err() || fs::rename(&temp, entry_path(dir, key)).is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+}
+
+/// Remove least recently used entries until the total size is within `limit`.
+pub fn prune(dir: &Path, limit: u64) -> std::io::Result<()> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+
+    let mut files = Vec::new();
+    let mut total = 0;
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().is_none_or(|x| x != EXTENSION) {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        total += metadata.len();
+        files.push((modified, metadata.len(), path));
+    }
+
+    files.sort();
+    for (_, size, path) in files {
+        if total <= limit {
+            break;
+        }
+        fs::remove_file(path)?;
+        total -= size;
+    }
+
+    Ok(())
+}
+
+/// Encode an entry as
+/// `MAGIC | hash | code length | reloc count | (offset, addend, symbol)* | code`
+/// where `hash` is the hash of the following bytes.
+fn encode(code: &[u8], relocs: &[HelperReloc]) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+    body.extend((code.len() as u32).to_le_bytes());
+    body.extend((relocs.len() as u32).to_le_bytes());
+    for x in relocs {
+        if x.kind != Reloc::Abs8 {
+            return None;
+        }
+        body.extend(x.offset.to_le_bytes());
+        body.extend(x.addend.to_le_bytes());
+        body.extend((x.symbol.len() as u32).to_le_bytes());
+        body.extend(x.symbol.as_bytes());
+    }
+    body.extend(code);
+
+    let mut ret = MAGIC.to_vec();
+    ret.extend(blake3::hash(&body).as_bytes());
+    ret.extend(body);
+    Some(ret)
+}
+
+fn decode(data: &[u8]) -> Option<CacheEntry> {
+    let symbols = wide_fn_addrs::symbols();
+
+    let data = data.strip_prefix(MAGIC)?;
+    let (hash, data) = data.split_first_chunk::<32>()?;
+    if blake3::hash(data) != blake3::Hash::from_bytes(*hash) {
+        return None;
+    }
+
+    let (code_len, data) = data.split_first_chunk::<4>()?;
+    let code_len = u32::from_le_bytes(*code_len) as usize;
+    let (len, mut data) = data.split_first_chunk::<4>()?;
+    let len = u32::from_le_bytes(*len) as usize;
+
+    let mut relocs = Vec::new();
+    for _ in 0..len {
+        let (offset, rest) = data.split_first_chunk::<4>()?;
+        let (addend, rest) = rest.split_first_chunk::<8>()?;
+        let (symbol_len, rest) = rest.split_first_chunk::<4>()?;
+        let symbol_len = u32::from_le_bytes(*symbol_len) as usize;
+        let (symbol, rest) = rest.split_at_checked(symbol_len)?;
+        data = rest;
+
+        // helpers which don't exist in this build can't be resolved
+        let (_, symbol) = symbols.iter().find(|(_, x)| x.as_bytes() == symbol)?;
+        relocs.push(HelperReloc {
+            offset: u32::from_le_bytes(*offset),
+            kind: Reloc::Abs8,
+            symbol,
+            addend: i64::from_le_bytes(*addend),
+        });
+    }
+
+    if data.is_empty()
+        || data.len() != code_len
+        || relocs.iter().any(|x| x.offset as usize + 8 > data.len())
+    {
+        return None;
+    }
+
+    Some(CacheEntry {
+        code: data.to_vec(),
+        relocs,
+    })
+}
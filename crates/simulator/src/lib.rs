@@ -1,9 +1,18 @@
 #[cfg(not(target_family = "wasm"))]
+pub mod aot;
+pub mod batch;
+#[cfg(not(target_family = "wasm"))]
 pub mod cranelift;
+pub mod debug_adapter;
 pub mod ir;
+#[cfg(not(target_family = "wasm"))]
+pub mod jit_cache;
 pub mod output_buffer;
+pub mod repl;
+pub mod signal_expr;
 pub mod simulator;
 pub mod simulator_error;
+pub mod statement_trace;
 pub mod testbench;
 pub mod wave_dumper;
 pub mod wavedrom;
@@ -0,0 +1,452 @@
+//! Interactive simulation commands used by `veryl repl`
+
+use crate::ir::{Event, ModuleVariables, Value, VarId, VarPath};
+use crate::signal_expr::{SignalExpr, parse_number, parse_value, read_element};
+use crate::simulator::Simulator;
+use crate::wave_dumper::WaveDumper;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const COMMANDS: &[&str] = &[
+    "step", "reset", "set", "get", "watch", "unwatch", "run", "dump", "list", "time", "help",
+    "quit",
+];
+
+const HELP: &str = "\
+step [clock] [N]        advance N clock edges (default: 1)
+reset [reset] [N]       assert reset for N clock edges (default: 3)
+set <port> <value>      drive a top-level input port
+get <expr>              print a signal value or evaluate an expression
+watch [path]            print the signal when it changes, or list watched signals
+unwatch <path>          remove a watched signal
+run [N]                 advance clock edges until a watched signal changes (at most N)
+run until <expr> [N]    advance clock edges until <expr> becomes true (at most N)
+dump on <file>          start dumping waveform to a VCD or FST file
+dump off                stop dumping waveform
+list [scope]            list signals and instances in a scope
+time                    print simulation time
+help                    print this help
+quit                    exit";
+
+/// Default limit of clock edges advanced by `run`
+const RUN_LIMIT: u64 = 100000;
+
+/// Result of a REPL command
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReplResponse {
+    Output(String),
+    Quit,
+}
+
+struct Watch {
+    path: String,
+    value: String,
+}
+
+pub struct Repl {
+    pub sim: Simulator,
+    watches: Vec<Watch>,
+    paths: Vec<String>,
+    clock: Option<VarId>,
+}
+
+fn collect_paths(module: &ModuleVariables, prefix: &str, ret: &mut Vec<String>) {
+    let mut variables: Vec<_> = module
+        .variables
+        .values()
+        .map(|x| format!("{prefix}{}", x.path))
+        .collect();
+    variables.sort();
+    ret.append(&mut variables);
+    for x in &module.children {
+        let path = format!("{prefix}{}", x.name);
+        ret.push(path.clone());
+        collect_paths(x, &format!("{path}."), ret);
+    }
+}
+
+fn find_scope<'a>(module: &'a ModuleVariables, path: &str) -> Option<&'a ModuleVariables> {
+    if path.is_empty() {
+        return Some(module);
+    }
+    let (head, tail) = path.split_once('.').unwrap_or((path, ""));
+    module
+        .children
+        .iter()
+        .find(|x| x.name.to_string() == head)
+        .and_then(|x| find_scope(x, tail))
+}
+
+fn parse_count(text: Option<&str>, default: u64) -> Result<u64, String> {
+    match text {
+        Some(x) => parse_number(x)
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or_else(|| format!("invalid count '{x}'")),
+        None => Ok(default),
+    }
+}
+
+fn is_count(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_digit())
+}
+
+impl Repl {
+    pub fn new(sim: Simulator) -> Self {
+        let mut paths = Vec::new();
+        collect_paths(&sim.ir.module_variables, "", &mut paths);
+        let mut ret = Self {
+            sim,
+            watches: Vec::new(),
+            paths,
+            clock: None,
+        };
+        ret.clock = ret.default_event(false).and_then(|x| x.var_id());
+        ret
+    }
+
+    /// Hierarchical paths of all signals and instances
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    /// Candidates to replace the last word of `line`
+    pub fn complete(&self, line: &str) -> Vec<String> {
+        let (head, word) = match line.rfind(char::is_whitespace) {
+            Some(x) => (line[..x].trim(), &line[x + 1..]),
+            None => ("", line),
+        };
+        let command = head.split_whitespace().next();
+
+        let candidates: Vec<&str> = match command {
+            None => COMMANDS.to_vec(),
+            Some("dump") if head == "dump" => vec!["on", "off"],
+            Some("run") if head == "run" => vec!["until"],
+            Some("help" | "time" | "quit") => Vec::new(),
+            Some(_) => self.paths.iter().map(|x| x.as_str()).collect(),
+        };
+        candidates
+            .into_iter()
+            .filter(|x| x.starts_with(word))
+            .map(|x| x.to_string())
+            .collect()
+    }
+
+    /// Execute one command line
+    pub fn execute(&mut self, line: &str) -> Result<ReplResponse, String> {
+        let words: Vec<_> = line.split_whitespace().collect();
+        let Some(command) = words.first() else {
+            return Ok(ReplResponse::Output(String::new()));
+        };
+        let args = &words[1..];
+
+        let output = match *command {
+            "step" => self.step(args)?,
+            "reset" => self.reset(args)?,
+            "set" => self.set(args)?,
+            "get" => {
+                if args.is_empty() {
+                    return Err("usage: get <expr>".to_string());
+                }
+                SignalExpr::parse(&args.join(" "))?.evaluate(&mut self.sim)?
+            }
+            "watch" => self.watch(args)?,
+            "unwatch" => {
+                let [path] = args else {
+                    return Err("usage: unwatch <path>".to_string());
+                };
+                let len = self.watches.len();
+                self.watches.retain(|x| x.path != *path);
+                if self.watches.len() == len {
+                    return Err(format!("'{path}' is not watched"));
+                }
+                String::new()
+            }
+            "run" => self.run(args)?,
+            "dump" => self.dump(args)?,
+            "list" => self.list(args)?,
+            "time" => self.sim.time.to_string(),
+            "help" => HELP.to_string(),
+            "quit" | "exit" => {
+                self.stop_dump();
+                return Ok(ReplResponse::Quit);
+            }
+            x => return Err(format!("unknown command '{x}'. Type 'help' for usage")),
+        };
+        Ok(ReplResponse::Output(output))
+    }
+
+    /// Stop waveform dump if it is running, and return the dumped file
+    pub fn stop_dump(&mut self) -> Option<PathBuf> {
+        self.sim.stop_dump().and_then(|x| x.into_path())
+    }
+
+    fn default_event(&self, reset: bool) -> Option<Event> {
+        let mut events: Vec<_> = self
+            .sim
+            .ir
+            .event_statements
+            .keys()
+            .filter(|x| match x {
+                Event::Clock(_) => !reset,
+                Event::Reset(_) => reset,
+                _ => false,
+            })
+            .map(|x| (self.var_name(x).unwrap_or_default(), x.clone()))
+            .collect();
+        events.sort_by(|a, b| a.0.cmp(&b.0));
+        events.into_iter().map(|x| x.1).next()
+    }
+
+    fn var_name(&self, event: &Event) -> Option<String> {
+        let id = event.var_id()?;
+        let variable = self.sim.ir.module_variables.variables.get(&id)?;
+        Some(variable.path.to_string())
+    }
+
+    fn event(&self, name: &str, reset: bool) -> Result<Event, String> {
+        let id = self
+            .sim
+            .ir
+            .module_variables
+            .variables
+            .iter()
+            .find(|(_, x)| x.path.to_string() == name)
+            .map(|(id, _)| *id)
+            .ok_or_else(|| format!("unknown signal '{name}'"))?;
+        Ok(if reset {
+            Event::Reset(id)
+        } else {
+            Event::Clock(id)
+        })
+    }
+
+    /// Resolve optional `[signal] [N]` arguments
+    fn event_args(
+        &self,
+        args: &[&str],
+        reset: bool,
+        default_count: u64,
+    ) -> Result<(Event, u64), String> {
+        let (name, count) = match args {
+            [] => (None, None),
+            [x] if is_count(x) => (None, Some(*x)),
+            [x] => (Some(*x), None),
+            [x, y] => (Some(*x), Some(*y)),
+            _ => return Err("too many arguments".to_string()),
+        };
+        let kind = if reset { "reset" } else { "clock" };
+        let event = match name {
+            Some(x) => self.event(x, reset)?,
+            None => self
+                .default_event(reset)
+                .ok_or_else(|| format!("no {kind} is found"))?,
+        };
+        Ok((event, parse_count(count, default_count)?))
+    }
+
+    fn set_dump_value(&mut self, id: Option<VarId>, value: u64) {
+        if self.sim.dump.is_some()
+            && let Some(id) = id
+        {
+            self.sim.set_var_by_id(&id, Value::new(value, 1, false));
+        }
+    }
+
+    /// Advance one clock edge in the same way as `clk.next()` of testbenches
+    fn edge(&mut self, event: &Event) {
+        let clock = match event {
+            Event::Clock(_) => event.var_id(),
+            _ => self.clock,
+        };
+        self.set_dump_value(clock, 1);
+        self.sim.step(event);
+        self.sim.time += 1;
+        self.set_dump_value(clock, 0);
+        self.sim.dump_variables();
+        self.sim.time += 1;
+    }
+
+    /// Update watched values and report changes
+    fn check_watches(&mut self, output: &mut String) -> bool {
+        let mut changed = false;
+        for i in 0..self.watches.len() {
+            let path = self.watches[i].path.clone();
+            let value = SignalExpr::Signal(path.clone(), None)
+                .evaluate(&mut self.sim)
+                .unwrap_or_default();
+            if value != self.watches[i].value {
+                let _ = writeln!(
+                    output,
+                    "[{}] {path}: {} -> {value}",
+                    self.sim.time, self.watches[i].value
+                );
+                self.watches[i].value = value;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn step(&mut self, args: &[&str]) -> Result<String, String> {
+        let (event, count) = self.event_args(args, false, 1)?;
+        let mut output = String::new();
+        for _ in 0..count {
+            self.edge(&event);
+            self.check_watches(&mut output);
+        }
+        Ok(output.trim_end().to_string())
+    }
+
+    fn reset(&mut self, args: &[&str]) -> Result<String, String> {
+        let (event, count) = self.event_args(args, true, 3)?;
+        let mut output = String::new();
+        self.set_dump_value(event.var_id(), 1);
+        for _ in 0..count {
+            self.edge(&event);
+            self.check_watches(&mut output);
+        }
+        self.set_dump_value(event.var_id(), 0);
+        Ok(output.trim_end().to_string())
+    }
+
+    fn set(&mut self, args: &[&str]) -> Result<String, String> {
+        let [port, value] = args else {
+            return Err("usage: set <port> <value>".to_string());
+        };
+        let Some(width) = self
+            .sim
+            .ir
+            .ports
+            .get(&VarPath::from_str(port).unwrap())
+            .and_then(|x| self.sim.ir.module_variables.variables.get(x))
+            .map(|x| x.width)
+        else {
+            return Err(format!("unknown port '{port}'"));
+        };
+        let value = parse_value(value, width).ok_or_else(|| format!("invalid value '{value}'"))?;
+        self.sim.set(port, value);
+
+        let mut output = String::new();
+        self.check_watches(&mut output);
+        Ok(output.trim_end().to_string())
+    }
+
+    fn watch(&mut self, args: &[&str]) -> Result<String, String> {
+        match args {
+            [] => Ok(self
+                .watches
+                .iter()
+                .map(|x| format!("{}: {}", x.path, x.value))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            [path] => {
+                let value = SignalExpr::Signal(path.to_string(), None).evaluate(&mut self.sim)?;
+                if !self.watches.iter().any(|x| x.path == *path) {
+                    self.watches.push(Watch {
+                        path: path.to_string(),
+                        value: value.clone(),
+                    });
+                }
+                Ok(format!("{path}: {value}"))
+            }
+            _ => Err("usage: watch [path]".to_string()),
+        }
+    }
+
+    fn run(&mut self, args: &[&str]) -> Result<String, String> {
+        let (condition, limit) = if args.first() == Some(&"until") {
+            if args.len() < 2 {
+                return Err("usage: run until <expr> [N]".to_string());
+            }
+            // trailing number is a limit only if the expression is complete without it
+            match SignalExpr::parse(&args[1..].join(" ")) {
+                Ok(x) => (Some(x), None),
+                Err(e) => match args.split_last() {
+                    Some((x, expr)) if expr.len() > 1 && is_count(x) => {
+                        (Some(SignalExpr::parse(&expr[1..].join(" "))?), Some(*x))
+                    }
+                    _ => return Err(e),
+                },
+            }
+        } else {
+            match args {
+                [] => (None, None),
+                [x] => (None, Some(*x)),
+                _ => return Err("usage: run [N]".to_string()),
+            }
+        };
+        let limit = parse_count(limit, RUN_LIMIT)?;
+        let event = self
+            .default_event(false)
+            .ok_or_else(|| "no clock is found".to_string())?;
+
+        let mut output = String::new();
+        for _ in 0..limit {
+            self.edge(&event);
+            let changed = self.check_watches(&mut output);
+            if let Some(x) = &condition {
+                if x.eval(&mut self.sim)?.unwrap_or(0) != 0 {
+                    let _ = writeln!(output, "condition met at time {}", self.sim.time);
+                    return Ok(output.trim_end().to_string());
+                }
+            } else if changed {
+                return Ok(output.trim_end().to_string());
+            }
+        }
+        if condition.is_some() {
+            let _ = writeln!(output, "condition not met within {limit} clock edges");
+        }
+        Ok(output.trim_end().to_string())
+    }
+
+    fn dump(&mut self, args: &[&str]) -> Result<String, String> {
+        match args {
+            ["on", path] => {
+                let path = PathBuf::from(path);
+                let dumper = if path.extension().is_some_and(|x| x == "fst") {
+                    WaveDumper::new_fst(&path.to_string_lossy())
+                } else {
+                    let file = std::fs::File::create(&path)
+                        .map_err(|e| format!("failed to create {}: {e}", path.display()))?;
+                    WaveDumper::new_vcd(Box::new(file))
+                };
+                self.stop_dump();
+                self.sim.start_dump(dumper.with_path(path.clone()));
+                Ok(format!("dumping waveform to {}", path.display()))
+            }
+            ["off"] => match self.stop_dump() {
+                Some(x) => Ok(format!("waveform is written to {}", x.display())),
+                None => Err("waveform is not dumped".to_string()),
+            },
+            _ => Err("usage: dump on <file> | dump off".to_string()),
+        }
+    }
+
+    fn list(&mut self, args: &[&str]) -> Result<String, String> {
+        let scope = match args {
+            [] => "",
+            [x] => x,
+            _ => return Err("usage: list [scope]".to_string()),
+        };
+        self.sim.ensure_comb_updated();
+        let use_4state = self.sim.ir.use_4state;
+        let module = find_scope(&self.sim.ir.module_variables, scope)
+            .ok_or_else(|| format!("unknown scope '{scope}'"))?;
+
+        let mut variables: Vec<_> = module.variables.values().collect();
+        variables.sort_by_key(|x| x.path.to_string());
+        let mut ret = Vec::new();
+        for x in variables {
+            let values: Vec<_> = (0..x.current_values.len())
+                .filter_map(|i| read_element(x, i, use_4state))
+                .map(|x| format!("{x:x}"))
+                .collect();
+            ret.push(format!("{} {}: {}", x.r#type, x.path, values.join(", ")));
+        }
+        for x in &module.children {
+            ret.push(format!("inst {}", x.name));
+        }
+        Ok(ret.join("\n"))
+    }
+}
@@ -0,0 +1,279 @@
+//! Expressions on signal values for watches and conditions of interactive simulation
+
+use crate::ir::{ModuleVariables, Value, Variable, read_native_value};
+use crate::simulator::Simulator;
+use num_bigint::BigUint;
+use num_traits::{Num, One, ToPrimitive};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+/// Expression on signal values used by watches and breakpoint conditions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignalExpr {
+    Signal(String, Option<usize>),
+    Number(u128),
+    Not(Box<SignalExpr>),
+    Binary(Box<SignalExpr>, BinaryOp, Box<SignalExpr>),
+}
+
+/// Parse a number like `10`, `0x1f` or `8'hff`
+pub fn parse_number(text: &str) -> Option<u128> {
+    parse_biguint(text)?.to_u128()
+}
+
+/// Parse a number into a value of `width` bits; upper bits are truncated
+pub fn parse_value(text: &str, width: usize) -> Option<Value> {
+    let mask = (BigUint::one() << width) - 1u32;
+    let payload = parse_biguint(text)? & mask;
+    Some(Value::new_biguint(payload, width, false))
+}
+
+fn parse_biguint(text: &str) -> Option<BigUint> {
+    let text = text.replace('_', "");
+    if let Some((_, x)) = text.split_once('\'') {
+        let x = x.strip_prefix('s').unwrap_or(x);
+        let (radix, digits) = match x.chars().next()? {
+            'b' => (2, &x[1..]),
+            'o' => (8, &x[1..]),
+            'd' => (10, &x[1..]),
+            'h' => (16, &x[1..]),
+            _ => (10, x),
+        };
+        BigUint::from_str_radix(digits, radix).ok()
+    } else if let Some(x) = text.strip_prefix("0x") {
+        BigUint::from_str_radix(x, 16).ok()
+    } else {
+        BigUint::from_str_radix(&text, 10).ok()
+    }
+}
+
+struct ExprParser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl ExprParser {
+    fn new(text: &str) -> Result<Self, String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            let start = i;
+            if c.is_whitespace() {
+                i += 1;
+                continue;
+            } else if matches!(
+                (c, next),
+                ('=', Some('=')) | ('!', Some('=')) | ('<', Some('=')) | ('>', Some('='))
+            ) || matches!((c, next), ('&', Some('&')) | ('|', Some('|')))
+            {
+                i += 2;
+            } else if matches!(c, '(' | ')' | '!' | '<' | '>') {
+                i += 1;
+            } else if c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '\'') {
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric()
+                        || matches!(chars[i], '_' | '$' | '\'' | '.' | '[' | ']'))
+                {
+                    i += 1;
+                }
+            } else {
+                return Err(format!("unexpected character '{c}'"));
+            }
+            tokens.push(chars[start..i].iter().collect());
+        }
+        Ok(Self { tokens, pos: 0 })
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(|x| x.as_str())
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let ret = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        ret
+    }
+
+    fn parse(mut self) -> Result<SignalExpr, String> {
+        let ret = self.or()?;
+        if let Some(x) = self.peek() {
+            return Err(format!("unexpected '{x}'"));
+        }
+        Ok(ret)
+    }
+
+    fn or(&mut self) -> Result<SignalExpr, String> {
+        let mut ret = self.and()?;
+        while self.peek() == Some("||") {
+            self.next();
+            ret = SignalExpr::Binary(Box::new(ret), BinaryOp::Or, Box::new(self.and()?));
+        }
+        Ok(ret)
+    }
+
+    fn and(&mut self) -> Result<SignalExpr, String> {
+        let mut ret = self.compare()?;
+        while self.peek() == Some("&&") {
+            self.next();
+            ret = SignalExpr::Binary(Box::new(ret), BinaryOp::And, Box::new(self.compare()?));
+        }
+        Ok(ret)
+    }
+
+    fn compare(&mut self) -> Result<SignalExpr, String> {
+        let lhs = self.unary()?;
+        let op = match self.peek() {
+            Some("==") => BinaryOp::Eq,
+            Some("!=") => BinaryOp::Ne,
+            Some("<") => BinaryOp::Lt,
+            Some("<=") => BinaryOp::Le,
+            Some(">") => BinaryOp::Gt,
+            Some(">=") => BinaryOp::Ge,
+            _ => return Ok(lhs),
+        };
+        self.next();
+        let rhs = self.unary()?;
+        Ok(SignalExpr::Binary(Box::new(lhs), op, Box::new(rhs)))
+    }
+
+    fn unary(&mut self) -> Result<SignalExpr, String> {
+        let Some(token) = self.next() else {
+            return Err("unexpected end of expression".to_string());
+        };
+        match token.as_str() {
+            "!" => Ok(SignalExpr::Not(Box::new(self.unary()?))),
+            "(" => {
+                let ret = self.or()?;
+                if self.next().as_deref() != Some(")") {
+                    return Err("missing ')'".to_string());
+                }
+                Ok(ret)
+            }
+            x if x.starts_with(|c: char| c.is_ascii_digit() || c == '\'') => parse_number(x)
+                .map(SignalExpr::Number)
+                .ok_or_else(|| format!("invalid number '{x}'")),
+            x if x.starts_with(|c: char| c.is_ascii_alphabetic() || matches!(c, '_' | '$')) => {
+                if let Some((name, index)) = x.strip_suffix(']').and_then(|x| x.split_once('[')) {
+                    let index = index
+                        .parse()
+                        .map_err(|_| format!("invalid index in '{x}'"))?;
+                    Ok(SignalExpr::Signal(name.to_string(), Some(index)))
+                } else {
+                    Ok(SignalExpr::Signal(x.to_string(), None))
+                }
+            }
+            x => Err(format!("unexpected '{x}'")),
+        }
+    }
+}
+
+/// Find a variable by hierarchical path like `u_dut.cnt`
+pub fn find_variable<'a>(module: &'a ModuleVariables, path: &str) -> Option<&'a Variable> {
+    if let Some((head, tail)) = path.split_once('.') {
+        for child in &module.children {
+            if child.name.to_string() == head
+                && let Some(x) = find_variable(child, tail)
+            {
+                return Some(x);
+            }
+        }
+    }
+    module
+        .variables
+        .values()
+        .find(|x| x.path.to_string() == path)
+}
+
+/// Read the value of the `index`-th element of `variable`
+pub fn read_element(variable: &Variable, index: usize, use_4state: bool) -> Option<Value> {
+    let ptr = *variable.current_values.get(index)?;
+    let value = unsafe {
+        read_native_value(
+            ptr,
+            variable.native_bytes,
+            use_4state,
+            variable.width as u32,
+            false,
+        )
+    };
+    Some(value)
+}
+
+/// Read a signal by hierarchical path and optional array index
+pub fn read_signal(sim: &mut Simulator, path: &str, index: Option<usize>) -> Result<Value, String> {
+    let value = match index {
+        None => sim.get_var(path),
+        Some(_) => None,
+    };
+    if let Some(x) = value {
+        return Ok(x);
+    }
+
+    sim.ensure_comb_updated();
+    let variable = find_variable(&sim.ir.module_variables, path)
+        .ok_or_else(|| format!("unknown signal '{path}'"))?;
+    read_element(variable, index.unwrap_or(0), sim.ir.use_4state)
+        .ok_or_else(|| format!("index out of range in '{path}'"))
+}
+
+impl SignalExpr {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        ExprParser::new(text)?.parse()
+    }
+
+    /// Evaluate the expression. `None` means the result is unknown because of X/Z.
+    pub fn eval(&self, sim: &mut Simulator) -> Result<Option<u128>, String> {
+        let ret = match self {
+            SignalExpr::Signal(path, index) => {
+                let value = read_signal(sim, path, *index)?;
+                (!value.is_xz()).then(|| value.payload_u128())
+            }
+            SignalExpr::Number(x) => Some(*x),
+            SignalExpr::Not(x) => x.eval(sim)?.map(|x| (x == 0) as u128),
+            SignalExpr::Binary(lhs, op, rhs) => {
+                let lhs = lhs.eval(sim)?;
+                let rhs = rhs.eval(sim)?;
+                lhs.zip(rhs).map(|(lhs, rhs)| {
+                    let ret = match op {
+                        BinaryOp::Eq => lhs == rhs,
+                        BinaryOp::Ne => lhs != rhs,
+                        BinaryOp::Lt => lhs < rhs,
+                        BinaryOp::Le => lhs <= rhs,
+                        BinaryOp::Gt => lhs > rhs,
+                        BinaryOp::Ge => lhs >= rhs,
+                        BinaryOp::And => lhs != 0 && rhs != 0,
+                        BinaryOp::Or => lhs != 0 || rhs != 0,
+                    };
+                    ret as u128
+                })
+            }
+        };
+        Ok(ret)
+    }
+
+    /// Evaluate the expression as text. Signals are shown in hexadecimal.
+    pub fn evaluate(&self, sim: &mut Simulator) -> Result<String, String> {
+        if let SignalExpr::Signal(path, index) = self {
+            let value = read_signal(sim, path, *index)?;
+            Ok(format!("{value:x}"))
+        } else {
+            Ok(self
+                .eval(sim)?
+                .map(|x| x.to_string())
+                .unwrap_or_else(|| "x".to_string()))
+        }
+    }
+}
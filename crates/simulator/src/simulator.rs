@@ -1,9 +1,11 @@
 use crate::ir::{
-    Event, Ir, ModuleVariables, Value, VarId, VarPath, read_native_value, write_native_value,
+    Event, Force, Ir, ModuleVariables, Value, VarId, VarPath, Variable, read_native_value,
+    write_native_value,
 };
 use crate::wave_dumper::{DumpVar, WaveDumper};
 use std::str::FromStr;
 use veryl_analyzer::value::MaskCache;
+use veryl_parser::resource_table;
 
 #[cfg(feature = "profile")]
 #[derive(Default, Debug)]
@@ -23,6 +25,11 @@ pub struct SimProfile {
 #[derive(Default, Debug)]
 pub struct SimProfile;
 
+/// Split a hierarchical path (e.g., "dut.cnt") into its segments.
+fn hierarchical_path(path: &str) -> VarPath {
+    VarPath(path.split('.').map(resource_table::insert_str).collect())
+}
+
 /// Number of consecutive first-try convergences needed before skipping
 /// the convergence check loop in settle_comb.
 const CONVERGENCE_WARMUP: u32 = 100;
@@ -142,21 +149,27 @@ impl Simulator {
     pub fn get_var(&mut self, path: &str) -> Option<Value> {
         self.ensure_comb_updated();
 
-        let target = VarPath::from_str(path).unwrap();
-        Self::find_var_in_module(&self.ir.module_variables, &target, self.ir.use_4state)
+        let target = hierarchical_path(path);
+        let var = Self::find_variable(&self.ir.module_variables, &target)?;
+        let value = unsafe {
+            read_native_value(
+                var.current_values[0],
+                var.native_bytes,
+                self.ir.use_4state,
+                var.width as u32,
+                false,
+            )
+        };
+        Some(value)
     }
 
-    fn find_var_in_module(
-        module: &ModuleVariables,
-        target: &VarPath,
-        use_4state: bool,
-    ) -> Option<Value> {
+    fn find_variable<'a>(module: &'a ModuleVariables, target: &VarPath) -> Option<&'a Variable> {
         // If target has multiple segments, try matching child module by name first
         if target.0.len() > 1 {
             for child in &module.children {
                 if child.name == target.0[0] {
                     let sub = VarPath::from_slice(&target.0[1..]);
-                    if let Some(v) = Self::find_var_in_module(child, &sub, use_4state) {
+                    if let Some(v) = Self::find_variable(child, &sub) {
                         return Some(v);
                     }
                 }
@@ -164,21 +177,110 @@ impl Simulator {
         }
 
         // Look for a variable whose path matches exactly
-        for var in module.variables.values() {
-            if var.path == *target {
+        module.variables.values().find(|x| x.path == *target)
+    }
+
+    /// Force a variable by hierarchical path (e.g., "dut.cnt") to `value`.
+    /// The forced value takes precedence over its drivers until `release`.
+    /// Returns false if the variable is not found.
+    ///
+    /// While any variable is forced, the design is evaluated by the interpreter,
+    /// because JIT-compiled functions don't observe forced values until they return.
+    pub fn force(&mut self, path: &str, value: Value) -> bool {
+        let target = hierarchical_path(path);
+        let Some(var) = Self::find_variable(&self.ir.module_variables, &target) else {
+            return false;
+        };
+
+        let mut value = value;
+        value.trunc(var.width);
+        let ptrs: Vec<_> = var
+            .current_values
+            .iter()
+            .chain(&var.next_values)
+            .copied()
+            .collect();
+
+        self.ir.forces.retain(|x| x.ptrs != ptrs);
+        self.ir.forces.push(Force {
+            ptrs,
+            native_bytes: var.native_bytes,
+            value,
+        });
+        self.ir.prepare_forces();
+        self.ir.apply_forces();
+        self.comb_dirty = true;
+        true
+    }
+
+    /// Release a variable forced by `force`.
+    /// Combinational variables return to the value of their drivers, and
+    /// registers keep the forced value until they are assigned next time.
+    /// Returns false if the variable is not forced.
+    pub fn release(&mut self, path: &str) -> bool {
+        let target = hierarchical_path(path);
+        let Some(var) = Self::find_variable(&self.ir.module_variables, &target) else {
+            return false;
+        };
+
+        let len = self.ir.forces.len();
+        self.ir
+            .forces
+            .retain(|x| x.ptrs.first() != var.current_values.first());
+        self.comb_dirty = true;
+        self.ir.forces.len() != len
+    }
+
+    /// Write `value` to a variable by hierarchical path once.
+    /// Unlike `force`, the value is overwritten when the variable is driven next time,
+    /// so it is mainly useful for registers.
+    /// Returns false if the variable is not found.
+    pub fn deposit(&mut self, path: &str, value: Value) -> bool {
+        let target = hierarchical_path(path);
+        let Some(var) = Self::find_variable(&self.ir.module_variables, &target) else {
+            return false;
+        };
+
+        let mut value = value;
+        value.trunc(var.width);
+        for &ptr in var.current_values.iter().chain(&var.next_values) {
+            unsafe {
+                write_native_value(ptr, var.native_bytes, self.ir.use_4state, &value);
+            }
+        }
+        self.comb_dirty = true;
+        true
+    }
+
+    /// Hierarchical paths of registers which contain X/Z bits.
+    pub fn x_registers(&mut self) -> Vec<String> {
+        self.ensure_comb_updated();
+
+        let mut ret = Vec::new();
+        if self.ir.use_4state {
+            Self::collect_x_registers(&self.ir.module_variables, "", &mut ret);
+        }
+        ret.sort();
+        ret
+    }
+
+    fn collect_x_registers(module: &ModuleVariables, prefix: &str, ret: &mut Vec<String>) {
+        for var in module.variables.values().filter(|x| x.is_register) {
+            let is_xz = var.current_values.iter().any(|&ptr| {
                 let value = unsafe {
-                    read_native_value(
-                        var.current_values[0],
-                        var.native_bytes,
-                        use_4state,
-                        var.width as u32,
-                        false,
-                    )
+                    read_native_value(ptr, var.native_bytes, true, var.width as u32, false)
                 };
-                return Some(value);
+                value.is_xz()
+            });
+            if is_xz {
+                ret.push(format!("{prefix}{}", var.path));
             }
         }
-        None
+
+        for child in &module.children {
+            let prefix = format!("{prefix}{}.", child.name);
+            Self::collect_x_registers(child, &prefix, ret);
+        }
     }
 
     pub fn ensure_comb_updated(&mut self) {
@@ -232,11 +334,7 @@ impl Simulator {
         #[cfg(feature = "profile")]
         let event_start = std::time::Instant::now();
 
-        if let Some(statements) = self.ir.event_statements.get(event) {
-            for x in statements {
-                x.eval_step(&mut self.mask_cache);
-            }
-        }
+        self.ir.eval_event(event, &mut self.mask_cache);
 
         #[cfg(feature = "profile")]
         {
@@ -323,6 +421,20 @@ impl Simulator {
         }
     }
 
+    /// Start dumping waveform from the current simulation state
+    pub fn start_dump(&mut self, dumper: WaveDumper) {
+        self.dump_vars.clear();
+        self.setup_dump(dumper);
+        self.ensure_comb_updated();
+        self.dump_start();
+    }
+
+    /// Stop dumping waveform and return the dumper
+    pub fn stop_dump(&mut self) -> Option<WaveDumper> {
+        self.dump_vars.clear();
+        self.dump.take()
+    }
+
     fn setup_dump(&mut self, mut dumper: WaveDumper) {
         dumper.timescale();
         dumper.setup_module(&self.ir.module_variables, &mut self.dump_vars);
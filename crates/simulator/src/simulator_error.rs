@@ -29,6 +29,17 @@ pub enum SimulatorError {
     #[error("{message}")]
     IoError { message: String },
 
+    #[diagnostic(severity(Error), code(seed_variable_not_found))]
+    #[error("seed variable \"{var_name}\" not found in module \"{module_name}\"")]
+    SeedVariableNotFound {
+        module_name: String,
+        var_name: String,
+    },
+
+    #[diagnostic(severity(Error), code(native_object_unsupported))]
+    #[error("module \"{module_name}\" cannot be compiled into a native object: {reason}")]
+    NativeObjectUnsupported { module_name: String, reason: String },
+
     #[diagnostic(severity(Error), code(unresolved_expression))]
     #[error("unresolved expression")]
     UnresolvedExpression {
@@ -0,0 +1,50 @@
+//! Thread-local record of executed statements of `always_ff` and
+//! `always_comb`, which decides hits of breakpoints in the debugger.
+//! Statements are recorded only if `Config::trace_statements` is set.
+
+use std::cell::RefCell;
+use veryl_analyzer::ir as air;
+use veryl_parser::token_range::TokenRange;
+
+thread_local! {
+    static TRACE: RefCell<Option<Vec<TokenRange>>> = const { RefCell::new(None) };
+}
+
+pub fn enable() {
+    TRACE.with(|x| {
+        *x.borrow_mut() = Some(Vec::new());
+    });
+}
+
+/// Take statements executed since the last call, and keep recording.
+pub fn take() -> Vec<TokenRange> {
+    TRACE.with(|x| {
+        x.borrow_mut()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    })
+}
+
+pub fn record(token: &TokenRange) {
+    TRACE.with(|x| {
+        if let Some(x) = x.borrow_mut().as_mut() {
+            x.push(*token);
+        }
+    });
+}
+
+/// Token of `stmt` which is recorded when it is executed
+pub fn statement_token(stmt: &air::Statement) -> Option<TokenRange> {
+    match stmt {
+        air::Statement::Assign(x) => Some(x.token),
+        air::Statement::If(x) => Some(x.token),
+        air::Statement::IfReset(x) => Some(x.token),
+        air::Statement::For(x) => Some(x.token),
+        air::Statement::SystemFunctionCall(x) => Some(x.comptime.token),
+        air::Statement::FunctionCall(x) => Some(x.comptime.token),
+        air::Statement::TbMethodCall(x) => Some(x.token),
+        air::Statement::Unsupported(x) => Some(*x),
+        air::Statement::Null => None,
+    }
+}
@@ -3,11 +3,15 @@ use crate::ir::{
     Event, Expression, Ir, ModuleVariables, SimForRange, Statement, SystemFunctionCall,
     TbMethodKind, Value, VarId, VarPath, write_native_value,
 };
+use crate::output_buffer;
 use crate::simulator::Simulator;
 use crate::simulator_error::SimulatorError;
 use crate::wave_dumper::WaveDumper;
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
 use veryl_analyzer::value::MaskCache;
 use veryl_parser::resource_table::StrId;
+use veryl_parser::token_range::TokenRange;
 
 pub enum TestbenchStatement {
     /// Normal simulator statement (assign, $display, etc.)
@@ -18,6 +22,7 @@ pub enum TestbenchStatement {
         count: Option<Expression>,
         high_time: u64,
         low_time: u64,
+        token: TokenRange,
     },
     /// rst.assert(clk, N)
     ResetAssert {
@@ -26,6 +31,7 @@ pub enum TestbenchStatement {
         duration: u64,
         high_time: u64,
         low_time: u64,
+        token: TokenRange,
     },
     /// $assert(cond, msg)
     Assert {
@@ -46,6 +52,12 @@ pub enum TestbenchStatement {
     },
     /// $finish
     Finish,
+    /// $force(path, value)
+    Force { path: String, value: Expression },
+    /// $release(path)
+    Release { path: String },
+    /// $deposit(path, value)
+    Deposit { path: String, value: Expression },
 }
 
 pub struct LoopVariable {
@@ -63,6 +75,139 @@ pub enum TestResult {
     Fail(String),
 }
 
+/// Observer of testbench execution used by interactive debuggers.
+///
+/// Returning `ControlFlow::Break` terminates the testbench.
+pub trait TestbenchHook {
+    /// Called before the clock or reset statement at `token` is executed
+    fn before_statement(&mut self, sim: &mut Simulator, token: &TokenRange) -> ControlFlow<()>;
+
+    /// Called after each clock edge driven by the testbench
+    fn after_edge(&mut self, sim: &mut Simulator, event: &Event) -> ControlFlow<()>;
+}
+
+impl TestbenchHook for () {
+    fn before_statement(&mut self, _sim: &mut Simulator, _token: &TokenRange) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    fn after_edge(&mut self, _sim: &mut Simulator, _event: &Event) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// Limits of a native testbench execution.
+/// Exceeding one of them fails the test with `SimulatorError::TestFailed`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TestLimits {
+    /// Wall-clock timeout; zero disables it
+    pub timeout: Option<Duration>,
+    /// Maximum number of cycles of each clock driven by the testbench.
+    /// Cycles with reset asserted are not counted.
+    pub max_cycles: Option<u64>,
+    /// Maximum simulation time
+    pub max_time: Option<u64>,
+}
+
+impl TestLimits {
+    /// Fill unspecified limits from `other`.
+    pub fn or(self, other: &TestLimits) -> TestLimits {
+        TestLimits {
+            timeout: self.timeout.or(other.timeout),
+            max_cycles: self.max_cycles.or(other.max_cycles),
+            max_time: self.max_time.or(other.max_time),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timeout.is_none() && self.max_cycles.is_none() && self.max_time.is_none()
+    }
+}
+
+/// Number of clock edges between wall-clock timeout checks
+const TIMEOUT_CHECK_INTERVAL: u64 = 1024;
+
+/// Hook which terminates the testbench when `TestLimits` is exceeded.
+///
+/// The timeout is checked before each testbench statement and every
+/// `TIMEOUT_CHECK_INTERVAL` edges, so a hang inside a single `step`
+/// (e.g. a combinational loop which never converges) can't be interrupted.
+struct LimitHook<'a> {
+    limits: &'a TestLimits,
+    deadline: Option<Instant>,
+    /// Edges of all clocks and resets, which decide the interval of timeout checks
+    edges: u64,
+    /// Cycles of each clock
+    cycles: HashMap<Event, u64>,
+    exceeded: Option<String>,
+}
+
+impl<'a> LimitHook<'a> {
+    fn new(limits: &'a TestLimits) -> Self {
+        Self {
+            limits,
+            deadline: limits
+                .timeout
+                .filter(|x| !x.is_zero())
+                .map(|x| Instant::now() + x),
+            edges: 0,
+            cycles: HashMap::default(),
+            exceeded: None,
+        }
+    }
+
+    fn check_timeout(&mut self, sim: &Simulator) -> ControlFlow<()> {
+        if let (Some(timeout), Some(deadline)) = (self.limits.timeout, self.deadline)
+            && Instant::now() >= deadline
+        {
+            self.exceeded = Some(format!(
+                "timeout ({}s) exceeded at time {}",
+                timeout.as_secs_f64(),
+                sim.time
+            ));
+            return ControlFlow::Break(());
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl TestbenchHook for LimitHook<'_> {
+    fn before_statement(&mut self, sim: &mut Simulator, _token: &TokenRange) -> ControlFlow<()> {
+        self.check_timeout(sim)
+    }
+
+    fn after_edge(&mut self, sim: &mut Simulator, event: &Event) -> ControlFlow<()> {
+        self.edges += 1;
+        if let Event::Clock(_) = event {
+            let cycles = self.cycles.entry(event.clone()).or_default();
+            *cycles += 1;
+            if let Some(max_cycles) = self.limits.max_cycles
+                && *cycles > max_cycles
+            {
+                self.exceeded = Some(format!(
+                    "max cycles ({max_cycles}) exceeded at time {}",
+                    sim.time
+                ));
+                return ControlFlow::Break(());
+            }
+        }
+        if let Some(max_time) = self.limits.max_time
+            && sim.time > max_time
+        {
+            self.exceeded = Some(format!(
+                "max time ({max_time}) exceeded at time {}",
+                sim.time
+            ));
+            return ControlFlow::Break(());
+        }
+        if self.edges.is_multiple_of(TIMEOUT_CHECK_INTERVAL) {
+            self.check_timeout(sim)
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
 /// Internal execution result that distinguishes Finish from normal continuation.
 #[derive(Debug, PartialEq, Eq)]
 enum ExecResult {
@@ -75,6 +220,10 @@ impl ExecResult {
     fn should_stop(&self) -> bool {
         !matches!(self, ExecResult::Continue)
     }
+
+    fn terminated() -> Self {
+        ExecResult::Fail("terminated by debugger".to_string())
+    }
 }
 
 impl From<ExecResult> for TestResult {
@@ -110,7 +259,7 @@ fn collect_tb_insts(
 ) {
     for stmt in stmts {
         match stmt {
-            Statement::TbMethodCall { inst, method } => match method {
+            Statement::TbMethodCall { inst, method, .. } => match method {
                 TbMethodKind::ClockNext { .. } => {
                     if !clock_insts.contains(inst) {
                         clock_insts.push(*inst);
@@ -169,7 +318,7 @@ fn compute_half_periods(period: u64) -> (u64, u64) {
 fn collect_clock_periods(stmts: &[Statement], periods: &mut HashMap<StrId, u64>) {
     for stmt in stmts {
         match stmt {
-            Statement::TbMethodCall { inst, method } => {
+            Statement::TbMethodCall { inst, method, .. } => {
                 if let TbMethodKind::ClockNext { period, .. } = method
                     && let Some(expr) = period
                 {
@@ -229,7 +378,11 @@ fn convert_stmt(
     default_reset_duration: u64,
 ) -> TestbenchStatement {
     match stmt {
-        Statement::TbMethodCall { inst, method } => match method {
+        Statement::TbMethodCall {
+            inst,
+            method,
+            token,
+        } => match method {
             TbMethodKind::ClockNext { count, period } => {
                 let clock = event_map.get(inst).cloned().unwrap_or(Event::Initial);
                 let p = if let Some(expr) = period {
@@ -244,6 +397,7 @@ fn convert_stmt(
                     count: count.clone(),
                     high_time,
                     low_time,
+                    token: *token,
                 }
             }
             TbMethodKind::ResetAssert { clock, duration } => {
@@ -263,6 +417,7 @@ fn convert_stmt(
                     duration: dur,
                     high_time,
                     low_time,
+                    token: *token,
                 }
             }
         },
@@ -273,6 +428,21 @@ fn convert_stmt(
             }
         }
         Statement::SystemFunctionCall(SystemFunctionCall::Finish) => TestbenchStatement::Finish,
+        Statement::SystemFunctionCall(SystemFunctionCall::Force { path, value }) => {
+            TestbenchStatement::Force {
+                path: path.clone(),
+                value: value.clone(),
+            }
+        }
+        Statement::SystemFunctionCall(SystemFunctionCall::Release { path }) => {
+            TestbenchStatement::Release { path: path.clone() }
+        }
+        Statement::SystemFunctionCall(SystemFunctionCall::Deposit { path, value }) => {
+            TestbenchStatement::Deposit {
+                path: path.clone(),
+                value: value.clone(),
+            }
+        }
         Statement::If(if_stmt) => {
             let then_block = convert_stmts(
                 &if_stmt.true_side,
@@ -333,7 +503,16 @@ fn convert_stmts(
 }
 
 pub fn run_testbench(sim: &mut Simulator, stmts: &[TestbenchStatement]) -> TestResult {
-    exec(sim, stmts).into()
+    run_testbench_with_hook(sim, stmts, &mut ())
+}
+
+/// Run testbench statements while notifying `hook` of clock statements and edges.
+pub fn run_testbench_with_hook(
+    sim: &mut Simulator,
+    stmts: &[TestbenchStatement],
+    hook: &mut dyn TestbenchHook,
+) -> TestResult {
+    exec(sim, stmts, hook).into()
 }
 
 /// Run a native testbench from a simulator IR.
@@ -345,19 +524,18 @@ pub fn run_native_testbench(
     dump: Option<WaveDumper>,
     module_name: String,
 ) -> Result<TestResult, SimulatorError> {
-    let mut sim = Simulator::new(ir, dump);
-    let event_map = build_event_map(&sim.ir.event_statements, &sim.ir.module_variables);
-    let clock_periods = build_clock_periods(&sim.ir.event_statements);
-
-    let token = sim.ir.token;
-    let initial_stmts = sim
-        .ir
-        .event_statements
-        .get(&Event::Initial)
-        .ok_or_else(|| SimulatorError::no_initial_block(&module_name, &token))?;
+    run_native_testbench_with_limits(ir, dump, module_name, &TestLimits::default())
+}
 
-    let tb_stmts = convert_initial_to_testbench(initial_stmts, &event_map, &clock_periods, 3);
-    let result = run_testbench(&mut sim, &tb_stmts);
+/// Run a native testbench like `run_native_testbench` under `limits`.
+pub fn run_native_testbench_with_limits(
+    ir: Ir,
+    dump: Option<WaveDumper>,
+    module_name: String,
+    limits: &TestLimits,
+) -> Result<TestResult, SimulatorError> {
+    let mut sim = Simulator::new(ir, dump);
+    let result = run_simulator_testbench(&mut sim, &module_name, limits)?;
 
     #[cfg(feature = "profile")]
     {
@@ -412,9 +590,50 @@ pub fn run_native_testbench(
     Ok(result)
 }
 
-fn exec(sim: &mut Simulator, stmts: &[TestbenchStatement]) -> ExecResult {
+/// Run the native testbench of the top module of `sim` under `limits`.
+pub fn run_simulator_testbench(
+    sim: &mut Simulator,
+    module_name: &str,
+    limits: &TestLimits,
+) -> Result<TestResult, SimulatorError> {
+    let event_map = build_event_map(&sim.ir.event_statements, &sim.ir.module_variables);
+    let clock_periods = build_clock_periods(&sim.ir.event_statements);
+
+    let token = sim.ir.token;
+    let initial_stmts = sim
+        .ir
+        .event_statements
+        .get(&Event::Initial)
+        .ok_or_else(|| SimulatorError::no_initial_block(module_name, &token))?;
+
+    let tb_stmts = convert_initial_to_testbench(initial_stmts, &event_map, &clock_periods, 3);
+    let result = if limits.is_empty() {
+        run_testbench(sim, &tb_stmts)
+    } else {
+        let mut hook = LimitHook::new(limits);
+        let result = run_testbench_with_hook(sim, &tb_stmts, &mut hook);
+        if let Some(message) = hook.exceeded {
+            return Err(SimulatorError::TestFailed { message });
+        }
+        result
+    };
+
+    if sim.ir.merge_fallback() {
+        log::warn!(
+            "{module_name}: pessimistic X-propagation can't merge both sides of some `if` statements (e.g. with system function calls), so their unknown conditions are treated as false"
+        );
+    }
+
+    Ok(result)
+}
+
+fn exec(
+    sim: &mut Simulator,
+    stmts: &[TestbenchStatement],
+    hook: &mut dyn TestbenchHook,
+) -> ExecResult {
     for stmt in stmts {
-        let result = exec_one(sim, stmt);
+        let result = exec_one(sim, stmt, hook);
         if result.should_stop() {
             return result;
         }
@@ -422,7 +641,11 @@ fn exec(sim: &mut Simulator, stmts: &[TestbenchStatement]) -> ExecResult {
     ExecResult::Continue
 }
 
-fn exec_one(sim: &mut Simulator, stmt: &TestbenchStatement) -> ExecResult {
+fn exec_one(
+    sim: &mut Simulator,
+    stmt: &TestbenchStatement,
+    hook: &mut dyn TestbenchHook,
+) -> ExecResult {
     match stmt {
         TestbenchStatement::Stmt(s) => {
             sim.ensure_comb_updated();
@@ -435,7 +658,11 @@ fn exec_one(sim: &mut Simulator, stmt: &TestbenchStatement) -> ExecResult {
             count,
             high_time,
             low_time,
+            token,
         } => {
+            if hook.before_statement(sim, token).is_break() {
+                return ExecResult::terminated();
+            }
             let n = if let Some(expr) = count {
                 sim.ensure_comb_updated();
                 let val = expr.eval(&mut sim.mask_cache);
@@ -449,6 +676,9 @@ fn exec_one(sim: &mut Simulator, stmt: &TestbenchStatement) -> ExecResult {
                     sim.set_var_by_id(&id, Value::new(1, 1, false));
                 }
                 sim.step(clock);
+                if hook.after_edge(sim, clock).is_break() {
+                    return ExecResult::terminated();
+                }
                 sim.time += high_time;
                 if has_dump {
                     if let Some(id) = clock.var_id() {
@@ -466,7 +696,11 @@ fn exec_one(sim: &mut Simulator, stmt: &TestbenchStatement) -> ExecResult {
             duration,
             high_time,
             low_time,
+            token,
         } => {
+            if hook.before_statement(sim, token).is_break() {
+                return ExecResult::terminated();
+            }
             // Step reset event for `duration` cycles.
             // In this simulator, Event::Reset represents a clock edge
             // with reset asserted (executes the if_reset branch of always_ff).
@@ -479,6 +713,9 @@ fn exec_one(sim: &mut Simulator, stmt: &TestbenchStatement) -> ExecResult {
                     sim.set_var_by_id(&id, Value::new(1, 1, false));
                 }
                 sim.step(reset);
+                if hook.after_edge(sim, reset).is_break() {
+                    return ExecResult::terminated();
+                }
                 sim.time += high_time;
                 if has_dump {
                     if let Some(id) = clock.var_id() {
@@ -491,6 +728,11 @@ fn exec_one(sim: &mut Simulator, stmt: &TestbenchStatement) -> ExecResult {
             if has_dump && let Some(id) = reset.var_id() {
                 sim.set_var_by_id(&id, Value::new(0, 1, false));
             }
+            if sim.ir.report_x_registers {
+                for path in sim.x_registers() {
+                    output_buffer::println(&format!("register {path} is X after reset"));
+                }
+            }
             ExecResult::Continue
         }
         TestbenchStatement::Assert { condition, message } => {
@@ -511,9 +753,9 @@ fn exec_one(sim: &mut Simulator, stmt: &TestbenchStatement) -> ExecResult {
             sim.ensure_comb_updated();
             let val = condition.eval(&mut sim.mask_cache);
             if val.payload_u64() != 0 {
-                exec(sim, then_block)
+                exec(sim, then_block, hook)
             } else {
-                exec(sim, else_block)
+                exec(sim, else_block, hook)
             }
         }
         TestbenchStatement::For {
@@ -527,7 +769,7 @@ fn exec_one(sim: &mut Simulator, stmt: &TestbenchStatement) -> ExecResult {
                     unsafe {
                         write_native_value(lv.ptr, lv.native_bytes, lv.use_4state, &val);
                     }
-                    exec(sim, body)
+                    exec(sim, body, hook)
                 };
                 match &lv.range {
                     SimForRange::Forward { start, end, step } => {
@@ -568,7 +810,7 @@ fn exec_one(sim: &mut Simulator, stmt: &TestbenchStatement) -> ExecResult {
                 }
             } else {
                 for _ in 0..*count {
-                    let result = exec(sim, body);
+                    let result = exec(sim, body, hook);
                     if result.should_stop() {
                         return result;
                     }
@@ -577,5 +819,30 @@ fn exec_one(sim: &mut Simulator, stmt: &TestbenchStatement) -> ExecResult {
             ExecResult::Continue
         }
         TestbenchStatement::Finish => ExecResult::Finished,
+        TestbenchStatement::Force { path, value } => {
+            sim.ensure_comb_updated();
+            let value = value.eval(&mut sim.mask_cache);
+            if sim.force(path, value) {
+                ExecResult::Continue
+            } else {
+                ExecResult::Fail(format!("$force: variable '{path}' is not found"))
+            }
+        }
+        TestbenchStatement::Release { path } => {
+            if sim.release(path) {
+                ExecResult::Continue
+            } else {
+                ExecResult::Fail(format!("$release: variable '{path}' is not forced"))
+            }
+        }
+        TestbenchStatement::Deposit { path, value } => {
+            sim.ensure_comb_updated();
+            let value = value.eval(&mut sim.mask_cache);
+            if sim.deposit(path, value) {
+                ExecResult::Continue
+            } else {
+                ExecResult::Fail(format!("$deposit: variable '{path}' is not found"))
+            }
+        }
     }
 }
@@ -13,6 +13,7 @@ use veryl_analyzer::ir::VarId;
 use veryl_analyzer::{Analyzer, AnalyzerError, Context, symbol_table};
 use veryl_metadata::Metadata;
 use veryl_parser::Parser;
+use veryl_parser::token_range::TokenRange;
 
 #[track_caller]
 fn analyze(code: &str, config: &Config) -> Ir {
@@ -21,10 +22,21 @@ fn analyze(code: &str, config: &Config) -> Ir {
 
 #[track_caller]
 fn analyze_top(code: &str, config: &Config, top: &str) -> Result<Ir, SimulatorError> {
+    let ir = analyze_air(code);
+    build_ir(&ir, top.into(), config)
+}
+
+#[track_caller]
+fn analyze_air(code: &str) -> air::Ir {
+    analyze_air_with_path(code, "")
+}
+
+#[track_caller]
+fn analyze_air_with_path(code: &str, path: &str) -> air::Ir {
     symbol_table::clear();
 
     let metadata = Metadata::create_default("prj").unwrap();
-    let parser = Parser::parse(&code, &"").unwrap();
+    let parser = Parser::parse(&code, &path).unwrap();
     let analyzer = Analyzer::new(&metadata);
     let mut context = Context::default();
 
@@ -48,7 +60,7 @@ fn analyze_top(code: &str, config: &Config, top: &str) -> Result<Ir, SimulatorEr
         .collect();
     assert!(errors.is_empty());
 
-    build_ir(&ir, top.into(), config)
+    ir
 }
 
 /// Analyze with per-file project names (simulates different prj for std vs user code)
@@ -356,6 +368,18 @@ where
     }
 }
 
+#[cfg(not(target_family = "wasm"))]
+mod aot;
+mod batch;
+mod debug_adapter;
 mod error;
+mod force;
+#[cfg(not(target_family = "wasm"))]
+mod jit_cache;
+mod limits;
+mod parallel;
+mod repl;
 mod simulation;
 mod testbench;
+mod wavedrom;
+mod xprop;
@@ -0,0 +1,375 @@
+use super::*;
+use crate::aot::{RUNTIME, RUNTIME_FILE, emit_object};
+use crate::ir::wide_fn_addrs;
+use crate::wide_ops::{self, pack_nb_width};
+use object::read::elf::ElfFile64;
+use object::{Object, ObjectSymbol};
+use std::fmt::Write;
+use std::process::Command;
+
+const CODE: &str = r#"module Top (
+    clk: input clock,
+    rst: input reset,
+    en: input logic,
+    cnt: output logic<8>,
+    acc: output logic<200>,
+) {
+    var x: logic<8>;
+
+    inst u: Sub (
+        i: cnt,
+        o: x,
+    );
+
+    always_ff {
+        if_reset {
+            cnt = 0;
+            acc = 1;
+        } else if en {
+            cnt += 1;
+            acc = (acc << 3) + acc + x;
+        }
+    }
+}
+
+module Sub (
+    i: input logic<8>,
+    o: output logic<8>,
+) {
+    assign o = i ^ 8'h5a;
+}
+"#;
+
+const MAIN: &str = r#"#include <stdio.h>
+#include "top.h"
+
+static void print(veryl_Top *m) {
+    printf("%llu", (unsigned long long)veryl_Top_get_cnt(m));
+    const uint8_t *acc = veryl_Top_ptr_acc(m);
+    for (int i = 0; i < 25; i++) printf(" %02x", acc[i]);
+    printf("\n");
+}
+
+int main(void) {
+    veryl_Top m;
+    veryl_Top_init(&m);
+    veryl_Top_eval_comb(&m);
+    veryl_Top_reset_rst(&m);
+    veryl_Top_eval_comb(&m);
+    print(&m);
+    veryl_Top_set_en(&m, 1);
+    for (int i = 0; i < 100; i++) {
+        veryl_Top_eval_comb(&m);
+        veryl_Top_clock_clk(&m);
+        veryl_Top_eval_comb(&m);
+        if (i % 10 == 9) print(&m);
+    }
+    return 0;
+}
+"#;
+
+fn expected() -> String {
+    let config = Config {
+        use_jit: false,
+        ..Default::default()
+    };
+    let mut sim = Simulator::new(analyze(CODE, &config), None);
+    let clk = sim.get_clock("clk").unwrap();
+    let rst = sim.get_reset("rst").unwrap();
+
+    let print = |sim: &mut Simulator| {
+        let cnt = sim.get("cnt").unwrap();
+        let acc = sim.get("acc").unwrap();
+        let mut bytes = vec![0; 32];
+        acc.write_payload_to_bytes(&mut bytes);
+        let bytes: Vec<_> = bytes[..25].iter().map(|x| format!(" {x:02x}")).collect();
+        format!("{}{}\n", cnt.to_u64().unwrap(), bytes.concat())
+    };
+
+    let mut ret = String::new();
+    sim.step(&rst);
+    ret.push_str(&print(&mut sim));
+    sim.set("en", Value::new(1, 1, false));
+    for i in 0..100 {
+        sim.step(&clk);
+        if i % 10 == 9 {
+            ret.push_str(&print(&mut sim));
+        }
+    }
+    ret
+}
+
+#[test]
+fn symbols() {
+    let ir = analyze_air(CODE);
+    let model = emit_object(&ir, "Top".into(), &Config::default()).unwrap();
+    assert_eq!(model.prefix, "veryl_Top");
+
+    let file = ElfFile64::<object::Endianness>::parse(model.object.as_slice()).unwrap();
+    let defined: Vec<_> = file
+        .symbols()
+        .filter(|x| x.is_global() && x.is_definition())
+        .map(|x| x.name().unwrap().to_string())
+        .collect();
+    for name in [
+        "veryl_Top_eval_comb",
+        "veryl_Top_clock_clk",
+        "veryl_Top_reset_rst",
+        "veryl_Top_ff_init",
+        "veryl_Top_comb_init",
+    ] {
+        assert!(defined.iter().any(|x| x == name), "{name} is not defined");
+    }
+    assert!(
+        file.symbols()
+            .any(|x| x.is_undefined() && x.name() == Ok("veryl_wide_add"))
+    );
+
+    assert!(
+        model
+            .header
+            .contains("void veryl_Top_clock_clk(veryl_Top *m);")
+    );
+    assert!(
+        model
+            .header
+            .contains("veryl_Top_set_en(veryl_Top *m, uint64_t v)")
+    );
+    assert!(model.header.contains("#define VERYL_TOP_ACC_WIDTH 200"));
+    assert!(!model.header.contains("veryl_Top_get_acc"));
+}
+
+#[test]
+#[ignore = "requires a C compiler"]
+fn link_and_run() {
+    let ir = analyze_air(CODE);
+    let model = emit_object(&ir, "Top".into(), &Config::default()).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    std::fs::write(dir.join("top.o"), &model.object).unwrap();
+    std::fs::write(dir.join("top.h"), &model.header).unwrap();
+    std::fs::write(dir.join(RUNTIME_FILE), RUNTIME).unwrap();
+    std::fs::write(dir.join("main.c"), MAIN).unwrap();
+
+    let status = Command::new("cc")
+        .current_dir(dir)
+        .args(["-o", "main", "main.c", "top.o", RUNTIME_FILE])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let output = Command::new(dir.join("main")).output().unwrap();
+
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected());
+}
+
+#[test]
+fn unsupported_statement() {
+    let code = r#"module Top (
+    clk: input clock,
+    a: input logic<8>,
+) {
+    always_ff {
+        $display("%d", a);
+    }
+}
+"#;
+    let ir = analyze_air(code);
+    let err = emit_object(&ir, "Top".into(), &Config::default()).err();
+    assert!(matches!(
+        err,
+        Some(SimulatorError::NativeObjectUnsupported { .. })
+    ));
+}
+
+#[derive(Clone, Copy)]
+enum Helper {
+    Binary(unsafe extern "C" fn(*mut u8, *const u8, *const u8, u32)),
+    Unary(unsafe extern "C" fn(*mut u8, *const u8, u32)),
+    Shift(unsafe extern "C" fn(*mut u8, *const u8, u64, u32)),
+    Compare(unsafe extern "C" fn(*const u8, *const u8, u32) -> i64),
+    Reduce(unsafe extern "C" fn(*const u8, u32) -> i64),
+}
+
+/// Helpers of `wide_ops` and whether the last argument is packed with width
+fn helpers() -> Vec<(Helper, bool)> {
+    use Helper::*;
+    use wide_ops::*;
+    vec![
+        (Binary(wide_band), false),
+        (Binary(wide_bor), false),
+        (Binary(wide_bxor), false),
+        (Binary(wide_bxor_not), false),
+        (Binary(wide_band_not), false),
+        (Unary(wide_bnot), false),
+        (Binary(wide_add), false),
+        (Binary(wide_sub), false),
+        (Binary(wide_mul), false),
+        (Unary(wide_negate), false),
+        (Unary(wide_copy), false),
+        (Compare(wide_eq), false),
+        (Compare(wide_ne), false),
+        (Compare(wide_ucmp), false),
+        (Compare(wide_scmp), true),
+        (Shift(wide_shl), false),
+        (Shift(wide_lshr), false),
+        (Shift(wide_ashr), true),
+        (Reduce(wide_is_nonzero), false),
+        (Reduce(wide_is_all_ones), true),
+        (Reduce(wide_popcnt_parity), false),
+        (Unary(wide_apply_mask), true),
+        (Unary(wide_fill_ones), true),
+    ]
+}
+
+/// Input pairs of `nb` bytes: random values, carry/borrow chains and equal values
+fn parity_inputs(nb: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+    let mut x = 0x2545f4914f6cdd1du64;
+    let mut random = || {
+        (0..nb)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x as u8
+            })
+            .collect::<Vec<_>>()
+    };
+    let (a, b) = (random(), random());
+    let mut one = vec![0; nb];
+    one[0] = 1;
+    vec![
+        (a.clone(), b),
+        (vec![0xff; nb], one.clone()),
+        (vec![0; nb], one),
+        (vec![0; nb], a.clone()),
+        (a.clone(), a),
+    ]
+}
+
+fn c_bytes(x: &[u8]) -> String {
+    let x: Vec<_> = x.iter().map(|x| format!("0x{x:02x}")).collect();
+    format!("{{{}}}", x.join(", "))
+}
+
+fn hex(x: &[u8]) -> String {
+    x.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+#[test]
+#[ignore = "requires a C compiler"]
+fn runtime_parity() {
+    let symbols = wide_fn_addrs::symbols();
+    let helpers = helpers();
+    assert_eq!(helpers.len(), symbols.len());
+
+    let mut main = format!(
+        "#include <stdio.h>\n#include \"{RUNTIME_FILE}\"\n\n\
+         static void dump(const uint8_t *x, size_t n) {{\n    \
+         for (size_t i = 0; i < n; i++) printf(\"%02x\", x[i]);\n    \
+         printf(\"\\n\");\n}}\n\nint main(void) {{\n"
+    );
+    let mut expected = vec![];
+
+    for (helper, packed) in helpers {
+        let addr = match helper {
+            Helper::Binary(f) => f as usize,
+            Helper::Unary(f) => f as usize,
+            Helper::Shift(f) => f as usize,
+            Helper::Compare(f) => f as usize,
+            Helper::Reduce(f) => f as usize,
+        };
+        let (_, name) = symbols.iter().find(|(x, _)| *x == addr).unwrap();
+
+        for width in [129usize, 192, 200, 256, 300] {
+            let nb = width.div_ceil(64) * 8;
+            let arg = if packed {
+                pack_nb_width(nb, width)
+            } else {
+                nb as u32
+            };
+            for (a, b) in parity_inputs(nb) {
+                // destination is initialized by `b` for ops reading it (e.g. apply_mask)
+                let mut dst = b.clone();
+                let decl = format!(
+                    "    {{\n        uint8_t a[] = {};\n        uint8_t b[] = {};\n        \
+                     uint8_t dst[] = {};\n        (void)a; (void)b; (void)dst;\n",
+                    c_bytes(&a),
+                    c_bytes(&b),
+                    c_bytes(&b)
+                );
+                let calls: Vec<(String, String)> = unsafe {
+                    match helper {
+                        Helper::Binary(f) => {
+                            f(dst.as_mut_ptr(), a.as_ptr(), b.as_ptr(), nb as u32);
+                            vec![(
+                                format!("{name}(dst, a, b, {arg}u); dump(dst, {nb});"),
+                                hex(&dst),
+                            )]
+                        }
+                        Helper::Unary(f) => {
+                            f(dst.as_mut_ptr(), a.as_ptr(), arg);
+                            vec![(
+                                format!("{name}(dst, a, {arg}u); dump(dst, {nb});"),
+                                hex(&dst),
+                            )]
+                        }
+                        Helper::Shift(f) => [0, 1, 63, 64, 65, 130, width - 1, width, 1000]
+                            .into_iter()
+                            .map(|amount| {
+                                f(dst.as_mut_ptr(), a.as_ptr(), amount as u64, arg);
+                                (
+                                    format!(
+                                        "{name}(dst, a, {amount}ull, {arg}u); dump(dst, {nb});"
+                                    ),
+                                    hex(&dst),
+                                )
+                            })
+                            .collect(),
+                        Helper::Compare(f) => {
+                            let ret = f(a.as_ptr(), b.as_ptr(), arg);
+                            vec![(
+                                format!("printf(\"%lld\\n\", (long long){name}(a, b, {arg}u));"),
+                                ret.to_string(),
+                            )]
+                        }
+                        Helper::Reduce(f) => {
+                            let ret = f(a.as_ptr(), arg);
+                            vec![(
+                                format!("printf(\"%lld\\n\", (long long){name}(a, {arg}u));"),
+                                ret.to_string(),
+                            )]
+                        }
+                    }
+                };
+                main.push_str(&decl);
+                for (call, result) in calls {
+                    let _ = writeln!(main, "        {call}");
+                    expected.push((call, result));
+                }
+                main.push_str("    }\n");
+            }
+        }
+    }
+    main.push_str("    return 0;\n}\n");
+
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    std::fs::write(dir.join(RUNTIME_FILE), RUNTIME).unwrap();
+    std::fs::write(dir.join("main.c"), main).unwrap();
+
+    let status = Command::new("cc")
+        .current_dir(dir)
+        .args(["-o", "main", "main.c"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let output = Command::new(dir.join("main")).output().unwrap();
+
+    let actual = String::from_utf8(output.stdout).unwrap();
+    let actual: Vec<_> = actual.lines().collect();
+    assert_eq!(actual.len(), expected.len());
+    for (actual, (call, expected)) in actual.iter().zip(&expected) {
+        assert_eq!(actual, expected, "{call}");
+    }
+}
@@ -0,0 +1,172 @@
+use super::*;
+use crate::batch::{BatchSimulator, has_seed};
+use crate::testbench::TestLimits;
+use veryl_parser::resource_table::StrId;
+
+const CODE: &str = r#"module Top (
+    clk: input clock,
+    rst: input reset,
+    x: input logic<8>,
+    sum: output logic<16>,
+) {
+    always_ff {
+        if_reset {
+            sum = 0;
+        } else {
+            sum += x;
+        }
+    }
+}
+"#;
+
+#[test]
+fn independent_lanes() {
+    for config in Config::all() {
+        let ir = analyze_air(CODE);
+        let mut batch = BatchSimulator::new(&ir, "Top".into(), &config, 4).unwrap();
+        assert_eq!(batch.len(), 4);
+
+        let clk = batch.get_clock("clk").unwrap();
+        let rst = batch.get_reset("rst").unwrap();
+        let inputs: Vec<_> = (0..4).map(|i| Value::new(i * 3 + 1, 8, false)).collect();
+
+        batch.step(&rst);
+        batch.set("x", &inputs);
+        for _ in 0..5 {
+            batch.step(&clk);
+        }
+
+        let sum: Vec<_> = batch
+            .get("sum")
+            .unwrap()
+            .iter()
+            .map(|x| x.to_u64().unwrap())
+            .collect();
+        assert_eq!(sum, vec![5, 20, 35, 50]);
+
+        batch.set_all("x", Value::new(1, 8, false));
+        batch.step(&clk);
+        assert_eq!(batch.lane(2).get("sum").unwrap().to_u64(), Some(36));
+    }
+}
+
+#[test]
+fn uneven_lanes() {
+    // 5 lanes on 4 threads are split into 3 chunks, and the last worker is idle
+    let config = Config {
+        threads: 4,
+        ..Default::default()
+    };
+    let ir = analyze_air(CODE);
+    let mut batch = BatchSimulator::new(&ir, "Top".into(), &config, 5).unwrap();
+
+    let clk = batch.get_clock("clk").unwrap();
+    let rst = batch.get_reset("rst").unwrap();
+    let inputs: Vec<_> = (0..5).map(|i| Value::new(i + 1, 8, false)).collect();
+
+    batch.step(&rst);
+    batch.set("x", &inputs);
+    for _ in 0..100 {
+        batch.step(&clk);
+    }
+
+    let sum: Vec<_> = batch
+        .get("sum")
+        .unwrap()
+        .iter()
+        .map(|x| x.to_u64().unwrap())
+        .collect();
+    assert_eq!(sum, vec![100, 200, 300, 400, 500]);
+}
+
+const SEED_CODE: &str = r#"module Acc (
+    clk: input clock,
+    rst: input reset,
+    x: input logic<32>,
+    sum: output logic<32>,
+) {
+    always_ff {
+        if_reset {
+            sum = 0;
+        } else {
+            sum += x;
+        }
+    }
+}
+
+#[test(test_acc)]
+#[seed(rng_seed)]
+module test_acc {
+    inst clk: $tb::clock_gen;
+    inst rst: $tb::reset_gen;
+
+    #[allow(unassign_variable)]
+    var rng_seed: logic<32>;
+    var sum: logic<32>;
+
+    inst dut: Acc (
+        clk,
+        rst,
+        x: rng_seed,
+        sum,
+    );
+
+    initial {
+        rst.assert(clk);
+        clk.next(10);
+        $assert(sum != 32'd50);
+        $finish();
+    }
+}
+"#;
+
+#[test]
+fn seed_sweep() {
+    let config = Config::default();
+
+    let ir = analyze_air(SEED_CODE);
+    let var: StrId = "rng_seed".into();
+    let mut batch = BatchSimulator::new(&ir, "test_acc".into(), &config, 3).unwrap();
+    assert!(batch.set_seeds(var, 10));
+    assert!(!batch.set_seeds("seed".into(), 10));
+    assert_eq!(
+        batch.lane(2).get_var("rng_seed").unwrap().to_u64(),
+        Some(12)
+    );
+
+    let lanes = |n: usize| -> Vec<Ir> {
+        (0..n)
+            .map(|_| analyze_top(SEED_CODE, &config, "test_acc").unwrap())
+            .collect()
+    };
+
+    let irs = lanes(5);
+    assert!(has_seed(&irs[0], var));
+    let limits = TestLimits::default();
+    let run = |irs, seed| {
+        BatchSimulator::from_irs(irs, None, 1)
+            .run_seed_sweep("test_acc", var, seed, &limits)
+            .unwrap()
+    };
+    assert_eq!(run(irs, 0), TestResult::Pass);
+
+    let result = run(lanes(8), 0);
+    assert!(matches!(result, TestResult::Fail(x) if x.starts_with("rng_seed = 5:")));
+
+    let result = run(lanes(4), 3);
+    assert!(matches!(result, TestResult::Fail(x) if x.starts_with("rng_seed = 5:")));
+
+    let ir = analyze(CODE, &config);
+    assert!(!has_seed(&ir, var));
+
+    let result = BatchSimulator::from_irs(lanes(1), None, 1).run_seed_sweep(
+        "test_acc",
+        "seed".into(),
+        0,
+        &limits,
+    );
+    assert!(matches!(
+        result,
+        Err(SimulatorError::SeedVariableNotFound { .. })
+    ));
+}
@@ -0,0 +1,327 @@
+use super::*;
+use crate::debug_adapter::{DebugSession, collect_hierarchy_blocks, collect_source_blocks};
+use serde_json::{Value as Json, json};
+use std::io::Cursor;
+
+const CODE: &str = r#"module Counter (
+    clk: input clock,
+    rst: input reset,
+    cnt: output logic<8>,
+) {
+    always_ff {
+        if_reset {
+            cnt = 0;
+        } else {
+            cnt += 1;
+        }
+    }
+}
+
+#[test(test_counter)]
+module test_counter {
+    inst clk: $tb::clock_gen;
+    inst rst: $tb::reset_gen;
+
+    var cnt: logic<8>;
+
+    inst dut: Counter (
+        clk: clk,
+        rst: rst,
+        cnt: cnt,
+    );
+
+    initial {
+        rst.assert(clk);
+        clk.next(5);
+        $display("cnt = %d", cnt);
+        $finish();
+    }
+}
+"#;
+
+fn encode(requests: &[Json]) -> Cursor<Vec<u8>> {
+    let mut ret = Vec::new();
+    for (i, x) in requests.iter().enumerate() {
+        let mut x = x.clone();
+        x["seq"] = json!(i + 1);
+        x["type"] = json!("request");
+        let body = x.to_string();
+        ret.extend(format!("Content-Length: {}\r\n\r\n{body}", body.len()).bytes());
+    }
+    Cursor::new(ret)
+}
+
+fn decode(mut output: &[u8]) -> Vec<Json> {
+    let mut ret = Vec::new();
+    while let Some(pos) = output.windows(4).position(|x| x == b"\r\n\r\n") {
+        let header = std::str::from_utf8(&output[..pos]).unwrap();
+        let length: usize = header["Content-Length: ".len()..].parse().unwrap();
+        let body = &output[pos + 4..pos + 4 + length];
+        ret.push(serde_json::from_slice(body).unwrap());
+        output = &output[pos + 4 + length..];
+    }
+    ret
+}
+
+fn response<'a>(messages: &'a [Json], command: &str) -> Vec<&'a Json> {
+    messages
+        .iter()
+        .filter(|x| x["type"] == "response" && x["command"] == command)
+        .collect()
+}
+
+fn event<'a>(messages: &'a [Json], event: &str) -> Vec<&'a Json> {
+    messages
+        .iter()
+        .filter(|x| x["type"] == "event" && x["event"] == event)
+        .collect()
+}
+
+fn debug(requests: &[Json]) -> (TestResult, Vec<Json>) {
+    debug_code(CODE, "test_counter", requests)
+}
+
+fn debug_code(code: &str, test: &str, requests: &[Json]) -> (TestResult, Vec<Json>) {
+    let ir = analyze_air_with_path(code, "counter.veryl");
+    let config = Config {
+        use_jit: false,
+        trace_statements: true,
+        ..Default::default()
+    };
+    let top = test.into();
+    let sim_ir = build_ir(&ir, top, &config).unwrap();
+    let sim = Simulator::new(sim_ir, None);
+
+    let event_map = build_event_map(&sim.ir.event_statements, &sim.ir.module_variables);
+    let clock_periods = build_clock_periods(&sim.ir.event_statements);
+    let initial = sim.ir.event_statements.get(&Event::Initial).unwrap();
+    let stmts = convert_initial_to_testbench(initial, &event_map, &clock_periods, 3);
+
+    let mut output = Vec::new();
+    let mut session = DebugSession::new(encode(requests), &mut output, collect_source_blocks(&ir));
+    let args = session.wait_launch().unwrap().unwrap();
+    assert_eq!(args["test"], test);
+    let result = session.run(sim, &stmts, collect_hierarchy_blocks(&ir, top));
+    (result, decode(&output))
+}
+
+fn set_breakpoints(breakpoints: Json) -> Json {
+    json!({
+        "command": "setBreakpoints",
+        "arguments": { "source": { "path": "counter.veryl" }, "breakpoints": breakpoints },
+    })
+}
+
+#[test]
+fn breakpoint_and_variables() {
+    let (result, messages) = debug(&[
+        json!({ "command": "initialize", "arguments": { "adapterID": "veryl" } }),
+        json!({ "command": "launch", "arguments": { "test": "test_counter" } }),
+        set_breakpoints(json!([
+            { "line": 10, "condition": "dut.cnt == 3" },
+            { "line": 30 },
+            { "line": 31 },
+        ])),
+        json!({ "command": "configurationDone" }),
+        // stop before `clk.next(5)`
+        json!({ "command": "stackTrace", "arguments": { "threadId": 1 } }),
+        json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+        // stop in always_ff when the condition is met
+        json!({ "command": "stackTrace", "arguments": { "threadId": 1 } }),
+        json!({ "command": "variables", "arguments": { "variablesReference": 2 } }),
+        json!({ "command": "variables", "arguments": { "variablesReference": 3 } }),
+        json!({ "command": "evaluate", "arguments": { "expression": "cnt" } }),
+        json!({ "command": "evaluate", "arguments": { "expression": "dut.cnt >= 3 && !(cnt == 'h4)" } }),
+        json!({ "command": "evaluate", "arguments": { "expression": "unknown" } }),
+        json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+        json!({ "command": "disconnect" }),
+    ]);
+    assert_eq!(result, TestResult::Pass);
+
+    let breakpoints = &response(&messages, "setBreakpoints")[0]["body"]["breakpoints"];
+    assert_eq!(breakpoints[0]["verified"], true);
+    assert_eq!(breakpoints[1]["verified"], true);
+    // `$display` in initial is not a clock/reset statement
+    assert_eq!(breakpoints[2]["verified"], false);
+
+    let stopped = event(&messages, "stopped");
+    assert_eq!(stopped.len(), 2);
+    assert_eq!(stopped[0]["body"]["hitBreakpointIds"], json!([2]));
+    assert_eq!(stopped[1]["body"]["hitBreakpointIds"], json!([1]));
+
+    let traces = response(&messages, "stackTrace");
+    let frames = &traces[0]["body"]["stackFrames"];
+    assert_eq!(frames[0]["name"], "initial (test_counter)");
+    assert_eq!(frames[0]["line"], 30);
+    let frames = &traces[1]["body"]["stackFrames"];
+    assert_eq!(frames[0]["name"], "always_ff (Counter)");
+    assert_eq!(frames[0]["line"], 10);
+    assert_eq!(frames[1]["line"], 30);
+
+    let variables = response(&messages, "variables");
+    let top = variables[0]["body"]["variables"].as_array().unwrap();
+    assert!(top.contains(&json!({
+        "name": "cnt",
+        "value": "8'h03",
+        "type": "logic<8>",
+        "evaluateName": "cnt",
+        "variablesReference": 0,
+    })));
+    assert!(top.contains(&json!({ "name": "dut", "value": "instance", "variablesReference": 3 })));
+    let dut = variables[1]["body"]["variables"].as_array().unwrap();
+    assert!(
+        dut.iter()
+            .any(|x| x["name"] == "cnt" && x["value"] == "8'h03")
+    );
+
+    let evaluate = response(&messages, "evaluate");
+    assert_eq!(evaluate[0]["body"]["result"], "8'h03");
+    assert_eq!(evaluate[1]["body"]["result"], "1");
+    assert_eq!(evaluate[2]["success"], false);
+
+    let output = event(&messages, "output");
+    assert_eq!(output[0]["body"]["output"], "cnt = 5\n");
+    assert_eq!(event(&messages, "exited")[0]["body"]["exitCode"], 0);
+    assert_eq!(event(&messages, "terminated").len(), 1);
+}
+
+#[test]
+fn step_by_clock_edge() {
+    let (result, messages) = debug(&[
+        json!({ "command": "initialize", "arguments": { "adapterID": "veryl" } }),
+        json!({ "command": "launch", "arguments": { "test": "test_counter", "stopOnEntry": true } }),
+        json!({ "command": "configurationDone" }),
+        json!({ "command": "next", "arguments": { "threadId": 1 } }),
+        json!({ "command": "next", "arguments": { "threadId": 1 } }),
+        json!({ "command": "variables", "arguments": { "variablesReference": 1 } }),
+        json!({ "command": "disconnect" }),
+    ]);
+    assert_eq!(
+        result,
+        TestResult::Fail("terminated by debugger".to_string())
+    );
+
+    let stopped = event(&messages, "stopped");
+    assert_eq!(stopped.len(), 3);
+    assert_eq!(stopped[0]["body"]["reason"], "entry");
+    assert_eq!(stopped[1]["body"]["reason"], "step");
+    assert_eq!(stopped[2]["body"]["reason"], "step");
+
+    let variables = &response(&messages, "variables")[0]["body"]["variables"];
+    assert_eq!(
+        variables[1],
+        json!({ "name": "edges", "value": "2", "variablesReference": 0 })
+    );
+    assert!(event(&messages, "exited").is_empty());
+}
+
+const BRANCH_CODE: &str = r#"module Counter (
+    clk: input 'a clock,
+    clk2: input 'b clock,
+    rst: input 'a reset,
+    cnt: output 'a logic<8>,
+    cnt2: output 'b logic<8>,
+    flag: output 'a logic,
+) {
+    always_ff (clk, rst) {
+        if_reset {
+            cnt = 0;
+        } else if cnt == 8'd100 {
+            cnt = 0;
+        } else {
+            cnt += 1;
+        }
+    }
+
+    always_ff (clk2) {
+        cnt2 += 1;
+    }
+
+    always_comb {
+        if cnt == 8'd200 {
+            flag = 1;
+        } else {
+            flag = 0;
+        }
+    }
+}
+
+#[test(test_branch)]
+module test_branch {
+    inst clk: $tb::clock_gen;
+    inst clk2: $tb::clock_gen;
+    inst rst: $tb::reset_gen;
+
+    var cnt: logic<8>;
+    var cnt2: logic<8>;
+    var flag: logic;
+
+    inst dut: Counter (
+        clk,
+        clk2,
+        rst,
+        cnt,
+        cnt2,
+        flag,
+    );
+
+    initial {
+        rst.assert(clk);
+        clk.next(3);
+        clk2.next(2);
+        $finish();
+    }
+}
+"#;
+
+#[test]
+fn breakpoint_on_executed_statement() {
+    let (result, messages) = debug_code(
+        BRANCH_CODE,
+        "test_branch",
+        &[
+            json!({ "command": "initialize", "arguments": { "adapterID": "veryl" } }),
+            json!({ "command": "launch", "arguments": { "test": "test_branch" } }),
+            set_breakpoints(json!([
+                // untaken branches of always_ff and always_comb
+                { "line": 13 },
+                { "line": 25 },
+                // always_ff of the second clock
+                { "line": 20 },
+                // taken branch of always_ff
+                { "line": 15, "condition": "dut.cnt == 3" },
+            ])),
+            json!({ "command": "configurationDone" }),
+            json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+            json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+            json!({ "command": "variables", "arguments": { "variablesReference": 3 } }),
+            json!({ "command": "continue", "arguments": { "threadId": 1 } }),
+            json!({ "command": "disconnect" }),
+        ],
+    );
+    assert_eq!(result, TestResult::Pass);
+
+    let breakpoints = &response(&messages, "setBreakpoints")[0]["body"]["breakpoints"];
+    for i in 0..4 {
+        assert_eq!(breakpoints[i]["verified"], true);
+    }
+
+    let stopped = event(&messages, "stopped");
+    assert_eq!(stopped.len(), 3);
+    assert_eq!(stopped[0]["body"]["hitBreakpointIds"], json!([4]));
+    assert_eq!(stopped[1]["body"]["hitBreakpointIds"], json!([3]));
+    assert_eq!(stopped[2]["body"]["hitBreakpointIds"], json!([3]));
+
+    // clk2 edges don't execute always_ff of clk
+    let dut = response(&messages, "variables")[0]["body"]["variables"]
+        .as_array()
+        .unwrap();
+    assert!(
+        dut.iter()
+            .any(|x| x["name"] == "cnt" && x["value"] == "8'h03")
+    );
+    assert!(
+        dut.iter()
+            .any(|x| x["name"] == "cnt2" && x["value"] == "8'h02")
+    );
+}
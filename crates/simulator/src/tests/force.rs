@@ -0,0 +1,178 @@
+use super::*;
+
+const CODE: &str = r#"module Top (
+    clk: input clock,
+    rst: input reset,
+    d: input logic<8>,
+    q: output logic<8>,
+    y: output logic<8>,
+) {
+    inst u: Sub (
+        clk,
+        rst,
+        d,
+        q,
+    );
+
+    assign y = q + 1;
+}
+
+module Sub (
+    clk: input clock,
+    rst: input reset,
+    d: input logic<8>,
+    q: output logic<8>,
+) {
+    var w: logic<8>;
+    var r: logic<8>;
+
+    assign w = d + 1;
+    assign q = r;
+
+    always_ff {
+        if_reset {
+            r = 0;
+        } else {
+            r = w;
+        }
+    }
+}
+"#;
+
+fn get(sim: &mut Simulator, path: &str) -> u64 {
+    sim.get_var(path).unwrap().to_u64().unwrap()
+}
+
+#[test]
+fn force_release() {
+    for config in Config::all() {
+        let mut sim = Simulator::new(analyze(CODE, &config), None);
+        let clk = sim.get_clock("clk").unwrap();
+        let rst = sim.get_reset("rst").unwrap();
+
+        sim.step(&rst);
+        sim.set("d", Value::new(10, 8, false));
+
+        // Forced comb variable holds over its driver.
+        // The merged JIT function of `Sub` reads `w` without memory access,
+        // so it is evaluated by the interpreter while forced.
+        assert!(sim.force("u.w", Value::new(100, 8, false)));
+        assert_eq!(get(&mut sim, "u.w"), 100);
+        sim.step(&clk);
+        assert_eq!(get(&mut sim, "q"), 100);
+        sim.set("d", Value::new(20, 8, false));
+        sim.step(&clk);
+        assert_eq!(get(&mut sim, "q"), 100);
+
+        // Released comb variable returns to its driver immediately
+        assert!(sim.release("u.w"));
+        sim.set("d", Value::new(20, 8, false));
+        assert_eq!(get(&mut sim, "u.w"), 21);
+        sim.step(&clk);
+        assert_eq!(get(&mut sim, "q"), 21);
+
+        // Forced register holds over clock edges
+        assert!(sim.force("u.r", Value::new(7, 8, false)));
+        assert_eq!(get(&mut sim, "y"), 8);
+        sim.step(&clk);
+        sim.step(&rst);
+        assert_eq!(get(&mut sim, "q"), 7);
+
+        // Released register keeps the forced value until the next assignment
+        assert!(sim.release("u.r"));
+        assert_eq!(get(&mut sim, "q"), 7);
+        sim.step(&clk);
+        assert_eq!(get(&mut sim, "q"), 21);
+
+        // Output port
+        assert!(sim.force("y", Value::new(0xff, 8, false)));
+        sim.step(&clk);
+        assert_eq!(sim.get("y").unwrap().to_u64(), Some(0xff));
+        assert!(sim.release("y"));
+        assert_eq!(sim.get("y").unwrap().to_u64(), Some(22));
+
+        assert!(!sim.force("u.unknown", Value::new(0, 8, false)));
+        assert!(!sim.release("u.r"));
+    }
+}
+
+#[test]
+fn deposit() {
+    for config in Config::all() {
+        let mut sim = Simulator::new(analyze(CODE, &config), None);
+        let clk = sim.get_clock("clk").unwrap();
+        let rst = sim.get_reset("rst").unwrap();
+
+        sim.step(&rst);
+        sim.set("d", Value::new(10, 8, false));
+
+        // Deposited register keeps the value until the next assignment
+        assert!(sim.deposit("u.r", Value::new(55, 8, false)));
+        assert_eq!(get(&mut sim, "q"), 55);
+        assert_eq!(get(&mut sim, "y"), 56);
+        sim.step(&clk);
+        assert_eq!(get(&mut sim, "q"), 11);
+
+        // Deposited comb variable is overwritten by its driver
+        assert!(sim.deposit("u.w", Value::new(55, 8, false)));
+        assert_eq!(get(&mut sim, "u.w"), 11);
+
+        assert!(!sim.deposit("u.unknown", Value::new(0, 8, false)));
+    }
+}
+
+#[test]
+fn testbench_force() {
+    let code = format!(
+        r#"{CODE}
+#[test(test_force)]
+module test_force {{
+    inst clk: $tb::clock_gen;
+    inst rst: $tb::reset_gen;
+
+    var d: logic<8>;
+    var q: logic<8>;
+    var y: logic<8>;
+
+    inst dut: Top (
+        clk,
+        rst,
+        d,
+        q,
+        y,
+    );
+
+    initial {{
+        rst.assert(clk);
+        d = 1;
+        $force("dut.u.r", 8'd40);
+        clk.next(2);
+        $assert(q == 8'd40, "force failed");
+        $release("dut.u.r");
+        $assert(q == 8'd40, "released register is changed");
+        clk.next(1);
+        $assert(q == 8'd2, "release failed");
+        $deposit("dut.u.r", 8'd90);
+        $assert(y == 8'd91, "deposit failed");
+        clk.next(1);
+        $assert(q == 8'd2, "deposit is not overwritten");
+        $finish();
+    }}
+}}
+"#
+    );
+
+    for config in Config::all() {
+        let ir = analyze_top(&code, &config, "test_force").unwrap();
+        let result = run_native_testbench(ir, None, "test_force".to_string()).unwrap();
+        assert_eq!(result, TestResult::Pass, "{config:?}");
+    }
+
+    let code = code.replace("$release(\"dut.u.r\")", "$release(\"dut.u.x\")");
+    let ir = analyze_top(&code, &Config::default(), "test_force").unwrap();
+    let result = run_native_testbench(ir, None, "test_force".to_string()).unwrap();
+    assert_eq!(
+        result,
+        TestResult::Fail("$release: variable 'dut.u.x' is not forced".to_string())
+    );
+}
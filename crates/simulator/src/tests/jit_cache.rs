@@ -0,0 +1,140 @@
+use super::*;
+use crate::jit_cache;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const CODE: &str = r#"module Top (
+    clk: input clock,
+    rst: input reset,
+    x: input logic<8>,
+    cnt: output logic<8>,
+    acc: output logic<200>,
+) {
+    always_ff {
+        if_reset {
+            cnt = 0;
+            acc = 1;
+        } else {
+            cnt += 1;
+            acc = (acc << 3) + acc + x;
+        }
+    }
+}
+"#;
+
+type Break = fn(&[u8]) -> Vec<u8>;
+
+fn cache_dir() -> (TempDir, PathBuf) {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path().join("cache");
+    (temp, dir)
+}
+
+fn entries(dir: &Path) -> Vec<(PathBuf, Vec<u8>)> {
+    let mut ret: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|x| {
+            let path = x.unwrap().path();
+            let data = std::fs::read(&path).unwrap();
+            (path, data)
+        })
+        .collect();
+    ret.sort();
+    ret
+}
+
+fn run(config: &Config) -> Vec<Value> {
+    let mut sim = Simulator::new(analyze(CODE, config), None);
+    let clk = sim.get_clock("clk").unwrap();
+    let rst = sim.get_reset("rst").unwrap();
+
+    let mut ret = vec![];
+    sim.step(&rst);
+    for i in 0..50 {
+        sim.set("x", Value::new(i * 7, 8, false));
+        sim.step(&clk);
+        ret.push(sim.get("cnt").unwrap());
+        ret.push(sim.get("acc").unwrap());
+    }
+    ret
+}
+
+#[test]
+fn reuse() {
+    let (_temp, dir) = cache_dir();
+    let config = Config {
+        use_jit: true,
+        jit_cache_dir: Some(dir.clone()),
+        ..Default::default()
+    };
+    let expected = run(&Config {
+        use_jit: false,
+        ..Default::default()
+    });
+
+    assert_eq!(run(&config), expected);
+    let first = entries(&dir);
+    assert!(!first.is_empty());
+
+    // All functions are loaded from the cache
+    assert_eq!(run(&config), expected);
+    assert_eq!(entries(&dir), first);
+}
+
+#[test]
+fn broken_entry() {
+    let (_temp, dir) = cache_dir();
+    let config = Config {
+        use_jit: true,
+        jit_cache_dir: Some(dir.clone()),
+        ..Default::default()
+    };
+
+    let expected = run(&config);
+    let first = entries(&dir);
+
+    let broken: [Break; 3] = [
+        // header only
+        |_| b"VERYLJIT".to_vec(),
+        // truncated
+        |x| x[..x.len() - 1].to_vec(),
+        // corrupted
+        |x| {
+            let mut x = x.to_vec();
+            *x.last_mut().unwrap() ^= 1;
+            x
+        },
+    ];
+    for f in broken {
+        for (path, data) in &first {
+            std::fs::write(path, f(data)).unwrap();
+        }
+
+        // Broken entries are recompiled and overwritten
+        assert_eq!(run(&config), expected);
+        assert_eq!(entries(&dir), first);
+    }
+}
+
+#[test]
+fn prune() {
+    let (_temp, dir) = cache_dir();
+    let config = Config {
+        use_jit: true,
+        jit_cache_dir: Some(dir.clone()),
+        ..Default::default()
+    };
+
+    run(&config);
+    let all = entries(&dir);
+    let total: usize = all.iter().map(|(_, x)| x.len()).sum();
+
+    jit_cache::prune(&dir, total as u64).unwrap();
+    assert_eq!(entries(&dir).len(), all.len());
+
+    jit_cache::prune(&dir, total as u64 - 1).unwrap();
+    assert!(entries(&dir).len() < all.len());
+
+    jit_cache::prune(&dir, 0).unwrap();
+    assert!(entries(&dir).is_empty());
+}
@@ -0,0 +1,137 @@
+use super::*;
+use crate::testbench::{TestLimits, run_native_testbench_with_limits};
+use std::time::Duration;
+
+const CODE: &str = r#"
+module Counter (
+    clk: input  clock    ,
+    rst: input  reset    ,
+    cnt: output logic<32>,
+) {
+    always_ff {
+        if_reset {
+            cnt = 0;
+        } else {
+            cnt += 1;
+        }
+    }
+}
+
+#[test(test_hang)]
+module test_hang {
+    inst clk: $tb::clock_gen;
+    inst rst: $tb::reset_gen;
+
+    var cnt: logic<32>;
+
+    inst dut: Counter (
+        clk,
+        rst,
+        cnt,
+    );
+
+    initial {
+        rst.assert(clk);
+        clk.next(32'hffffffff);
+        $finish();
+    }
+}
+"#;
+
+fn run(limits: &TestLimits) -> Result<TestResult, SimulatorError> {
+    let ir = analyze_top(CODE, &Config::default(), "test_hang").unwrap();
+    run_native_testbench_with_limits(ir, None, "test_hang".to_string(), limits)
+}
+
+fn message(result: Result<TestResult, SimulatorError>) -> String {
+    match result {
+        Err(SimulatorError::TestFailed { message }) => message,
+        x => panic!("unexpected result: {x:?}"),
+    }
+}
+
+#[test]
+fn max_cycles() {
+    let limits = TestLimits {
+        max_cycles: Some(100),
+        ..Default::default()
+    };
+    // Cycles with reset asserted are not counted
+    assert_eq!(
+        message(run(&limits)),
+        "max cycles (100) exceeded at time 206"
+    );
+}
+
+#[test]
+fn max_time() {
+    let limits = TestLimits {
+        max_time: Some(555),
+        ..Default::default()
+    };
+    assert_eq!(message(run(&limits)), "max time (555) exceeded at time 556");
+}
+
+#[test]
+fn timeout() {
+    let limits = TestLimits {
+        timeout: Some(Duration::from_millis(10)),
+        ..Default::default()
+    };
+    assert!(message(run(&limits)).starts_with("timeout (0.01s) exceeded at time "));
+
+    // Zero timeout is disabled
+    let limits = TestLimits {
+        timeout: Some(Duration::ZERO),
+        max_cycles: Some(100),
+        ..Default::default()
+    };
+    assert_eq!(
+        message(run(&limits)),
+        "max cycles (100) exceeded at time 206"
+    );
+}
+
+#[test]
+fn within_limits() {
+    let code = CODE.replace("32'hffffffff", "10");
+    let ir = analyze_top(&code, &Config::default(), "test_hang").unwrap();
+    let limits = TestLimits {
+        timeout: Some(Duration::from_secs(60)),
+        max_cycles: Some(10),
+        max_time: Some(120),
+    };
+    let result = run_native_testbench_with_limits(ir, None, "test_hang".to_string(), &limits);
+    assert_eq!(result.unwrap(), TestResult::Pass);
+
+    // Cycles are counted for each clock
+    let code = CODE
+        .replace(
+            "inst rst: $tb::reset_gen;",
+            "inst rst: $tb::reset_gen;\n    inst clk2: $tb::clock_gen;",
+        )
+        .replace(
+            "clk.next(32'hffffffff);",
+            "clk.next(10);\n        clk2.next(10);",
+        );
+    let ir = analyze_top(&code, &Config::default(), "test_hang").unwrap();
+    let limits = TestLimits {
+        max_cycles: Some(10),
+        ..Default::default()
+    };
+    let result = run_native_testbench_with_limits(ir, None, "test_hang".to_string(), &limits);
+    assert_eq!(result.unwrap(), TestResult::Pass);
+
+    let limits = TestLimits {
+        max_cycles: Some(5),
+        ..Default::default()
+    }
+    .or(&TestLimits {
+        max_cycles: Some(100),
+        max_time: Some(1000),
+        ..Default::default()
+    });
+    assert_eq!(limits.max_cycles, Some(5));
+    assert_eq!(limits.max_time, Some(1000));
+    assert_eq!(limits.timeout, None);
+}
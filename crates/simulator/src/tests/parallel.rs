@@ -0,0 +1,194 @@
+use super::*;
+use crate::output_buffer;
+
+const CODE: &str = r#"
+module Lane #(
+    param STEP: u32 = 1,
+) (
+    clk   : input  clock    ,
+    rst   : input  reset    ,
+    i_data: input  logic<32>,
+    o_data: output logic<32>,
+) {
+    var acc: logic<32>;
+    var mix: logic<32>;
+
+    assign mix = (acc ^ i_data) + STEP;
+
+    always_ff {
+        if_reset {
+            acc = STEP;
+        } else {
+            acc = mix + (acc >> 1);
+        }
+    }
+
+    assign o_data = acc;
+}
+
+module Top (
+    clk   : input  clock    ,
+    rst   : input  reset    ,
+    i_data: input  logic<32>,
+    o_sum : output logic<32>,
+) {
+    var data: logic<32> [32];
+
+    for i in 0..32 :g {
+        inst u: Lane #(
+            STEP: i + 1,
+        ) (
+            clk             ,
+            rst             ,
+            i_data          ,
+            o_data: data[i] ,
+        );
+    }
+
+    assign o_sum = data[0] ^ data[5] ^ data[31];
+}
+"#;
+
+#[test]
+fn bit_identical_to_single_thread() {
+    for config in Config::all().into_iter().filter(|x| x.threads <= 1) {
+        dbg!(&config);
+        let parallel_config = Config {
+            threads: 4,
+            ..config.clone()
+        };
+
+        let mut serial = Simulator::new(analyze(CODE, &config), None);
+        let mut parallel = Simulator::new(analyze(CODE, &parallel_config), None);
+
+        assert!(serial.ir.comb_stages.is_empty());
+        assert!(serial.ir.event_chunks.is_empty());
+        assert!(parallel.ir.comb_stages.iter().any(|x| x.len() > 1));
+        assert!(!parallel.ir.event_chunks.is_empty());
+
+        for sim in [&mut serial, &mut parallel] {
+            let clk = sim.get_clock("clk").unwrap();
+            let rst = sim.get_reset("rst").unwrap();
+            sim.step(&rst);
+            for i in 0..50u64 {
+                sim.set("i_data", Value::new(i * 0x9e37_79b9, 32, false));
+                sim.step(&clk);
+            }
+            sim.ensure_comb_updated();
+        }
+
+        assert_eq!(serial.get("o_sum"), parallel.get("o_sum"));
+        assert_eq!(serial.ir.ff_values, parallel.ir.ff_values);
+        assert_eq!(serial.ir.comb_values, parallel.ir.comb_values);
+    }
+}
+
+#[test]
+fn display_order() {
+    let code = r#"
+    module Top (
+        clk: input clock,
+        rst: input reset,
+    ) {
+        var a: logic<8>;
+        var b: logic<8>;
+        var c: logic<8>;
+
+        always_ff {
+            if_reset {
+                a = 0;
+            } else {
+                a += 1;
+                $display("a = %d", a);
+            }
+        }
+
+        always_ff {
+            if_reset {
+                b = 0;
+            } else {
+                b += 2;
+            }
+        }
+
+        always_ff {
+            if_reset {
+                c = 0;
+            } else {
+                c += 3;
+                $display("c = %d", c);
+            }
+        }
+    }
+    "#;
+
+    for config in Config::all().into_iter().filter(|x| x.threads <= 1) {
+        dbg!(&config);
+        let parallel_config = Config {
+            threads: 4,
+            ..config.clone()
+        };
+
+        let mut outputs = vec![];
+        for config in [config, parallel_config] {
+            let mut sim = Simulator::new(analyze(code, &config), None);
+            let clk = sim.get_clock("clk").unwrap();
+            let rst = sim.get_reset("rst").unwrap();
+
+            // `b` is independent of the blocks calling `$display`
+            let chunks = sim.ir.event_chunks.get(&clk).map(|x| x.len());
+            assert_eq!(chunks.is_some(), config.threads > 1);
+
+            output_buffer::enable();
+            sim.step(&rst);
+            for _ in 0..4 {
+                sim.step(&clk);
+            }
+            outputs.push(output_buffer::take());
+        }
+
+        assert_eq!(outputs[0].lines().count(), 8);
+        assert_eq!(outputs[0], outputs[1]);
+    }
+}
+
+#[test]
+fn worker_panic() {
+    use crate::ir::WorkerPool;
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+    use veryl_analyzer::value::MaskCache;
+
+    let stages: Vec<_> = (0..3)
+        .map(|s| {
+            (0..4)
+                .map(|t| (s * 8 + t * 2)..(s * 8 + t * 2 + 2))
+                .collect()
+        })
+        .collect();
+
+    // a panic on a worker (index 10 is thread 1) and on the simulator thread
+    for target in [10, 16] {
+        let pool = WorkerPool::new(4);
+        let mut mask_cache = MaskCache::default();
+        let eval = |i: usize, _: &mut MaskCache| {
+            if i == target {
+                panic!("panic at {i}");
+            }
+        };
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            pool.run_with(&eval, &stages, &mut mask_cache)
+        }));
+        let payload = result.unwrap_err();
+        assert_eq!(
+            payload.downcast_ref::<String>().unwrap(),
+            &format!("panic at {target}")
+        );
+
+        // poisoned pool rejects later jobs instead of waiting on the barrier
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            pool.run_with(&|_, _| {}, &stages, &mut mask_cache)
+        }));
+        assert!(result.is_err());
+    }
+}
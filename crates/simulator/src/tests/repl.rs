@@ -0,0 +1,146 @@
+use super::*;
+use crate::repl::{Repl, ReplResponse};
+
+const CODE: &str = r#"module Top (
+    clk: input clock,
+    rst: input reset,
+    en: input logic,
+    cnt: output logic<8>,
+) {
+    inst u: Sub (
+        i: cnt,
+    );
+
+    always_ff {
+        if_reset {
+            cnt = 0;
+        } else if en {
+            cnt += 1;
+        }
+    }
+}
+
+module Sub (
+    i: input logic<8>,
+) {
+    var o: logic<8>;
+    assign o = i;
+}
+"#;
+
+fn repl() -> Repl {
+    let config = Config {
+        use_jit: false,
+        ..Default::default()
+    };
+    Repl::new(Simulator::new(analyze(CODE, &config), None))
+}
+
+#[track_caller]
+fn output(repl: &mut Repl, line: &str) -> String {
+    match repl.execute(line).unwrap() {
+        ReplResponse::Output(x) => x,
+        ReplResponse::Quit => panic!("unexpected quit"),
+    }
+}
+
+#[test]
+fn step_and_watch() {
+    let mut repl = repl();
+
+    output(&mut repl, "reset");
+    output(&mut repl, "set en 1");
+    assert_eq!(output(&mut repl, "watch u.o"), "u.o: 8'h00");
+    assert_eq!(
+        output(&mut repl, "step clk 2"),
+        "[8] u.o: 8'h00 -> 8'h01\n[10] u.o: 8'h01 -> 8'h02"
+    );
+    assert_eq!(output(&mut repl, "get cnt"), "8'h02");
+    assert_eq!(output(&mut repl, "time"), "10");
+
+    assert_eq!(output(&mut repl, "run until cnt == 5"), {
+        "[12] u.o: 8'h02 -> 8'h03\n[14] u.o: 8'h03 -> 8'h04\n[16] u.o: 8'h04 -> 8'h05\n\
+         condition met at time 16"
+    });
+    output(&mut repl, "unwatch u.o");
+    assert_eq!(
+        output(&mut repl, "run until cnt == 100 3"),
+        "condition not met within 3 clock edges"
+    );
+    assert_eq!(output(&mut repl, "get cnt"), "8'h08");
+
+    output(&mut repl, "set en 0");
+    output(&mut repl, "step 3");
+    assert_eq!(output(&mut repl, "get u.o"), "8'h08");
+
+    assert!(repl.execute("set u.i 1").is_err());
+    assert!(repl.execute("step unknown").is_err());
+    assert!(repl.execute("get u.unknown").is_err());
+    assert_eq!(repl.execute("quit"), Ok(ReplResponse::Quit));
+}
+
+#[test]
+fn list_and_complete() {
+    let mut repl = repl();
+
+    assert_eq!(
+        output(&mut repl, "list u"),
+        "logic<8> i: 8'h00\nlogic<8> o: 8'h00"
+    );
+    assert!(output(&mut repl, "list").ends_with("inst u"));
+    assert!(repl.execute("list v").is_err());
+
+    assert_eq!(repl.complete("st"), vec!["step"]);
+    assert_eq!(repl.complete("dump o"), vec!["on", "off"]);
+    assert_eq!(repl.complete("get u"), vec!["u", "u.i", "u.o"]);
+    assert_eq!(repl.complete("watch u.o"), vec!["u.o"]);
+    assert!(repl.complete("time ").is_empty());
+}
+
+#[test]
+fn dump_on_demand() {
+    let mut repl = repl();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("repl.vcd");
+
+    output(&mut repl, "reset");
+    output(&mut repl, "set en 1");
+    output(&mut repl, &format!("dump on {}", path.display()));
+    output(&mut repl, "step 2");
+    assert_eq!(
+        output(&mut repl, "dump off"),
+        format!("waveform is written to {}", path.display())
+    );
+    output(&mut repl, "step 2");
+
+    let vcd = std::fs::read_to_string(&path).unwrap();
+    assert!(vcd.contains("$scope module u $end"));
+    // dumped from the current time, and stopped by `dump off`
+    assert!(vcd.contains("#6\n"));
+    assert!(vcd.contains("#9\n"));
+    assert!(!vcd.contains("#10\n"));
+    assert!(repl.execute("dump off").is_err());
+}
+
+#[test]
+fn wide_ports() {
+    let code = r#"module Top (
+    a: input logic<100>,
+    b: output logic<100>,
+) {
+    assign b = a;
+}
+"#;
+    let config = Config {
+        use_jit: false,
+        ..Default::default()
+    };
+    let mut repl = Repl::new(Simulator::new(analyze(code, &config), None));
+
+    output(&mut repl, "set a 100'h8_0000_0000_0000_0000_1234_5678");
+    assert_eq!(output(&mut repl, "get b"), "100'h8000000000000000012345678");
+    // upper bits beyond the port width are truncated
+    output(&mut repl, "set a 0x1f_0000_0000_0000_0000_0000_0001");
+    assert_eq!(output(&mut repl, "get b"), "100'hf000000000000000000000001");
+    assert!(repl.execute("set a 100'hxyz").is_err());
+}
@@ -29,6 +29,7 @@ fn testbench_counter_clock_next() {
                 duration: 3,
                 high_time: 1,
                 low_time: 1,
+                token: TokenRange::default(),
             },
             TestbenchStatement::For {
                 count: 10,
@@ -37,6 +38,7 @@ fn testbench_counter_clock_next() {
                     count: None,
                     high_time: 1,
                     low_time: 1,
+                    token: TokenRange::default(),
                 }],
                 loop_var: None,
             },
@@ -78,6 +80,7 @@ fn testbench_reset_clears_counter() {
                 duration: 5,
                 high_time: 1,
                 low_time: 1,
+                token: TokenRange::default(),
             },
             TestbenchStatement::Finish,
         ];
@@ -117,6 +120,7 @@ fn testbench_for_loop() {
                 duration: 1,
                 high_time: 1,
                 low_time: 1,
+                token: TokenRange::default(),
             },
             // Step 5 times using For loop (each iteration steps 1 clock)
             TestbenchStatement::For {
@@ -126,6 +130,7 @@ fn testbench_for_loop() {
                     count: None,
                     high_time: 1,
                     low_time: 1,
+                    token: TokenRange::default(),
                 }],
                 loop_var: None,
             },
@@ -167,6 +172,7 @@ fn testbench_finish_stops_execution() {
                 duration: 1,
                 high_time: 1,
                 low_time: 1,
+                token: TokenRange::default(),
             },
             TestbenchStatement::For {
                 count: 5,
@@ -175,6 +181,7 @@ fn testbench_finish_stops_execution() {
                     count: None,
                     high_time: 1,
                     low_time: 1,
+                    token: TokenRange::default(),
                 }],
                 loop_var: None,
             },
@@ -187,6 +194,7 @@ fn testbench_finish_stops_execution() {
                     count: None,
                     high_time: 1,
                     low_time: 1,
+                    token: TokenRange::default(),
                 }],
                 loop_var: None,
             },
@@ -746,6 +754,7 @@ fn testbench_vcd_clock_reset_waveform() {
                 duration: 2,
                 high_time: 1,
                 low_time: 1,
+                token: TokenRange::default(),
             },
             TestbenchStatement::For {
                 count: 3,
@@ -754,6 +763,7 @@ fn testbench_vcd_clock_reset_waveform() {
                     count: None,
                     high_time: 1,
                     low_time: 1,
+                    token: TokenRange::default(),
                 }],
                 loop_var: None,
             },
@@ -890,6 +900,7 @@ fn testbench_fst_clock_reset_waveform() {
                 duration: 2,
                 high_time: 1,
                 low_time: 1,
+                token: TokenRange::default(),
             },
             TestbenchStatement::For {
                 count: 3,
@@ -898,6 +909,7 @@ fn testbench_fst_clock_reset_waveform() {
                     count: None,
                     high_time: 1,
                     low_time: 1,
+                    token: TokenRange::default(),
                 }],
                 loop_var: None,
             },
@@ -1044,6 +1056,7 @@ fn testbench_array_input_port() {
                 duration: 3,
                 high_time: 1,
                 low_time: 1,
+                token: TokenRange::default(),
             },
             TestbenchStatement::For {
                 count: 5,
@@ -1052,6 +1065,7 @@ fn testbench_array_input_port() {
                     count: None,
                     high_time: 1,
                     low_time: 1,
+                    token: TokenRange::default(),
                 }],
                 loop_var: None,
             },
@@ -0,0 +1,244 @@
+use super::*;
+use crate::wavedrom::{
+    SignalKind, WaveScenario, classify_signals, parse_wavedrom, run_wavedrom_test,
+};
+use std::collections::HashMap;
+
+fn run(code: &str, json: &str, ports: &[(&str, &str)], config: &Config) -> TestResult {
+    let ir = analyze(code, config);
+    let mut sim = Simulator::new(ir, None);
+
+    let ports: Vec<_> = ports
+        .iter()
+        .map(|(x, y)| (x.to_string(), y.to_string()))
+        .collect();
+    let mut scenario: WaveScenario = parse_wavedrom(json).unwrap();
+    classify_signals(&mut scenario, &ports);
+
+    let clock_events: HashMap<_, _> = scenario
+        .signals
+        .iter()
+        .filter(|s| s.kind == SignalKind::Clock)
+        .map(|s| (s.name.clone(), sim.get_clock(&s.name).unwrap()))
+        .collect();
+    let default_clock = clock_events.values().next().cloned().unwrap();
+    let reset_event = sim.get_reset("rst");
+
+    let mut port_widths = HashMap::new();
+    for (name, _) in &ports {
+        let width = sim.get(name).unwrap().width();
+        port_widths.insert(name.clone(), width);
+    }
+
+    run_wavedrom_test(
+        &mut sim,
+        &scenario,
+        &clock_events,
+        &default_clock,
+        &reset_event,
+        3,
+        &port_widths,
+    )
+}
+
+#[test]
+fn data_lane() {
+    let code = r#"
+    module Top (
+        clk : input  clock   ,
+        rst : input  reset   ,
+        din : input  logic<8>,
+        dout: output logic<8>,
+    ) {
+        always_ff {
+            if_reset {
+                dout = 0;
+            } else {
+                dout = din;
+            }
+        }
+    }
+    "#;
+
+    let ports = [
+        ("clk", "input"),
+        ("rst", "input"),
+        ("din", "input"),
+        ("dout", "output"),
+    ];
+
+    let json = r#"{signal: [
+        {name: 'clk',  wave: 'p.....'},
+        {name: 'din',  wave: '==.=.=', data: ['0xa5', "8'h3c", '-1', '0b1']},
+        {name: 'dout', wave: '0==.==', data: ['0xa5', '0x3x', '0xff', 'x']}
+    ]}"#;
+
+    for config in Config::all() {
+        assert_eq!(run(code, json, &ports, &config), TestResult::Pass);
+    }
+
+    let json = r#"{signal: [
+        {name: 'clk',  wave: 'p.....'},
+        {name: 'din',  wave: '==.=.=', data: ['0xa5', "8'h3c", '-1', '0b1']},
+        {name: 'dout', wave: '0==.==', data: ['0xa5', '0x3c', '0xfe', '0xff']}
+    ]}"#;
+
+    for config in Config::all() {
+        let TestResult::Fail(message) = run(code, json, &ports, &config) else {
+            panic!("test should fail");
+        };
+        assert_eq!(
+            message,
+            "cycle 4: signal 'dout' expected 0xfe but got 0xff
+      dout |    0    1    2    3    4    5
+  expected |    0 0xa5 0x3c 0x3c 0xfe 0xff
+    actual |    0 0xa5 0x3c 0x3c 0xff 0xff
+           |                        ^"
+        );
+    }
+}
+
+#[test]
+fn multi_clock() {
+    let code = r#"
+    module Top (
+        clk_a: input  'a clock   ,
+        clk_b: input  'b clock   ,
+        rst  : input  'a reset   ,
+        cnt_a: output 'a logic<4>,
+        cnt_b: output 'b logic<4>,
+    ) {
+        always_ff (clk_a, rst) {
+            if_reset {
+                cnt_a = 0;
+            } else {
+                cnt_a += 1;
+            }
+        }
+        unsafe (cdc) {
+            always_ff (clk_b, rst) {
+                if_reset {
+                    cnt_b = 0;
+                } else {
+                    cnt_b += 1;
+                }
+            }
+        }
+    }
+    "#;
+
+    let ports = [
+        ("clk_a", "input"),
+        ("clk_b", "input"),
+        ("rst", "input"),
+        ("cnt_a", "output"),
+        ("cnt_b", "output"),
+    ];
+
+    let json = r#"{signal: [
+        {name: 'clk_a', wave: 'p.......'},
+        {name: 'clk_b', wave: 'p...', period: 2},
+        {name: 'rst',   wave: '10......'},
+        {name: 'cnt_a', wave: '========', data: [0, 0, 1, 2, 3, 4, 5, 6]},
+        {name: 'cnt_b', wave: 'x===', data: [1, 2, 3], period: 2}
+    ]}"#;
+
+    for config in Config::all() {
+        assert_eq!(run(code, json, &ports, &config), TestResult::Pass);
+    }
+
+    // clk_b is shifted by a half of clk_a period
+    let json = r#"{signal: [
+        {name: 'clk_a', wave: 'p.......'},
+        {name: 'clk_b', wave: 'p....', period: 2, phase: -0.5},
+        {name: 'rst',   wave: '10......'},
+        {name: 'cnt_b', wave: 'x====', data: [1, 2, 3, 4], period: 2, phase: -0.5}
+    ]}"#;
+
+    for config in Config::all() {
+        assert_eq!(run(code, json, &ports, &config), TestResult::Pass);
+    }
+}
+
+#[test]
+fn dump_wavedrom() {
+    use crate::wave_dumper::{SharedVec, WaveDumper};
+    use crate::wavedrom::{CycleRange, WavedromSelection};
+
+    let code = r#"
+    module Top (
+        clk: input  clock   ,
+        rst: input  reset   ,
+        en : input  logic   ,
+        cnt: output logic<8>,
+    ) {
+        always_ff {
+            if_reset {
+                cnt = 0;
+            } else if en {
+                cnt += 1;
+            }
+        }
+    }
+    "#;
+
+    for config in Config::all() {
+        let ir = analyze(code, &config);
+
+        let dump_buf = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let selection = WavedromSelection {
+            signals: vec!["clk".to_string(), "en".to_string(), "cnt".to_string()],
+            cycles: CycleRange {
+                start: 1,
+                end: Some(6),
+            },
+        };
+        let dumper = WaveDumper::new_wavedrom(Box::new(SharedVec(dump_buf.clone())), selection);
+        let mut sim = Simulator::new(ir, Some(dumper));
+
+        let clk = sim.get_clock("clk").unwrap();
+        let rst = sim.get_reset("rst").unwrap();
+        let next = |count| TestbenchStatement::For {
+            count,
+            body: vec![TestbenchStatement::ClockNext {
+                clock: clk.clone(),
+                count: None,
+                high_time: 1,
+                low_time: 1,
+                token: TokenRange::default(),
+            }],
+            loop_var: None,
+        };
+
+        sim.set("en", Value::new(0, 1, false));
+        let stmts = vec![
+            TestbenchStatement::ResetAssert {
+                reset: rst.clone(),
+                clock: clk.clone(),
+                duration: 2,
+                high_time: 1,
+                low_time: 1,
+                token: TokenRange::default(),
+            },
+            next(2),
+        ];
+        assert_eq!(run_testbench(&mut sim, &stmts), TestResult::Pass);
+        sim.set("en", Value::new(1, 1, false));
+        assert_eq!(run_testbench(&mut sim, &[next(3)]), TestResult::Pass);
+        drop(sim);
+
+        let dump = String::from_utf8(dump_buf.lock().unwrap().clone()).unwrap();
+        let expected = r#"{"signal": [
+  {"name": "clk", "wave": "p...."},
+  {"name": "en", "wave": "0..1."},
+  {"name": "cnt", "wave": "=..==", "data": ["0", "1", "2"]}
+],
+"head": {"tick": 1}}
+"#;
+        assert_eq!(dump, expected);
+
+        // The output can be parsed as a WaveDrom scenario
+        let scenario = parse_wavedrom(&dump).unwrap();
+        assert_eq!(scenario.signals.len(), 3);
+    }
+}
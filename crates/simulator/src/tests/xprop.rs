@@ -0,0 +1,225 @@
+use super::*;
+use crate::ir::{RegisterInit, XProp};
+use crate::output_buffer;
+
+fn configs_4state() -> impl Iterator<Item = Config> {
+    Config::all().into_iter().filter(|x| x.use_4state)
+}
+
+#[test]
+fn xprop_mode() {
+    let code = r#"module Top (
+    c: input logic,
+    a: input logic<4>,
+    b: input logic<4>,
+    y: output logic<4>,
+) {
+    always_comb {
+        if c {
+            y = a;
+        } else {
+            y = b;
+        }
+    }
+}
+"#;
+
+    for xprop in [XProp::Standard, XProp::Pessimistic, XProp::Optimistic] {
+        for config in configs_4state() {
+            let config = Config { xprop, ..config };
+            let mut sim = Simulator::new(analyze(code, &config), None);
+            sim.set("c", Value::new_x(1, false));
+            sim.set("a", Value::new(0b1100, 4, false));
+            sim.set("b", Value::new(0b1010, 4, false));
+
+            let y = sim.get("y").unwrap();
+            let expected = match xprop {
+                XProp::Standard => "4'b1010",
+                XProp::Pessimistic => "4'b1xx0",
+                XProp::Optimistic => "4'b1100",
+            };
+            assert_eq!(format!("{y:b}"), expected, "{config:?}");
+
+            // Known condition is not affected
+            sim.set("c", Value::new(1, 1, false));
+            assert_eq!(sim.get("y").unwrap().to_u64(), Some(0b1100));
+        }
+    }
+}
+
+#[test]
+fn xprop_side_effect() {
+    let code = r#"module Top (
+    clk: input clock,
+    c: input logic,
+    a: input logic<4>,
+    y: output logic<4>,
+) {
+    always_ff {
+        if c {
+            y = a;
+            $display("true");
+        } else {
+            y = 0;
+            $display("false");
+        }
+    }
+}
+"#;
+
+    for config in configs_4state() {
+        let config = Config {
+            xprop: XProp::Pessimistic,
+            ..config
+        };
+        let mut sim = Simulator::new(analyze(code, &config), None);
+        let clk = sim.get_clock("clk").unwrap();
+        sim.set("c", Value::new_x(1, false));
+        sim.set("a", Value::new(0b1100, 4, false));
+
+        // Sides with `$display` are not merged, and evaluated as standard mode
+        output_buffer::enable();
+        sim.step(&clk);
+        assert_eq!(output_buffer::take(), "false\n", "{config:?}");
+        assert_eq!(sim.get("y").unwrap().to_u64(), Some(0), "{config:?}");
+        assert!(sim.ir.merge_fallback(), "{config:?}");
+
+        // The fallback is reported for each simulation
+        let mut sim = Simulator::new(analyze(code, &config), None);
+        let clk = sim.get_clock("clk").unwrap();
+        sim.set("c", Value::new(1, 1, false));
+        sim.step(&clk);
+        assert!(!sim.ir.merge_fallback(), "{config:?}");
+        output_buffer::take();
+    }
+}
+
+#[test]
+fn xprop_case() {
+    let code = r#"module Top (
+    s: input logic<2>,
+    a: input logic<4>,
+    b: input logic<4>,
+    c: input logic<4>,
+    y: output logic<4>,
+) {
+    always_comb {
+        case s {
+            0: y = a;
+            1: y = b;
+            default: y = c;
+        }
+    }
+}
+"#;
+
+    for xprop in [XProp::Standard, XProp::Pessimistic, XProp::Optimistic] {
+        for config in configs_4state() {
+            let config = Config { xprop, ..config };
+            let mut sim = Simulator::new(analyze(code, &config), None);
+            sim.set("s", Value::new_x(2, false));
+            sim.set("a", Value::new(0b1100, 4, false));
+            sim.set("b", Value::new(0b1010, 4, false));
+            sim.set("c", Value::new(0b1001, 4, false));
+
+            let y = sim.get("y").unwrap();
+            let expected = match xprop {
+                XProp::Standard => "4'b1001",
+                XProp::Pessimistic => "4'b1xxx",
+                XProp::Optimistic => "4'b1100",
+            };
+            assert_eq!(format!("{y:b}"), expected, "{config:?}");
+
+            // Items which can't match the partially unknown selector are excluded
+            sim.set("s", Value::from_u128(0b10, 0b01, 2, false));
+            assert_eq!(sim.get("y").unwrap().to_u64(), Some(0b1001), "{config:?}");
+
+            sim.set("s", Value::new(1, 2, false));
+            assert_eq!(sim.get("y").unwrap().to_u64(), Some(0b1010), "{config:?}");
+            assert!(!sim.ir.merge_fallback());
+        }
+    }
+}
+
+const REG_CODE: &str = r#"module Top (
+    clk: input clock,
+    rst: input reset,
+    d: input logic<8>,
+    q: output logic<8>,
+    r: output logic<8>,
+) {
+    inst u: Sub (
+        clk,
+        d,
+    );
+
+    always_ff {
+        if_reset {
+            q = 0;
+        } else {
+            q = d;
+        }
+    }
+
+    always_ff {
+        r = d;
+    }
+}
+
+module Sub (
+    clk: input clock,
+    d: input logic<8>,
+) {
+    var w: logic<8>;
+
+    always_ff {
+        w = d;
+    }
+}
+"#;
+
+#[test]
+fn register_init() {
+    for config in configs_4state() {
+        let init = |register_init| {
+            let config = Config {
+                register_init,
+                ..config.clone()
+            };
+            let mut sim = Simulator::new(analyze(REG_CODE, &config), None);
+            format!("{:b}", sim.get("r").unwrap())
+        };
+
+        assert_eq!(init(RegisterInit::X), "8'bxxxxxxxx");
+        assert_eq!(init(RegisterInit::Zero), "8'b00000000");
+        assert_eq!(init(RegisterInit::One), "8'b11111111");
+        assert_eq!(init(RegisterInit::Random(1)), init(RegisterInit::Random(1)));
+        assert_ne!(init(RegisterInit::Random(1)), init(RegisterInit::Random(2)));
+    }
+}
+
+#[test]
+fn x_registers_after_reset() {
+    for config in configs_4state() {
+        let config = Config {
+            register_init: RegisterInit::X,
+            ..config
+        };
+        let mut sim = Simulator::new(analyze(REG_CODE, &config), None);
+        let rst = sim.get_reset("rst").unwrap();
+        let clk = sim.get_clock("clk").unwrap();
+
+        sim.step(&rst);
+        assert_eq!(sim.x_registers(), vec!["r".to_string(), "u.w".to_string()]);
+
+        sim.set("d", Value::new(1, 8, false));
+        sim.step(&clk);
+        assert!(sim.x_registers().is_empty());
+    }
+
+    // No X in 2-state simulation
+    let mut sim = Simulator::new(analyze(REG_CODE, &Config::default()), None);
+    let rst = sim.get_reset("rst").unwrap();
+    sim.step(&rst);
+    assert!(sim.x_registers().is_empty());
+}
@@ -1,4 +1,5 @@
 use crate::ir::{ModuleVariables, Value, read_native_value};
+use crate::wavedrom::{WavedromDumper, WavedromSelection};
 use std::io::Write;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
@@ -21,6 +22,7 @@ impl Write for SharedVec {
 pub enum VarHandle {
     Vcd(vcd::IdCode),
     Fst(fst_writer::FstSignalId),
+    Wavedrom(usize),
 }
 
 pub struct WaveDumper {
@@ -31,6 +33,7 @@ pub struct WaveDumper {
 enum WaveDumperKind {
     Vcd(VcdDumper),
     Fst(Box<FstDumper>),
+    Wavedrom(Box<WavedromDumper>),
 }
 
 struct VcdDumper {
@@ -74,6 +77,14 @@ impl WaveDumper {
         }
     }
 
+    /// WaveDrom JSON is written to `io` when the dumper is dropped.
+    pub fn new_wavedrom(io: Box<dyn Write + Send>, selection: WavedromSelection) -> Self {
+        WaveDumper {
+            kind: WaveDumperKind::Wavedrom(Box::new(WavedromDumper::new(io, selection))),
+            path: None,
+        }
+    }
+
     pub fn with_path(mut self, path: PathBuf) -> Self {
         self.path = Some(path);
         self
@@ -95,6 +106,9 @@ impl WaveDumper {
             WaveDumperKind::Fst(_) => {
                 // Already set in FstInfo during construction
             }
+            WaveDumperKind::Wavedrom(_) => {
+                // WaveDrom has no timescale
+            }
         }
     }
 
@@ -109,10 +123,15 @@ impl WaveDumper {
                 }
                 _ => panic!("FST: add_module called after header finished"),
             },
+            WaveDumperKind::Wavedrom(w) => w.add_module(name),
         }
     }
 
     pub fn add_wire(&mut self, width: u32, name: &str) -> VarHandle {
+        self.add_var(width, name, false)
+    }
+
+    fn add_var(&mut self, width: u32, name: &str, is_clock: bool) -> VarHandle {
         match &mut self.kind {
             WaveDumperKind::Vcd(v) => {
                 let code = v.writer.add_wire(width, name).unwrap();
@@ -133,6 +152,7 @@ impl WaveDumper {
                 }
                 _ => panic!("FST: add_wire called after header finished"),
             },
+            WaveDumperKind::Wavedrom(w) => VarHandle::Wavedrom(w.add_wire(width, name, is_clock)),
         }
     }
 
@@ -147,6 +167,7 @@ impl WaveDumper {
                 }
                 _ => panic!("FST: upscope called after header finished"),
             },
+            WaveDumperKind::Wavedrom(w) => w.upscope(),
         }
     }
 
@@ -165,6 +186,7 @@ impl WaveDumper {
                     _ => panic!("FST: finish_header called in wrong state"),
                 }
             }
+            WaveDumperKind::Wavedrom(w) => w.finish_header(),
         }
     }
 
@@ -173,8 +195,8 @@ impl WaveDumper {
             WaveDumperKind::Vcd(v) => {
                 v.writer.begin(SimulationCommand::Dumpvars).unwrap();
             }
-            WaveDumperKind::Fst(_) => {
-                // no-op for FST
+            WaveDumperKind::Fst(_) | WaveDumperKind::Wavedrom(_) => {
+                // no-op for FST and WaveDrom
             }
         }
     }
@@ -184,8 +206,8 @@ impl WaveDumper {
             WaveDumperKind::Vcd(v) => {
                 v.writer.end().unwrap();
             }
-            WaveDumperKind::Fst(_) => {
-                // no-op for FST
+            WaveDumperKind::Fst(_) | WaveDumperKind::Wavedrom(_) => {
+                // no-op for FST and WaveDrom
             }
         }
     }
@@ -201,6 +223,7 @@ impl WaveDumper {
                 }
                 _ => panic!("FST: timestamp called before header finished"),
             },
+            WaveDumperKind::Wavedrom(w) => w.timestamp(),
         }
     }
 
@@ -224,16 +247,26 @@ impl WaveDumper {
                     _ => panic!("FST: change_vector called before header finished"),
                 }
             }
+            WaveDumperKind::Wavedrom(w) => {
+                let VarHandle::Wavedrom(index) = handle else {
+                    panic!("WaveDrom dumper received non-WaveDrom handle");
+                };
+                w.change(index, value);
+            }
         }
     }
 
     pub fn setup_module(&mut self, module_vars: &ModuleVariables, dump_vars: &mut Vec<DumpVar>) {
         self.add_module(&sanitize_wave_name(&module_vars.name.to_string()));
 
-        for x in module_vars.variables.values() {
+        // sort by declaration order to make the output deterministic
+        let mut variables: Vec<_> = module_vars.variables.iter().collect();
+        variables.sort_by_key(|(id, _)| **id);
+
+        for (_, x) in variables {
             let name = sanitize_wave_name(&x.path.to_string());
             let width = x.width as u32;
-            let handle = self.add_wire(width, &name);
+            let handle = self.add_var(width, &name, x.r#type.is_clock());
             dump_vars.push(DumpVar {
                 handle,
                 ptr: x.current_values[0],
@@ -1,8 +1,14 @@
 use crate::ir::{Event, Value, VarId};
 use crate::simulator::Simulator;
 use crate::testbench::TestResult;
-use veryl_analyzer::wavedrom::preprocess_json5;
-pub use veryl_analyzer::wavedrom::strip_port_prefix;
+use std::collections::HashMap;
+use std::fmt::Write;
+pub use veryl_analyzer::wavedrom::{CycleRange, parse_cycle_range, strip_port_prefix};
+use veryl_analyzer::wavedrom::{WaveData, is_data_wave_char, parse_wave_data, preprocess_json5};
+
+/// Maximum number of ticks per time slot.
+/// `period` and `phase` should be multiples of `1 / MAX_RESOLUTION`.
+const MAX_RESOLUTION: u64 = 64;
 
 /// Parsed WaveDrom scenario containing signals and their waveforms.
 #[derive(Debug)]
@@ -11,12 +17,16 @@ pub struct WaveScenario {
 }
 
 /// A single signal in the WaveDrom scenario.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct WaveSignal {
     pub name: String,
     pub kind: SignalKind,
     pub wave: Vec<WaveChar>,
     pub data: Vec<String>,
+    /// Time slots per wave character
+    pub period: f64,
+    /// Shift of the wave to the left in time slots
+    pub phase: f64,
 }
 
 /// Classification of a signal after port mapping.
@@ -68,7 +78,11 @@ fn extract_signals(value: &serde_json::Value) -> Result<Vec<WaveSignal>, String>
             let data: Vec<String> = if let Some(d) = obj.get("data") {
                 if let Some(arr) = d.as_array() {
                     arr.iter()
-                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                        .filter_map(|v| match v {
+                            serde_json::Value::String(s) => Some(s.to_string()),
+                            serde_json::Value::Number(n) => Some(n.to_string()),
+                            _ => None,
+                        })
                         .collect()
                 } else if let Some(s) = d.as_str() {
                     s.split_whitespace().map(|s| s.to_string()).collect()
@@ -79,12 +93,20 @@ fn extract_signals(value: &serde_json::Value) -> Result<Vec<WaveSignal>, String>
                 Vec::new()
             };
 
+            let period = obj.get("period").and_then(|v| v.as_f64()).unwrap_or(1.0);
+            let phase = obj.get("phase").and_then(|v| v.as_f64()).unwrap_or(0.0);
+            if period <= 0.0 {
+                return Err(format!("signal '{name}' has non-positive period {period}"));
+            }
+
             let wave = expand_wave(wave_str)?;
             signals.push(WaveSignal {
                 name: name.to_string(),
                 kind: SignalKind::Unknown,
                 wave,
                 data,
+                period,
+                phase,
             });
         }
     }
@@ -99,72 +121,50 @@ fn expand_wave(wave_str: &str) -> Result<Vec<WaveChar>, String> {
     let mut data_idx = 0;
 
     for c in wave_str.chars() {
-        match c {
-            '|' => continue,
+        let wc = match c {
+            '|' | ' ' => continue,
             '.' => {
                 if let Some(ref prev) = last_char {
                     result.push(prev.clone());
                 }
+                continue;
             }
-            'p' | 'P' => {
-                let wc = WaveChar::PosedgeClock;
-                result.push(wc.clone());
-                last_char = Some(wc);
-            }
-            'n' | 'N' => {
-                let wc = WaveChar::NegedgeClock;
-                result.push(wc.clone());
-                last_char = Some(wc);
-            }
-            '0' => {
-                let wc = WaveChar::Low;
-                result.push(wc.clone());
-                last_char = Some(wc);
-            }
-            '1' => {
-                let wc = WaveChar::High;
-                result.push(wc.clone());
-                last_char = Some(wc);
-            }
-            'x' => {
-                let wc = WaveChar::DontCare;
-                result.push(wc.clone());
-                last_char = Some(wc);
-            }
-            'z' => {
-                let wc = WaveChar::HighZ;
-                result.push(wc.clone());
-                last_char = Some(wc);
-            }
-            '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' | '=' => {
-                let wc = WaveChar::Data(data_idx);
-                result.push(wc.clone());
-                last_char = Some(wc);
+            'p' | 'P' => WaveChar::PosedgeClock,
+            'n' | 'N' => WaveChar::NegedgeClock,
+            '0' | 'l' | 'L' => WaveChar::Low,
+            '1' | 'h' | 'H' => WaveChar::High,
+            'x' => WaveChar::DontCare,
+            'z' => WaveChar::HighZ,
+            c if is_data_wave_char(c) => {
                 data_idx += 1;
+                WaveChar::Data(data_idx - 1)
             }
-            ' ' => continue,
             _ => {
                 return Err(format!("unknown wave character: '{c}'"));
             }
-        }
+        };
+        result.push(wc.clone());
+        last_char = Some(wc);
     }
 
     Ok(result)
 }
 
-/// Parse a data value string (hex/bin/dec) to u64.
-fn parse_data_value(s: &str) -> Result<u64, String> {
-    let s = s.trim();
-    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
-        u64::from_str_radix(hex, 16).map_err(|e| format!("invalid hex value '{s}': {e}"))
-    } else if let Some(bin) = s.strip_prefix("0b").or_else(|| s.strip_prefix("0B")) {
-        u64::from_str_radix(bin, 2).map_err(|e| format!("invalid binary value '{s}': {e}"))
+/// Parse a data value string (hex/bin/dec, Veryl literal or don't-care).
+fn parse_data_value(s: &str) -> Result<WaveData, String> {
+    parse_wave_data(s).ok_or_else(|| format!("invalid data value '{}'", s.trim()))
+}
+
+fn width_mask(width: usize) -> u128 {
+    if width >= 128 {
+        u128::MAX
     } else {
-        s.parse::<u64>()
-            .map_err(|e| format!("invalid decimal value '{s}': {e}"))
+        (1u128 << width) - 1
     }
 }
 
+/// Value of the wave character.
+/// Bits which are don't-care are represented as X (`mask_xz` is set).
 fn wave_value(wc: &WaveChar, data: &[String], width: usize) -> Result<Option<Value>, String> {
     match wc {
         WaveChar::Low => Ok(Some(Value::new(0, width, false))),
@@ -174,7 +174,12 @@ fn wave_value(wc: &WaveChar, data: &[String], width: usize) -> Result<Option<Val
         WaveChar::Data(idx) => {
             if *idx < data.len() {
                 let val = parse_data_value(&data[*idx])?;
-                Ok(Some(Value::new(val, width, false)))
+                let mask = val.mask & width_mask(width);
+                if mask == width_mask(width) {
+                    return Ok(None);
+                }
+                let payload = val.value & !mask & width_mask(width);
+                Ok(Some(Value::from_u128(payload, mask, width, false)))
             } else {
                 Err(format!(
                     "data index {idx} out of range (only {} data values)",
@@ -186,14 +191,48 @@ fn wave_value(wc: &WaveChar, data: &[String], width: usize) -> Result<Option<Val
     }
 }
 
+/// Whether `actual` matches `expected` except don't-care bits of `expected`
+fn is_matched(expected: &Value, actual: &Value, width: usize) -> bool {
+    let care = !expected.mask_xz_u128() & width_mask(width);
+    (expected.payload_u128() ^ actual.payload_u128()) & care == 0
+}
+
+/// Format a value for failure messages: decimal for small values, hex otherwise,
+/// and `x` for unknown bits/digits.
+fn format_value(value: &Value, width: usize) -> String {
+    let mask = value.mask_xz_u128() & width_mask(width);
+    let payload = value.payload_u128() & width_mask(width);
+    if mask == width_mask(width) {
+        return "x".to_string();
+    }
+    if mask == 0 {
+        return if payload < 10 {
+            payload.to_string()
+        } else {
+            format!("0x{payload:x}")
+        };
+    }
+
+    let digits = width.div_ceil(4).max(1);
+    let mut ret = "0x".to_string();
+    for i in (0..digits).rev() {
+        let shift = i * 4;
+        if (mask >> shift) & 0xf != 0 {
+            ret.push('x');
+        } else {
+            write!(ret, "{:x}", (payload >> shift) & 0xf).unwrap();
+        }
+    }
+    ret
+}
+
 /// Classify signals based on module port information.
 pub fn classify_signals(scenario: &mut WaveScenario, port_info: &[(String, String)]) {
     for signal in &mut scenario.signals {
-        if !signal.wave.is_empty()
-            && matches!(
-                signal.wave[0],
-                WaveChar::PosedgeClock | WaveChar::NegedgeClock
-            )
+        if signal
+            .wave
+            .iter()
+            .any(|x| matches!(x, WaveChar::PosedgeClock | WaveChar::NegedgeClock))
         {
             signal.kind = SignalKind::Clock;
             continue;
@@ -214,19 +253,193 @@ pub fn classify_signals(scenario: &mut WaveScenario, port_info: &[(String, Strin
     }
 }
 
+/// Convert time slots to ticks if it is on the tick grid
+fn to_ticks(slots: f64, resolution: u64) -> Option<i64> {
+    let ticks = slots * resolution as f64;
+    let rounded = ticks.round();
+    ((ticks - rounded).abs() < 1e-6).then_some(rounded as i64)
+}
+
+/// Number of ticks per time slot.
+/// Every wave character boundary and clock edge (including the inactive edge
+/// in the middle of a clock cycle) falls on a tick.
+fn resolution(signals: &[WaveSignal]) -> Result<u64, String> {
+    // always even, so that the default clock has its inactive edge on the grid
+    'outer: for resolution in (2..=MAX_RESOLUTION).step_by(2) {
+        for signal in signals {
+            let period = to_ticks(signal.period, resolution);
+            let phase = to_ticks(signal.phase, resolution);
+            let Some(period) = period else {
+                continue 'outer;
+            };
+            if phase.is_none() || (signal.kind == SignalKind::Clock && period % 2 != 0) {
+                continue 'outer;
+            }
+        }
+        return Ok(resolution);
+    }
+    Err(format!(
+        "period and phase should be multiples of 1/{MAX_RESOLUTION}"
+    ))
+}
+
+/// Position of a signal on the tick timeline
+#[derive(Clone, Copy)]
+struct Lane {
+    period: i64,
+    phase: i64,
+    len: usize,
+}
+
+impl Lane {
+    fn new(signal: &WaveSignal, resolution: u64) -> Self {
+        Self {
+            period: to_ticks(signal.period, resolution).unwrap_or(1).max(1),
+            phase: to_ticks(signal.phase, resolution).unwrap_or(0),
+            len: signal.wave.len(),
+        }
+    }
+
+    /// Index of the wave character and offset from the beginning of it at `tick`
+    fn cell(&self, tick: u64) -> Option<(usize, i64)> {
+        let t = tick as i64 + self.phase;
+        if t < 0 {
+            return None;
+        }
+        let idx = (t / self.period) as usize;
+        (idx < self.len).then_some((idx, t % self.period))
+    }
+
+    /// Index of the wave character beginning at `tick`
+    fn start(&self, tick: u64) -> Option<usize> {
+        self.cell(tick)
+            .and_then(|(idx, ofs)| (ofs == 0).then_some(idx))
+    }
+
+    fn end(&self) -> u64 {
+        (self.len as i64 * self.period - self.phase).max(0) as u64
+    }
+}
+
+struct ClockLane<'a> {
+    signal: &'a WaveSignal,
+    lane: Lane,
+    event: Event,
+    var_id: Option<VarId>,
+}
+
+impl ClockLane<'_> {
+    /// Whether the active edge occurs at `tick`, and the clock level after `tick`
+    fn state(&self, tick: u64) -> (bool, Option<bool>) {
+        let Some((idx, ofs)) = self.lane.cell(tick) else {
+            return (false, None);
+        };
+        let half = self.lane.period / 2;
+        match self.signal.wave[idx] {
+            WaveChar::PosedgeClock if ofs == 0 => (tick > 0, Some(true)),
+            WaveChar::PosedgeClock if ofs == half => (false, Some(false)),
+            WaveChar::NegedgeClock if ofs == 0 => (tick > 0, Some(false)),
+            WaveChar::NegedgeClock if ofs == half => (false, Some(true)),
+            WaveChar::High if ofs == 0 => (false, Some(true)),
+            WaveChar::Low if ofs == 0 => (false, Some(false)),
+            _ => (false, None),
+        }
+    }
+}
+
+/// Expected and actual values of a checked wave character
+struct CheckedCell {
+    index: usize,
+    expected: Option<Value>,
+    actual: Value,
+    matched: bool,
+}
+
+struct CheckedSignal<'a> {
+    signal: &'a WaveSignal,
+    lane: Lane,
+    width: usize,
+    cells: Vec<CheckedCell>,
+}
+
+/// Render expected and actual waves of mismatched signals
+fn format_wave_diff(checked: &[CheckedSignal]) -> String {
+    let mut ret = String::new();
+    for x in checked {
+        if x.cells.iter().all(|x| x.matched) {
+            continue;
+        }
+
+        let cycle: Vec<_> = x.cells.iter().map(|x| x.index.to_string()).collect();
+        let expected: Vec<_> = x
+            .cells
+            .iter()
+            .map(|c| {
+                c.expected
+                    .as_ref()
+                    .map(|v| format_value(v, x.width))
+                    .unwrap_or_else(|| "x".to_string())
+            })
+            .collect();
+        let actual: Vec<_> = x
+            .cells
+            .iter()
+            .map(|c| format_value(&c.actual, x.width))
+            .collect();
+        let marker: Vec<_> = x
+            .cells
+            .iter()
+            .map(|c| if c.matched { "" } else { "^" }.to_string())
+            .collect();
+
+        let width = cycle
+            .iter()
+            .chain(&expected)
+            .chain(&actual)
+            .map(|x| x.len())
+            .max()
+            .unwrap_or(1);
+        let label = x.signal.name.len().max("expected".len());
+
+        let mut row = |name: &str, cells: &[String]| {
+            let mut line = format!("  {name:>label$} |");
+            for cell in cells {
+                write!(line, " {cell:>width$}").unwrap();
+            }
+            writeln!(ret, "{}", line.trim_end()).unwrap();
+        };
+        row(&x.signal.name, &cycle);
+        row("expected", &expected);
+        row("actual", &actual);
+        row("", &marker);
+    }
+    ret
+}
+
 /// Run a WaveDrom scenario against a simulator instance.
 ///
-/// Each cycle: drive inputs -> evaluate comb -> check outputs -> step clock/reset.
-/// This order ensures outputs reflect the pre-edge state (previous FF values +
-/// current combinational inputs), matching WaveDrom timing conventions.
+/// The scenario is simulated on a timeline of ticks, which is fine enough to
+/// represent `period` and `phase` of every signal.
+/// At each tick: step active clock edges (or reset) -> drive inputs -> evaluate
+/// comb -> check outputs. So outputs reflect the state after the edge at the
+/// beginning of the wave character, matching WaveDrom timing conventions.
+///
+/// `clock_events` maps clock signal names to their events. If the scenario has no
+/// clock signal, `default_clock` is stepped at every time slot.
 pub fn run_wavedrom_test(
     sim: &mut Simulator,
     scenario: &WaveScenario,
-    clock_event: &Event,
+    clock_events: &HashMap<String, Event>,
+    default_clock: &Event,
     reset_event: &Option<Event>,
     default_reset_cycles: u64,
-    port_widths: &std::collections::HashMap<String, usize>,
+    port_widths: &HashMap<String, usize>,
 ) -> TestResult {
+    let resolution = match resolution(&scenario.signals) {
+        Ok(x) => x,
+        Err(e) => return TestResult::Fail(e),
+    };
+
     let reset_signal_idx = scenario
         .signals
         .iter()
@@ -235,8 +448,49 @@ pub fn run_wavedrom_test(
     let reset_active_low =
         reset_signal_idx.is_some_and(|idx| is_active_low_reset(&scenario.signals[idx].name));
 
+    let length = scenario
+        .signals
+        .iter()
+        .map(|s| Lane::new(s, resolution).end())
+        .max()
+        .unwrap_or(0);
+
+    // Use the default clock at every time slot if there is no clock signal
+    let default_clock_signal = WaveSignal {
+        name: String::new(),
+        kind: SignalKind::Clock,
+        wave: vec![WaveChar::PosedgeClock; length.div_ceil(resolution) as usize],
+        data: vec![],
+        period: 1.0,
+        phase: 0.0,
+    };
+    let mut clocks: Vec<_> = scenario
+        .signals
+        .iter()
+        .filter(|s| s.kind == SignalKind::Clock)
+        .map(|s| {
+            let event = clock_events
+                .get(&s.name)
+                .cloned()
+                .unwrap_or_else(|| default_clock.clone());
+            (s, event)
+        })
+        .collect();
+    if clocks.is_empty() {
+        clocks.push((&default_clock_signal, default_clock.clone()));
+    }
+    let clocks: Vec<_> = clocks
+        .into_iter()
+        .map(|(signal, event)| ClockLane {
+            signal,
+            lane: Lane::new(signal, resolution),
+            var_id: event.var_id(),
+            event,
+        })
+        .collect();
+
     let has_dump = sim.dump.is_some();
-    let clock_var_id: Option<VarId> = clock_event.var_id();
+    let clock_var_id: Option<VarId> = clocks[0].var_id;
     let reset_var_id: Option<VarId> = reset_event.as_ref().and_then(|e| e.var_id());
 
     if reset_signal_idx.is_none()
@@ -264,21 +518,89 @@ pub fn run_wavedrom_test(
         }
     }
 
-    let max_len = scenario
+    let lanes: Vec<_> = scenario
         .signals
         .iter()
-        .map(|s| s.wave.len())
-        .max()
-        .unwrap_or(0);
+        .map(|s| Lane::new(s, resolution))
+        .collect();
+    let mut checked: Vec<_> = scenario
+        .signals
+        .iter()
+        .filter(|s| s.kind == SignalKind::Output)
+        .map(|s| CheckedSignal {
+            signal: s,
+            lane: Lane::new(s, resolution),
+            width: port_widths.get(&s.name).copied().unwrap_or(1),
+            cells: vec![],
+        })
+        .collect();
+    let mut failure: Option<String> = None;
+
+    for tick in 0..=length {
+        // Step active clock edges, or reset if it is asserted just before the edge
+        let reset_asserted = tick > 0
+            && reset_event.is_some()
+            && reset_signal_idx.is_some_and(|idx| {
+                lanes[idx].cell(tick - 1).is_some_and(|(i, _)| {
+                    is_reset_asserted(&scenario.signals[idx].wave[i], reset_active_low)
+                })
+            });
+        let mut events: Vec<&Event> = vec![];
+        let mut level_changed = false;
+        for clock in &clocks {
+            let (edge, level) = clock.state(tick);
+            if has_dump
+                && let Some(level) = level
+                && let Some(ref id) = clock.var_id
+            {
+                sim.set_var_by_id(id, Value::new(level as u64, 1, false));
+                level_changed = true;
+            }
+            if edge {
+                let event = if reset_asserted {
+                    reset_event.as_ref().unwrap()
+                } else {
+                    &clock.event
+                };
+                if !events.contains(&event) {
+                    events.push(event);
+                }
+            }
+        }
+        if has_dump
+            && reset_signal_idx.is_some()
+            && let Some(ref id) = reset_var_id
+        {
+            sim.set_var_by_id(id, Value::new(reset_asserted as u64, 1, false));
+        }
+        for (i, event) in events.iter().enumerate() {
+            // Dump only once for simultaneous edges
+            if i + 1 < events.len() {
+                let dump = sim.dump.take();
+                sim.step(event);
+                sim.dump = dump;
+            } else {
+                sim.step(event);
+            }
+        }
+        if has_dump && events.is_empty() && level_changed {
+            sim.dump_variables();
+        }
+
+        if tick == length {
+            break;
+        }
 
-    for t in 0..max_len {
-        // Drive input signals
-        for signal in &scenario.signals {
-            if signal.kind != SignalKind::Input || t >= signal.wave.len() {
+        // Drive input signals at the beginning of wave characters
+        for (signal, lane) in scenario.signals.iter().zip(&lanes) {
+            if signal.kind != SignalKind::Input {
                 continue;
             }
+            let Some(idx) = lane.start(tick) else {
+                continue;
+            };
             let width = port_widths.get(&signal.name).copied().unwrap_or(1);
-            if let Ok(Some(val)) = wave_value(&signal.wave[t], &signal.data, width) {
+            if let Ok(Some(val)) = wave_value(&signal.wave[idx], &signal.data, width) {
                 sim.set(&signal.name, val);
             }
         }
@@ -286,75 +608,54 @@ pub fn run_wavedrom_test(
         // Evaluate combinational logic before checking outputs
         sim.ensure_comb_updated();
 
-        // Check output signals (pre-edge values)
-        for signal in &scenario.signals {
-            if signal.kind != SignalKind::Output || t >= signal.wave.len() {
+        // Check output signals at the beginning of wave characters
+        for x in &mut checked {
+            let Some(idx) = x.lane.start(tick) else {
                 continue;
-            }
-            let width = port_widths.get(&signal.name).copied().unwrap_or(1);
-            match wave_value(&signal.wave[t], &signal.data, width) {
-                Ok(Some(expected)) => {
-                    let actual = sim.get(&signal.name);
-                    if let Some(actual) = actual {
-                        if actual.payload_u64() != expected.payload_u64() {
-                            return TestResult::Fail(format!(
-                                "cycle {t}: signal '{}' expected {} but got {}",
-                                signal.name,
-                                expected.payload_u64(),
-                                actual.payload_u64(),
-                            ));
-                        }
-                    } else {
-                        return TestResult::Fail(format!(
-                            "cycle {t}: signal '{}' not found in simulator",
-                            signal.name,
-                        ));
-                    }
-                }
-                Ok(None) => {}
+            };
+            let expected = match wave_value(&x.signal.wave[idx], &x.signal.data, x.width) {
+                Ok(expected) => expected,
                 Err(e) => {
-                    return TestResult::Fail(format!("cycle {t}: signal '{}': {e}", signal.name));
+                    return TestResult::Fail(format!(
+                        "cycle {idx}: signal '{}': {e}",
+                        x.signal.name
+                    ));
                 }
+            };
+            let Some(actual) = sim.get(&x.signal.name) else {
+                return TestResult::Fail(format!(
+                    "cycle {idx}: signal '{}' not found in simulator",
+                    x.signal.name,
+                ));
+            };
+            let matched = expected
+                .as_ref()
+                .is_none_or(|e| is_matched(e, &actual, x.width));
+            if !matched && failure.is_none() {
+                failure = Some(format!(
+                    "cycle {idx}: signal '{}' expected {} but got {}",
+                    x.signal.name,
+                    format_value(expected.as_ref().unwrap(), x.width),
+                    format_value(&actual, x.width),
+                ));
             }
+            x.cells.push(CheckedCell {
+                index: idx,
+                expected,
+                actual,
+                matched,
+            });
         }
 
-        // Step clock or reset edge
-        let step_event = if let Some(rst_idx) = reset_signal_idx
-            && let Some(rst_event) = reset_event
-            && t < scenario.signals[rst_idx].wave.len()
-        {
-            let rst_asserted =
-                is_reset_asserted(&scenario.signals[rst_idx].wave[t], reset_active_low);
-            if rst_asserted {
-                if has_dump && let Some(ref id) = reset_var_id {
-                    sim.set_var_by_id(id, Value::new(1, 1, false));
-                }
-                rst_event
-            } else {
-                if has_dump && let Some(ref id) = reset_var_id {
-                    sim.set_var_by_id(id, Value::new(0, 1, false));
-                }
-                clock_event
-            }
-        } else {
-            clock_event
-        };
-
-        if has_dump && let Some(ref id) = clock_var_id {
-            sim.set_var_by_id(id, Value::new(1, 1, false));
-        }
-        sim.step(step_event);
-        sim.time += 1;
-        if has_dump {
-            if let Some(ref id) = clock_var_id {
-                sim.set_var_by_id(id, Value::new(0, 1, false));
-            }
-            sim.dump_variables();
-        }
         sim.time += 1;
     }
 
-    TestResult::Pass
+    if let Some(failure) = failure {
+        let diff = format_wave_diff(&checked);
+        TestResult::Fail(format!("{failure}\n{}", diff.trim_end()))
+    } else {
+        TestResult::Pass
+    }
 }
 
 /// Check if a reset name indicates active-low polarity (ends with `_n`).
@@ -383,6 +684,248 @@ fn is_reset_name(name: &str) -> bool {
         || stripped.ends_with("_reset")
 }
 
+/// Signals and cycles written by the WaveDrom dumper.
+#[derive(Clone, Debug, Default)]
+pub struct WavedromSelection {
+    /// Signal names relative to the top module.
+    /// All variables of the top module are written if empty.
+    pub signals: Vec<String>,
+    pub cycles: CycleRange,
+}
+
+struct RecordedVar {
+    name: String,
+    width: usize,
+    is_clock: bool,
+    /// Whether the variable belongs to the top module
+    is_top: bool,
+}
+
+/// Recorder of simulation values which writes a WaveDrom diagram on drop.
+///
+/// A column is sampled at the first timestamp after each rising edge of the clock,
+/// so registers show the values updated by the edge.
+/// If no clock is found, each timestamp becomes a column.
+pub(crate) struct WavedromDumper {
+    io: Box<dyn std::io::Write + Send>,
+    selection: WavedromSelection,
+    scopes: Vec<String>,
+    vars: Vec<RecordedVar>,
+    /// Indices of variables written as lanes
+    lanes: Vec<usize>,
+    clock: Option<usize>,
+    watched: Vec<bool>,
+    values: Vec<Option<Value>>,
+    in_timestamp: bool,
+    clock_level: bool,
+    cycle: u64,
+    samples: Vec<Vec<Option<Value>>>,
+}
+
+impl WavedromDumper {
+    pub(crate) fn new(io: Box<dyn std::io::Write + Send>, selection: WavedromSelection) -> Self {
+        Self {
+            io,
+            selection,
+            scopes: Vec::new(),
+            vars: Vec::new(),
+            lanes: Vec::new(),
+            clock: None,
+            watched: Vec::new(),
+            values: Vec::new(),
+            in_timestamp: false,
+            clock_level: false,
+            cycle: 0,
+            samples: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add_module(&mut self, name: &str) {
+        self.scopes.push(name.to_string());
+    }
+
+    pub(crate) fn upscope(&mut self) {
+        self.scopes.pop();
+    }
+
+    pub(crate) fn add_wire(&mut self, width: u32, name: &str, is_clock: bool) -> usize {
+        // The scope of the top module is omitted
+        let mut path: Vec<_> = self.scopes.iter().skip(1).map(|x| x.as_str()).collect();
+        path.push(name);
+        self.vars.push(RecordedVar {
+            name: path.join("."),
+            width: width as usize,
+            is_clock,
+            is_top: self.scopes.len() <= 1,
+        });
+        self.values.push(None);
+        self.vars.len() - 1
+    }
+
+    pub(crate) fn finish_header(&mut self) {
+        self.lanes = if self.selection.signals.is_empty() {
+            let mut lanes: Vec<_> = (0..self.vars.len())
+                .filter(|x| self.vars[*x].is_top)
+                .collect();
+            lanes.sort_by_key(|x| !self.vars[*x].is_clock);
+            lanes
+        } else {
+            let mut lanes = Vec::new();
+            for name in &self.selection.signals {
+                if let Some(x) = self.vars.iter().position(|x| &x.name == name) {
+                    lanes.push(x);
+                } else {
+                    log::warn!("signal '{name}' for WaveDrom output is not found");
+                }
+            }
+            lanes
+        };
+        self.clock = self
+            .lanes
+            .iter()
+            .copied()
+            .find(|x| self.vars[*x].is_clock)
+            .or_else(|| self.vars.iter().position(|x| x.is_top && x.is_clock));
+
+        self.watched = vec![false; self.vars.len()];
+        for x in self.lanes.iter().chain(self.clock.iter()) {
+            self.watched[*x] = true;
+        }
+    }
+
+    pub(crate) fn timestamp(&mut self) {
+        self.sample();
+        self.in_timestamp = true;
+    }
+
+    pub(crate) fn change(&mut self, handle: usize, value: &Value) {
+        if self.watched[handle] {
+            self.values[handle] = Some(value.clone());
+        }
+    }
+
+    /// Take a sample of the values at the last timestamp
+    fn sample(&mut self) {
+        if !std::mem::take(&mut self.in_timestamp) {
+            return;
+        }
+
+        if let Some(clock) = self.clock {
+            let level = self.values[clock]
+                .as_ref()
+                .is_some_and(|x| x.payload_u128() & 1 == 1 && x.mask_xz_u128() & 1 == 0);
+            let rising = level && !self.clock_level;
+            self.clock_level = level;
+            if !rising {
+                return;
+            }
+        }
+
+        if self.selection.cycles.contains(self.cycle) {
+            let sample = self.lanes.iter().map(|x| self.values[*x].clone()).collect();
+            self.samples.push(sample);
+        }
+        self.cycle += 1;
+    }
+
+    fn lane(&self, index: usize, width: usize) -> (String, Vec<String>) {
+        let mask = width_mask(width);
+        let mut wave = String::new();
+        let mut data = Vec::new();
+        let mut prev = None;
+
+        for (i, sample) in self.samples.iter().enumerate() {
+            let value = &sample[index];
+            let key = value
+                .as_ref()
+                .map(|x| (x.payload_u128() & mask, x.mask_xz_u128() & mask));
+            if i != 0 && key == prev {
+                wave.push('.');
+                continue;
+            }
+            prev = key;
+
+            let c = match (value, key) {
+                (Some(value), Some((payload, mask_xz))) => {
+                    if width == 1 {
+                        match (payload, mask_xz) {
+                            (x, 0) => {
+                                if x == 0 {
+                                    '0'
+                                } else {
+                                    '1'
+                                }
+                            }
+                            (0, _) => 'x',
+                            _ => 'z',
+                        }
+                    } else if mask_xz == mask {
+                        if payload == mask { 'z' } else { 'x' }
+                    } else {
+                        data.push(format_value(value, width));
+                        '='
+                    }
+                }
+                _ => 'x',
+            };
+            wave.push(c);
+        }
+
+        (wave, data)
+    }
+
+    fn render(&self) -> String {
+        let quote = |x: &str| serde_json::to_string(x).unwrap();
+
+        let mut ret = String::new();
+        writeln!(ret, "{{\"signal\": [").unwrap();
+        for (i, var) in self.lanes.iter().enumerate() {
+            let var = *var;
+            let (wave, data) = if Some(var) == self.clock {
+                let wave = if self.samples.is_empty() {
+                    String::new()
+                } else {
+                    format!("p{}", ".".repeat(self.samples.len() - 1))
+                };
+                (wave, Vec::new())
+            } else {
+                self.lane(i, self.vars[var].width)
+            };
+
+            write!(
+                ret,
+                "  {{\"name\": {}, \"wave\": {}",
+                quote(&self.vars[var].name),
+                quote(&wave)
+            )
+            .unwrap();
+            if !data.is_empty() {
+                let data: Vec<_> = data.iter().map(|x| quote(x)).collect();
+                write!(ret, ", \"data\": [{}]", data.join(", ")).unwrap();
+            }
+            let comma = if i + 1 == self.lanes.len() { "" } else { "," };
+            writeln!(ret, "}}{comma}").unwrap();
+        }
+        writeln!(ret, "],").unwrap();
+        writeln!(
+            ret,
+            "\"head\": {{\"tick\": {}}}}}",
+            self.selection.cycles.start
+        )
+        .unwrap();
+        ret
+    }
+}
+
+impl Drop for WavedromDumper {
+    fn drop(&mut self) {
+        self.sample();
+        let text = self.render();
+        let _ = std::io::Write::write_all(&mut self.io, text.as_bytes());
+        let _ = std::io::Write::flush(&mut self.io);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -431,9 +974,10 @@ mod tests {
         assert_eq!(scenario.signals[0].wave[0], WaveChar::Data(0));
         assert_eq!(scenario.signals[0].wave[2], WaveChar::Data(1));
         assert_eq!(scenario.signals[0].wave[4], WaveChar::Data(2));
-        assert_eq!(parse_data_value("0xFF").unwrap(), 255);
-        assert_eq!(parse_data_value("0b1010").unwrap(), 10);
-        assert_eq!(parse_data_value("42").unwrap(), 42);
+        assert_eq!(parse_data_value("0xFF").unwrap(), WaveData::new(255));
+        assert_eq!(parse_data_value("0b1010").unwrap(), WaveData::new(10));
+        assert_eq!(parse_data_value("42").unwrap(), WaveData::new(42));
+        assert!(parse_data_value("IDLE").is_err());
     }
 
     #[test]
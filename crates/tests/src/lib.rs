@@ -10,6 +10,30 @@ const PACKAGE_SELF_REF_TESTS: [&str; 2] = ["84_package_self_ref_1", "84_package_
 #[cfg(test)]
 static DEPENDENCY_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
 
+#[cfg(test)]
+fn analyze(metadata: &veryl_metadata::Metadata, code: &str) -> Vec<veryl_analyzer::AnalyzerError> {
+    analyze_ir(metadata, code, None)
+}
+
+#[cfg(test)]
+fn analyze_ir(
+    metadata: &veryl_metadata::Metadata,
+    code: &str,
+    ir: Option<&mut veryl_analyzer::ir::Ir>,
+) -> Vec<veryl_analyzer::AnalyzerError> {
+    use veryl_analyzer::{Analyzer, Context};
+
+    let parser = veryl_parser::Parser::parse(code, &"").unwrap();
+    let analyzer = Analyzer::new(metadata);
+    let mut context = Context::default();
+    let mut errors = vec![];
+    errors.append(&mut analyzer.analyze_pass1(&"prj", &parser.veryl));
+    errors.append(&mut Analyzer::analyze_post_pass1());
+    errors.append(&mut analyzer.analyze_pass2(&"prj", &parser.veryl, &mut context, ir));
+    errors.append(&mut Analyzer::analyze_post_pass2());
+    errors
+}
+
 #[cfg(test)]
 mod parser {
     use std::fs;
@@ -551,6 +575,7 @@ mod filelist {
     use veryl_analyzer::{Analyzer, Context};
     use veryl_metadata::Metadata;
     use veryl_parser::Parser;
+    use veryl_path::PathSet;
 
     fn check_list(paths: &[String], expected: &[&str]) {
         let paths: Vec<_> = paths.iter().map(|x| x.as_str()).collect();
@@ -574,17 +599,10 @@ mod filelist {
         assert!(path0 < path1);
     }
 
-    #[test]
-    fn test() {
-        let path = std::env::current_dir().unwrap();
-        let path = path.join("../../testcases/filelist");
-        let metadata_path = Metadata::search_from(path).unwrap();
-        let mut metadata = Metadata::load(&metadata_path).unwrap();
-        let paths = metadata.paths::<PathBuf>(&[], false, true).unwrap();
-
+    fn analyze(metadata: &Metadata, paths: &[PathSet]) {
         let mut contexts = Vec::new();
 
-        for path in &paths {
+        for path in paths {
             let input = fs::read_to_string(&path.src).unwrap();
             let parser = Parser::parse(&input, &path.src).unwrap();
 
@@ -607,8 +625,19 @@ mod filelist {
         let err = Analyzer::analyze_post_pass2();
         dbg!(&err);
         assert!(err.is_empty());
+    }
 
-        let paths = veryl::cmd_build::CmdBuild::sort_filelist(&metadata, &paths, false);
+    #[test]
+    fn test() {
+        let path = std::env::current_dir().unwrap();
+        let path = path.join("../../testcases/filelist");
+        let metadata_path = Metadata::search_from(path).unwrap();
+        let mut metadata = Metadata::load(&metadata_path).unwrap();
+        let paths = metadata.paths::<PathBuf>(&[], false, true).unwrap();
+
+        analyze(&metadata, &paths);
+
+        let paths = veryl::cmd_build::CmdBuild::sort_filelist(&metadata, &paths, false).unwrap();
         let paths: Vec<_> = paths
             .into_iter()
             .map(|x| x.src.file_name().unwrap().to_string_lossy().into_owned())
@@ -659,6 +688,139 @@ mod filelist {
         check_order(&paths, "19_package_o.veryl", "20_module_p.veryl");
         check_order(&paths, "20_module_p.veryl", "21_alias_q.veryl");
     }
+
+    #[test]
+    fn tops() {
+        let path = std::env::current_dir().unwrap();
+        let path = path.join("../../testcases/filelist");
+        let metadata_path = Metadata::search_from(path).unwrap();
+        let mut metadata = Metadata::load(&metadata_path).unwrap();
+        let paths = metadata.paths::<PathBuf>(&[], false, true).unwrap();
+
+        analyze(&metadata, &paths);
+
+        metadata.build.tops = vec!["ModuleX".to_string()];
+        let err = veryl::cmd_build::CmdBuild::sort_filelist(&metadata, &paths, false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "top \"ModuleX\" is not found in project \"filelist\""
+        );
+
+        metadata.build.tops = vec!["ModuleE".to_string(), "ModuleC".to_string()];
+        let paths = veryl::cmd_build::CmdBuild::sort_filelist(&metadata, &paths, false).unwrap();
+        let paths: Vec<_> = paths
+            .into_iter()
+            .map(|x| x.src.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+
+        dbg!(&paths);
+
+        let all = &[
+            "05_module_c.veryl",
+            "06_package_c.veryl",
+            "07_module_d.veryl",
+            "08_module_e.veryl",
+            "09_module_f.veryl",
+            "ram.veryl",
+        ];
+        check_list(&paths, all);
+
+        check_order(&paths, "ram.veryl", "05_module_c.veryl");
+        check_order(&paths, "06_package_c.veryl", "07_module_d.veryl");
+        check_order(&paths, "07_module_d.veryl", "09_module_f.veryl");
+        check_order(&paths, "09_module_f.veryl", "08_module_e.veryl");
+    }
+
+    #[test]
+    fn filelist_text() {
+        use veryl::cmd_build::CmdBuild;
+        use veryl_metadata::FilelistType;
+
+        let path = std::env::current_dir().unwrap();
+        let path = path.join("../../testcases/filelist");
+        let metadata_path = Metadata::search_from(path).unwrap();
+        let mut metadata = Metadata::load(&metadata_path).unwrap();
+        metadata.project.description = Some(r#"filelist "test" \"#.to_string());
+        metadata.test.include_files = vec![PathBuf::from("src/ram.veryl")];
+
+        let base = metadata.project_path().canonicalize().unwrap();
+        let files = vec![
+            base.join("src/01_package_a.veryl"),
+            base.join("src/03_module_a.veryl"),
+        ];
+        let text = |x| CmdBuild::gen_filelist_text(&metadata, x, &files).unwrap();
+        let abs = |x: &str| base.join(x).to_string_lossy().into_owned();
+
+        assert_eq!(
+            text(FilelistType::Absolute),
+            format!(
+                "{}\n{}\n",
+                abs("src/01_package_a.veryl"),
+                abs("src/03_module_a.veryl")
+            )
+        );
+        assert_eq!(
+            text(FilelistType::Relative),
+            "src/01_package_a.veryl\nsrc/03_module_a.veryl\n"
+        );
+        assert_eq!(
+            text(FilelistType::Verilator),
+            format!(
+                "+incdir+{}\n{}\n{}\n",
+                abs("src"),
+                abs("src/01_package_a.veryl"),
+                abs("src/03_module_a.veryl")
+            )
+        );
+        assert_eq!(
+            text(FilelistType::Vivado),
+            format!(
+                r#"set veryl_files [list \
+    {{{}}} \
+    {{{}}} \
+]
+set veryl_include_dirs [list {{{}}}]
+if {{[current_project -quiet] ne ""}} {{
+    add_files -norecurse $veryl_files
+    set_property file_type SystemVerilog [get_files $veryl_files]
+    set_property include_dirs $veryl_include_dirs [current_fileset]
+}} else {{
+    # pass `-include_dirs $veryl_include_dirs` to synth_design
+    read_verilog -sv $veryl_files
+}}
+"#,
+                abs("src/01_package_a.veryl"),
+                abs("src/03_module_a.veryl"),
+                abs("src")
+            )
+        );
+        assert_eq!(
+            text(FilelistType::Quartus),
+            r#"set_global_assignment -name SEARCH_PATH "src"
+set_global_assignment -name SYSTEMVERILOG_FILE "src/01_package_a.veryl"
+set_global_assignment -name SYSTEMVERILOG_FILE "src/03_module_a.veryl"
+"#
+        );
+        assert_eq!(
+            text(FilelistType::Fusesoc),
+            r#"CAPI=2:
+name: ::filelist:0.1.0
+description: "filelist \"test\" \\"
+
+filesets:
+  rtl:
+    file_type: systemVerilogSource
+    files:
+      - src/01_package_a.veryl
+      - src/03_module_a.veryl
+      - src/ram.veryl: {is_include_file: true}
+
+targets:
+  default:
+    filesets: [rtl]
+"#
+        );
+    }
 }
 
 #[cfg(test)]
@@ -851,3 +1013,721 @@ mod native_test {
         }
     }
 }
+
+#[cfg(test)]
+mod incremental {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use veryl::OptBuild;
+    use veryl::cmd_build::CmdBuild;
+    use veryl_metadata::Metadata;
+
+    fn copy_dir(src: &Path, dst: &Path) {
+        fs::create_dir_all(dst).unwrap();
+        for entry in fs::read_dir(src).unwrap() {
+            let path = entry.unwrap().path();
+            let dst = dst.join(path.file_name().unwrap());
+            if path.is_dir() {
+                copy_dir(&path, &dst);
+            } else {
+                fs::copy(&path, &dst).unwrap();
+            }
+        }
+    }
+
+    fn outputs(path: &Path, ret: &mut BTreeMap<PathBuf, String>) {
+        for entry in fs::read_dir(path).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                if !path.ends_with(".build") {
+                    outputs(&path, ret);
+                }
+            } else if path.extension().is_some_and(|x| x == "sv" || x == "f") {
+                ret.insert(path.clone(), fs::read_to_string(&path).unwrap());
+            }
+        }
+    }
+
+    // analyzer tables are thread local, so each build is executed on a new thread.
+    // outputs and the number of analyzed files are returned.
+    fn build(path: &Path, incremental: bool) -> (BTreeMap<PathBuf, String>, usize) {
+        let path = path.to_path_buf();
+        std::thread::spawn(move || {
+            let metadata_path = Metadata::search_from(&path).unwrap();
+            let mut metadata = Metadata::load(&metadata_path).unwrap();
+            metadata.build.incremental = incremental;
+
+            let build = CmdBuild::new(OptBuild {
+                files: Vec::new(),
+                check: false,
+                top: Vec::new(),
+                filelist_type: Vec::new(),
+                watch: false,
+            });
+            assert!(build.exec(&mut metadata, false, true, None).unwrap());
+
+            let mut ret = BTreeMap::new();
+            outputs(&path, &mut ret);
+            (ret, build.analyzed_files())
+        })
+        .join()
+        .unwrap()
+    }
+
+    fn cache_keys(path: &Path) -> BTreeMap<String, String> {
+        let index = fs::read_to_string(path.join(".build/cache/index.json")).unwrap();
+        let index: serde_json::Value = serde_json::from_str(&index).unwrap();
+        index["entries"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(path, key)| {
+                let name = Path::new(path).file_name().unwrap().to_string_lossy();
+                (name.into_owned(), key.as_str().unwrap().to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test() {
+        let src = std::env::current_dir()
+            .unwrap()
+            .join("../../testcases/filelist");
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        copy_dir(&src, dir);
+
+        let (full, total) = build(dir, false);
+        let _ = build(dir, true);
+        assert!(dir.join(".build/cache/index.json").exists());
+
+        // all outputs are restored from cache without analysis
+        let (cached, analyzed) = build(dir, true);
+        assert_eq!(full, cached);
+        assert_eq!(analyzed, 0);
+        let keys = cache_keys(dir);
+
+        // modify a leaf package only
+        let package_a = dir.join("src/01_package_a.veryl");
+        let text = fs::read_to_string(&package_a).unwrap();
+        fs::write(
+            &package_a,
+            text.replace("A: logic,", "A: logic,\n        B: logic,"),
+        )
+        .unwrap();
+
+        // only the package and the module depending on it are analyzed
+        let (incremental, analyzed) = build(dir, true);
+        let (full, _) = build(dir, false);
+        assert_eq!(full, incremental);
+        assert_eq!(analyzed, 2);
+        assert!(
+            incremental
+                .iter()
+                .any(|(path, text)| path.ends_with("01_package_a.sv") && text.contains("B;"))
+        );
+
+        // the untouched module depending on the package is re-emitted,
+        // and the other files are still restored from cache
+        let new_keys = cache_keys(dir);
+        for (name, key) in &keys {
+            let changed = name == "01_package_a.veryl" || name == "03_module_a.veryl";
+            assert_eq!(new_keys[name] != *key, changed, "{name}");
+        }
+
+        // new dependency changes filelist order, so all files are analyzed
+        let module_b = dir.join("src/04_module_b.veryl");
+        let text = fs::read_to_string(&module_b).unwrap();
+        fs::write(
+            &module_b,
+            text.replace("= 1;", "= 1;\n    let _g: PackageG::G = 0;"),
+        )
+        .unwrap();
+
+        let (incremental, analyzed) = build(dir, true);
+        let (full, _) = build(dir, false);
+        assert_eq!(full, incremental);
+        assert_eq!(analyzed, total);
+    }
+}
+
+#[cfg(test)]
+mod doc {
+    use crate::analyze;
+    use veryl::doc::{
+        DOC_JSON_SCHEMA_VERSION, block_diagram, build_coverage, build_json, highlight,
+    };
+    use veryl_analyzer::doc_coverage::DocItemKind;
+    use veryl_analyzer::symbol::SymbolKind;
+    use veryl_analyzer::symbol_table;
+    use veryl_metadata::Metadata;
+    use veryl_parser::Parser;
+
+    const CODE: &str = r#"
+interface InterfaceA {
+    var a: logic;
+    modport mp {
+        a: input,
+    }
+}
+
+module ModuleA (
+    i_clk_a: input  'a clock,
+    i_clk_b: input  'b clock,
+    i_d    : input  'a logic,
+    o_d    : output 'b logic,
+) {
+    var d  : 'a logic;
+    inst u_if: InterfaceA;
+
+    inst u0: ModuleB (
+        i_clk: i_clk_a,
+        i_d       ,
+        o_d  : d  ,
+        bus  : u_if,
+    );
+
+    inst u1: 'b ModuleB (
+        i_clk: i_clk_b,
+        i_d  : d      ,
+        o_d           ,
+        bus  : u_if   ,
+    );
+}
+
+module ModuleB (
+    i_clk: input   clock            ,
+    i_d  : input   logic            ,
+    o_d  : output  logic            ,
+    bus  : modport InterfaceA::mp   ,
+) {
+    assign o_d = i_d;
+}
+"#;
+
+    #[test]
+    fn block_diagram_test() {
+        let metadata = Metadata::create_default("prj").unwrap();
+        analyze(&metadata, CODE);
+
+        let symbol = |name: &str| {
+            symbol_table::get_all()
+                .into_iter()
+                .find(|x| matches!(x.kind, SymbolKind::Module(_)) && x.token.to_string() == name)
+                .unwrap()
+        };
+
+        assert!(block_diagram(&symbol("ModuleB")).is_none());
+
+        let ret = block_diagram(&symbol("ModuleA")).unwrap();
+        let expect = r#"flowchart LR
+    p_i_clk_a(["i_clk_a"])
+    p_i_clk_b(["i_clk_b"])
+    p_i_d(["i_d"])
+    p_o_d(["o_d"])
+    i_u_if{{"u_if: InterfaceA"}}
+    i_u0["u0: ModuleB"]
+    i_u1["u1: ModuleB"]
+    i_u0 -->|"d"| i_u1
+    p_i_clk_a -->|"i_clk_a"| i_u0
+    p_i_clk_b -->|"i_clk_b"| i_u1
+    p_i_d -->|"i_d"| i_u0
+    i_u1 -->|"o_d"| p_o_d
+    i_u_if <==>|"u_if"| i_u0
+    i_u_if <==>|"u_if"| i_u1
+    %% clock domain 'a
+    classDef cd0 stroke:#4e79a7,fill:#dbe4ef
+    class p_i_clk_a,p_i_d cd0
+    linkStyle 0,1,3 stroke:#4e79a7
+    %% clock domain 'b
+    classDef cd1 stroke:#f28e2b,fill:#fce5d0
+    class p_i_clk_b,p_o_d,i_u1 cd1
+    linkStyle 2,4,6 stroke:#f28e2b
+"#;
+        assert_eq!(ret, expect);
+    }
+
+    #[test]
+    fn json() {
+        let code = r#"
+/// Bus interface
+pub interface InterfaceA #(
+    /// Data width
+    param W: u32 = 8,
+) {
+    var data: logic<W>;
+    /// Master side
+    modport master {
+        data: output,
+    }
+}
+
+/// Common definitions
+pub package PackageA {
+    /// Bus width
+    const WIDTH: u32 = 16;
+    function inc (x: input logic<WIDTH>) -> logic<WIDTH> {
+        return x + 1;
+    }
+}
+
+pub module ModuleA (
+    i_clk: input  'a clock          ,
+    /// Data
+    i_d  : input  'a logic<8>   [2] ,
+    o_d  : output 'a logic<8>   [2] ,
+) {
+    assign o_d = i_d;
+}
+"#;
+        let metadata = Metadata::create_default("prj").unwrap();
+        analyze(&metadata, &code);
+
+        let ret = build_json(&metadata, &["prj".to_string()]);
+        assert_eq!(ret.schema_version, DOC_JSON_SCHEMA_VERSION);
+        assert_eq!(ret.project.name, "prj");
+
+        let module = &ret.modules[0];
+        assert_eq!(module.name, "ModuleA");
+        assert_eq!(module.project, "prj");
+        assert_eq!(module.location.line, 23);
+        let port = &module.ports[1];
+        assert_eq!(port.name, "i_d");
+        assert_eq!(port.direction, "input");
+        assert_eq!(port.width, ["8"]);
+        assert_eq!(port.array, ["2"]);
+        assert_eq!(port.clock_domain.as_deref(), Some("'a"));
+        assert_eq!(port.doc.as_deref(), Some("Data"));
+
+        let interface = &ret.interfaces[0];
+        assert_eq!(interface.doc.as_deref(), Some("Bus interface"));
+        assert_eq!(interface.parameters[0].name, "W");
+        assert_eq!(interface.parameters[0].default.as_deref(), Some("8"));
+        assert_eq!(interface.parameters[0].doc.as_deref(), Some("Data width"));
+        let modport = &interface.modports[0];
+        assert_eq!(modport.name, "master");
+        assert_eq!(modport.doc.as_deref(), Some("Master side"));
+        assert_eq!(modport.members[0].name, "data");
+        assert_eq!(modport.members[0].direction, "output");
+
+        let package = &ret.packages[0];
+        assert_eq!(package.parameters[0].name, "WIDTH");
+        assert_eq!(package.parameters[0].kind, "const");
+        assert_eq!(package.parameters[0].default.as_deref(), Some("16"));
+        assert_eq!(package.functions[0].name, "inc");
+        assert_eq!(package.functions[0].ports[0].name, "x");
+        assert_eq!(
+            package.functions[0].return_type.as_deref(),
+            Some("logic<WIDTH>")
+        );
+    }
+
+    #[test]
+    fn source_highlight() {
+        let code = r#"/// Adder
+module ModuleA (
+    i_clk: input 'a clock,
+    o_d  : output logic<8>,
+) {
+    /* block
+       comment */
+    assign o_d = 8'h1 + $clog2(4) & 2;
+}"#;
+        let parser = Parser::parse(&code, &"").unwrap();
+        let ret = highlight(&parser.veryl, code);
+        let lines: Vec<_> = ret.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert_eq!(
+            lines[0],
+            r#"<span class="line_number" id="L1">1</span> <span class="hljs-comment">/// Adder</span>"#
+        );
+        assert_eq!(
+            lines[2],
+            r#"<span class="line_number" id="L3">3</span>     i_clk: <span class="hljs-keyword">input</span> <span class="hljs-attribute">'a</span> <span class="hljs-keyword">clock</span>,"#
+        );
+        assert_eq!(
+            lines[3],
+            r#"<span class="line_number" id="L4">4</span>     o_d  : <span class="hljs-keyword">output</span> <span class="hljs-keyword">logic</span>&lt;<span class="hljs-number">8</span>&gt;,"#
+        );
+        assert_eq!(
+            lines[5],
+            r#"<span class="line_number" id="L6">6</span>     <span class="hljs-comment">/* block</span>"#
+        );
+        assert_eq!(
+            lines[6],
+            r#"<span class="line_number" id="L7">7</span> <span class="hljs-comment">       comment */</span>"#
+        );
+        assert_eq!(
+            lines[7],
+            r#"<span class="line_number" id="L8">8</span>     <span class="hljs-keyword">assign</span> o_d = <span class="hljs-number">8'h1</span> + <span class="hljs-built_in">$clog2</span>(<span class="hljs-number">4</span>) &amp; <span class="hljs-number">2</span>;"#
+        );
+    }
+
+    #[test]
+    fn coverage() {
+        let code = r#"
+/// Bus interface
+pub interface InterfaceA #(
+    param W: u32 = 8,
+) {
+    var data: logic<W>;
+}
+
+pub package PackageA {
+    /// Bus width
+    const WIDTH: u32 = 16;
+    /// Increment
+    function inc (x: input logic<WIDTH>) -> logic<WIDTH> {
+        return x + 1;
+    }
+}
+
+/// Top module
+pub module ModuleA (
+    /// Data
+    i_d: input  logic,
+    o_d: output logic,
+) {
+    assign o_d = i_d;
+}
+
+module ModuleB {}
+"#;
+        let metadata = Metadata::create_default("prj").unwrap();
+        analyze(&metadata, &code);
+
+        let ret = build_coverage(&metadata, &["prj".to_string(), "$std".to_string()]);
+        assert_eq!(ret.len(), 1);
+
+        let coverage = &ret[0];
+        assert_eq!(coverage.name, "prj");
+        assert_eq!(coverage.total.documented, 5);
+        assert_eq!(coverage.total.total, 8);
+        assert_eq!(coverage.kinds[&DocItemKind::Module].documented, 1);
+        assert_eq!(coverage.kinds[&DocItemKind::Port].total, 2);
+        assert_eq!(coverage.kinds[&DocItemKind::PackageMember].documented, 1);
+        assert_eq!(coverage.files.len(), 1);
+
+        let undocumented: Vec<_> = coverage
+            .undocumented
+            .iter()
+            .map(|x| x.name.as_str())
+            .collect();
+        assert_eq!(undocumented, ["InterfaceA::W", "PackageA", "ModuleA::o_d"]);
+    }
+}
+
+#[cfg(test)]
+mod regmap {
+    use crate::{analyze, analyze_ir};
+    use std::str::FromStr;
+    use veryl::regmap::{Regmap, generate_c_header, generate_veryl};
+    use veryl_analyzer::ir::Ir;
+    use veryl_analyzer::value::Value;
+    use veryl_metadata::Metadata;
+    use veryl_parser::Parser;
+    use veryl_simulator::Simulator;
+    use veryl_simulator::ir::{Config, build_ir};
+
+    const REGMAP: &str = r#"
+name = "RegsA"
+description = "Register block A"
+bus = "apb"
+address_width = 8
+
+[[registers]]
+name = "CTRL"
+offset = 0x0
+
+[[registers.fields]]
+name = "EN"
+bits = "0"
+access = "rw"
+
+[[registers.fields]]
+name = "MODE"
+bits = "7:4"
+access = "rw"
+reset = 0x5
+
+[[registers]]
+name = "STATUS"
+offset = 0x4
+
+[[registers.fields]]
+name = "BUSY"
+bits = "0"
+access = "ro"
+
+[[registers.fields]]
+name = "ERR"
+bits = "1"
+access = "w1c"
+
+[[registers.fields]]
+name = "START"
+bits = "2"
+access = "w1s"
+"#;
+
+    #[test]
+    fn generate() {
+        let regmap = Regmap::from_str(REGMAP).unwrap();
+        assert_eq!(regmap.registers[0].reset(), 0x50);
+
+        let code = generate_veryl(&regmap, "regs_a.toml");
+        let metadata = Metadata::create_default("prj").unwrap();
+        let errors = analyze(&metadata, &code);
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let header = generate_c_header(&regmap, "regs_a.toml");
+        assert!(header.contains("#define REGS_A_CTRL_RESET 0x00000050u\n"));
+        assert!(header.contains("#define REGS_A_CTRL_MODE_SHIFT 4u\n"));
+        assert!(header.contains("#define REGS_A_CTRL_MODE_MASK 0x000000f0u\n"));
+        assert!(header.contains("#define REGS_A_STATUS_OFFSET 0x4u\n"));
+
+        let regmap = REGMAP.replace("Register block A", "Register block A */ B");
+        let regmap = Regmap::from_str(&regmap).unwrap();
+        let header = generate_c_header(&regmap, "regs_a.toml");
+        assert!(header.contains("/* Register block A * / B */\n"));
+    }
+
+    #[test]
+    fn simulate() {
+        let regmap = Regmap::from_str(REGMAP).unwrap();
+        let code = generate_veryl(&regmap, "regs_a.toml");
+        let metadata = Metadata::create_default("prj").unwrap();
+        let mut ir = Ir::default();
+        let errors = analyze_ir(&metadata, &code, Some(&mut ir));
+        assert!(errors.is_empty(), "{errors:?}");
+
+        for config in Config::all() {
+            let sim_ir = build_ir(&ir, "RegsA".into(), &config).unwrap();
+            let mut sim = Simulator::new(sim_ir, None);
+            let clk = sim.get_clock("i_clk").unwrap();
+            let rst = sim.get_reset("i_rst").unwrap();
+            let value = |x: u64, width: usize| Value::new(x, width, false);
+
+            for (port, width) in [
+                ("i_psel", 1),
+                ("i_penable", 1),
+                ("i_pwrite", 1),
+                ("i_paddr", 8),
+                ("i_pwdata", 32),
+                ("i_pstrb", 4),
+                ("i_status_busy", 1),
+                ("i_status_err_set", 1),
+                ("i_status_start_clear", 1),
+            ] {
+                sim.set(port, value(0, width));
+            }
+            sim.step(&rst);
+            assert_eq!(sim.get("o_ctrl_mode").unwrap(), value(0x5, 4));
+
+            // write CTRL
+            sim.set("i_psel", value(1, 1));
+            sim.set("i_penable", value(1, 1));
+            sim.set("i_pwrite", value(1, 1));
+            sim.set("i_pwdata", value(0xa1, 32));
+            sim.set("i_pstrb", value(0xf, 4));
+            sim.step(&clk);
+            assert_eq!(sim.get("o_ctrl_en").unwrap(), value(1, 1));
+            assert_eq!(sim.get("o_ctrl_mode").unwrap(), value(0xa, 4));
+            assert_eq!(sim.get("o_pslverr").unwrap(), value(0, 1));
+
+            // set ERR by hardware, and START by software
+            sim.set("i_pwrite", value(0, 1));
+            sim.set("i_penable", value(0, 1));
+            sim.set("i_status_err_set", value(1, 1));
+            sim.step(&clk);
+            sim.set("i_status_err_set", value(0, 1));
+            sim.set("i_penable", value(1, 1));
+            sim.set("i_pwrite", value(1, 1));
+            sim.set("i_paddr", value(0x4, 8));
+            sim.set("i_pwdata", value(0x4, 32));
+            sim.step(&clk);
+            assert_eq!(sim.get("o_status_err").unwrap(), value(1, 1));
+            assert_eq!(sim.get("o_status_start").unwrap(), value(1, 1));
+
+            // read STATUS
+            sim.set("i_pwrite", value(0, 1));
+            sim.set("i_status_busy", value(1, 1));
+            sim.step(&clk);
+            assert_eq!(sim.get("o_prdata").unwrap(), value(0x7, 32));
+
+            // clear ERR by writing 1
+            sim.set("i_pwrite", value(1, 1));
+            sim.set("i_pwdata", value(0x2, 32));
+            sim.step(&clk);
+            assert_eq!(sim.get("o_status_err").unwrap(), value(0, 1));
+            assert_eq!(sim.get("o_status_start").unwrap(), value(1, 1));
+
+            // access to unmapped address
+            sim.set("i_paddr", value(0x8, 8));
+            sim.step(&clk);
+            assert_eq!(sim.get("o_pslverr").unwrap(), value(1, 1));
+        }
+    }
+
+    #[test]
+    fn axi4_lite() {
+        let text = REGMAP.replace("bus = \"apb\"", "bus = \"axi4_lite\"");
+        let regmap = Regmap::from_str(&text).unwrap();
+        let code = generate_veryl(&regmap, "regs_a.toml");
+        assert!(code.contains("pub alias package RegsABusPkg = $std::axi4_lite_pkg::<8, 4, 1>;"));
+        assert!(code.contains("bus: modport $std::axi4_lite_if::<RegsABusPkg>::slave,"));
+        assert!(Parser::parse(&code, &"").is_ok());
+    }
+
+    #[test]
+    fn invalid() {
+        let overlap = REGMAP.replace("bits = \"7:4\"", "bits = \"4:0\"");
+        assert!(Regmap::from_str(&overlap).is_err());
+
+        let misaligned = REGMAP.replace("offset = 0x4", "offset = 0x2");
+        assert!(Regmap::from_str(&misaligned).is_err());
+
+        let duplicated = REGMAP.replace("offset = 0x4", "offset = 0x0");
+        assert!(Regmap::from_str(&duplicated).is_err());
+
+        let reset_overflow = REGMAP.replace("reset = 0x5", "reset = 0x10");
+        assert!(Regmap::from_str(&reset_overflow).is_err());
+
+        let out_of_range = REGMAP.replace("bits = \"7:4\"", "bits = \"32:4\"");
+        assert!(Regmap::from_str(&out_of_range).is_err());
+    }
+}
+
+#[cfg(test)]
+mod ipxact {
+    use crate::analyze;
+    use veryl::ipxact::{export_component, import_component};
+    use veryl_analyzer::symbol_table;
+    use veryl_metadata::{IpxactBusInterface, IpxactBusMode, Metadata};
+
+    #[test]
+    fn export() {
+        let code = r#"
+        interface InterfaceA {
+            var valid: logic;
+            var data : logic<8>;
+            modport slave {
+                valid: input,
+                data : input,
+            }
+        }
+
+        /// Module A
+        pub module ModuleA #(
+            param W: u32 = 4,
+        ) (
+            i_clk: input clock,
+            o_d  : output logic<W> [2],
+            #[expand(modport)]
+            bus: modport InterfaceA::slave,
+        ) {
+            assign o_d = '{0, 0};
+        }
+        "#;
+
+        let mut metadata = Metadata::create_default("prj").unwrap();
+        metadata.ipxact.bus_interfaces.push(IpxactBusInterface {
+            interface: "InterfaceA".to_string(),
+            modport: "slave".to_string(),
+            mode: IpxactBusMode::Slave,
+            bus_type: "acme:bus:a:1.0".to_string(),
+            abstraction_type: Some("acme:bus:a_rtl:1.0".to_string()),
+            port_maps: [("data".to_string(), "DATA_IN".to_string())].into(),
+        });
+        let errors = analyze(&metadata, code);
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let symbol = symbol_table::get_all()
+            .into_iter()
+            .find(|x| x.token.to_string() == "ModuleA")
+            .unwrap();
+        let xml = export_component(&metadata, &symbol, None).unwrap();
+        assert!(xml.contains("<ipxact:moduleName>prj_ModuleA</ipxact:moduleName>"));
+        assert!(xml.contains("<ipxact:description>Module A</ipxact:description>"));
+        assert!(xml.contains(r#"<ipxact:moduleParameter parameterId="W" resolve="user">"#));
+        assert!(xml.contains("<ipxact:left>W-1</ipxact:left>"));
+        assert!(
+            xml.contains(r#"<ipxact:busType vendor="acme" library="bus" name="a" version="1.0"/>"#)
+        );
+        assert!(xml.contains("<ipxact:name>DATA_IN</ipxact:name>"));
+        assert!(xml.contains("<ipxact:name>__bus_data</ipxact:name>"));
+        assert!(xml.contains("<ipxact:slave/>"));
+    }
+
+    #[test]
+    fn import() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ipxact:component xmlns:ipxact="http://www.accellera.org/XMLSchema/IPXACT/1685-2014">
+  <ipxact:vendor>acme</ipxact:vendor>
+  <ipxact:library>ip</ipxact:library>
+  <ipxact:name>fifo</ipxact:name>
+  <ipxact:version>1.0</ipxact:version>
+  <ipxact:description>FIFO &amp; &#x3C;buffer&gt;</ipxact:description>
+  <ipxact:model>
+    <ipxact:instantiations>
+      <ipxact:componentInstantiation>
+        <ipxact:name>rtl</ipxact:name>
+        <ipxact:moduleName>acme_fifo</ipxact:moduleName>
+        <ipxact:moduleParameters>
+          <ipxact:moduleParameter parameterId="id_width">
+            <ipxact:name>WIDTH</ipxact:name>
+            <ipxact:value>8</ipxact:value>
+          </ipxact:moduleParameter>
+        </ipxact:moduleParameters>
+      </ipxact:componentInstantiation>
+    </ipxact:instantiations>
+    <ipxact:ports>
+      <ipxact:port>
+        <ipxact:name>clk</ipxact:name>
+        <ipxact:wire><ipxact:direction>in</ipxact:direction></ipxact:wire>
+      </ipxact:port>
+      <ipxact:port>
+        <ipxact:name>wdata</ipxact:name>
+        <ipxact:wire>
+          <ipxact:direction>in</ipxact:direction>
+          <ipxact:vectors>
+            <ipxact:vector>
+              <ipxact:left>id_width - 1</ipxact:left>
+              <ipxact:right>0</ipxact:right>
+            </ipxact:vector>
+          </ipxact:vectors>
+        </ipxact:wire>
+      </ipxact:port>
+      <ipxact:port>
+        <ipxact:name>__level</ipxact:name>
+        <ipxact:wire>
+          <ipxact:direction>out</ipxact:direction>
+          <ipxact:vectors>
+            <ipxact:vector>
+              <ipxact:left>3</ipxact:left>
+              <ipxact:right>0</ipxact:right>
+            </ipxact:vector>
+          </ipxact:vectors>
+        </ipxact:wire>
+      </ipxact:port>
+    </ipxact:ports>
+  </ipxact:model>
+</ipxact:component>
+"#;
+
+        let code = import_component(xml, "fifo.xml").unwrap();
+        assert!(code.contains("/// FIFO & <buffer>\npub proto module fifo_proto"));
+        assert!(code.contains("wdata: input logic<WIDTH>,"));
+        assert!(code.contains("level: output logic<4>,"));
+        assert!(code.contains("inst u: $sv::acme_fifo"));
+        assert!(code.contains("__level: level,"));
+
+        let metadata = Metadata::create_default("prj").unwrap();
+        let errors = analyze(&metadata, &code);
+        assert!(errors.is_empty(), "{errors:?}");
+
+        assert!(import_component("<ipxact:busDefinition/>", "").is_err());
+        assert!(import_component("<ipxact:component>", "").is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use log::debug;
+use miette::{IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use veryl_analyzer::{symbol_table, type_dag};
+use veryl_metadata::Metadata;
+use veryl_parser::resource_table::{self, PathId};
+use veryl_parser::veryl_token::TokenSource;
+use veryl_path::PathSet;
+
+const INDEX_FILE: &str = "index.json";
+
+/// Persistent cache of emitted outputs placed at `.build/cache`.
+///
+/// Each entry is keyed by the content hash of the source file, the content hashes of
+/// all files it depends on through the file DAG, and the build configuration.
+/// So an entry is invalidated when the file or any of its dependencies is modified.
+///
+/// The file DAG of the last build is also stored to plan a partial build,
+/// which parses and analyzes the invalidated files and their dependencies only.
+pub struct BuildCache {
+    dir: PathBuf,
+    index: CacheIndex,
+    config_hash: String,
+    content_hashes: HashMap<PathBuf, String>,
+    pub hit: usize,
+    pub miss: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CacheIndex {
+    entries: BTreeMap<PathBuf, String>,
+    #[serde(default)]
+    analysis: Option<Analysis>,
+}
+
+/// Analysis result of the last build which reported no diagnostic
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Analysis {
+    /// Files which each file depends on through the file DAG
+    pub dependencies: BTreeMap<PathBuf, Vec<PathBuf>>,
+    /// Symbols defined at the top level of each file
+    pub symbols: BTreeMap<PathBuf, Vec<String>>,
+    /// Files in the order of filelist
+    pub filelist: Vec<PathBuf>,
+    pub include_tests: bool,
+}
+
+/// Files processed by a partial build
+pub struct Plan {
+    /// Files which should be emitted again
+    pub dirty: HashSet<PathBuf>,
+    /// Dirty files and their dependencies which should be analyzed
+    pub analyzed: HashSet<PathBuf>,
+    pub keys: HashMap<PathBuf, String>,
+    pub analysis: Analysis,
+}
+
+impl BuildCache {
+    pub fn load(metadata: &Metadata) -> Self {
+        let dir = metadata.project_dot_build_path().join("cache");
+        let index = fs::read_to_string(dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|x| serde_json::from_str(&x).ok())
+            .unwrap_or_default();
+
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(veryl_metadata::VERYL_VERSION.as_bytes());
+        hasher.update(metadata.project.name.as_bytes());
+        hasher.update(
+            serde_json::to_string(&metadata.build)
+                .unwrap_or_default()
+                .as_bytes(),
+        );
+        hasher.update(
+            serde_json::to_string(&metadata.format)
+                .unwrap_or_default()
+                .as_bytes(),
+        );
+        let config_hash = hasher.finalize().to_hex().to_string();
+
+        Self {
+            dir,
+            index,
+            config_hash,
+            content_hashes: HashMap::new(),
+            hit: 0,
+            miss: 0,
+        }
+    }
+
+    /// Register the content of source files which are read by the current build
+    pub fn add_content(&mut self, path: &Path, input: &str) {
+        let hash = blake3::hash(input.as_bytes()).to_hex().to_string();
+        self.content_hashes.insert(path.to_path_buf(), hash);
+    }
+
+    /// Files which each file depends on through the file DAG.
+    /// This should be called after `type_dag` is constructed.
+    pub fn dependencies(paths: &[PathSet]) -> BTreeMap<PathBuf, Vec<PathBuf>> {
+        // invert dependent_files to get dependencies of each file
+        let mut dependencies: HashMap<PathId, HashSet<PathId>> = HashMap::new();
+        for (path, dependents) in type_dag::dependent_files() {
+            for x in dependents {
+                dependencies.entry(x).or_default().insert(path);
+            }
+        }
+
+        let mut ret = BTreeMap::new();
+        for path in paths {
+            let id = resource_table::insert_path(&path.src);
+            let mut x: Vec<_> = dependencies
+                .get(&id)
+                .map(|x| x.iter().map(|x| PathBuf::from(x.to_string())).collect())
+                .unwrap_or_default();
+            x.sort();
+            ret.insert(path.src.clone(), x);
+        }
+        ret
+    }
+
+    /// Symbols defined at the top level of each file.
+    /// This should be called after `analyze_pass1`.
+    pub fn symbols(paths: &[PathSet]) -> BTreeMap<PathBuf, Vec<String>> {
+        let mut ret: BTreeMap<_, Vec<_>> =
+            paths.iter().map(|x| (x.src.clone(), Vec::new())).collect();
+        for symbol in symbol_table::get_all() {
+            if symbol.namespace.paths.len() == 1
+                && let TokenSource::File { path, .. } = symbol.token.source
+                && let Some(x) = ret.get_mut(&PathBuf::from(path.to_string()))
+            {
+                x.push(format!("{}::{}", symbol.namespace, symbol.token));
+            }
+        }
+        for x in ret.values_mut() {
+            x.sort();
+        }
+        ret
+    }
+
+    /// Calculate cache keys of all registered files
+    pub fn keys(
+        &self,
+        paths: &[PathSet],
+        dependencies: &BTreeMap<PathBuf, Vec<PathBuf>>,
+    ) -> HashMap<PathBuf, String> {
+        let mut ret = HashMap::new();
+        for path in paths {
+            let Some(content) = self.content_hashes.get(&path.src) else {
+                continue;
+            };
+
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(self.config_hash.as_bytes());
+            hasher.update(path.prj.as_bytes());
+            hasher.update(path.dst.to_string_lossy().as_bytes());
+            hasher.update(path.map.to_string_lossy().as_bytes());
+            hasher.update(content.as_bytes());
+
+            for x in dependencies.get(&path.src).into_iter().flatten() {
+                // unknown dependency makes the key unique to invalidate the entry
+                let hash = self.content_hashes.get(x).cloned().unwrap_or_else(|| {
+                    fs::read(x).map_or(String::new(), |x| blake3::hash(&x).to_hex().to_string())
+                });
+                hasher.update(x.to_string_lossy().as_bytes());
+                hasher.update(hash.as_bytes());
+            }
+
+            ret.insert(path.src.clone(), hasher.finalize().to_hex().to_string());
+        }
+        ret
+    }
+
+    /// Plan a partial build from the analysis result of the last build.
+    /// `None` means that all files should be analyzed.
+    /// This should be called after the content of all files are registered.
+    pub fn plan(&self, paths: &[PathSet], include_tests: bool) -> Option<Plan> {
+        let analysis = self.index.analysis.as_ref()?;
+        if analysis.include_tests != include_tests
+            || analysis.dependencies.len() != paths.len()
+            || paths
+                .iter()
+                .any(|x| !analysis.dependencies.contains_key(&x.src))
+        {
+            return None;
+        }
+
+        let keys = self.keys(paths, &analysis.dependencies);
+        let mut dirty = HashSet::new();
+        for path in paths {
+            let key = keys.get(&path.src)?;
+            let cached = self.index.entries.get(&path.src) == Some(key)
+                && self.entry_path(key, "sv").exists();
+            if !cached {
+                dirty.insert(path.src.clone());
+            }
+        }
+
+        let mut analyzed = dirty.clone();
+        for x in &dirty {
+            analyzed.extend(analysis.dependencies[x].iter().cloned());
+        }
+
+        Some(Plan {
+            dirty,
+            analyzed,
+            keys,
+            analysis: analysis.clone(),
+        })
+    }
+
+    /// Set the analysis result used by the next build
+    pub fn set_analysis(&mut self, analysis: Option<Analysis>) {
+        self.index.analysis = analysis;
+    }
+
+    fn entry_path(&self, key: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{extension}"))
+    }
+
+    /// Get cached output and source map
+    pub fn get(&mut self, src: &Path, key: &str) -> Option<(Vec<u8>, Option<Vec<u8>>)> {
+        let ret = if self.index.entries.get(src).is_some_and(|x| x == key) {
+            let output = fs::read(self.entry_path(key, "sv")).ok();
+            let map = fs::read(self.entry_path(key, "sv.map")).ok();
+            output.map(|x| (x, map))
+        } else {
+            None
+        };
+
+        if ret.is_some() {
+            self.hit += 1;
+        } else {
+            self.miss += 1;
+        }
+        ret
+    }
+
+    pub fn insert(
+        &mut self,
+        src: &Path,
+        key: &str,
+        output: &[u8],
+        map: Option<&[u8]>,
+    ) -> Result<()> {
+        if !self.dir.exists() {
+            fs::create_dir_all(&self.dir).into_diagnostic()?;
+        }
+
+        if let Some(old) = self
+            .index
+            .entries
+            .insert(src.to_path_buf(), key.to_string())
+            && old != key
+        {
+            self.remove_entry(&old);
+        }
+
+        fs::write(self.entry_path(key, "sv"), output).into_diagnostic()?;
+        let map_path = self.entry_path(key, "sv.map");
+        if let Some(map) = map {
+            fs::write(map_path, map).into_diagnostic()?;
+        } else if map_path.exists() {
+            fs::remove_file(map_path).into_diagnostic()?;
+        }
+        Ok(())
+    }
+
+    fn remove_entry(&self, key: &str) {
+        let _ = fs::remove_file(self.entry_path(key, "sv"));
+        let _ = fs::remove_file(self.entry_path(key, "sv.map"));
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if !self.dir.exists() {
+            fs::create_dir_all(&self.dir).into_diagnostic()?;
+        }
+
+        let text = serde_json::to_string(&self.index).into_diagnostic()?;
+        fs::write(self.dir.join(INDEX_FILE), text).into_diagnostic()?;
+
+        debug!("Used build cache ({} hit, {} miss)", self.hit, self.miss);
+        Ok(())
+    }
+}
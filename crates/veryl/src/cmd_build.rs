@@ -1,17 +1,21 @@
 use crate::OptBuild;
 use crate::StopWatch;
+use crate::build_cache::{Analysis, BuildCache, Plan};
 use crate::cmd_check::CheckError;
 use crate::context::Context;
 use crate::diff::print_diff;
 use crate::utils;
-use log::{debug, info};
+use crate::watcher;
+use log::{debug, info, warn};
 use miette::{IntoDiagnostic, Result, WrapErr};
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tempfile::TempDir;
 use veryl_analyzer::namespace::Namespace;
-use veryl_analyzer::symbol::SymbolKind;
+use veryl_analyzer::symbol::{Symbol, SymbolKind};
+use veryl_analyzer::symbol_path::SymbolPath;
 use veryl_analyzer::{Analyzer, symbol_table, type_dag};
 use veryl_emitter::Emitter;
 use veryl_metadata::{FilelistType, Metadata, SourceMapTarget, Target};
@@ -20,11 +24,28 @@ use veryl_path::PathSet;
 
 pub struct CmdBuild {
     opt: OptBuild,
+    force_incremental: bool,
+    analyzed_files: AtomicUsize,
 }
 
 impl CmdBuild {
     pub fn new(opt: OptBuild) -> Self {
-        Self { opt }
+        Self {
+            opt,
+            force_incremental: false,
+            analyzed_files: AtomicUsize::new(0),
+        }
+    }
+
+    /// Use the build cache even if `build.incremental` is disabled
+    pub fn force_incremental(mut self, x: bool) -> Self {
+        self.force_incremental = x;
+        self
+    }
+
+    /// Number of files parsed and analyzed by the last execution
+    pub fn analyzed_files(&self) -> usize {
+        self.analyzed_files.load(Ordering::Relaxed)
     }
 
     pub fn exec(
@@ -32,8 +53,37 @@ impl CmdBuild {
         metadata: &mut Metadata,
         include_tests: bool,
         quiet: bool,
+        ir: Option<&mut veryl_analyzer::ir::Ir>,
+    ) -> Result<bool> {
+        if self.opt.watch {
+            watcher::watch(metadata, |metadata, changed| {
+                // generated files of the previous cycle can be reused
+                let incremental = self.force_incremental || changed.is_some();
+                self.exec_once(metadata, include_tests, quiet, incremental, None)
+            })
+        } else {
+            self.exec_once(metadata, include_tests, quiet, self.force_incremental, ir)
+        }
+    }
+
+    fn exec_once(
+        &self,
+        metadata: &mut Metadata,
+        include_tests: bool,
+        quiet: bool,
+        force_incremental: bool,
         mut ir: Option<&mut veryl_analyzer::ir::Ir>,
     ) -> Result<bool> {
+        if let Some((first, rest)) = self.opt.filelist_type.split_first() {
+            metadata.build.filelist_type = (*first).into();
+            metadata.build.filelist_types = rest.iter().map(|x| (*x).into()).collect();
+            metadata.check()?;
+        }
+
+        if !self.opt.top.is_empty() {
+            metadata.build.tops = self.opt.top.clone();
+        }
+
         let paths = metadata.paths(&self.opt.files, true, true)?;
 
         let mut check_error = CheckError::new(metadata.build.error_count_limit);
@@ -41,6 +91,25 @@ impl CmdBuild {
 
         let mut stopwatch = StopWatch::new();
 
+        // cache can't be used for check because emitted outputs should be compared
+        let incremental = metadata.build.incremental || force_incremental;
+        let mut cache = if incremental && !self.opt.check {
+            Some(BuildCache::load(metadata))
+        } else {
+            None
+        };
+
+        // partial build can't provide IR and reachability of all files
+        let partial = ir.is_none()
+            && metadata.build.tops.is_empty()
+            && !matches!(metadata.build.target, Target::Bundle { .. });
+        if partial
+            && let Some(cache) = &mut cache
+            && let Some(ret) = self.exec_partial(metadata, &paths, include_tests, quiet, cache)?
+        {
+            return Ok(ret);
+        }
+
         for path in &paths {
             info!("Processing file ({})", path.src.to_string_lossy());
 
@@ -49,6 +118,10 @@ impl CmdBuild {
                 .wrap_err("")?;
             let parser = Parser::parse(&input, &path.src)?;
 
+            if let Some(cache) = &mut cache {
+                cache.add_content(&path.src, &input);
+            }
+
             let analyzer = Analyzer::new(metadata);
             let mut errors = analyzer.analyze_pass1(&path.prj, &parser.veryl);
             check_error = check_error.append(&mut errors).check_err()?;
@@ -56,6 +129,7 @@ impl CmdBuild {
             let context = Context::new(path.clone(), input, parser, analyzer)?;
             contexts.push(context);
         }
+        self.analyzed_files.store(contexts.len(), Ordering::Relaxed);
 
         debug!(
             "Executed parse/analyze_pass1 ({} milliseconds, {} files)",
@@ -63,6 +137,8 @@ impl CmdBuild {
             paths.len(),
         );
 
+        let symbols = cache.as_ref().map(|_| BuildCache::symbols(&paths));
+
         let mut errors = Analyzer::analyze_post_pass1();
         check_error = check_error.append(&mut errors).check_err()?;
 
@@ -71,8 +147,23 @@ impl CmdBuild {
             stopwatch.lap()
         );
 
-        if metadata.build.incremental && metadata.build_info.veryl_version_match() {
-            Self::check_skip(metadata, &mut contexts);
+        // cached files skip emission only because diagnostics of analyze_pass2 should be kept
+        let dependencies = cache.as_ref().map(|_| BuildCache::dependencies(&paths));
+        let mut keys = HashMap::new();
+        let mut cached = HashMap::new();
+        if let Some(cache) = &mut cache
+            && let Some(dependencies) = &dependencies
+        {
+            keys = cache.keys(&paths, dependencies);
+            for context in &contexts {
+                let src = &context.path.src;
+                if let Some(key) = keys.get(src)
+                    && let Some(x) = cache.get(src, key)
+                {
+                    debug!("Using cached file ({})", src.to_string_lossy());
+                    cached.insert(src.clone(), x);
+                }
+            }
         }
 
         let mut analyzer_context = veryl_analyzer::Context::default();
@@ -99,6 +190,22 @@ impl CmdBuild {
             stopwatch.lap()
         );
 
+        if !metadata.build.tops.is_empty() {
+            let reachable = Self::reachable_files(metadata)?;
+            for context in &mut contexts {
+                if !reachable.contains(&context.path.src) {
+                    if context.path.prj == metadata.project.name {
+                        warn!(
+                            "Skipping unreachable file ({})",
+                            context.path.src.to_string_lossy()
+                        );
+                    }
+                    context.skip = true;
+                    cached.remove(&context.path.src);
+                }
+            }
+        }
+
         let temp_dir = if let Target::Bundle { .. } = &metadata.build.target {
             Some(TempDir::new().into_diagnostic()?)
         } else {
@@ -107,110 +214,431 @@ impl CmdBuild {
 
         let mut all_pass = true;
         for context in contexts.drain(..) {
-            if !context.skip {
-                let path = &context.path;
-                let (dst, map) = if let Some(ref temp_dir) = temp_dir {
-                    let dst_temp = temp_dir.path().join(
-                        path.dst
-                            .strip_prefix(metadata.project_path())
-                            .into_diagnostic()?,
-                    );
-                    let map_temp = temp_dir.path().join(
-                        path.map
-                            .strip_prefix(metadata.project_path())
-                            .into_diagnostic()?,
-                    );
-                    (dst_temp, map_temp)
+            let path = &context.path;
+            if let Some((output, source_map)) = cached.remove(&path.src) {
+                Self::restore_output(metadata, path, temp_dir.as_ref(), &output, source_map)?;
+            } else if !context.skip {
+                let key = keys.get(&path.src);
+                let cache = cache.as_mut();
+                all_pass &=
+                    self.emit_output(metadata, &context, temp_dir.as_ref(), quiet, cache, key)?;
+            }
+            // context (including parser AST and input string) is dropped here
+        }
+
+        debug!("Executed emit ({} milliseconds)", stopwatch.lap());
+
+        if !self.opt.check {
+            let sorted = Self::sort_filelist(metadata, &paths, include_tests)?;
+
+            // analysis result can be reused only if it is complete and has no diagnostic
+            if let Some(cache) = &mut cache {
+                let analysis = if partial && check_error.related.is_empty() {
+                    Some(Analysis {
+                        dependencies: dependencies.unwrap_or_default(),
+                        symbols: symbols.unwrap_or_default(),
+                        filelist: sorted.iter().map(|x| x.src.clone()).collect(),
+                        include_tests,
+                    })
                 } else {
-                    (path.dst.clone(), path.map.clone())
+                    None
                 };
+                cache.set_analysis(analysis);
+                cache.save()?;
+            }
 
-                let mut emitter = Emitter::new(metadata, &path.src, &dst, &map);
-                emitter.emit(&path.prj, &context.parser.veryl, &context.input);
+            self.gen_filelist(metadata, sorted, temp_dir)?;
+        }
 
-                let dst_dir = dst.parent().unwrap();
-                if !dst_dir.exists() {
-                    std::fs::create_dir_all(dst.parent().unwrap()).into_diagnostic()?;
-                }
+        debug!("Executed filelist ({} milliseconds)", stopwatch.lap());
 
-                let exclude_check = context.path.prj == "$std";
+        let _ = check_error.check_err()?;
+        Ok(all_pass)
+    }
 
-                if self.opt.check && !exclude_check {
-                    let output = fs::read_to_string(&dst).unwrap_or(String::new());
-                    if output != emitter.as_str() {
-                        if !quiet {
-                            print_diff(&path.src, &output, emitter.as_str());
-                        }
-                        all_pass = false;
-                    }
-                } else {
-                    let written = utils::write_file_if_changed(&dst, emitter.as_str().as_bytes())?;
-                    if written {
-                        debug!("Output file ({})", dst.to_string_lossy());
-                    }
+    /// Build by analyzing only the modified files and their dependencies,
+    /// and restoring the others from the cache.
+    /// `None` is returned if all files should be analyzed by full build.
+    fn exec_partial(
+        &self,
+        metadata: &mut Metadata,
+        paths: &[PathSet],
+        include_tests: bool,
+        quiet: bool,
+        cache: &mut BuildCache,
+    ) -> Result<Option<bool>> {
+        let mut inputs = HashMap::new();
+        for path in paths {
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            cache.add_content(&path.src, &input);
+            inputs.insert(path.src.clone(), input);
+        }
+
+        let Some(plan) = cache.plan(paths, include_tests) else {
+            return Ok(None);
+        };
+
+        // analyzer tables are thread local,
+        // so the tables of the partial build are discarded if full build is necessary
+        std::thread::scope(|s| {
+            s.spawn(|| self.exec_plan(metadata, paths, inputs, quiet, cache, plan))
+                .join()
+                .unwrap_or_else(|x| std::panic::resume_unwind(x))
+        })
+    }
+
+    fn exec_plan(
+        &self,
+        metadata: &mut Metadata,
+        paths: &[PathSet],
+        mut inputs: HashMap<PathBuf, String>,
+        quiet: bool,
+        cache: &mut BuildCache,
+        plan: Plan,
+    ) -> Result<Option<bool>> {
+        let mut stopwatch = StopWatch::new();
 
-                    metadata.add_generated_file(dst);
+        let analyzed: Vec<_> = paths
+            .iter()
+            .filter(|x| plan.analyzed.contains(&x.src))
+            .cloned()
+            .collect();
 
-                    if metadata.build.sourcemap_target != SourceMapTarget::None {
-                        let source_map = emitter.source_map();
-                        source_map.set_source_content(&context.input);
-                        let source_map = source_map.to_bytes().into_diagnostic()?;
+        // diagnostics are reported by full build
+        let mut errors = Vec::new();
+        let mut contexts = Vec::new();
+        for path in &analyzed {
+            info!("Processing file ({})", path.src.to_string_lossy());
 
-                        let map_dir = map.parent().unwrap();
-                        if !map_dir.exists() {
-                            std::fs::create_dir_all(map.parent().unwrap()).into_diagnostic()?;
-                        }
+            let input = inputs.remove(&path.src).unwrap_or_default();
+            let Ok(parser) = Parser::parse(&input, &path.src) else {
+                return Ok(None);
+            };
 
-                        let written = utils::write_file_if_changed(&map, &source_map)?;
-                        if written {
-                            debug!("Output map ({})", map.to_string_lossy());
-                        }
+            let analyzer = Analyzer::new(metadata);
+            errors.append(&mut analyzer.analyze_pass1(&path.prj, &parser.veryl));
 
-                        metadata.add_generated_file(map);
-                    }
-                }
+            let context = Context::new(path.clone(), input, parser, analyzer)?;
+            contexts.push(context);
+        }
+        self.analyzed_files.store(contexts.len(), Ordering::Relaxed);
+
+        debug!(
+            "Executed parse/analyze_pass1 ({} milliseconds, {} of {} files)",
+            stopwatch.lap(),
+            analyzed.len(),
+            paths.len(),
+        );
+
+        let symbols = BuildCache::symbols(&analyzed);
+        errors.append(&mut Analyzer::analyze_post_pass1());
+        let dependencies = BuildCache::dependencies(&analyzed);
+
+        let mut analyzer_context = veryl_analyzer::Context::default();
+        for context in &contexts {
+            let path = &context.path;
+            errors.append(&mut context.analyzer.analyze_pass2(
+                &path.prj,
+                &context.parser.veryl,
+                &mut analyzer_context,
+                None,
+            ));
+        }
+        errors.append(&mut Analyzer::analyze_post_pass2());
+
+        debug!("Executed analyze ({} milliseconds)", stopwatch.lap());
+
+        // files depending on modified files and filelist order may be changed
+        let unchanged = plan.dirty.iter().all(|x| {
+            dependencies.get(x) == plan.analysis.dependencies.get(x)
+                && symbols.get(x) == plan.analysis.symbols.get(x)
+        });
+        if !errors.is_empty() || !unchanged {
+            debug!("Falling back to full build");
+            return Ok(None);
+        }
+
+        let mut contexts: HashMap<_, _> = contexts
+            .into_iter()
+            .map(|x| (x.path.src.clone(), x))
+            .collect();
+        for path in paths {
+            let key = plan.keys.get(&path.src);
+            if plan.dirty.contains(&path.src)
+                && let Some(context) = contexts.remove(&path.src)
+            {
+                self.emit_output(metadata, &context, None, quiet, Some(cache), key)?;
+            } else if let Some(key) = key
+                && let Some((output, source_map)) = cache.get(&path.src, key)
+            {
+                Self::restore_output(metadata, path, None, &output, source_map)?;
+            } else {
+                return Ok(None);
             }
-            // context (including parser AST and input string) is dropped here
         }
 
         debug!("Executed emit ({} milliseconds)", stopwatch.lap());
 
-        if !self.opt.check {
-            self.gen_filelist(metadata, &paths, temp_dir, include_tests)?;
-        }
+        let mut table: HashMap<_, _> = paths.iter().map(|x| (&x.src, x)).collect();
+        let sorted = plan
+            .analysis
+            .filelist
+            .iter()
+            .filter_map(|x| table.remove(x).cloned())
+            .collect();
+        self.gen_filelist(metadata, sorted, None)?;
+
+        cache.save()?;
 
         debug!("Executed filelist ({} milliseconds)", stopwatch.lap());
 
-        let _ = check_error.check_err()?;
-        Ok(all_pass)
+        Ok(Some(true))
+    }
+
+    /// Emit the file, and return whether the output is not changed in check mode
+    fn emit_output(
+        &self,
+        metadata: &mut Metadata,
+        context: &Context,
+        temp_dir: Option<&TempDir>,
+        quiet: bool,
+        cache: Option<&mut BuildCache>,
+        key: Option<&String>,
+    ) -> Result<bool> {
+        let path = &context.path;
+        let (dst, map) = Self::output_paths(metadata, path, temp_dir)?;
+
+        let mut emitter = Emitter::new(metadata, &path.src, &dst, &map);
+        emitter.emit(&path.prj, &context.parser.veryl, &context.input);
+
+        let exclude_check = context.path.prj == "$std";
+
+        if self.opt.check && !exclude_check {
+            let output = fs::read_to_string(&dst).unwrap_or(String::new());
+            if output != emitter.as_str() {
+                if !quiet {
+                    print_diff(&path.src, &output, emitter.as_str());
+                }
+                return Ok(false);
+            }
+        } else {
+            let output = emitter.as_str().as_bytes().to_vec();
+            Self::write_output(metadata, &dst, &output)?;
+
+            let source_map = if metadata.build.sourcemap_target != SourceMapTarget::None {
+                let source_map = emitter.source_map();
+                source_map.set_source_content(&context.input);
+                let source_map = source_map.to_bytes().into_diagnostic()?;
+                Self::write_output(metadata, &map, &source_map)?;
+                Some(source_map)
+            } else {
+                None
+            };
+
+            if let Some(cache) = cache
+                && let Some(key) = key
+            {
+                cache.insert(&path.src, key, &output, source_map.as_deref())?;
+            }
+        }
+        Ok(true)
+    }
+
+    fn restore_output(
+        metadata: &mut Metadata,
+        path: &PathSet,
+        temp_dir: Option<&TempDir>,
+        output: &[u8],
+        source_map: Option<Vec<u8>>,
+    ) -> Result<()> {
+        let (dst, map) = Self::output_paths(metadata, path, temp_dir)?;
+        Self::write_output(metadata, &dst, output)?;
+        if let Some(source_map) = source_map {
+            Self::write_output(metadata, &map, &source_map)?;
+        }
+        Ok(())
     }
 
-    fn gen_filelist_line(&self, metadata: &Metadata, path: &Path) -> Result<String> {
+    /// Output paths of the file, which are placed at the temporary directory for bundle
+    fn output_paths(
+        metadata: &Metadata,
+        path: &PathSet,
+        temp_dir: Option<&TempDir>,
+    ) -> Result<(PathBuf, PathBuf)> {
+        if let Some(temp_dir) = temp_dir {
+            let dst_temp = temp_dir.path().join(
+                path.dst
+                    .strip_prefix(metadata.project_path())
+                    .into_diagnostic()?,
+            );
+            let map_temp = temp_dir.path().join(
+                path.map
+                    .strip_prefix(metadata.project_path())
+                    .into_diagnostic()?,
+            );
+            Ok((dst_temp, map_temp))
+        } else {
+            Ok((path.dst.clone(), path.map.clone()))
+        }
+    }
+
+    fn write_output(metadata: &mut Metadata, path: &PathBuf, output: &[u8]) -> Result<()> {
+        let dir = path.parent().unwrap();
+        if !dir.exists() {
+            std::fs::create_dir_all(dir).into_diagnostic()?;
+        }
+
+        let written = utils::write_file_if_changed(path, output)?;
+        if written {
+            debug!("Output file ({})", path.to_string_lossy());
+        }
+
+        metadata.add_generated_file(path.clone());
+        Ok(())
+    }
+
+    pub fn gen_filelist_text(
+        metadata: &Metadata,
+        filelist_type: FilelistType,
+        paths: &[PathBuf],
+    ) -> Result<String> {
         let base_path = metadata.project_path();
-        let path = path.canonicalize().into_diagnostic()?;
-        let relative = path.strip_prefix(&base_path).into_diagnostic()?;
-        Ok(match metadata.build.filelist_type {
-            FilelistType::Absolute => format!("{}\n", path.to_string_lossy()),
-            FilelistType::Relative => format!("{}\n", relative.to_string_lossy()),
+
+        let mut files = Vec::new();
+        for path in paths {
+            let path = path.canonicalize().into_diagnostic()?;
+            let relative = path
+                .strip_prefix(&base_path)
+                .into_diagnostic()?
+                .to_path_buf();
+            files.push((path, relative));
+        }
+
+        let mut include_dirs = Vec::new();
+        for file in &metadata.test.include_files {
+            let dir = base_path.join(file);
+            let dir = dir.parent().unwrap_or(&base_path);
+            let dir = dir.canonicalize().into_diagnostic()?;
+            if !include_dirs.contains(&dir) {
+                include_dirs.push(dir);
+            }
+        }
+
+        let mut text = String::new();
+        match filelist_type {
+            FilelistType::Absolute => {
+                for (path, _) in &files {
+                    text.push_str(&format!("{}\n", path.to_string_lossy()));
+                }
+            }
+            FilelistType::Relative => {
+                for (_, relative) in &files {
+                    text.push_str(&format!("{}\n", relative.to_string_lossy()));
+                }
+            }
             FilelistType::Flgen => {
-                format!("source_file '{}'\n", relative.to_string_lossy())
+                for (_, relative) in &files {
+                    text.push_str(&format!("source_file '{}'\n", relative.to_string_lossy()));
+                }
             }
-        })
+            FilelistType::Vivado => {
+                // add_files is used in project mode, and read_verilog is used in non-project mode
+                text.push_str("set veryl_files [list \\\n");
+                for (path, _) in &files {
+                    text.push_str(&format!("    {{{}}} \\\n", path.to_string_lossy()));
+                }
+                text.push_str("]\n");
+                let dirs: Vec<_> = include_dirs
+                    .iter()
+                    .map(|x| format!(" {{{}}}", x.to_string_lossy()))
+                    .collect();
+                text.push_str(&format!("set veryl_include_dirs [list{}]\n", dirs.join("")));
+                text.push_str("if {[current_project -quiet] ne \"\"} {\n");
+                text.push_str("    add_files -norecurse $veryl_files\n");
+                text.push_str(
+                    "    set_property file_type SystemVerilog [get_files $veryl_files]\n",
+                );
+                if !include_dirs.is_empty() {
+                    text.push_str(
+                        "    set_property include_dirs $veryl_include_dirs [current_fileset]\n",
+                    );
+                }
+                text.push_str("} else {\n");
+                text.push_str("    # pass `-include_dirs $veryl_include_dirs` to synth_design\n");
+                text.push_str("    read_verilog -sv $veryl_files\n");
+                text.push_str("}\n");
+            }
+            FilelistType::Quartus => {
+                for dir in &include_dirs {
+                    let dir = dir.strip_prefix(&base_path).unwrap_or(dir);
+                    text.push_str(&format!(
+                        "set_global_assignment -name SEARCH_PATH \"{}\"\n",
+                        dir.to_string_lossy()
+                    ));
+                }
+                for (_, relative) in &files {
+                    text.push_str(&format!(
+                        "set_global_assignment -name SYSTEMVERILOG_FILE \"{}\"\n",
+                        relative.to_string_lossy()
+                    ));
+                }
+            }
+            FilelistType::Verilator => {
+                for dir in &include_dirs {
+                    text.push_str(&format!("+incdir+{}\n", dir.to_string_lossy()));
+                }
+                for (path, _) in &files {
+                    text.push_str(&format!("{}\n", path.to_string_lossy()));
+                }
+            }
+            FilelistType::Fusesoc => {
+                let version = metadata
+                    .project
+                    .version
+                    .as_ref()
+                    .map(|x| x.to_string())
+                    .unwrap_or("0.0.0".to_string());
+                text.push_str("CAPI=2:\n");
+                text.push_str(&format!("name: ::{}:{}\n", metadata.project.name, version));
+                if let Some(description) = &metadata.project.description {
+                    // JSON string is also a valid double-quoted YAML scalar
+                    let description = serde_json::to_string(description).into_diagnostic()?;
+                    text.push_str(&format!("description: {description}\n"));
+                }
+                text.push_str("\nfilesets:\n");
+                text.push_str("  rtl:\n");
+                text.push_str("    file_type: systemVerilogSource\n");
+                text.push_str("    files:\n");
+                // files are listed in compile order because FuseSoC keeps the order
+                for (_, relative) in &files {
+                    text.push_str(&format!("      - {}\n", relative.to_string_lossy()));
+                }
+                for file in &metadata.test.include_files {
+                    text.push_str(&format!(
+                        "      - {}: {{is_include_file: true}}\n",
+                        file.to_string_lossy()
+                    ));
+                }
+                text.push_str("\ntargets:\n");
+                text.push_str("  default:\n");
+                text.push_str("    filesets: [rtl]\n");
+            }
+        }
+
+        Ok(text)
     }
 
     fn gen_filelist(
         &self,
         metadata: &mut Metadata,
-        paths: &[PathSet],
+        paths: Vec<PathSet>,
         temp_dir: Option<TempDir>,
-        include_tests: bool,
     ) -> Result<()> {
-        let filelist_path = metadata.filelist_path();
         let base_path = metadata.project_path();
 
-        let paths = Self::sort_filelist(metadata, paths, include_tests);
-
-        let text = if let Target::Bundle { path } = &metadata.build.target {
+        let files = if let Target::Bundle { path } = &metadata.build.target {
             let temp_dir = temp_dir.unwrap();
             let mut text = String::new();
             let target_path = base_path.join(path);
@@ -230,29 +658,68 @@ impl CmdBuild {
 
             metadata.add_generated_file(target_path.clone());
 
-            self.gen_filelist_line(metadata, &target_path)?
+            vec![target_path]
         } else {
-            let mut text = String::new();
-            for path in paths {
-                let line = self.gen_filelist_line(metadata, &path.dst)?;
-                text.push_str(&line);
-            }
-            text
+            paths.into_iter().map(|x| x.dst).collect()
         };
 
-        utils::write_file_if_changed(&filelist_path, text.as_bytes())?;
+        for (filelist_type, filelist_path) in metadata.filelist_paths() {
+            let text = Self::gen_filelist_text(metadata, filelist_type, &files)?;
+
+            utils::write_file_if_changed(&filelist_path, text.as_bytes())?;
 
-        info!("Output filelist ({})", filelist_path.to_string_lossy());
-        metadata.add_generated_file(filelist_path);
+            info!("Output filelist ({})", filelist_path.to_string_lossy());
+            metadata.add_generated_file(filelist_path);
+        }
 
         Ok(())
     }
 
+    fn resolve_tops(metadata: &Metadata) -> Result<Vec<Symbol>> {
+        let mut prj_namespace = Namespace::new();
+        prj_namespace.push(resource_table::insert_str(&metadata.project.name));
+
+        let mut ret = Vec::new();
+        for top in &metadata.build.tops {
+            let path = SymbolPath::new(&[resource_table::insert_str(top)]);
+            let symbol = symbol_table::resolve((&path, &prj_namespace))
+                .ok()
+                .map(|x| (*x.found).clone())
+                .filter(|x| {
+                    matches!(
+                        x.kind,
+                        SymbolKind::Module(_) | SymbolKind::Interface(_) | SymbolKind::Package(_)
+                    )
+                });
+            let Some(symbol) = symbol else {
+                miette::bail!(
+                    "top \"{top}\" is not found in project \"{}\"",
+                    metadata.project.name
+                );
+            };
+            ret.push(symbol);
+        }
+
+        Ok(ret)
+    }
+
+    fn reachable_files(metadata: &Metadata) -> Result<HashSet<PathBuf>> {
+        let mut ret = HashSet::new();
+        for top in Self::resolve_tops(metadata)? {
+            for symbol in type_dag::dependencies(top.id) {
+                if let TokenSource::File { path, .. } = symbol.token.source {
+                    ret.insert(PathBuf::from(format!("{path}")));
+                }
+            }
+        }
+        Ok(ret)
+    }
+
     pub fn sort_filelist(
         metadata: &Metadata,
         paths: &[PathSet],
         include_tests: bool,
-    ) -> Vec<PathSet> {
+    ) -> Result<Vec<PathSet>> {
         let mut table = HashMap::new();
         for path in paths {
             table.insert(path.src.clone(), path);
@@ -262,11 +729,19 @@ impl CmdBuild {
         let mut prj_namespace = Namespace::new();
         prj_namespace.push(resource_table::insert_str(&metadata.project.name));
 
-        let mut candidate_symbols: Vec<_> = type_dag::connected_components()
-            .into_iter()
-            .filter(|symbols| symbols[0].namespace.included(&prj_namespace))
-            .flatten()
-            .collect();
+        let mut candidate_symbols: Vec<_> = if metadata.build.tops.is_empty() {
+            type_dag::connected_components()
+                .into_iter()
+                .filter(|symbols| symbols[0].namespace.included(&prj_namespace))
+                .flatten()
+                .collect()
+        } else {
+            // Collect files reachable from tops only
+            Self::resolve_tops(metadata)?
+                .into_iter()
+                .flat_map(|x| type_dag::dependencies(x.id))
+                .collect()
+        };
         if include_tests {
             candidate_symbols.extend(symbol_table::get_all().into_iter().filter(|symbol| {
                 matches!(symbol.kind, SymbolKind::Test(_))
@@ -299,44 +774,13 @@ impl CmdBuild {
             }
         }
 
-        for path in used_paths.into_values() {
-            ret.push(path.clone());
-        }
-
-        ret
-    }
-
-    pub fn check_skip(metadata: &Metadata, contexts: &mut Vec<Context>) {
-        let mut updated_files = HashSet::new();
-        let dependent_files = veryl_analyzer::type_dag::dependent_files();
-        for context in contexts.iter() {
-            let updated = if let Some(generated) =
-                metadata.build_info.generated_files.get(&context.path.dst)
-            {
-                context.modified > *generated
-            } else {
-                true
-            };
-            if updated {
-                let path = resource_table::insert_path(&context.path.src);
-                updated_files.insert(path);
-
-                if let Some(dependents) = dependent_files.get(&path) {
-                    for x in dependents {
-                        updated_files.insert(*x);
-                    }
-                }
-            }
-        }
-        for context in contexts {
-            let path = resource_table::insert_path(&context.path.src);
-            if !updated_files.contains(&path) {
-                context.skip = true;
-                debug!(
-                    "Skipping unmodified file ({})",
-                    context.path.src.to_string_lossy()
-                );
+        // remaining files keep the original order to make the filelist stable
+        for path in paths {
+            if let Some(x) = used_paths.remove(&path.src) {
+                ret.push(x.clone());
             }
         }
+
+        Ok(ret)
     }
 }
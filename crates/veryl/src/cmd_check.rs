@@ -1,5 +1,6 @@
 use crate::OptCheck;
 use crate::context::Context;
+use crate::watcher;
 use log::info;
 use miette::{self, Diagnostic, IntoDiagnostic, Result, WrapErr};
 use std::fs;
@@ -65,6 +66,15 @@ impl CmdCheck {
     }
 
     pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        if self.opt.watch {
+            // all files are checked in each cycle to report diagnostics of untouched files again
+            watcher::watch(metadata, |metadata, _| self.exec_once(metadata))
+        } else {
+            self.exec_once(metadata)
+        }
+    }
+
+    fn exec_once(&self, metadata: &mut Metadata) -> Result<bool> {
         let paths = metadata.paths(&self.opt.files, true, true)?;
 
         let mut check_error = CheckError::new(metadata.build.error_count_limit);
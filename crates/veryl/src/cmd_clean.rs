@@ -36,6 +36,14 @@ impl CmdClean {
             fs::remove_dir_all(&doc_path).into_diagnostic()?;
         }
 
+        for name in ["cache", "jit_cache"] {
+            let cache_path = metadata.project_dot_build_path().join(name);
+            if cache_path.exists() {
+                info!("Removing dir  ({})", cache_path.to_string_lossy());
+                fs::remove_dir_all(&cache_path).into_diagnostic()?;
+            }
+        }
+
         metadata.build_info.generated_files.clear();
         metadata.build_info.veryl_version = None;
         metadata.save_build_info()?;
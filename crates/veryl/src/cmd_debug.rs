@@ -0,0 +1,127 @@
+use crate::cmd_build::CmdBuild;
+use crate::{OptBuild, OptDebug};
+use log::info;
+use miette::{IntoDiagnostic, Result};
+use std::io::BufReader;
+use veryl_analyzer::symbol::TestType;
+use veryl_analyzer::symbol_table;
+use veryl_metadata::{FilelistType, Metadata};
+use veryl_parser::resource_table;
+use veryl_simulator::debug_adapter::{
+    DebugSession, collect_hierarchy_blocks, collect_source_blocks,
+};
+use veryl_simulator::ir::{Config, Event, build_ir};
+use veryl_simulator::simulator::Simulator;
+use veryl_simulator::simulator_error::SimulatorError;
+use veryl_simulator::testbench::{
+    TestResult, TestbenchStatement, build_clock_periods, build_event_map,
+    convert_initial_to_testbench,
+};
+
+pub struct CmdDebug {
+    opt: OptDebug,
+}
+
+struct DebugTarget {
+    sim: Simulator,
+    stmts: Vec<TestbenchStatement>,
+    top: resource_table::StrId,
+}
+
+impl CmdDebug {
+    pub fn new(opt: OptDebug) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        metadata.build.filelist_type = FilelistType::Absolute;
+        metadata.build.filelist_types.clear();
+        metadata.build.tops.clear();
+
+        let build = CmdBuild::new(OptBuild {
+            files: self.opt.files.clone(),
+            check: false,
+            top: Vec::new(),
+            filelist_type: Vec::new(),
+            watch: false,
+        });
+
+        let mut ir = veryl_analyzer::ir::Ir::default();
+        build.exec(metadata, true, false, Some(&mut ir))?;
+
+        // stdout is used as DAP transport, so logs are written to stderr only
+        let stdin = std::io::stdin();
+        let stdout = std::io::stdout();
+        let mut session = DebugSession::new(
+            BufReader::new(stdin.lock()),
+            stdout.lock(),
+            collect_source_blocks(&ir),
+        );
+
+        let Some(args) = session.wait_launch().into_diagnostic()? else {
+            return Ok(true);
+        };
+        let test = args["test"]
+            .as_str()
+            .map(|x| x.to_string())
+            .or_else(|| self.opt.test.clone());
+        let Some(test) = test else {
+            session.fail_launch("test name is not specified");
+            return Ok(false);
+        };
+
+        let target = match self.prepare(metadata, &ir, &test) {
+            Ok(x) => x,
+            Err(e) => {
+                session.fail_launch(&e.to_string());
+                return Ok(false);
+            }
+        };
+
+        info!("Debugging test ({test})");
+        let blocks = collect_hierarchy_blocks(&ir, target.top);
+        let result = session.run(target.sim, &target.stmts, blocks);
+        Ok(result == TestResult::Pass)
+    }
+
+    fn prepare(
+        &self,
+        metadata: &Metadata,
+        ir: &veryl_analyzer::ir::Ir,
+        test: &str,
+    ) -> std::result::Result<DebugTarget, SimulatorError> {
+        let tests = symbol_table::get_tests(&metadata.project.name);
+        let Some((name, property)) = tests.iter().find(|(x, _)| x.to_string() == test) else {
+            return Err(SimulatorError::TestFailed {
+                message: format!("test {test} is not found"),
+            });
+        };
+        if !matches!(property.r#type, TestType::Native) {
+            return Err(SimulatorError::TestFailed {
+                message: format!("test {test} is not a native test"),
+            });
+        }
+
+        let top = property.top.unwrap_or(*name);
+        let config = Config {
+            use_jit: !self.opt.disable_jit,
+            trace_statements: true,
+            ..Config::default()
+        };
+        let sim_ir = build_ir(ir, top, &config)?;
+        let sim = Simulator::new(sim_ir, None);
+
+        let event_map = build_event_map(&sim.ir.event_statements, &sim.ir.module_variables);
+        let clock_periods = build_clock_periods(&sim.ir.event_statements);
+        let initial = sim
+            .ir
+            .event_statements
+            .get(&Event::Initial)
+            .ok_or_else(|| {
+                SimulatorError::no_initial_block(&sim.ir.name.to_string(), &sim.ir.token)
+            })?;
+        let stmts = convert_initial_to_testbench(initial, &event_map, &clock_periods, 3);
+
+        Ok(DebugTarget { sim, stmts, top })
+    }
+}
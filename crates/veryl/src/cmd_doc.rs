@@ -1,6 +1,7 @@
-use crate::OptDoc;
 use crate::context::Context;
-use crate::doc::{DocBuilder, TopLevelItem};
+use crate::doc::{DocBuilder, SourceFile, TopLevelItem, build_coverage, build_json, highlight};
+use crate::regmap::Regmap;
+use crate::{DocFormat, OptDoc};
 use log::info;
 use miette::{IntoDiagnostic, Result, WrapErr};
 use std::collections::BTreeMap;
@@ -54,55 +55,104 @@ impl CmdDoc {
 
         Analyzer::analyze_post_pass2();
 
+        if self.opt.coverage {
+            let mut projects: Vec<_> = paths.iter().map(|x| x.prj.clone()).collect();
+            projects.sort();
+            projects.dedup();
+            for coverage in build_coverage(metadata, &projects) {
+                println!("{coverage}");
+            }
+            return Ok(true);
+        }
+
+        if let DocFormat::Json = self.opt.format {
+            let mut projects: Vec<_> = paths.iter().map(|x| x.prj.clone()).collect();
+            projects.sort();
+            projects.dedup();
+            let json = build_json(metadata, &projects);
+            let text = serde_json::to_string_pretty(&json).into_diagnostic()?;
+
+            let doc_path = metadata.doc_path();
+            fs::create_dir_all(&doc_path).into_diagnostic()?;
+            let json_path = doc_path.join("doc.json");
+            fs::write(&json_path, text).into_diagnostic()?;
+            info!("Output document ({})", json_path.to_string_lossy());
+            return Ok(true);
+        }
+
+        let mut projects: Vec<_> = paths
+            .iter()
+            .map(|x| x.prj.clone())
+            .filter(|x| !x.starts_with('$'))
+            .collect();
+        projects.sort();
+        projects.dedup();
+
         let mut modules = BTreeMap::new();
         let mut proto_modules = BTreeMap::new();
         let mut interfaces = BTreeMap::new();
         let mut packages = BTreeMap::new();
 
         for symbol in veryl_analyzer::symbol_table::get_all() {
+            if !symbol.public || symbol.namespace.depth() != 1 {
+                continue;
+            }
+            let project = symbol.namespace.paths[0].to_string();
+            if !projects.contains(&project) {
+                continue;
+            }
+
             let text = resource_table::get_str_value(symbol.token.text).unwrap();
-            let file_name = text.clone();
+            // items of dependencies are prefixed by the project name to avoid name conflicts
+            let file_name = if project == metadata.project.name {
+                text.clone()
+            } else {
+                format!("{project}.{text}")
+            };
+            let key = (project.clone(), text.clone());
             let symbol = symbol.clone();
-            if format!("{}", symbol.namespace) == metadata.project.name && symbol.public {
-                match &symbol.kind {
-                    SymbolKind::Module(x) => {
-                        let html_name = fmt_generic_parameters(&text, &x.generic_parameters);
-                        let item = TopLevelItem {
-                            file_name,
-                            html_name,
-                            symbol,
-                        };
-                        modules.insert(text, item);
-                    }
-                    SymbolKind::ProtoModule(_) => {
-                        let html_name = file_name.clone();
-                        let item = TopLevelItem {
-                            file_name,
-                            html_name,
-                            symbol,
-                        };
-                        proto_modules.insert(text, item);
-                    }
-                    SymbolKind::Interface(x) => {
-                        let html_name = fmt_generic_parameters(&text, &x.generic_parameters);
-                        let item = TopLevelItem {
-                            file_name,
-                            html_name,
-                            symbol,
-                        };
-                        interfaces.insert(text, item);
-                    }
-                    SymbolKind::Package(x) => {
-                        let html_name = fmt_generic_parameters(&text, &x.generic_parameters);
-                        let item = TopLevelItem {
-                            file_name,
-                            html_name,
-                            symbol,
-                        };
-                        packages.insert(text, item);
-                    }
-                    _ => (),
+            match &symbol.kind {
+                SymbolKind::Module(x) => {
+                    let html_name = fmt_generic_parameters(&text, &x.generic_parameters);
+                    let item = TopLevelItem {
+                        project,
+                        file_name,
+                        html_name,
+                        symbol,
+                    };
+                    modules.insert(key, item);
+                }
+                SymbolKind::ProtoModule(_) => {
+                    let html_name = text.clone();
+                    let item = TopLevelItem {
+                        project,
+                        file_name,
+                        html_name,
+                        symbol,
+                    };
+                    proto_modules.insert(key, item);
+                }
+                SymbolKind::Interface(x) => {
+                    let html_name = fmt_generic_parameters(&text, &x.generic_parameters);
+                    let item = TopLevelItem {
+                        project,
+                        file_name,
+                        html_name,
+                        symbol,
+                    };
+                    interfaces.insert(key, item);
+                }
+                SymbolKind::Package(x) => {
+                    let html_name = fmt_generic_parameters(&text, &x.generic_parameters);
+                    let item = TopLevelItem {
+                        project,
+                        file_name,
+                        html_name,
+                        symbol,
+                    };
+                    packages.insert(key, item);
                 }
+                _ => (),
             }
         }
 
@@ -111,7 +161,34 @@ impl CmdDoc {
         let interfaces: Vec<_> = interfaces.into_values().collect();
         let packages: Vec<_> = packages.into_values().collect();
 
-        let builder = DocBuilder::new(metadata, modules, proto_modules, interfaces, packages)?;
+        let base = metadata.project_path();
+        let regmaps = metadata
+            .regmap
+            .sources
+            .iter()
+            .map(|x| Regmap::load(base.join(x)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let sources: Vec<_> = contexts
+            .into_iter()
+            .filter(|x| projects.contains(&x.path.prj))
+            .map(|x| SourceFile {
+                project: x.path.prj.clone(),
+                path_id: resource_table::insert_path(&x.path.src),
+                code: highlight(&x.parser.veryl, &x.input),
+                path: x.path.src,
+            })
+            .collect();
+
+        let builder = DocBuilder::new(
+            metadata,
+            modules,
+            proto_modules,
+            interfaces,
+            packages,
+            regmaps,
+            sources,
+        )?;
         builder.build()?;
 
         Ok(true)
@@ -3,7 +3,9 @@ use crate::diff::print_diff;
 use crate::utils;
 use log::{debug, info};
 use miette::{IntoDiagnostic, Result, WrapErr};
+use std::collections::HashMap;
 use std::fs;
+use std::path::PathBuf;
 use veryl_analyzer::Analyzer;
 use veryl_formatter::Formatter;
 use veryl_metadata::Metadata;
@@ -19,7 +21,19 @@ impl CmdFmt {
     }
 
     pub fn exec(&self, metadata: &mut Metadata, quiet: bool) -> Result<bool> {
-        let paths = metadata.paths(&self.opt.files, true, false)?;
+        let mut files = self.opt.files.clone();
+        let mut lines: HashMap<PathBuf, Vec<(u32, u32)>> = HashMap::new();
+        for x in &self.opt.lines {
+            let path = fs::canonicalize(&x.path)
+                .into_diagnostic()
+                .wrap_err(format!("{}", x.path.to_string_lossy()))?;
+            if !lines.contains_key(&path) {
+                files.push(x.path.clone());
+            }
+            lines.entry(path).or_default().push((x.beg, x.end));
+        }
+
+        let paths = metadata.paths(&files, true, false)?;
 
         let mut all_pass = true;
         for path in &paths {
@@ -33,7 +47,11 @@ impl CmdFmt {
             let _ = analyzer.analyze_pass1(&path.prj, &parser.veryl);
 
             let mut formatter = Formatter::new(metadata);
-            formatter.format(&parser.veryl, &input);
+            if let Some(lines) = lines.get(&path.src) {
+                formatter.format_lines(&parser.veryl, &input, lines);
+            } else {
+                formatter.format(&parser.veryl, &input);
+            }
 
             let pass = input.as_str() == formatter.as_str();
 
@@ -0,0 +1,120 @@
+use crate::OptIpxact;
+use crate::context::Context;
+use crate::ipxact::{export_component, import_component};
+use crate::utils;
+use log::{debug, info};
+use miette::{IntoDiagnostic, Result, WrapErr};
+use std::fs;
+use std::path::Path;
+use veryl_analyzer::symbol::SymbolKind;
+use veryl_analyzer::{Analyzer, symbol_table};
+use veryl_formatter::Formatter;
+use veryl_metadata::Metadata;
+use veryl_parser::Parser;
+
+pub struct CmdIpxact {
+    opt: OptIpxact,
+}
+
+impl CmdIpxact {
+    pub fn new(opt: OptIpxact) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        if !self.opt.import.is_empty() {
+            for src in &self.opt.import {
+                self.import(metadata, src)?;
+            }
+            return Ok(true);
+        }
+
+        let paths = metadata.paths(&self.opt.files, true, true)?;
+
+        let mut contexts = Vec::new();
+
+        for path in &paths {
+            info!("Processing file ({})", path.src.to_string_lossy());
+
+            let input = fs::read_to_string(&path.src)
+                .into_diagnostic()
+                .wrap_err("")?;
+            let parser = Parser::parse(&input, &path.src)?;
+            let analyzer = Analyzer::new(metadata);
+            analyzer.analyze_pass1(&path.prj, &parser.veryl);
+
+            let context = Context::new(path.clone(), input, parser, analyzer)?;
+            contexts.push(context);
+        }
+
+        Analyzer::analyze_post_pass1();
+
+        let mut analyzer_context = veryl_analyzer::Context::default();
+        for context in &contexts {
+            let path = &context.path;
+            context.analyzer.analyze_pass2(
+                &path.prj,
+                &context.parser.veryl,
+                &mut analyzer_context,
+                None,
+            );
+        }
+
+        Analyzer::analyze_post_pass2();
+
+        let ipxact_path = metadata.ipxact_path();
+        let mut symbols: Vec<_> = symbol_table::get_all()
+            .into_iter()
+            .filter(|x| {
+                x.public
+                    && x.namespace.to_string() == metadata.project.name
+                    && matches!(&x.kind, SymbolKind::Module(x) if x.generic_parameters.is_empty())
+            })
+            .collect();
+        symbols.sort_by_key(|x| x.token.to_string());
+
+        for symbol in &symbols {
+            let source = symbol.token.source.to_string();
+            let dst = paths
+                .iter()
+                .find(|x| x.src.to_string_lossy() == source)
+                .map(|x| x.dst.clone());
+            let file = dst
+                .as_ref()
+                .and_then(|x| pathdiff::diff_paths(x, &ipxact_path));
+
+            let text = export_component(metadata, symbol, file.as_deref())?;
+            let path = ipxact_path.join(format!("{}.xml", symbol.token));
+            fs::create_dir_all(&ipxact_path).into_diagnostic()?;
+            if utils::write_file_if_changed(&path, text.as_bytes())? {
+                debug!("Output file ({})", path.to_string_lossy());
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn import(&self, metadata: &Metadata, src: &Path) -> Result<()> {
+        info!("Processing file ({})", src.to_string_lossy());
+
+        let text = fs::read_to_string(src)
+            .into_diagnostic()
+            .wrap_err(format!("{}", src.to_string_lossy()))?;
+        let source = src
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let code =
+            import_component(&text, &source).wrap_err(format!("{}", src.to_string_lossy()))?;
+
+        let path = src.with_extension("veryl");
+        let parser = Parser::parse(&code, &path).wrap_err(format!("{}", path.to_string_lossy()))?;
+        let mut formatter = Formatter::new(metadata);
+        formatter.format(&parser.veryl, &code);
+
+        if utils::write_file_if_changed(&path, formatter.as_str().as_bytes())? {
+            debug!("Output file ({})", path.to_string_lossy());
+        }
+        Ok(())
+    }
+}
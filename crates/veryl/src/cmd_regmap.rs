@@ -0,0 +1,90 @@
+use crate::OptRegmap;
+use crate::diff::print_diff;
+use crate::regmap::{Regmap, generate_c_header, generate_veryl, snake_case};
+use crate::utils;
+use log::{debug, info};
+use miette::{IntoDiagnostic, Result, WrapErr};
+use std::fs;
+use std::path::{Path, PathBuf};
+use veryl_formatter::Formatter;
+use veryl_metadata::Metadata;
+use veryl_parser::Parser;
+
+pub struct CmdRegmap {
+    opt: OptRegmap,
+}
+
+impl CmdRegmap {
+    pub fn new(opt: OptRegmap) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &Metadata, quiet: bool) -> Result<bool> {
+        let sources: Vec<PathBuf> = if self.opt.files.is_empty() {
+            let base = metadata.project_path();
+            metadata
+                .regmap
+                .sources
+                .iter()
+                .map(|x| base.join(x))
+                .collect()
+        } else {
+            self.opt.files.clone()
+        };
+
+        let mut all_pass = true;
+        for src in &sources {
+            info!("Processing file ({})", src.to_string_lossy());
+
+            let regmap = Regmap::load(src)?;
+            let (veryl_path, c_header_path) = output_paths(metadata, src, &regmap);
+            let source = src
+                .file_name()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let code = generate_veryl(&regmap, &source);
+            let parser = Parser::parse(&code, &veryl_path)
+                .wrap_err(format!("{}", veryl_path.to_string_lossy()))?;
+            let mut formatter = Formatter::new(metadata);
+            formatter.format(&parser.veryl, &code);
+            let code = formatter.as_str().to_string();
+            let header = generate_c_header(&regmap, &source);
+
+            for (path, text) in [(&veryl_path, code), (&c_header_path, header)] {
+                if self.opt.check {
+                    let org = fs::read_to_string(path).unwrap_or_default();
+                    if org != text {
+                        if !quiet {
+                            print_diff(path, &org, &text);
+                        }
+                        all_pass = false;
+                    }
+                } else {
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent).into_diagnostic()?;
+                    }
+                    if utils::write_file_if_changed(path, text.as_bytes())? {
+                        debug!("Output file ({})", path.to_string_lossy());
+                    }
+                }
+            }
+        }
+
+        Ok(all_pass)
+    }
+}
+
+/// Paths of the generated Veryl source and C header.
+/// The Veryl source is put beside the description, and the C header is put at
+/// `regmap.c_header_path` if it is specified.
+pub fn output_paths(metadata: &Metadata, src: &Path, regmap: &Regmap) -> (PathBuf, PathBuf) {
+    let stem = snake_case(&regmap.name);
+    let veryl_path = src.with_file_name(format!("{stem}.veryl"));
+    let c_header_path = if let Some(x) = &metadata.regmap.c_header_path {
+        metadata.project_path().join(x).join(format!("{stem}.h"))
+    } else {
+        src.with_file_name(format!("{stem}.h"))
+    };
+    (veryl_path, c_header_path)
+}
@@ -0,0 +1,172 @@
+use crate::OptBuild;
+use crate::OptRepl;
+use crate::cmd_build::CmdBuild;
+use console::{Key, Style, Term};
+use miette::{IntoDiagnostic, Result};
+use veryl_metadata::{FilelistType, Metadata};
+use veryl_parser::resource_table;
+use veryl_simulator::ir::{Config, build_ir};
+use veryl_simulator::repl::{Repl, ReplResponse};
+use veryl_simulator::simulator::Simulator;
+
+const PROMPT: &str = "veryl> ";
+
+pub struct CmdRepl {
+    opt: OptRepl,
+}
+
+impl CmdRepl {
+    pub fn new(opt: OptRepl) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        metadata.build.filelist_type = FilelistType::Absolute;
+        metadata.build.filelist_types.clear();
+        metadata.build.tops.clear();
+
+        let build = CmdBuild::new(OptBuild {
+            files: self.opt.files.clone(),
+            check: false,
+            top: Vec::new(),
+            filelist_type: Vec::new(),
+            watch: false,
+        });
+
+        let mut ir = veryl_analyzer::ir::Ir::default();
+        build.exec(metadata, true, false, Some(&mut ir))?;
+
+        let config = Config {
+            use_jit: !self.opt.disable_jit,
+            ..Config::default()
+        };
+        let top = resource_table::insert_str(&self.opt.top);
+        let sim_ir = build_ir(&ir, top, &config)?;
+        let mut repl = Repl::new(Simulator::new(sim_ir, None));
+
+        let term = Term::stdout();
+        let error = Style::new().red().bright();
+        let mut history = Vec::new();
+
+        term.write_line("Type 'help' for usage").into_diagnostic()?;
+        loop {
+            let line = if term.is_term() {
+                read_line(&term, &repl, &history).into_diagnostic()?
+            } else {
+                let mut line = String::new();
+                let len = std::io::stdin().read_line(&mut line).into_diagnostic()?;
+                (len != 0).then(|| line.trim_end().to_string())
+            };
+            let Some(line) = line else {
+                repl.stop_dump();
+                break;
+            };
+
+            if !line.trim().is_empty() && history.last() != Some(&line) {
+                history.push(line.clone());
+            }
+
+            match repl.execute(&line) {
+                Ok(ReplResponse::Output(x)) => {
+                    if !x.is_empty() {
+                        term.write_line(&x).into_diagnostic()?;
+                    }
+                }
+                Ok(ReplResponse::Quit) => break,
+                Err(x) => {
+                    term.write_line(&error.apply_to(x).to_string())
+                        .into_diagnostic()?;
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// Read a line with history and tab-completion, and return `None` at EOF
+fn read_line(term: &Term, repl: &Repl, history: &[String]) -> std::io::Result<Option<String>> {
+    let mut buffer = String::new();
+    let mut index = history.len();
+
+    term.write_str(PROMPT)?;
+    loop {
+        match term.read_key()? {
+            Key::Enter => {
+                term.write_line("")?;
+                return Ok(Some(buffer));
+            }
+            Key::CtrlC => {
+                term.write_line("")?;
+                return Ok(None);
+            }
+            Key::Char('\u{4}') if buffer.is_empty() => {
+                term.write_line("")?;
+                return Ok(None);
+            }
+            Key::Char(x) if !x.is_control() => buffer.push(x),
+            Key::Backspace => {
+                buffer.pop();
+            }
+            Key::ArrowUp if index > 0 => {
+                index -= 1;
+                buffer = history[index].clone();
+            }
+            Key::ArrowDown if index < history.len() => {
+                index += 1;
+                buffer = history.get(index).cloned().unwrap_or_default();
+            }
+            Key::Tab => {
+                let candidates = repl.complete(&buffer);
+                let word_start = buffer.rfind(' ').map(|x| x + 1).unwrap_or(0);
+                match candidates.as_slice() {
+                    [] => (),
+                    [x] => {
+                        buffer.truncate(word_start);
+                        buffer.push_str(x);
+                        if !has_child(repl, x) {
+                            buffer.push(' ');
+                        }
+                    }
+                    _ => {
+                        let prefix = common_prefix(&candidates);
+                        if prefix.len() > buffer.len() - word_start {
+                            buffer.truncate(word_start);
+                            buffer.push_str(&prefix);
+                        } else {
+                            term.write_line("")?;
+                            term.write_line(&candidates.join("  "))?;
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+        term.clear_line()?;
+        term.write_str(PROMPT)?;
+        term.write_str(&buffer)?;
+    }
+}
+
+fn has_child(repl: &Repl, path: &str) -> bool {
+    let prefix = format!("{path}.");
+    repl.paths().iter().any(|x| x.starts_with(&prefix))
+}
+
+fn common_prefix(candidates: &[String]) -> String {
+    let mut ret = candidates[0].clone();
+    for x in &candidates[1..] {
+        let len = ret
+            .chars()
+            .zip(x.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        ret.truncate(
+            ret.char_indices()
+                .nth(len)
+                .map(|x| x.0)
+                .unwrap_or(ret.len()),
+        );
+    }
+    ret
+}
@@ -0,0 +1,59 @@
+use crate::OptBuild;
+use crate::OptSim;
+use crate::cmd_build::CmdBuild;
+use log::info;
+use miette::{IntoDiagnostic, Result};
+use veryl_metadata::{FilelistType, Metadata};
+use veryl_parser::resource_table;
+use veryl_simulator::aot::{RUNTIME, RUNTIME_FILE, emit_object};
+use veryl_simulator::ir::Config;
+
+pub struct CmdSim {
+    opt: OptSim,
+}
+
+impl CmdSim {
+    pub fn new(opt: OptSim) -> Self {
+        Self { opt }
+    }
+
+    pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        metadata.build.filelist_type = FilelistType::Absolute;
+        metadata.build.filelist_types.clear();
+        metadata.build.tops.clear();
+
+        let build = CmdBuild::new(OptBuild {
+            files: self.opt.files.clone(),
+            check: false,
+            top: Vec::new(),
+            filelist_type: Vec::new(),
+            watch: false,
+        });
+
+        let mut ir = veryl_analyzer::ir::Ir::default();
+        build.exec(metadata, true, false, Some(&mut ir))?;
+
+        let top = resource_table::insert_str(&self.opt.top);
+        let model = emit_object(&ir, top, &Config::default())?;
+
+        let object_path = &self.opt.emit_object;
+        let header_path = object_path.with_extension("h");
+        let runtime_path = object_path.with_file_name(RUNTIME_FILE);
+        if let Some(dir) = object_path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(dir).into_diagnostic()?;
+        }
+
+        for (path, data) in [
+            (object_path, model.object.as_slice()),
+            (&header_path, model.header.as_bytes()),
+            (&runtime_path, RUNTIME.as_bytes()),
+        ] {
+            info!("Output file ({})", path.to_string_lossy());
+            std::fs::write(path, data).into_diagnostic()?;
+        }
+
+        Ok(true)
+    }
+}
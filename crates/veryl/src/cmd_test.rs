@@ -1,21 +1,32 @@
 use crate::cmd_build::CmdBuild;
-use crate::runner::{Cocotb, CocotbSource, Dsim, Vcs, Verilator, Vivado};
+use crate::runner::{Cocotb, CocotbSource, Dsim, Runner, Vcs, Verilator, Vivado};
+use crate::test_report::{TestCase, TestKind, TestReport};
+use crate::watcher;
 use crate::{OptBuild, OptTest};
 use log::{error, info, warn};
 use miette::Result;
+use std::collections::HashSet;
 use std::path::PathBuf;
-use veryl_analyzer::symbol::TestType;
+use std::time::{Duration, Instant};
+use veryl_analyzer::symbol::{TestType, WavedromProperty};
 use veryl_analyzer::symbol_table;
 use veryl_metadata::WaveFormFormat;
 use veryl_metadata::{FilelistType, Metadata, SimType, WaveFormTarget};
 use veryl_parser::resource_table::{self, PathId};
+use veryl_simulator::batch::{BatchSimulator, has_seed};
 use veryl_simulator::ir::{Config, Ir, ProtoModuleCache, build_ir_cached};
+use veryl_simulator::jit_cache;
 use veryl_simulator::output_buffer;
 use veryl_simulator::simulator::Simulator;
 use veryl_simulator::simulator_error::SimulatorError;
-use veryl_simulator::testbench::{TestResult, run_native_testbench};
+use veryl_simulator::testbench::{TestLimits, TestResult, run_native_testbench_with_limits};
 use veryl_simulator::wave_dumper::WaveDumper;
-use veryl_simulator::wavedrom::{self, SignalKind, classify_signals, parse_wavedrom};
+use veryl_simulator::wavedrom::{
+    self, SignalKind, WavedromSelection, classify_signals, parse_wavedrom,
+};
+
+/// Name of the built-in simulator in test reports
+const NATIVE_SIMULATOR: &str = "Veryl";
 
 pub struct CmdTest {
     opt: OptTest,
@@ -24,6 +35,8 @@ pub struct CmdTest {
 struct NativeTestJob {
     module_name: String,
     sim_ir: Ir,
+    /// Additional IRs of the seed sweep of tests with `#[seed(var)]`
+    sweep: Vec<Ir>,
     dump: Option<WaveDumper>,
 }
 
@@ -31,10 +44,19 @@ struct PendingNativeTest {
     test_name: String,
     top: Option<resource_table::StrId>,
     test_path: PathId,
+    wavedrom: Option<WavedromProperty>,
+    limits: TestLimits,
+    /// Seed variable given by `#[seed(var)]`
+    seed: Option<resource_table::StrId>,
 }
 
-fn wave_output_path(name: &str, test_path: PathId, metadata: &Metadata) -> PathBuf {
-    let target_name = format!("{}.{}", name, metadata.test.waveform_format.extension());
+fn wave_output_path(
+    name: &str,
+    test_path: PathId,
+    metadata: &Metadata,
+    extension: &str,
+) -> PathBuf {
+    let target_name = format!("{name}.{extension}");
     match &metadata.test.waveform_target {
         WaveFormTarget::Target => PathBuf::from(test_path.to_string())
             .parent()
@@ -44,12 +66,43 @@ fn wave_output_path(name: &str, test_path: PathId, metadata: &Metadata) -> PathB
     }
 }
 
+/// Signals and cycles of WaveDrom output if it is requested for the test.
+/// Options of `veryl test` take precedence over `#[wavedrom]` attribute.
+fn wavedrom_selection(
+    opt: &OptTest,
+    property: Option<&WavedromProperty>,
+) -> Option<WavedromSelection> {
+    let requested = opt.wavedrom || (opt.wave && property.is_some());
+    if !requested {
+        return None;
+    }
+
+    let signals = if opt.wavedrom_signals.is_empty() {
+        property
+            .map(|x| x.signals.iter().map(|x| x.to_string()).collect())
+            .unwrap_or_default()
+    } else {
+        opt.wavedrom_signals.clone()
+    };
+    let cycles = opt
+        .wavedrom_cycles
+        .or(property.and_then(|x| x.cycles))
+        .unwrap_or_default();
+    Some(WavedromSelection { signals, cycles })
+}
+
 fn create_wave_dumper(
     name: &str,
     test_path: PathId,
     metadata: &Metadata,
+    wavedrom: Option<WavedromSelection>,
 ) -> std::result::Result<WaveDumper, SimulatorError> {
-    let path = wave_output_path(name, test_path, metadata);
+    let extension = if wavedrom.is_some() {
+        "json"
+    } else {
+        metadata.test.waveform_format.extension()
+    };
+    let path = wave_output_path(name, test_path, metadata, extension);
     if let Some(parent) = path.parent()
         && !parent.exists()
     {
@@ -59,14 +112,18 @@ fn create_wave_dumper(
     }
     let path_str = path.to_string_lossy().to_string();
     info!("  Dumping waveform to {}", path_str);
-    let dumper = match metadata.test.waveform_format {
-        WaveFormFormat::Vcd => {
-            let file = std::fs::File::create(&path).map_err(|e| SimulatorError::IoError {
-                message: format!("failed to create waveform file {}: {e}", path.display()),
-            })?;
-            WaveDumper::new_vcd(Box::new(file))
+    let create_file = || {
+        std::fs::File::create(&path).map_err(|e| SimulatorError::IoError {
+            message: format!("failed to create waveform file {}: {e}", path.display()),
+        })
+    };
+    let dumper = if let Some(selection) = wavedrom {
+        WaveDumper::new_wavedrom(Box::new(create_file()?), selection)
+    } else {
+        match metadata.test.waveform_format {
+            WaveFormFormat::Vcd => WaveDumper::new_vcd(Box::new(create_file()?)),
+            WaveFormFormat::Fst => WaveDumper::new_fst(&path_str),
         }
-        WaveFormFormat::Fst => WaveDumper::new_fst(&path_str),
     };
     Ok(dumper.with_path(path))
 }
@@ -77,35 +134,61 @@ impl CmdTest {
     }
 
     pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        if self.opt.watch {
+            watcher::watch(metadata, |metadata, changed| {
+                self.exec_once(metadata, changed)
+            })
+        } else {
+            self.exec_once(metadata, None)
+        }
+    }
+
+    fn exec_once(
+        &self,
+        metadata: &mut Metadata,
+        changed: Option<&HashSet<PathBuf>>,
+    ) -> Result<bool> {
         // force filelist_type to absolute which can be refered from temporary directory
         metadata.build.filelist_type = FilelistType::Absolute;
+        metadata.build.filelist_types.clear();
+        // tests may not be reachable from tops
+        metadata.build.tops.clear();
 
         let build = CmdBuild::new(OptBuild {
             files: self.opt.files.clone(),
             check: false,
-        });
+            top: Vec::new(),
+            filelist_type: Vec::new(),
+            watch: false,
+        })
+        // generated files of the previous cycle can be reused
+        .force_incremental(changed.is_some());
 
         let mut ir = veryl_analyzer::ir::Ir::default();
         build.exec(metadata, true, false, Some(&mut ir))?;
 
-        let tests = symbol_table::get_tests(&metadata.project.name);
-        let doc_tests = symbol_table::get_doc_tests(&metadata.project.name);
+        let mut tests = symbol_table::get_tests(&metadata.project.name);
+        let mut doc_tests = symbol_table::get_doc_tests(&metadata.project.name);
 
-        let total_tests = tests.len();
-        let tests: Vec<_> = if self.opt.include_ignored {
-            tests
+        // run only tests affected by the modification
+        if let Some(changed) = changed {
+            let affected = watcher::affected_files(changed);
+            tests.retain(|(_, property)| affected.contains(&property.path));
+            doc_tests.retain(|x| affected.contains(&x.path));
+        }
+
+        let (tests, ignored_tests): (Vec<_>, Vec<_>) = if self.opt.include_ignored {
+            (tests, Vec::new())
         } else if self.opt.ignored {
             tests
                 .into_iter()
-                .filter(|(_, property)| property.ignored)
-                .collect()
+                .partition(|(_, property)| property.ignored)
         } else {
             tests
                 .into_iter()
-                .filter(|(_, property)| !property.ignored)
-                .collect()
+                .partition(|(_, property)| !property.ignored)
         };
-        let ignored_count = total_tests - tests.len();
+        let ignored_count = ignored_tests.len();
 
         if ignored_count > 0 {
             info!("{ignored_count} test(s) ignored");
@@ -143,15 +226,45 @@ impl CmdTest {
             metadata.test.simulator
         };
 
+        let jit_cache_dir = (!self.opt.disable_jit && !self.opt.disable_jit_cache)
+            .then(|| metadata.project_dot_build_path().join("jit_cache"));
         let config = Config {
             use_jit: !self.opt.disable_jit,
             disable_ff_opt: self.opt.disable_ff_opt,
+            threads: self.opt.threads,
+            jit_cache_dir: jit_cache_dir.clone(),
+            use_4state: self.opt.four_state,
+            xprop: self.opt.xprop.into(),
+            register_init: self.opt.register_init.to_config(self.opt.seed),
+            report_x_registers: self.opt.report_x,
             ..Config::default()
         };
         let mut proto_cache = ProtoModuleCache::default();
 
         let mut success = 0;
         let mut failure = 0;
+        let mut report = TestReport::new(&metadata.project.name);
+
+        for (test, property) in &ignored_tests {
+            let name = test.to_string();
+            if self
+                .opt
+                .test
+                .as_ref()
+                .is_none_or(|x| name.contains(x.as_str()))
+            {
+                let simulator = simulator_name(&property.r#type, sim_type);
+                report.push(TestCase::new(&name, test_kind(&property.r#type), simulator));
+            }
+        }
+
+        // Options of `veryl test` take precedence over `[test]` section
+        let limits =
+            test_limits(self.opt.timeout, self.opt.max_cycles, self.opt.max_time).or(&test_limits(
+                Some(metadata.test.timeout),
+                metadata.test.max_cycles,
+                metadata.test.max_time,
+            ));
 
         let mut pending_native: Vec<PendingNativeTest> = Vec::new();
         let mut non_native_tests = Vec::new();
@@ -163,6 +276,14 @@ impl CmdTest {
                         test_name: test.to_string(),
                         top: property.top,
                         test_path: property.path,
+                        wavedrom: property.wavedrom.clone(),
+                        limits: test_limits(
+                            property.limit.timeout,
+                            property.limit.max_cycles,
+                            property.limit.max_time,
+                        )
+                        .or(&limits),
+                        seed: property.seed,
                     });
                 }
                 _ => {
@@ -172,17 +293,21 @@ impl CmdTest {
         }
 
         if !pending_native.is_empty() {
-            let num_threads = std::thread::available_parallelism()
+            // Each native test uses `config.threads` threads
+            let num_threads = (std::thread::available_parallelism()
                 .map(|n| n.get())
                 .unwrap_or(1)
-                .min(pending_native.len());
+                / config.threads.max(1))
+            .clamp(1, pending_native.len());
             let pending_queue = std::sync::Mutex::new(pending_native.into_iter());
             let table_snapshot = resource_table::export_tables();
 
             type JobResult = (
                 String,
-                std::result::Result<(TestResult, Option<PathBuf>), SimulatorError>,
+                std::result::Result<TestResult, SimulatorError>,
+                Option<PathBuf>,
                 String,
+                std::time::Duration,
             );
             let ir_ref = &ir;
             let config_ref = &config;
@@ -203,27 +328,54 @@ impl CmdTest {
                                 let pending = queue.lock().unwrap().next();
                                 let Some(pending) = pending else { break };
                                 output_buffer::enable();
+                                let start = Instant::now();
                                 let build_result = prepare_native_test(
                                     ir_ref,
                                     &pending.test_name,
                                     &pending.top,
                                     opt_ref,
                                     pending.test_path,
+                                    pending.wavedrom.as_ref(),
+                                    pending.seed,
                                     metadata_ref,
                                     config_ref,
                                     &mut thread_cache,
                                 );
+                                let mut wave_path = None;
                                 let run_result = match build_result {
                                     Ok(job) => {
-                                        let wave_path =
+                                        wave_path =
                                             job.dump.as_ref().and_then(|d| d.path().cloned());
-                                        run_native_testbench(job.sim_ir, job.dump, job.module_name)
-                                            .map(|r| (r, wave_path))
+                                        if let Some(seed) = pending.seed {
+                                            let irs = std::iter::once(job.sim_ir)
+                                                .chain(job.sweep)
+                                                .collect();
+                                            BatchSimulator::from_irs(irs, job.dump, 1)
+                                                .run_seed_sweep(
+                                                    &job.module_name,
+                                                    seed,
+                                                    opt_ref.seed,
+                                                    &pending.limits,
+                                                )
+                                        } else {
+                                            run_native_testbench_with_limits(
+                                                job.sim_ir,
+                                                job.dump,
+                                                job.module_name,
+                                                &pending.limits,
+                                            )
+                                        }
                                     }
                                     Err(e) => Err(e),
                                 };
                                 let output = output_buffer::take();
-                                thread_results.push((pending.test_name, run_result, output));
+                                thread_results.push((
+                                    pending.test_name,
+                                    run_result,
+                                    wave_path,
+                                    output,
+                                    start.elapsed(),
+                                ));
                             }
                             thread_results
                         })
@@ -232,28 +384,31 @@ impl CmdTest {
                 handles.into_iter().map(|h| h.join().unwrap()).collect()
             });
 
-            for (test_name, result, output) in results.into_iter().flatten() {
+            for (test_name, result, wave_path, output, duration) in results.into_iter().flatten() {
                 info!("Executing test ({test_name})");
                 if !output.is_empty() {
                     print!("{output}");
                 }
-                match result {
-                    Ok((TestResult::Pass, wave_path)) => {
+                let case = TestCase::new(&test_name, TestKind::Native, NATIVE_SIMULATOR);
+                let message = match result {
+                    Ok(TestResult::Pass) => {
                         info!("Succeeded test ({test_name})");
                         success += 1;
+                        report.push(case.passed(duration, wave_path.clone()));
                         if let Some(path) = wave_path {
                             metadata.add_generated_file(path);
                         }
+                        continue;
                     }
-                    Ok((TestResult::Fail(msg), _)) => {
-                        error!("Failed test ({test_name}): {msg}");
-                        failure += 1;
-                    }
-                    Err(e) => {
-                        error!("Failed test ({test_name}): {e}");
-                        failure += 1;
-                    }
-                }
+                    Ok(TestResult::Fail(msg)) => msg,
+                    Err(e) => e.to_string(),
+                };
+                error!(
+                    "Failed test ({test_name}): {message} (seed = {})",
+                    self.opt.seed
+                );
+                failure += 1;
+                report.push(case.failed(duration, wave_path, message));
             }
         }
 
@@ -272,15 +427,37 @@ impl CmdTest {
                 TestType::Native => unreachable!(),
             };
 
-            if runner.run(metadata, *test, property.top, property.path, self.opt.wave)? {
-                success += 1;
-                if self.opt.wave {
-                    let test_name = test.to_string();
-                    let path = wave_output_path(&test_name, property.path, metadata);
-                    metadata.add_generated_file(path);
+            let test_name = test.to_string();
+            let case = TestCase::new(&test_name, TestKind::External, runner.name());
+            let wave_path = self.opt.wave.then(|| {
+                wave_output_path(
+                    &test_name,
+                    property.path,
+                    metadata,
+                    metadata.test.waveform_format.extension(),
+                )
+            });
+
+            let start = Instant::now();
+            match runner.run(metadata, *test, property.top, property.path, self.opt.wave) {
+                Ok(true) => {
+                    success += 1;
+                    report.push(case.passed(start.elapsed(), wave_path.clone()));
+                    if let Some(path) = wave_path {
+                        metadata.add_generated_file(path);
+                    }
+                }
+                Ok(false) => {
+                    failure += 1;
+                    let message = format!("{} reported failure", runner.name());
+                    report.push(case.failed(start.elapsed(), None, message));
+                }
+                Err(e) => {
+                    // Keep running the remaining tests so that the report covers all of them
+                    error!("Failed to run test ({test_name}): {e}");
+                    failure += 1;
+                    report.push(case.failed(start.elapsed(), None, e.to_string()));
                 }
-            } else {
-                failure += 1;
             }
         }
 
@@ -288,12 +465,14 @@ impl CmdTest {
             let module_name = dt.module_name.to_string();
             info!("Executing doc test ({module_name})");
 
+            let case = TestCase::new(&module_name, TestKind::Doc, NATIVE_SIMULATOR);
+            let start = Instant::now();
             match run_doc_test(
                 &ir,
                 &module_name,
                 &dt.wavedrom_json,
                 &dt.ports,
-                self.opt.wave,
+                &self.opt,
                 dt.path,
                 metadata,
                 &config,
@@ -302,6 +481,7 @@ impl CmdTest {
                 Ok(wave_path) => {
                     info!("Succeeded doc test ({module_name})");
                     success += 1;
+                    report.push(case.passed(start.elapsed(), wave_path.clone()));
                     if let Some(path) = wave_path {
                         metadata.add_generated_file(path);
                     }
@@ -309,6 +489,7 @@ impl CmdTest {
                 Err(e) => {
                     error!("Failed doc test ({module_name}): {e}");
                     failure += 1;
+                    report.push(case.failed(start.elapsed(), None, e.to_string()));
                 }
             }
         }
@@ -320,7 +501,22 @@ impl CmdTest {
         };
         let summary = format!("Completed tests : {success} passed, {failure} failed{ignored_msg}");
 
-        if self.opt.wave {
+        if let Some(dir) = &jit_cache_dir
+            && let Err(e) = jit_cache::prune(dir, self.opt.jit_cache_size * 1024 * 1024)
+        {
+            warn!("Failed to prune JIT cache ({}): {e}", dir.to_string_lossy());
+        }
+
+        for target in &self.opt.report {
+            if let Err(e) = report.write(target) {
+                error!(
+                    "Failed to write test report ({}): {e}",
+                    target.path.to_string_lossy()
+                );
+            }
+        }
+
+        if self.opt.wave || self.opt.wavedrom {
             metadata
                 .save_build_info()
                 .map_err(|e| miette::miette!("{e}"))?;
@@ -336,6 +532,34 @@ impl CmdTest {
     }
 }
 
+fn test_kind(x: &TestType) -> TestKind {
+    match x {
+        TestType::Native => TestKind::Native,
+        _ => TestKind::External,
+    }
+}
+
+fn simulator_name(x: &TestType, sim_type: SimType) -> &'static str {
+    match x {
+        TestType::Native => NATIVE_SIMULATOR,
+        TestType::Inline => match sim_type {
+            SimType::Verilator => Verilator::new().name(),
+            SimType::Vcs => Vcs::new().name(),
+            SimType::Dsim => Dsim::new().name(),
+            SimType::Vivado => Vivado::new().name(),
+        },
+        TestType::CocotbEmbed(_) | TestType::CocotbInclude(_) => "Cocotb",
+    }
+}
+
+fn test_limits(timeout: Option<u64>, max_cycles: Option<u64>, max_time: Option<u64>) -> TestLimits {
+    TestLimits {
+        timeout: timeout.map(Duration::from_secs),
+        max_cycles,
+        max_time,
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn prepare_native_test(
     ir: &veryl_analyzer::ir::Ir,
@@ -343,6 +567,8 @@ fn prepare_native_test(
     top: &Option<resource_table::StrId>,
     opt: &OptTest,
     test_path: PathId,
+    wavedrom: Option<&WavedromProperty>,
+    seed: Option<resource_table::StrId>,
     metadata: &Metadata,
     config: &Config,
     cache: &mut ProtoModuleCache,
@@ -360,10 +586,26 @@ fn prepare_native_test(
     })?;
     let sim_ir = build_ir_cached(ir, top_str_id, config, cache)?;
 
+    // Each seed of the sweep shares the cached ProtoModule and JIT code
+    let sweep = if seed.is_some_and(|x| has_seed(&sim_ir, x)) {
+        (1..opt.seeds)
+            .map(|_| build_ir_cached(ir, top_str_id, config, cache))
+            .collect::<std::result::Result<_, _>>()?
+    } else {
+        Vec::new()
+    };
+
     let module_name = sim_ir.name.to_string();
 
-    let dump = if opt.wave {
-        Some(create_wave_dumper(test_name, test_path, metadata)?)
+    let dump = if let Some(selection) = wavedrom_selection(opt, wavedrom) {
+        Some(create_wave_dumper(
+            test_name,
+            test_path,
+            metadata,
+            Some(selection),
+        )?)
+    } else if opt.wave {
+        Some(create_wave_dumper(test_name, test_path, metadata, None)?)
     } else {
         None
     };
@@ -371,6 +613,7 @@ fn prepare_native_test(
     Ok(NativeTestJob {
         module_name,
         sim_ir,
+        sweep,
         dump,
     })
 }
@@ -381,7 +624,7 @@ fn run_doc_test(
     module_name: &str,
     wavedrom_json: &str,
     ports: &[(String, String)],
-    wave: bool,
+    opt: &OptTest,
     source_path: PathId,
     metadata: &Metadata,
     config: &Config,
@@ -399,43 +642,52 @@ fn run_doc_test(
     })?;
     let sim_ir = build_ir_cached(ir, top_str_id, config, cache)?;
 
-    let dump = if wave {
+    let dump = if opt.wave || opt.wavedrom {
         let doc_name = format!("{}_doc", module_name);
-        Some(create_wave_dumper(&doc_name, source_path, metadata)?)
+        let wavedrom = wavedrom_selection(opt, None);
+        Some(create_wave_dumper(
+            &doc_name,
+            source_path,
+            metadata,
+            wavedrom,
+        )?)
     } else {
         None
     };
 
     let mut sim = Simulator::new(sim_ir, dump);
 
-    // Resolve clock event
-    let fallback_clock = || {
-        sim.ir
-            .event_statements
-            .keys()
-            .find(|e| matches!(e, veryl_simulator::ir::Event::Clock(_)))
-            .cloned()
-            .unwrap_or(veryl_simulator::ir::Event::Initial)
-    };
-    let clock_event = scenario
-        .signals
-        .iter()
-        .find(|s| s.kind == SignalKind::Clock)
-        .and_then(|s| {
-            sim.get_clock(&s.name)
-                .or_else(|| sim.get_clock(&format!("i_{}", s.name)))
-        })
-        .unwrap_or_else(fallback_clock);
+    remap_signal_names(&mut scenario, ports);
 
-    // Resolve reset event
-    let reset_event = sim
+    // Resolve clock events (after remap so signal names match port names)
+    let fallback_clock = sim
         .ir
         .event_statements
         .keys()
-        .find(|e| matches!(e, veryl_simulator::ir::Event::Reset(_)))
-        .cloned();
+        .find(|e| matches!(e, veryl_simulator::ir::Event::Clock(_)))
+        .cloned()
+        .unwrap_or(veryl_simulator::ir::Event::Initial);
+    let clock_events: std::collections::HashMap<_, _> = scenario
+        .signals
+        .iter()
+        .filter(|s| s.kind == SignalKind::Clock)
+        .filter_map(|s| Some((s.name.clone(), sim.get_clock(&s.name)?)))
+        .collect();
 
-    remap_signal_names(&mut scenario, ports);
+    // Resolve reset event by the reset signal if it is used in the module
+    let reset_event = scenario
+        .signals
+        .iter()
+        .filter(|s| s.kind == SignalKind::Input)
+        .filter_map(|s| sim.get_reset(&s.name))
+        .find(|e| sim.ir.event_statements.contains_key(e))
+        .or_else(|| {
+            sim.ir
+                .event_statements
+                .keys()
+                .find(|e| matches!(e, veryl_simulator::ir::Event::Reset(_)))
+                .cloned()
+        });
 
     // Build port width map (after remap so signal names match port names)
     let mut port_widths = std::collections::HashMap::new();
@@ -451,7 +703,8 @@ fn run_doc_test(
     let result = wavedrom::run_wavedrom_test(
         &mut sim,
         &scenario,
-        &clock_event,
+        &clock_events,
+        &fallback_clock,
         &reset_event,
         3,
         &port_widths,
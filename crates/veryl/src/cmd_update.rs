@@ -1,17 +1,22 @@
 use crate::OptUpdate;
+use log::{error, info};
 use miette::Result;
 use veryl_metadata::{Lockfile, Metadata};
 
 pub struct CmdUpdate {
-    _opt: OptUpdate,
+    opt: OptUpdate,
 }
 
 impl CmdUpdate {
     pub fn new(opt: OptUpdate) -> Self {
-        Self { _opt: opt }
+        Self { opt }
     }
 
     pub fn exec(&self, metadata: &mut Metadata) -> Result<bool> {
+        if self.opt.verify {
+            return self.verify(metadata);
+        }
+
         if metadata.lockfile_path.exists() {
             let mut lockfile = Lockfile::load(metadata)?;
             let modified = lockfile.update(metadata, true)?;
@@ -25,4 +30,27 @@ impl CmdUpdate {
 
         Ok(true)
     }
+
+    fn verify(&self, metadata: &mut Metadata) -> Result<bool> {
+        if !metadata.lockfile_path.exists() {
+            info!("Skipping verification because Veryl.lock is not found");
+            return Ok(true);
+        }
+
+        let mut lockfile = Lockfile::load(metadata)?;
+        let errors = lockfile.verify()?;
+
+        for x in &errors {
+            error!("{x}");
+        }
+
+        if errors.is_empty() {
+            info!(
+                "Verified dependencies ({})",
+                metadata.lockfile_path.to_string_lossy()
+            );
+        }
+
+        Ok(errors.is_empty())
+    }
 }
@@ -1,7 +1,15 @@
+mod block_diagram;
+mod coverage;
 mod doc_builder;
+mod json;
 mod mermaid;
+mod source;
 mod utils;
 mod wavedrom;
+pub use block_diagram::*;
+pub use coverage::*;
 pub use doc_builder::*;
+pub use json::*;
 pub use mermaid::*;
+pub use source::*;
 pub use wavedrom::*;
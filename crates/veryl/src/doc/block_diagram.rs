@@ -0,0 +1,309 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use veryl_analyzer::symbol::{ClockDomain, Direction, Port, Symbol, SymbolKind};
+use veryl_analyzer::symbol_table;
+use veryl_parser::resource_table::StrId;
+
+/// Stroke and fill colors of clock domains
+const PALETTE: &[(&str, &str)] = &[
+    ("#4e79a7", "#dbe4ef"),
+    ("#f28e2b", "#fce5d0"),
+    ("#59a14f", "#dcecd9"),
+    ("#e15759", "#f8dcdc"),
+    ("#b07aa1", "#efe3ec"),
+    ("#76b7b2", "#e2f0ef"),
+    ("#edc948", "#fbf3d6"),
+    ("#9c755f", "#ebe2dc"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    Driver,
+    Sink,
+    Bidir,
+    Bundle,
+}
+
+impl From<Direction> for Role {
+    fn from(x: Direction) -> Self {
+        match x {
+            Direction::Input => Role::Sink,
+            Direction::Output => Role::Driver,
+            Direction::Interface | Direction::Modport => Role::Bundle,
+            Direction::Inout | Direction::Import => Role::Bidir,
+        }
+    }
+}
+
+struct Node {
+    id: String,
+    shape: String,
+    clock_domain: Option<String>,
+}
+
+struct Edge {
+    from: String,
+    to: String,
+    arrow: &'static str,
+    label: String,
+    clock_domain: Option<String>,
+}
+
+#[derive(Default)]
+struct Net {
+    endpoints: Vec<(String, Role)>,
+    clock_domain: Option<String>,
+}
+
+impl Net {
+    fn add(&mut self, node: &str, role: Role) {
+        if !self.endpoints.iter().any(|x| x.0 == node && x.1 == role) {
+            self.endpoints.push((node.to_string(), role));
+        }
+    }
+}
+
+fn clock_domain_name(x: &ClockDomain) -> Option<String> {
+    match x {
+        ClockDomain::Explicit(_) | ClockDomain::Inferred(_) => Some(x.to_string()),
+        _ => None,
+    }
+}
+
+fn escape(x: &str) -> String {
+    x.replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+/// Ports of the instantiated component, and whether it is an interface
+fn component_ports(inst: &Symbol) -> Option<(Vec<Port>, bool)> {
+    let SymbolKind::Instance(ref x) = inst.kind else {
+        return None;
+    };
+    let found = symbol_table::resolve((&x.type_name.mangled_path(), &inst.namespace))
+        .ok()?
+        .found;
+    let kind = if let SymbolKind::GenericInstance(x) = &found.kind {
+        symbol_table::get(x.base)?.kind
+    } else {
+        found.kind.clone()
+    };
+    match kind {
+        SymbolKind::Module(x) => Some((x.ports, false)),
+        SymbolKind::ProtoModule(x) => Some((x.ports, false)),
+        SymbolKind::Interface(_) => Some((vec![], true)),
+        _ => Some((vec![], false)),
+    }
+}
+
+/// Generate a mermaid flowchart of instances in the module.
+///
+/// Child instances are drawn as boxes, port connections as edges,
+/// and interface/modport connections as thick edges.
+/// Nodes and edges are colored by clock domain.
+pub fn block_diagram(symbol: &Symbol) -> Option<String> {
+    let SymbolKind::Module(ref property) = symbol.kind else {
+        return None;
+    };
+
+    let namespace = symbol.inner_namespace();
+    let mut instances: Vec<_> = symbol_table::get_all()
+        .into_iter()
+        .filter(|x| matches!(x.kind, SymbolKind::Instance(_)) && x.namespace.included(&namespace))
+        .collect();
+    if instances.is_empty() {
+        return None;
+    }
+    instances.sort_by_key(|x| (x.token.line, x.token.column));
+
+    let mut nodes = Vec::new();
+    // signals which can be referred from port connections
+    let mut signals: HashMap<StrId, (String, Role)> = HashMap::new();
+    let mut domains: HashMap<String, Option<String>> = HashMap::new();
+
+    for port in &property.ports {
+        let port_property = port.property();
+        let name = port.name().to_string();
+        let id = format!("p_{name}");
+        let clock_domain = clock_domain_name(&port_property.clock_domain);
+        let role = Role::from(port_property.direction);
+        let shape = if role == Role::Bundle {
+            format!("{id}[[\"{name}\"]]")
+        } else {
+            format!("{id}([\"{name}\"])")
+        };
+        // the parent port is the converse of child ports
+        let role = match role {
+            Role::Driver => Role::Sink,
+            Role::Sink => Role::Driver,
+            x => x,
+        };
+        signals.insert(port.name(), (id.clone(), role));
+        domains.insert(id.clone(), clock_domain.clone());
+        nodes.push(Node {
+            id,
+            shape,
+            clock_domain,
+        });
+    }
+
+    let mut components = Vec::new();
+    for inst in &instances {
+        let SymbolKind::Instance(ref x) = inst.kind else {
+            unreachable!();
+        };
+        let name = inst.token.text.to_string();
+        let id = format!("i_{name}");
+        let label = escape(&format!("{name}: {}", x.type_name));
+        let clock_domain = clock_domain_name(&x.clock_domain);
+        let (ports, is_interface) = component_ports(inst).unwrap_or_default();
+        let shape = if is_interface {
+            signals.insert(inst.token.text, (id.clone(), Role::Bundle));
+            format!("{id}{{{{\"{label}\"}}}}")
+        } else {
+            format!("{id}[\"{label}\"]")
+        };
+        domains.insert(id.clone(), clock_domain.clone());
+        nodes.push(Node {
+            id: id.clone(),
+            shape,
+            clock_domain,
+        });
+        components.push((id, ports, x.port_connects.clone(), inst.namespace.clone()));
+    }
+
+    let mut nets: BTreeMap<String, Net> = BTreeMap::new();
+    for (id, ports, connects, namespace) in &components {
+        let mut connects: Vec<_> = connects.iter().collect();
+        connects.sort_by_key(|(x, _)| (x.line, x.column));
+        for (port, target) in connects {
+            let role = ports
+                .iter()
+                .find(|x| x.name() == port.text)
+                .map(|x| Role::from(x.property().direction))
+                .unwrap_or(Role::Bidir);
+
+            for identifier in &target.identifiers {
+                let path = identifier.path();
+                let Some(first) = path.first() else {
+                    continue;
+                };
+                let net_name = path
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+                    .join(".");
+                let net = nets.entry(net_name).or_default();
+
+                if let Some((signal, signal_role)) = signals.get(first) {
+                    net.clock_domain = domains.get(signal).cloned().flatten();
+                    if path.len() == 1 || *signal_role != Role::Bundle {
+                        net.add(signal, *signal_role);
+                    } else {
+                        // member of bundle drives or is driven by the child port
+                        let member_role = match role {
+                            Role::Driver => Role::Sink,
+                            Role::Sink => Role::Driver,
+                            _ => Role::Bidir,
+                        };
+                        net.add(signal, member_role);
+                    }
+                } else if let Ok(x) = symbol_table::resolve((&vec![*first], namespace))
+                    && let SymbolKind::Variable(x) = &x.found.kind
+                {
+                    net.clock_domain = clock_domain_name(&x.clock_domain);
+                }
+                net.add(id, role);
+            }
+        }
+    }
+
+    let mut edges = Vec::new();
+    for (name, net) in &nets {
+        let endpoints = &net.endpoints;
+        let domain = |x: &str| {
+            net.clock_domain
+                .clone()
+                .or_else(|| domains.get(x).cloned().flatten())
+        };
+        if endpoints.iter().any(|x| x.1 == Role::Bundle) {
+            let (hub, _) = &endpoints[0];
+            for (x, _) in &endpoints[1..] {
+                edges.push(Edge {
+                    from: hub.clone(),
+                    to: x.clone(),
+                    arrow: "<==>",
+                    label: name.clone(),
+                    clock_domain: domain(hub).or_else(|| domain(x)),
+                });
+            }
+            continue;
+        }
+
+        for (i, (from, from_role)) in endpoints.iter().enumerate() {
+            for (j, (to, to_role)) in endpoints.iter().enumerate() {
+                if from == to {
+                    continue;
+                }
+                let arrow = match (from_role, to_role) {
+                    (Role::Driver, Role::Sink | Role::Bidir) => "-->",
+                    (Role::Bidir, Role::Sink) => "-->",
+                    (Role::Bidir, Role::Bidir) if i < j => "<-->",
+                    _ => continue,
+                };
+                edges.push(Edge {
+                    from: from.clone(),
+                    to: to.clone(),
+                    arrow,
+                    label: name.clone(),
+                    clock_domain: domain(from).or_else(|| domain(to)),
+                });
+            }
+        }
+    }
+
+    let mut ret = String::new();
+    writeln!(ret, "flowchart LR").unwrap();
+    for node in &nodes {
+        writeln!(ret, "    {}", node.shape).unwrap();
+    }
+    for edge in &edges {
+        writeln!(
+            ret,
+            "    {} {}|\"{}\"| {}",
+            edge.from,
+            edge.arrow,
+            escape(&edge.label),
+            edge.to
+        )
+        .unwrap();
+    }
+
+    let clock_domains: BTreeSet<_> = nodes
+        .iter()
+        .filter_map(|x| x.clock_domain.clone())
+        .collect();
+    for (i, clock_domain) in clock_domains.iter().enumerate() {
+        let (stroke, fill) = PALETTE[i % PALETTE.len()];
+        writeln!(ret, "    %% clock domain {clock_domain}").unwrap();
+        writeln!(ret, "    classDef cd{i} stroke:{stroke},fill:{fill}").unwrap();
+        let ids: Vec<_> = nodes
+            .iter()
+            .filter(|x| x.clock_domain.as_ref() == Some(clock_domain))
+            .map(|x| x.id.as_str())
+            .collect();
+        writeln!(ret, "    class {} cd{i}", ids.join(",")).unwrap();
+        let links: Vec<_> = edges
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.clock_domain.as_ref() == Some(clock_domain))
+            .map(|(i, _)| i.to_string())
+            .collect();
+        if !links.is_empty() {
+            writeln!(ret, "    linkStyle {} stroke:{stroke}", links.join(",")).unwrap();
+        }
+    }
+
+    Some(ret)
+}
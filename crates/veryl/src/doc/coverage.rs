@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use veryl_analyzer::doc_coverage::{DocItem, DocItemKind, collect_doc_items};
+use veryl_metadata::Metadata;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CoverageCount {
+    pub documented: usize,
+    pub total: usize,
+}
+
+impl CoverageCount {
+    fn add(&mut self, documented: bool) {
+        self.total += 1;
+        if documented {
+            self.documented += 1;
+        }
+    }
+
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.documented as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+impl fmt::Display for CoverageCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:6.2}% ({}/{})",
+            self.percent(),
+            self.documented,
+            self.total
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UndocumentedItem {
+    pub kind: DocItemKind,
+    pub name: String,
+    pub path: String,
+    pub line: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProjectCoverage {
+    pub name: String,
+    pub total: CoverageCount,
+    pub kinds: BTreeMap<DocItemKind, CoverageCount>,
+    pub files: BTreeMap<String, CoverageCount>,
+    pub undocumented: Vec<UndocumentedItem>,
+}
+
+impl fmt::Display for ProjectCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Documentation coverage of {}: {}", self.name, self.total)?;
+        writeln!(f)?;
+        writeln!(f, "  By kind:")?;
+        for (kind, count) in &self.kinds {
+            writeln!(f, "    {:<16} {count}", kind.to_string())?;
+        }
+        writeln!(f)?;
+        writeln!(f, "  By file:")?;
+        for (path, count) in &self.files {
+            writeln!(f, "    {count} {path}")?;
+        }
+        if !self.undocumented.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "  Undocumented:")?;
+            for x in &self.undocumented {
+                writeln!(f, "    {}:{}: {} {}", x.path, x.line, x.kind, x.name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn relative_path(metadata: &Metadata, item: &DocItem) -> String {
+    let path = item.token.source.to_string();
+    let path = Path::new(&path);
+    let path = metadata
+        .metadata_path
+        .parent()
+        .and_then(|x| path.strip_prefix(x).ok())
+        .unwrap_or(path);
+    path.to_string_lossy().to_string()
+}
+
+pub fn build_coverage(metadata: &Metadata, projects: &[String]) -> Vec<ProjectCoverage> {
+    let mut ret = Vec::new();
+
+    for project in projects {
+        if project.starts_with('$') || ret.iter().any(|x: &ProjectCoverage| &x.name == project) {
+            continue;
+        }
+
+        let mut coverage = ProjectCoverage {
+            name: project.clone(),
+            total: CoverageCount::default(),
+            kinds: BTreeMap::new(),
+            files: BTreeMap::new(),
+            undocumented: Vec::new(),
+        };
+
+        for item in collect_doc_items(project) {
+            let path = relative_path(metadata, &item);
+            coverage.total.add(item.documented);
+            coverage
+                .kinds
+                .entry(item.kind)
+                .or_default()
+                .add(item.documented);
+            coverage
+                .files
+                .entry(path.clone())
+                .or_default()
+                .add(item.documented);
+            if !item.documented {
+                coverage.undocumented.push(UndocumentedItem {
+                    kind: item.kind,
+                    name: item.name,
+                    path,
+                    line: item.token.line,
+                });
+            }
+        }
+
+        ret.push(coverage);
+    }
+
+    ret
+}
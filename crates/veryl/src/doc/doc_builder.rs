@@ -1,17 +1,21 @@
-use crate::doc::{Mermaid, Wavedrom};
+use crate::doc::{Mermaid, SourceFile, Wavedrom, block_diagram};
+use crate::regmap::{Bus, Regmap};
 use handlebars::Handlebars;
 use mdbook::{Config, MDBook};
 use miette::{IntoDiagnostic, Result};
 use serde::Serialize;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::PathBuf;
 use tempfile::TempDir;
-use veryl_analyzer::symbol::{ClockDomain, ParameterKind, Symbol, SymbolKind};
-use veryl_analyzer::symbol_table;
+use veryl_analyzer::symbol::{
+    ClockDomain, ParameterKind, Symbol, SymbolId, SymbolKind, Type, TypeKind,
+};
+use veryl_analyzer::{namespace_table, symbol_table};
 use veryl_metadata::{Metadata, MetadataError};
-use veryl_parser::veryl_token::Token;
+use veryl_parser::resource_table::{self, PathId, StrId};
+use veryl_parser::veryl_token::{Token, TokenSource};
 
 const SUMMARY_TMPL: &str = r###"
 # Summary
@@ -40,6 +44,27 @@ const SUMMARY_TMPL: &str = r###"
   {{#each packages}}
   - [{{this.0}}]({{this.1}}.md)
   {{/each}}
+{{#if dependencies}}
+
+- [Dependencies](dependencies.md)
+  {{#each dependencies}}
+  - [{{this.0}}]({{this.1}}.md)
+  {{/each}}
+{{/if}}
+{{#if regmaps}}
+
+- [Register Maps](regmaps.md)
+  {{#each regmaps}}
+  - [{{this.0}}]({{this.1}}.md)
+  {{/each}}
+{{/if}}
+{{#if sources}}
+
+- [Sources](sources.md)
+  {{#each sources}}
+  - [{{this.0}}]({{this.1}}.md)
+  {{/each}}
+{{/if}}
 "###;
 
 #[derive(Serialize)]
@@ -50,6 +75,9 @@ struct SummaryData {
     proto_modules: Vec<(String, String)>,
     interfaces: Vec<(String, String)>,
     packages: Vec<(String, String)>,
+    dependencies: Vec<(String, String)>,
+    regmaps: Vec<(String, String)>,
+    sources: Vec<(String, String)>,
 }
 
 const INDEX_TMPL: &str = r###"
@@ -84,6 +112,11 @@ const INDEX_TMPL: &str = r###"
 {{#include interfaces.md}}
 {{#include packages.md}}
 {{{{/raw}}}}
+{{#if regmaps}}
+{{{{raw}}}}
+{{#include regmaps.md}}
+{{{{/raw}}}}
+{{/if}}
 "###;
 
 #[derive(Serialize)]
@@ -93,6 +126,7 @@ struct IndexData {
     version: String,
     repository: Option<String>,
     license: Option<String>,
+    regmaps: bool,
 }
 
 const LIST_TMPL: &str = r###"
@@ -128,6 +162,10 @@ struct ListItem {
 const MODULE_TMPL: &str = r#"
 ## {{name}}
 
+{{#if source}}
+<a class="source_link" href="{{source}}">source</a>
+{{/if}}
+
 {{description}}
 
 {{#if generic_parameters}}
@@ -198,16 +236,39 @@ const MODULE_TMPL: &str = r#"
 </tbody>
 </table>
 {{/if}}
+
+{{#if block_diagram}}
+### Block Diagram
+---
+
+```mermaid
+{{block_diagram}}
+```
+{{/if}}
+
+{{#if used_by}}
+### Used By
+---
+
+<ul>
+{{#each used_by}}
+<li>{{this}}</li>
+{{/each}}
+</ul>
+{{/if}}
 "#;
 
 #[derive(Serialize)]
 struct ModuleData {
     name: String,
+    source: Option<String>,
     description: String,
     generic_parameters: Vec<GenericParameterData>,
     parameters: Vec<ParameterData>,
     clock_domains: Vec<String>,
     ports: Vec<PortData>,
+    block_diagram: Option<String>,
+    used_by: Vec<String>,
 }
 
 #[derive(Serialize)]
@@ -235,6 +296,10 @@ struct PortData {
 const PROTO_MODULE_TMPL: &str = r#"
 ## {{name}}
 
+{{#if source}}
+<a class="source_link" href="{{source}}">source</a>
+{{/if}}
+
 {{description}}
 
 {{#if parameters}}
@@ -289,20 +354,37 @@ const PROTO_MODULE_TMPL: &str = r#"
 </tbody>
 </table>
 {{/if}}
+
+{{#if used_by}}
+### Used By
+---
+
+<ul>
+{{#each used_by}}
+<li>{{this}}</li>
+{{/each}}
+</ul>
+{{/if}}
 "#;
 
 #[derive(Serialize)]
 struct ProtoModuleData {
     name: String,
+    source: Option<String>,
     description: String,
     parameters: Vec<ParameterData>,
     clock_domains: Vec<String>,
     ports: Vec<PortData>,
+    used_by: Vec<String>,
 }
 
 const INTERFACE_TMPL: &str = r#"
 ## {{name}}
 
+{{#if source}}
+<a class="source_link" href="{{source}}">source</a>
+{{/if}}
+
 {{description}}
 
 {{#if parameters}}
@@ -321,26 +403,198 @@ const INTERFACE_TMPL: &str = r#"
 </tbody>
 </table>
 {{/if}}
+
+{{#if used_by}}
+### Used By
+---
+
+<ul>
+{{#each used_by}}
+<li>{{this}}</li>
+{{/each}}
+</ul>
+{{/if}}
 "#;
 
 #[derive(Serialize)]
 struct InterfaceData {
     name: String,
+    source: Option<String>,
     description: String,
     parameters: Vec<ParameterData>,
+    used_by: Vec<String>,
 }
 
 const PACKAGE_TMPL: &str = r###"
 ## {{name}}
 
+{{#if source}}
+<a class="source_link" href="{{source}}">source</a>
+{{/if}}
+
 {{description}}
 
+{{#if members}}
+### Members
+---
+
+<table class="table_list">
+<tbody>
+{{#each members}}
+<tr>
+    <th class="table_list_item" id="{{this.name}}">{{this.name}}</th>
+    <td class="table_list_item"><span class="hljs-keyword">{{this.kind}}</span></td>
+    <td class="table_list_item"><span class="hljs-type">{{this.typ}}</span></td>
+    <td class="table_list_item">{{this.description}}</td>
+</tr>
+{{/each}}
+</tbody>
+</table>
+{{/if}}
+
+{{#if used_by}}
+### Used By
+---
+
+<ul>
+{{#each used_by}}
+<li>{{this}}</li>
+{{/each}}
+</ul>
+{{/if}}
 "###;
 
+#[derive(Serialize)]
+struct MemberData {
+    name: String,
+    kind: String,
+    typ: String,
+    description: String,
+}
+
 #[derive(Serialize)]
 struct PackageData {
     name: String,
+    source: Option<String>,
     description: String,
+    members: Vec<MemberData>,
+    used_by: Vec<String>,
+}
+
+const SOURCE_TMPL: &str = r###"
+## {{name}}
+---
+
+<pre class="source"><code class="nohighlight hljs">{{code}}</code></pre>
+"###;
+
+#[derive(Serialize)]
+struct SourceData {
+    name: String,
+    code: String,
+}
+
+const REGMAP_TMPL: &str = r###"
+## {{name}}
+
+{{description}}
+
+<table class="table_list">
+<tbody>
+<tr>
+    <th class="table_list_item">Bus</th>
+    <td class="table_list_item">{{bus}}</td>
+</tr>
+<tr>
+    <th class="table_list_item">Address Width</th>
+    <td class="table_list_item">{{address_width}}</td>
+</tr>
+<tr>
+    <th class="table_list_item">Data Width</th>
+    <td class="table_list_item">{{data_width}}</td>
+</tr>
+</tbody>
+</table>
+
+### Registers
+---
+
+<table class="table_list">
+<tbody>
+<tr>
+    <th class="table_list_item">Offset</th>
+    <th class="table_list_item">Name</th>
+    <th class="table_list_item">Reset</th>
+    <th class="table_list_item">Description</th>
+</tr>
+{{#each registers}}
+<tr>
+    <td class="table_list_item"><code>{{this.offset}}</code></td>
+    <td class="table_list_item"><a href="#{{this.anchor}}">{{this.name}}</a></td>
+    <td class="table_list_item"><code>{{this.reset}}</code></td>
+    <td class="table_list_item">{{this.description}}</td>
+</tr>
+{{/each}}
+</tbody>
+</table>
+
+{{#each registers}}
+### {{this.name}}
+---
+
+{{this.description}}
+
+<table class="table_list">
+<tbody>
+<tr>
+    <th class="table_list_item">Bits</th>
+    <th class="table_list_item">Field</th>
+    <th class="table_list_item">Access</th>
+    <th class="table_list_item">Reset</th>
+    <th class="table_list_item">Description</th>
+</tr>
+{{#each this.fields}}
+<tr>
+    <td class="table_list_item"><code>{{this.bits}}</code></td>
+    <td class="table_list_item">{{this.name}}</td>
+    <td class="table_list_item"><span class="hljs-keyword">{{this.access}}</span></td>
+    <td class="table_list_item"><code>{{this.reset}}</code></td>
+    <td class="table_list_item">{{this.description}}</td>
+</tr>
+{{/each}}
+</tbody>
+</table>
+
+{{/each}}
+"###;
+
+#[derive(Serialize)]
+struct RegmapData {
+    name: String,
+    description: Option<String>,
+    bus: String,
+    address_width: u32,
+    data_width: u32,
+    registers: Vec<RegisterData>,
+}
+
+#[derive(Serialize)]
+struct RegisterData {
+    name: String,
+    anchor: String,
+    offset: String,
+    reset: String,
+    description: Option<String>,
+    fields: Vec<FieldData>,
+}
+
+#[derive(Serialize)]
+struct FieldData {
+    bits: String,
+    name: String,
+    access: String,
+    reset: String,
+    description: Option<String>,
 }
 
 pub struct DocBuilder {
@@ -354,10 +608,17 @@ pub struct DocBuilder {
     proto_modules: Vec<TopLevelItem>,
     interfaces: Vec<TopLevelItem>,
     packages: Vec<TopLevelItem>,
+    regmaps: Vec<Regmap>,
+    sources: Vec<SourceFile>,
+    /// File names of items indexed by project and item name
+    item_files: HashMap<(StrId, StrId), String>,
+    /// Page names of source files
+    source_files: HashMap<PathId, String>,
 }
 
 #[derive(Clone)]
 pub struct TopLevelItem {
+    pub project: String,
     pub file_name: String,
     pub html_name: String,
     pub symbol: Symbol,
@@ -370,6 +631,8 @@ impl DocBuilder {
         proto_modules: Vec<TopLevelItem>,
         interfaces: Vec<TopLevelItem>,
         packages: Vec<TopLevelItem>,
+        regmaps: Vec<Regmap>,
+        sources: Vec<SourceFile>,
     ) -> Result<Self> {
         let temp_dir = tempfile::tempdir().into_diagnostic()?;
         let root_dir = temp_dir.path().to_path_buf();
@@ -378,6 +641,31 @@ impl DocBuilder {
         fs::create_dir(&src_dir).into_diagnostic()?;
         fs::create_dir(&theme_dir).into_diagnostic()?;
 
+        let mut item_files = HashMap::new();
+        for x in modules
+            .iter()
+            .chain(proto_modules.iter())
+            .chain(interfaces.iter())
+            .chain(packages.iter())
+        {
+            let project = resource_table::insert_str(&x.project);
+            item_files.insert((project, x.symbol.token.text), x.file_name.clone());
+        }
+
+        let mut source_files = HashMap::new();
+        let mut used = HashSet::new();
+        for x in &sources {
+            let file_name = x.path.file_name().unwrap_or_default().to_string_lossy();
+            let mut page = format!("source.{}.{}", x.project, file_name);
+            let mut i = 1;
+            while used.contains(&page) {
+                page = format!("source.{}.{}.{}", x.project, file_name, i);
+                i += 1;
+            }
+            used.insert(page.clone());
+            source_files.insert(x.path_id, page);
+        }
+
         Ok(Self {
             metadata: metadata.clone(),
             temp_dir,
@@ -388,6 +676,10 @@ impl DocBuilder {
             proto_modules,
             interfaces,
             packages,
+            regmaps,
+            sources,
+            item_files,
+            source_files,
         })
     }
 
@@ -400,6 +692,8 @@ impl DocBuilder {
         self.build_component("proto_modules.md", self.build_proto_modules())?;
         self.build_component("interfaces.md", self.build_interfaces())?;
         self.build_component("packages.md", self.build_packages())?;
+        self.build_component("dependencies.md", self.build_dependencies())?;
+        self.build_component("sources.md", self.build_sources())?;
 
         for x in &self.modules {
             let file = format!("{}.md", x.file_name);
@@ -421,6 +715,20 @@ impl DocBuilder {
             self.build_component(&file, self.build_package(&x.html_name, &x.symbol))?;
         }
 
+        if !self.regmaps.is_empty() {
+            self.build_component("regmaps.md", self.build_regmaps())?;
+        }
+
+        for x in &self.regmaps {
+            let file = format!("{}.md", regmap_file_name(x));
+            self.build_component(&file, self.build_regmap(x))?;
+        }
+
+        for x in &self.sources {
+            let file = format!("{}.md", self.source_files[&x.path_id]);
+            self.build_component(&file, self.build_source(x))?;
+        }
+
         let mut cfg = Config::default();
         cfg.build.build_dir = self.metadata.doc_path();
         cfg.set("output.html.no-section-label", true).unwrap();
@@ -464,6 +772,16 @@ impl DocBuilder {
     border: unset;
     background-color: var(--bg);
 }
+
+.source_link {
+    float: right;
+}
+
+.line_number {
+    color: var(--fg);
+    opacity: 0.5;
+    user-select: none;
+}
         "##;
 
         let file = self.theme_dir.join("custom.css");
@@ -493,6 +811,117 @@ impl DocBuilder {
         Ok(())
     }
 
+    fn is_local(&self, item: &TopLevelItem) -> bool {
+        item.project == self.metadata.project.name
+    }
+
+    fn dependencies(&self) -> Vec<TopLevelItem> {
+        let mut ret: Vec<_> = self
+            .modules
+            .iter()
+            .chain(self.proto_modules.iter())
+            .chain(self.interfaces.iter())
+            .chain(self.packages.iter())
+            .filter(|x| !self.is_local(x))
+            .cloned()
+            .collect();
+        ret.sort_by(|a, b| (&a.project, &a.html_name).cmp(&(&b.project, &b.html_name)));
+        ret
+    }
+
+    fn source_name(&self, source: &SourceFile) -> String {
+        if source.project == self.metadata.project.name {
+            let path = self
+                .metadata
+                .metadata_path
+                .parent()
+                .and_then(|x| source.path.strip_prefix(x).ok())
+                .unwrap_or(&source.path);
+            path.to_string_lossy().to_string()
+        } else {
+            let file_name = source.path.file_name().unwrap_or_default();
+            format!("{}/{}", source.project, file_name.to_string_lossy())
+        }
+    }
+
+    /// Link to the source page at the definition of `token`
+    fn source_link(&self, token: &Token) -> Option<String> {
+        if let TokenSource::File { path, .. } = token.source {
+            let page = self.source_files.get(&path)?;
+            Some(format!("{page}.html#L{}", token.line))
+        } else {
+            None
+        }
+    }
+
+    /// Link to the page documenting `id`, or to the page of its enclosing item
+    fn symbol_link(&self, id: SymbolId) -> Option<String> {
+        let symbol = symbol_table::get(id)?;
+        let paths = &symbol.namespace.paths;
+        match paths.len() {
+            0 => None,
+            1 => {
+                let file = self.item_files.get(&(paths[0], symbol.token.text))?;
+                Some(format!("{file}.html"))
+            }
+            n => {
+                let file = self.item_files.get(&(paths[0], paths[1]))?;
+                let anchor = if n == 2 { symbol.token.text } else { paths[2] };
+                Some(format!("{file}.html#{anchor}"))
+            }
+        }
+    }
+
+    fn type_html(&self, r#type: &Type) -> String {
+        let text = r#type.to_string().replace('<', "&lt;").replace('>', "&gt;");
+        if let TypeKind::UserDefined(x) = &r#type.kind
+            && let Some(link) = x.symbol.and_then(|x| self.symbol_link(x))
+        {
+            format!("<a href=\"{link}\">{text}</a>")
+        } else {
+            text
+        }
+    }
+
+    /// Items referring `symbol` or, for packages, its members
+    fn used_by(&self, symbol: &Symbol) -> Vec<String> {
+        let mut symbols = vec![symbol.clone()];
+        if let SymbolKind::Package(x) = &symbol.kind {
+            symbols.extend(x.members.iter().filter_map(|x| symbol_table::get(*x)));
+        }
+
+        let this = (symbol.namespace.paths[0], symbol.token.text);
+        let mut users = BTreeSet::new();
+        for x in &symbols {
+            for reference in &x.references {
+                if let Some(namespace) = namespace_table::get(reference.id)
+                    && namespace.paths.len() >= 2
+                {
+                    let user = (namespace.paths[0], namespace.paths[1]);
+                    if user != this {
+                        users.insert((user.0.to_string(), user.1.to_string(), user));
+                    }
+                }
+            }
+        }
+
+        users
+            .into_iter()
+            .map(|(project, name, key)| {
+                let text = if project == self.metadata.project.name {
+                    name
+                } else {
+                    format!("{project}::{name}")
+                };
+                if let Some(file) = self.item_files.get(&key) {
+                    format!("<a href=\"{file}.html\">{text}</a>")
+                } else {
+                    text
+                }
+            })
+            .collect()
+    }
+
     fn build_component(&self, name: &str, content: String) -> Result<()> {
         let file = self.src_dir.join(name);
         let mut file = File::create(file).into_diagnostic()?;
@@ -504,27 +933,46 @@ impl DocBuilder {
         let modules: Vec<_> = self
             .modules
             .iter()
+            .filter(|x| self.is_local(x))
             .cloned()
             .map(|x| (x.html_name, x.file_name))
             .collect();
         let proto_modules: Vec<_> = self
             .proto_modules
             .iter()
+            .filter(|x| self.is_local(x))
             .cloned()
             .map(|x| (x.html_name, x.file_name))
             .collect();
         let interfaces: Vec<_> = self
             .interfaces
             .iter()
+            .filter(|x| self.is_local(x))
             .cloned()
             .map(|x| (x.html_name, x.file_name))
             .collect();
         let packages: Vec<_> = self
             .packages
             .iter()
+            .filter(|x| self.is_local(x))
             .cloned()
             .map(|x| (x.html_name, x.file_name))
             .collect();
+        let dependencies: Vec<_> = self
+            .dependencies()
+            .into_iter()
+            .map(|x| (format!("{}::{}", x.project, x.html_name), x.file_name))
+            .collect();
+        let regmaps: Vec<_> = self
+            .regmaps
+            .iter()
+            .map(|x| (x.name.clone(), regmap_file_name(x)))
+            .collect();
+        let sources: Vec<_> = self
+            .sources
+            .iter()
+            .map(|x| (self.source_name(x), self.source_files[&x.path_id].clone()))
+            .collect();
         let data = SummaryData {
             name: self.metadata.project.name.clone(),
             version: format!(
@@ -539,6 +987,9 @@ impl DocBuilder {
             proto_modules,
             interfaces,
             packages,
+            dependencies,
+            regmaps,
+            sources,
         };
 
         let mut handlebars = Handlebars::new();
@@ -560,6 +1011,7 @@ impl DocBuilder {
             description: self.metadata.project.description.clone(),
             repository: self.metadata.project.repository.clone(),
             license: self.metadata.project.license.clone(),
+            regmaps: !self.regmaps.is_empty(),
         };
 
         let mut handlebars = Handlebars::new();
@@ -571,6 +1023,7 @@ impl DocBuilder {
         let items: Vec<_> = self
             .modules
             .iter()
+            .filter(|x| self.is_local(x))
             .map(|x| ListItem {
                 file_name: x.file_name.clone(),
                 html_name: x.html_name.clone(),
@@ -592,6 +1045,7 @@ impl DocBuilder {
         let items: Vec<_> = self
             .proto_modules
             .iter()
+            .filter(|x| self.is_local(x))
             .map(|x| ListItem {
                 file_name: x.file_name.clone(),
                 html_name: x.html_name.clone(),
@@ -613,6 +1067,7 @@ impl DocBuilder {
         let items: Vec<_> = self
             .interfaces
             .iter()
+            .filter(|x| self.is_local(x))
             .map(|x| ListItem {
                 file_name: x.file_name.clone(),
                 html_name: x.html_name.clone(),
@@ -634,6 +1089,7 @@ impl DocBuilder {
         let items: Vec<_> = self
             .packages
             .iter()
+            .filter(|x| self.is_local(x))
             .map(|x| ListItem {
                 file_name: x.file_name.clone(),
                 html_name: x.html_name.clone(),
@@ -651,6 +1107,145 @@ impl DocBuilder {
         handlebars.render_template(LIST_TMPL, &data).unwrap()
     }
 
+    fn build_dependencies(&self) -> String {
+        let mut ret = String::new();
+        let dependencies = self.dependencies();
+        let mut projects: Vec<_> = dependencies.iter().map(|x| x.project.clone()).collect();
+        projects.dedup();
+
+        for project in projects {
+            let items: Vec<_> = dependencies
+                .iter()
+                .filter(|x| x.project == project)
+                .map(|x| ListItem {
+                    file_name: x.file_name.clone(),
+                    html_name: x.html_name.clone(),
+                    description: x.symbol.doc_comment.format(true),
+                })
+                .collect();
+
+            let data = ListData {
+                name: project,
+                items,
+            };
+
+            let mut handlebars = Handlebars::new();
+            handlebars.register_escape_fn(handlebars::no_escape);
+            ret.push_str(&handlebars.render_template(LIST_TMPL, &data).unwrap());
+        }
+        ret
+    }
+
+    fn build_sources(&self) -> String {
+        let items: Vec<_> = self
+            .sources
+            .iter()
+            .map(|x| ListItem {
+                file_name: self.source_files[&x.path_id].clone(),
+                html_name: self.source_name(x),
+                description: x.project.clone(),
+            })
+            .collect();
+
+        let data = ListData {
+            name: "Sources".to_string(),
+            items,
+        };
+
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(handlebars::no_escape);
+        handlebars.render_template(LIST_TMPL, &data).unwrap()
+    }
+
+    fn build_source(&self, source: &SourceFile) -> String {
+        let data = SourceData {
+            name: self.source_name(source),
+            code: source.code.clone(),
+        };
+
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(handlebars::no_escape);
+        handlebars.render_template(SOURCE_TMPL, &data).unwrap()
+    }
+
+    fn build_regmaps(&self) -> String {
+        let items: Vec<_> = self
+            .regmaps
+            .iter()
+            .map(|x| ListItem {
+                file_name: regmap_file_name(x),
+                html_name: x.name.clone(),
+                description: x
+                    .description
+                    .as_ref()
+                    .and_then(|x| x.lines().next())
+                    .unwrap_or("")
+                    .to_string(),
+            })
+            .collect();
+
+        let data = ListData {
+            name: "Register Maps".to_string(),
+            items,
+        };
+
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(handlebars::no_escape);
+        handlebars.render_template(LIST_TMPL, &data).unwrap()
+    }
+
+    fn build_regmap(&self, regmap: &Regmap) -> String {
+        let bytes = regmap.data_bytes() as usize;
+        let registers: Vec<_> = regmap
+            .registers
+            .iter()
+            .map(|x| {
+                let mut fields: Vec<_> = x.fields.iter().collect();
+                fields.sort_by_key(|x| std::cmp::Reverse(x.lsb()));
+                let fields: Vec<_> = fields
+                    .into_iter()
+                    .map(|x| FieldData {
+                        bits: if x.width() == 1 {
+                            format!("[{}]", x.lsb())
+                        } else {
+                            format!("[{}:{}]", x.msb(), x.lsb())
+                        },
+                        name: x.name.clone(),
+                        access: x.access.to_string(),
+                        reset: format!("{:#x}", x.reset),
+                        description: x.description.clone(),
+                    })
+                    .collect();
+                RegisterData {
+                    name: x.name.clone(),
+                    anchor: x.name.to_lowercase(),
+                    offset: format!("{:#06x}", x.offset),
+                    reset: format!("0x{:0width$x}", x.reset(), width = bytes * 2),
+                    description: x.description.clone(),
+                    fields,
+                }
+            })
+            .collect();
+
+        let bus = match regmap.bus {
+            Bus::Axi4Lite => "AXI4-Lite",
+            Bus::Apb => "APB",
+        };
+
+        let data = RegmapData {
+            name: regmap.name.clone(),
+            description: regmap.description.clone(),
+            bus: bus.to_string(),
+            address_width: regmap.address_width,
+            data_width: regmap.data_width,
+            registers,
+        };
+
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(handlebars::no_escape);
+        handlebars.render_template(REGMAP_TMPL, &data).unwrap()
+    }
+
     fn build_module(&self, name: &str, symbol: &Symbol) -> String {
         if let SymbolKind::Module(property) = &symbol.kind {
             let generic_parameters: Vec<_> = property
@@ -675,7 +1270,7 @@ impl DocBuilder {
                 .filter(|x| matches!(x.property().kind, ParameterKind::Param,))
                 .map(|x| ParameterData {
                     name: x.name.to_string(),
-                    typ: format!("{}", x.property().r#type),
+                    typ: self.type_html(&x.property().r#type),
                     description: get_comment_from_token(&x.property().token),
                 })
                 .collect();
@@ -707,7 +1302,7 @@ impl DocBuilder {
                         name: x.name().to_string(),
                         direction: format!("{}", x.property().direction),
                         clock_domain,
-                        typ: x.property().r#type.to_string(),
+                        typ: self.type_html(&x.property().r#type),
                         description: get_comment_from_token(&x.property().token),
                     }
                 })
@@ -715,11 +1310,14 @@ impl DocBuilder {
 
             let data = ModuleData {
                 name: name.to_string(),
+                source: self.source_link(&symbol.token),
                 description: symbol.doc_comment.format(false),
                 generic_parameters,
                 parameters,
                 clock_domains,
                 ports,
+                block_diagram: block_diagram(symbol),
+                used_by: self.used_by(symbol),
             };
 
             let mut handlebars = Handlebars::new();
@@ -738,7 +1336,7 @@ impl DocBuilder {
                 .filter(|x| matches!(x.property().kind, ParameterKind::Param,))
                 .map(|x| ParameterData {
                     name: x.name.to_string(),
-                    typ: format!("{}", x.property().r#type),
+                    typ: self.type_html(&x.property().r#type),
                     description: get_comment_from_token(&x.property().token),
                 })
                 .collect();
@@ -770,7 +1368,7 @@ impl DocBuilder {
                         name: x.name().to_string(),
                         direction: format!("{}", x.property().direction),
                         clock_domain,
-                        typ: x.property().r#type.to_string(),
+                        typ: self.type_html(&x.property().r#type),
                         description: get_comment_from_token(&x.property().token),
                     }
                 })
@@ -778,10 +1376,12 @@ impl DocBuilder {
 
             let data = ProtoModuleData {
                 name: name.to_string(),
+                source: self.source_link(&symbol.token),
                 description: symbol.doc_comment.format(false),
                 parameters,
                 clock_domains,
                 ports,
+                used_by: self.used_by(symbol),
             };
 
             let mut handlebars = Handlebars::new();
@@ -802,15 +1402,17 @@ impl DocBuilder {
                 .filter(|x| matches!(x.property().kind, ParameterKind::Param,))
                 .map(|x| ParameterData {
                     name: x.name.to_string(),
-                    typ: format!("{}", x.property().r#type),
+                    typ: self.type_html(&x.property().r#type),
                     description: get_comment_from_token(&x.property().token),
                 })
                 .collect();
 
             let data = InterfaceData {
                 name: name.to_string(),
+                source: self.source_link(&symbol.token),
                 description: symbol.doc_comment.format(false),
                 parameters,
+                used_by: self.used_by(symbol),
             };
 
             let mut handlebars = Handlebars::new();
@@ -822,10 +1424,39 @@ impl DocBuilder {
     }
 
     fn build_package(&self, name: &str, symbol: &Symbol) -> String {
-        if let SymbolKind::Package(_) = &symbol.kind {
+        if let SymbolKind::Package(property) = &symbol.kind {
+            let members: Vec<_> = property
+                .members
+                .iter()
+                .filter_map(|x| {
+                    let member = symbol_table::get(*x)?;
+                    let (kind, typ) = match &member.kind {
+                        SymbolKind::Parameter(x) => ("const", self.type_html(&x.r#type)),
+                        SymbolKind::TypeDef(x) => ("type", self.type_html(&x.r#type)),
+                        SymbolKind::Struct(_) => ("struct", String::new()),
+                        SymbolKind::Union(_) => ("union", String::new()),
+                        SymbolKind::Enum(_) => ("enum", String::new()),
+                        SymbolKind::Function(x) => {
+                            let typ = x.ret.as_ref().map(|x| self.type_html(x));
+                            ("function", typ.unwrap_or_default())
+                        }
+                        _ => return None,
+                    };
+                    Some(MemberData {
+                        name: member.token.to_string(),
+                        kind: kind.to_string(),
+                        typ,
+                        description: member.doc_comment.format(false),
+                    })
+                })
+                .collect();
+
             let data = PackageData {
                 name: name.to_string(),
+                source: self.source_link(&symbol.token),
                 description: symbol.doc_comment.format(false),
+                members,
+                used_by: self.used_by(symbol),
             };
 
             let mut handlebars = Handlebars::new();
@@ -837,6 +1468,11 @@ impl DocBuilder {
     }
 }
 
+/// Register maps use a separate file name because the generated module has the same name
+fn regmap_file_name(regmap: &Regmap) -> String {
+    format!("{}_regmap", regmap.name)
+}
+
 fn get_comment_from_token(token: &Token) -> Option<String> {
     if let Ok(symbol) = symbol_table::resolve(token) {
         Some(symbol.found.doc_comment.format(false))
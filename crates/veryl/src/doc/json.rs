@@ -0,0 +1,392 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::Path;
+use veryl_analyzer::symbol::{
+    DocComment, Parameter, ParameterKind, Port, Symbol, SymbolId, SymbolKind, Type,
+};
+use veryl_analyzer::symbol_table;
+use veryl_metadata::Metadata;
+use veryl_parser::Stringifier;
+use veryl_parser::veryl_grammar_trait::Expression;
+use veryl_parser::veryl_token::Token;
+use veryl_parser::veryl_walker::VerylWalker;
+
+/// Version of the JSON schema.
+/// This should be incremented when the schema is changed incompatibly.
+pub const DOC_JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DocJson {
+    pub schema_version: u32,
+    pub project: ProjectDoc,
+    pub dependencies: Vec<String>,
+    pub modules: Vec<ComponentDoc>,
+    pub proto_modules: Vec<ComponentDoc>,
+    pub interfaces: Vec<ComponentDoc>,
+    pub proto_interfaces: Vec<ComponentDoc>,
+    pub packages: Vec<ComponentDoc>,
+    pub proto_packages: Vec<ComponentDoc>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ProjectDoc {
+    pub name: String,
+    pub version: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Location {
+    pub path: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ComponentDoc {
+    pub name: String,
+    pub project: String,
+    pub doc: Option<String>,
+    pub location: Location,
+    pub proto: Option<String>,
+    pub generic_parameters: Vec<GenericParameterDoc>,
+    pub parameters: Vec<ParameterDoc>,
+    pub ports: Vec<PortDoc>,
+    pub modports: Vec<ModportDoc>,
+    pub functions: Vec<FunctionDoc>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GenericParameterDoc {
+    pub name: String,
+    pub bound: String,
+    pub default: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ParameterDoc {
+    pub name: String,
+    pub kind: String,
+    pub r#type: String,
+    pub default: Option<String>,
+    pub doc: Option<String>,
+    pub location: Location,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PortDoc {
+    pub name: String,
+    pub direction: String,
+    pub r#type: String,
+    pub width: Vec<String>,
+    pub array: Vec<String>,
+    pub clock_domain: Option<String>,
+    pub default: Option<String>,
+    pub doc: Option<String>,
+    pub location: Location,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ModportDoc {
+    pub name: String,
+    pub doc: Option<String>,
+    pub location: Location,
+    pub members: Vec<ModportMemberDoc>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ModportMemberDoc {
+    pub name: String,
+    pub direction: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FunctionDoc {
+    pub name: String,
+    pub doc: Option<String>,
+    pub location: Location,
+    pub ports: Vec<PortDoc>,
+    pub return_type: Option<String>,
+}
+
+struct JsonBuilder<'a> {
+    metadata: &'a Metadata,
+}
+
+impl JsonBuilder<'_> {
+    fn location(&self, token: &Token) -> Location {
+        let path = token.source.to_string();
+        let path = Path::new(&path);
+        let path = self
+            .metadata
+            .metadata_path
+            .parent()
+            .and_then(|x| path.strip_prefix(x).ok())
+            .unwrap_or(path);
+        Location {
+            path: path.to_string_lossy().to_string(),
+            line: token.line,
+            column: token.column,
+        }
+    }
+
+    fn generic_parameters(&self, ids: &[SymbolId]) -> Vec<GenericParameterDoc> {
+        ids.iter()
+            .filter_map(|x| {
+                let symbol = symbol_table::get(*x)?;
+                if let SymbolKind::GenericParameter(x) = symbol.kind {
+                    Some(GenericParameterDoc {
+                        name: symbol.token.to_string(),
+                        bound: x.bound.to_string(),
+                        default: x.default_value.map(|x| x.to_string()),
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn parameter(&self, symbol: &Symbol) -> Option<ParameterDoc> {
+        let SymbolKind::Parameter(x) = &symbol.kind else {
+            return None;
+        };
+        let kind = match x.kind {
+            ParameterKind::Param => "param",
+            ParameterKind::Const => "const",
+        };
+        Some(ParameterDoc {
+            name: symbol.token.to_string(),
+            kind: kind.to_string(),
+            r#type: x.r#type.to_string(),
+            default: x.value.as_ref().map(expression),
+            doc: doc_comment(&symbol.doc_comment),
+            location: self.location(&symbol.token),
+        })
+    }
+
+    fn parameters(&self, parameters: &[Parameter]) -> Vec<ParameterDoc> {
+        parameters
+            .iter()
+            .filter_map(|x| self.parameter(&symbol_table::get(x.symbol)?))
+            .collect()
+    }
+
+    fn ports(&self, ports: &[Port]) -> Vec<PortDoc> {
+        ports
+            .iter()
+            .map(|x| {
+                let symbol = x.symbol();
+                let property = x.property();
+                PortDoc {
+                    name: x.name().to_string(),
+                    direction: property.direction.to_string(),
+                    r#type: property.r#type.to_string(),
+                    width: expressions(&property.r#type.width),
+                    array: expressions(&property.r#type.array),
+                    clock_domain: Some(property.clock_domain.to_string()).filter(|x| !x.is_empty()),
+                    default: property.default_value.as_ref().map(expression),
+                    doc: doc_comment(&symbol.doc_comment),
+                    location: self.location(&symbol.token),
+                }
+            })
+            .collect()
+    }
+
+    fn function(&self, symbol: &Symbol) -> Option<FunctionDoc> {
+        let (SymbolKind::Function(x) | SymbolKind::ProtoFunction(x)) = &symbol.kind else {
+            return None;
+        };
+        Some(FunctionDoc {
+            name: symbol.token.to_string(),
+            doc: doc_comment(&symbol.doc_comment),
+            location: self.location(&symbol.token),
+            ports: self.ports(&x.ports),
+            return_type: x.ret.as_ref().map(Type::to_string),
+        })
+    }
+
+    fn modport(&self, symbol: &Symbol) -> Option<ModportDoc> {
+        let SymbolKind::Modport(x) = &symbol.kind else {
+            return None;
+        };
+        let members = x
+            .members
+            .iter()
+            .filter_map(|x| {
+                let member = symbol_table::get(*x)?;
+                let direction = match &member.kind {
+                    SymbolKind::ModportVariableMember(x) => x.direction.to_string(),
+                    SymbolKind::ModportFunctionMember(_) => "import".to_string(),
+                    _ => return None,
+                };
+                Some(ModportMemberDoc {
+                    name: member.token.to_string(),
+                    direction,
+                })
+            })
+            .collect();
+        Some(ModportDoc {
+            name: symbol.token.to_string(),
+            doc: doc_comment(&symbol.doc_comment),
+            location: self.location(&symbol.token),
+            members,
+        })
+    }
+
+    fn members(&self, doc: &mut ComponentDoc, members: &[SymbolId]) {
+        for x in members {
+            let Some(symbol) = symbol_table::get(*x) else {
+                continue;
+            };
+            if let Some(x) = self.parameter(&symbol) {
+                doc.parameters.push(x);
+            } else if let Some(x) = self.function(&symbol) {
+                doc.functions.push(x);
+            } else if let Some(x) = self.modport(&symbol) {
+                doc.modports.push(x);
+            }
+        }
+    }
+
+    fn component(&self, symbol: &Symbol, project: &str) -> ComponentDoc {
+        let mut ret = ComponentDoc {
+            name: symbol.token.to_string(),
+            project: project.to_string(),
+            doc: doc_comment(&symbol.doc_comment),
+            location: self.location(&symbol.token),
+            proto: None,
+            generic_parameters: vec![],
+            parameters: vec![],
+            ports: vec![],
+            modports: vec![],
+            functions: vec![],
+        };
+
+        match &symbol.kind {
+            SymbolKind::Module(x) => {
+                ret.proto = x.proto.as_ref().map(|x| x.to_string());
+                ret.generic_parameters = self.generic_parameters(&x.generic_parameters);
+                ret.parameters = self.parameters(&x.parameters);
+                ret.ports = self.ports(&x.ports);
+            }
+            SymbolKind::ProtoModule(x) => {
+                ret.parameters = self.parameters(&x.parameters);
+                ret.ports = self.ports(&x.ports);
+            }
+            SymbolKind::Interface(x) => {
+                ret.proto = x.proto.as_ref().map(|x| x.to_string());
+                ret.generic_parameters = self.generic_parameters(&x.generic_parameters);
+                ret.parameters = self.parameters(&x.parameters);
+                // parameters are also included in members
+                let members: Vec<_> = x
+                    .members
+                    .iter()
+                    .copied()
+                    .filter(|x| !is_parameter(*x))
+                    .collect();
+                self.members(&mut ret, &members);
+            }
+            SymbolKind::ProtoInterface(x) => {
+                ret.parameters = self.parameters(&x.parameters);
+                let members: Vec<_> = x
+                    .members
+                    .iter()
+                    .copied()
+                    .filter(|x| !is_parameter(*x))
+                    .collect();
+                self.members(&mut ret, &members);
+            }
+            SymbolKind::Package(x) => {
+                ret.proto = x.proto.as_ref().map(|x| x.to_string());
+                ret.generic_parameters = self.generic_parameters(&x.generic_parameters);
+                self.members(&mut ret, &x.members);
+            }
+            SymbolKind::ProtoPackage(x) => {
+                self.members(&mut ret, &x.members);
+            }
+            _ => (),
+        }
+        ret
+    }
+}
+
+fn is_parameter(id: SymbolId) -> bool {
+    symbol_table::get(id).is_some_and(|x| matches!(x.kind, SymbolKind::Parameter(_)))
+}
+
+fn doc_comment(x: &DocComment) -> Option<String> {
+    if x.is_empty() {
+        None
+    } else {
+        let text = x.format(false);
+        let lines: Vec<_> = text
+            .lines()
+            .map(|x| x.strip_prefix(' ').unwrap_or(x))
+            .collect();
+        Some(lines.join("\n"))
+    }
+}
+
+fn expression(x: &Expression) -> String {
+    let mut stringifier = Stringifier::new();
+    stringifier.expression(x);
+    stringifier.as_str().to_string()
+}
+
+fn expressions(x: &[Expression]) -> Vec<String> {
+    x.iter().map(expression).collect()
+}
+
+/// Build machine-readable documentation of public top-level items in `projects`.
+/// Projects prefixed by `$` (e.g. the standard library) are excluded.
+pub fn build_json(metadata: &Metadata, projects: &[String]) -> DocJson {
+    let builder = JsonBuilder { metadata };
+
+    let mut symbols: Vec<_> = symbol_table::get_all()
+        .into_iter()
+        .filter(|x| x.public && x.namespace.depth() == 1)
+        .filter_map(|x| {
+            let project = x.namespace.paths[0].to_string();
+            (projects.contains(&project) && !project.starts_with('$')).then_some((project, x))
+        })
+        .collect();
+    symbols.sort_by_key(|(project, x)| (project.clone(), x.token.to_string()));
+
+    let mut ret = DocJson {
+        schema_version: DOC_JSON_SCHEMA_VERSION,
+        project: ProjectDoc {
+            name: metadata.project.name.clone(),
+            version: metadata.project.version.as_ref().map(|x| x.to_string()),
+            description: metadata.project.description.clone(),
+        },
+        dependencies: projects
+            .iter()
+            .filter(|x| **x != metadata.project.name && !x.starts_with('$'))
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+        modules: vec![],
+        proto_modules: vec![],
+        interfaces: vec![],
+        proto_interfaces: vec![],
+        packages: vec![],
+        proto_packages: vec![],
+    };
+
+    for (project, symbol) in &symbols {
+        let list = match symbol.kind {
+            SymbolKind::Module(_) => &mut ret.modules,
+            SymbolKind::ProtoModule(_) => &mut ret.proto_modules,
+            SymbolKind::Interface(_) => &mut ret.interfaces,
+            SymbolKind::ProtoInterface(_) => &mut ret.proto_interfaces,
+            SymbolKind::Package(_) => &mut ret.packages,
+            SymbolKind::ProtoPackage(_) => &mut ret.proto_packages,
+            _ => continue,
+        };
+        list.push(builder.component(symbol, project));
+    }
+
+    ret
+}
@@ -0,0 +1,151 @@
+use std::path::PathBuf;
+use veryl_parser::keyword::KEYWORDS;
+use veryl_parser::resource_table::PathId;
+use veryl_parser::veryl_grammar_trait::*;
+use veryl_parser::veryl_token::{Token, VerylToken};
+use veryl_parser::veryl_walker::VerylWalker;
+
+/// Source file rendered as a syntax-highlighted page
+#[derive(Clone)]
+pub struct SourceFile {
+    pub project: String,
+    pub path: PathBuf,
+    pub path_id: PathId,
+    /// Highlighted HTML by `highlight`
+    pub code: String,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn push_span(ret: &mut String, class: &str, text: &str) {
+    // spans are closed at line ends so that line numbers can be inserted between lines
+    let lines: Vec<_> = text.split('\n').map(escape).collect();
+    let open = format!("<span class=\"hljs-{class}\">");
+    let close = "</span>";
+    ret.push_str(&open);
+    ret.push_str(&lines.join(&format!("{close}\n{open}")));
+    ret.push_str(close);
+}
+
+/// Byte ranges of tokens and comments with highlight class
+#[derive(Default)]
+struct Highlighter {
+    spans: Vec<(usize, usize, Option<&'static str>)>,
+    class: Option<&'static str>,
+}
+
+impl Highlighter {
+    fn push(&mut self, token: &Token, class: Option<&'static str>) {
+        let beg = token.pos as usize;
+        self.spans.push((beg, beg + token.length as usize, class));
+    }
+
+    fn with_class(&mut self, class: &'static str, f: impl FnOnce(&mut Self)) {
+        self.class = Some(class);
+        f(self);
+        self.class = None;
+    }
+}
+
+impl VerylWalker for Highlighter {
+    fn veryl_token(&mut self, arg: &VerylToken) {
+        let text = arg.token.to_string();
+        let class = if self.class.is_some() {
+            self.class
+        } else if KEYWORDS.contains(&text.as_str()) {
+            Some("keyword")
+        } else if text.starts_with('$') {
+            Some("built_in")
+        } else {
+            None
+        };
+        self.push(&arg.token, class);
+        for x in &arg.comments {
+            self.push(x, Some("comment"));
+        }
+    }
+
+    fn string_literal(&mut self, arg: &StringLiteral) {
+        self.with_class("string", |x| x.veryl_token(&arg.string_literal_token));
+    }
+
+    fn exponent(&mut self, arg: &Exponent) {
+        self.with_class("number", |x| x.veryl_token(&arg.exponent_token));
+    }
+
+    fn fixed_point(&mut self, arg: &FixedPoint) {
+        self.with_class("number", |x| x.veryl_token(&arg.fixed_point_token));
+    }
+
+    fn based(&mut self, arg: &Based) {
+        self.with_class("number", |x| x.veryl_token(&arg.based_token));
+    }
+
+    fn base_less(&mut self, arg: &BaseLess) {
+        self.with_class("number", |x| x.veryl_token(&arg.base_less_token));
+    }
+
+    fn all_bit(&mut self, arg: &AllBit) {
+        self.with_class("number", |x| x.veryl_token(&arg.all_bit_token));
+    }
+
+    fn clock_domain(&mut self, arg: &ClockDomain) {
+        self.with_class("attribute", |x| {
+            x.veryl_token(&arg.quote.quote_token);
+            x.veryl_token(&arg.identifier.identifier_token);
+        });
+    }
+}
+
+/// Highlight Veryl source `text` parsed as `input` to HTML lines with `L<n>` anchors
+pub fn highlight(input: &Veryl, text: &str) -> String {
+    let mut highlighter = Highlighter::default();
+    highlighter.veryl(input);
+    let mut spans = highlighter.spans;
+    spans.sort_by_key(|x| x.0);
+
+    // merge adjacent tokens of the same class (e.g. `'` and `a` of clock domain)
+    let mut merged: Vec<(usize, usize, Option<&str>)> = Vec::new();
+    for x in spans {
+        match merged.last_mut() {
+            Some(last) if x.2.is_some() && last.2 == x.2 && last.1 == x.0 => last.1 = x.1,
+            _ => merged.push(x),
+        }
+    }
+
+    let mut ret = String::new();
+    let mut pos = 0;
+    for (beg, end, class) in merged {
+        if beg < pos || end > text.len() {
+            continue;
+        }
+        ret.push_str(&escape(&text[pos..beg]));
+        let token = &text[beg..end];
+        if let Some(class) = class {
+            // line comments include the trailing newline
+            let body = token.trim_end_matches(['\r', '\n']);
+            push_span(&mut ret, class, body);
+            ret.push_str(&token[body.len()..]);
+        } else {
+            ret.push_str(&escape(token));
+        }
+        pos = end;
+    }
+    ret.push_str(&escape(&text[pos..]));
+
+    let lines: Vec<_> = ret.trim_end_matches('\n').split('\n').collect();
+    let digits = lines.len().to_string().len();
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let n = i + 1;
+            format!("<span class=\"line_number\" id=\"L{n}\">{n:>digits$}</span> {x}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,78 @@
+mod export;
+mod import;
+mod xml;
+pub use export::*;
+pub use import::*;
+pub use xml::*;
+
+use miette::{self, Diagnostic};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Diagnostic, Debug)]
+pub enum IpxactError {
+    #[diagnostic(
+        code(IpxactError::InvalidVlnv),
+        help("use \"vendor:library:name:version\"")
+    )]
+    #[error("\"{vlnv}\" is not a valid VLNV")]
+    InvalidVlnv { vlnv: String },
+
+    #[diagnostic(code(IpxactError::InvalidXml), help(""))]
+    #[error("invalid XML: {cause}")]
+    InvalidXml { cause: String },
+
+    #[diagnostic(code(IpxactError::MissingElement), help(""))]
+    #[error("\"{name}\" element is not found")]
+    MissingElement { name: String },
+}
+
+/// Vendor, library, name and version identifying an IP-XACT object
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vlnv {
+    pub vendor: String,
+    pub library: String,
+    pub name: String,
+    pub version: String,
+}
+
+impl Vlnv {
+    pub fn attrs(&self) -> [(&str, &str); 4] {
+        [
+            ("vendor", &self.vendor),
+            ("library", &self.library),
+            ("name", &self.name),
+            ("version", &self.version),
+        ]
+    }
+}
+
+impl FromStr for Vlnv {
+    type Err = IpxactError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let x: Vec<_> = s.split(':').collect();
+        if x.len() != 4 || x.iter().any(|x| x.is_empty()) {
+            return Err(IpxactError::InvalidVlnv {
+                vlnv: s.to_string(),
+            });
+        }
+        Ok(Self {
+            vendor: x[0].to_string(),
+            library: x[1].to_string(),
+            name: x[2].to_string(),
+            version: x[3].to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Vlnv {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = format!(
+            "{}:{}:{}:{}",
+            self.vendor, self.library, self.name, self.version
+        );
+        text.fmt(f)
+    }
+}
@@ -0,0 +1,442 @@
+use crate::ipxact::{IPXACT_NAMESPACE, IpxactError, Vlnv, XmlWriter};
+use log::warn;
+use std::path::Path;
+use veryl_analyzer::attribute::ExpandItem;
+use veryl_analyzer::attribute_table;
+use veryl_analyzer::symbol::{Direction, ParameterKind, Port, Symbol, SymbolKind, Type, TypeKind};
+use veryl_analyzer::symbol_table;
+use veryl_metadata::{IpxactBusInterface, IpxactBusMode, Metadata};
+use veryl_parser::Stringifier;
+use veryl_parser::veryl_grammar_trait::Expression;
+use veryl_parser::veryl_walker::VerylWalker;
+
+fn expression(x: &Expression) -> String {
+    let mut stringifier = Stringifier::new();
+    stringifier.expression(x);
+    stringifier.as_str().to_string()
+}
+
+fn is_identifier(x: &str) -> bool {
+    x.chars().all(|x| x.is_ascii_alphanumeric() || x == '_')
+}
+
+/// Width of the type as (left, right) of IP-XACT vector.
+/// Widths which can't be evaluated are kept as expressions.
+fn vector(r#type: &Type) -> Option<(String, String)> {
+    let base = match r#type.kind {
+        TypeKind::U8 | TypeKind::I8 | TypeKind::P8 => 8,
+        TypeKind::U16 | TypeKind::I16 | TypeKind::P16 => 16,
+        TypeKind::U32 | TypeKind::I32 | TypeKind::P32 | TypeKind::F32 => 32,
+        TypeKind::U64 | TypeKind::I64 | TypeKind::P64 | TypeKind::F64 => 64,
+        TypeKind::UserDefined(_) => return None,
+        _ => 1,
+    };
+
+    let width: Vec<_> = r#type.width.iter().map(expression).collect();
+    let numbers: Option<Vec<u64>> = width.iter().map(|x| x.parse().ok()).collect();
+    let left = match numbers {
+        Some(x) => {
+            let width = x.iter().product::<u64>() * base;
+            if width == 1 {
+                return None;
+            }
+            (width - 1).to_string()
+        }
+        None => {
+            let mut width: Vec<_> = width
+                .iter()
+                .map(|x| {
+                    if is_identifier(x) {
+                        x.clone()
+                    } else {
+                        format!("({x})")
+                    }
+                })
+                .collect();
+            if base != 1 {
+                width.push(base.to_string());
+            }
+            format!("{}-1", width.join("*"))
+        }
+    };
+    Some((left, "0".to_string()))
+}
+
+fn type_name(r#type: &Type) -> Option<String> {
+    if let TypeKind::UserDefined(x) = &r#type.kind {
+        Some(x.path.to_string())
+    } else {
+        None
+    }
+}
+
+fn ipxact_direction(x: Direction) -> Option<&'static str> {
+    match x {
+        Direction::Input => Some("in"),
+        Direction::Output => Some("out"),
+        Direction::Inout => Some("inout"),
+        _ => None,
+    }
+}
+
+struct PhysicalPort {
+    name: String,
+    direction: &'static str,
+    vector: Option<(String, String)>,
+    array: Vec<String>,
+    type_name: Option<String>,
+}
+
+struct BusInterface<'a> {
+    name: String,
+    config: &'a IpxactBusInterface,
+    /// pairs of logical port and physical port
+    port_maps: Vec<(String, String)>,
+}
+
+struct Exporter<'a> {
+    metadata: &'a Metadata,
+    ports: Vec<PhysicalPort>,
+    bus_interfaces: Vec<BusInterface<'a>>,
+}
+
+impl<'a> Exporter<'a> {
+    fn bus_config(&self, r#type: &Type) -> Option<&'a IpxactBusInterface> {
+        let user_defined = r#type.get_user_defined()?;
+        let mut path: Vec<_> = user_defined
+            .path
+            .paths
+            .iter()
+            .map(|x| x.base.to_string())
+            .collect();
+        let modport = path.pop()?;
+        let interface = path.join("::");
+        self.metadata.ipxact.bus_interfaces.iter().find(|x| {
+            x.modport == modport
+                && (x.interface == interface || interface.ends_with(&format!("::{}", x.interface)))
+        })
+    }
+
+    fn port(&mut self, port: &Port) {
+        let property = port.property();
+        let name = port.name().to_string();
+
+        if let Some(direction) = ipxact_direction(property.direction) {
+            self.ports.push(PhysicalPort {
+                name,
+                direction,
+                vector: vector(&property.r#type),
+                array: property.r#type.array.iter().map(expression).collect(),
+                type_name: type_name(&property.r#type),
+            });
+            return;
+        }
+
+        if property.direction != Direction::Modport {
+            warn!("Port \"{name}\" can't be exported to IP-XACT");
+            return;
+        }
+
+        if !attribute_table::is_expand(&port.token.token, ExpandItem::Modport) {
+            warn!(
+                "Port \"{name}\" is exported as expanded ports, but #[expand(modport)] is not specified"
+            );
+        }
+
+        let symbol = port.symbol();
+        let Some((_, Some(modport))) = property.r#type.trace_user_defined(Some(&symbol.namespace))
+        else {
+            warn!("Modport of port \"{name}\" is not found");
+            return;
+        };
+        let SymbolKind::Modport(modport) = modport.kind else {
+            return;
+        };
+
+        let array: Option<Vec<usize>> = property
+            .r#type
+            .array
+            .iter()
+            .map(|x| expression(x).parse().ok())
+            .collect();
+        let Some(array) = array else {
+            warn!("Array size of port \"{name}\" should be a constant number");
+            return;
+        };
+        let mut indices = vec![vec![]];
+        for size in &array {
+            indices = indices
+                .into_iter()
+                .flat_map(|x: Vec<usize>| {
+                    (0..*size).map(move |i| {
+                        let mut x = x.clone();
+                        x.push(i);
+                        x
+                    })
+                })
+                .collect();
+        }
+
+        let config = self.bus_config(&property.r#type);
+        for index in indices {
+            let prefix = if index.is_empty() {
+                name.clone()
+            } else {
+                let index: Vec<_> = index.iter().map(|x| x.to_string()).collect();
+                format!("{name}_{}", index.join("_"))
+            };
+
+            let mut port_maps = vec![];
+            for member in &modport.members {
+                let Some(member) = symbol_table::get(*member) else {
+                    continue;
+                };
+                let SymbolKind::ModportVariableMember(x) = member.kind else {
+                    continue;
+                };
+                let Some(variable) = symbol_table::get(x.variable) else {
+                    continue;
+                };
+                let SymbolKind::Variable(variable_property) = variable.kind else {
+                    continue;
+                };
+                let Some(direction) = ipxact_direction(x.direction) else {
+                    continue;
+                };
+
+                let member_name = variable.token.to_string();
+                let physical = format!("__{prefix}_{member_name}");
+                if let Some(config) = config {
+                    let logical = config
+                        .port_maps
+                        .get(&member_name)
+                        .cloned()
+                        .unwrap_or_else(|| member_name.to_uppercase());
+                    port_maps.push((logical, physical.clone()));
+                }
+                self.ports.push(PhysicalPort {
+                    name: physical,
+                    direction,
+                    vector: vector(&variable_property.r#type),
+                    array: vec![],
+                    type_name: type_name(&variable_property.r#type),
+                });
+            }
+
+            if let Some(config) = config {
+                self.bus_interfaces.push(BusInterface {
+                    name: prefix,
+                    config,
+                    port_maps,
+                });
+            }
+        }
+    }
+}
+
+fn write_bus_interface(writer: &mut XmlWriter, x: &BusInterface) -> Result<(), IpxactError> {
+    let bus_type: Vlnv = x.config.bus_type.parse()?;
+    writer.start("busInterface", &[]);
+    writer.element("name", &x.name);
+    writer.empty("busType", &bus_type.attrs());
+    if let Some(abstraction_type) = &x.config.abstraction_type {
+        let abstraction_type: Vlnv = abstraction_type.parse()?;
+        writer.start("abstractionTypes", &[]);
+        writer.start("abstractionType", &[]);
+        writer.empty("abstractionRef", &abstraction_type.attrs());
+        writer.start("portMaps", &[]);
+        for (logical, physical) in &x.port_maps {
+            writer.start("portMap", &[]);
+            writer.start("logicalPort", &[]);
+            writer.element("name", logical);
+            writer.end("logicalPort");
+            writer.start("physicalPort", &[]);
+            writer.element("name", physical);
+            writer.end("physicalPort");
+            writer.end("portMap");
+        }
+        writer.end("portMaps");
+        writer.end("abstractionType");
+        writer.end("abstractionTypes");
+    }
+    let mode = match x.config.mode {
+        IpxactBusMode::Master => "master",
+        IpxactBusMode::Slave => "slave",
+        IpxactBusMode::MirroredMaster => "mirroredMaster",
+        IpxactBusMode::MirroredSlave => "mirroredSlave",
+    };
+    writer.empty(mode, &[]);
+    writer.end("busInterface");
+    Ok(())
+}
+
+fn write_port(writer: &mut XmlWriter, x: &PhysicalPort) {
+    writer.start("port", &[]);
+    writer.element("name", &x.name);
+    writer.start("wire", &[]);
+    writer.element("direction", x.direction);
+    if let Some((left, right)) = &x.vector {
+        writer.start("vectors", &[]);
+        writer.start("vector", &[]);
+        writer.element("left", left);
+        writer.element("right", right);
+        writer.end("vector");
+        writer.end("vectors");
+    }
+    if let Some(type_name) = &x.type_name {
+        writer.start("wireTypeDefs", &[]);
+        writer.start("wireTypeDef", &[]);
+        writer.element("typeName", type_name);
+        writer.element("viewRef", "rtl");
+        writer.end("wireTypeDef");
+        writer.end("wireTypeDefs");
+    }
+    writer.end("wire");
+    if !x.array.is_empty() {
+        writer.start("arrays", &[]);
+        for size in &x.array {
+            let right = match size.parse::<u64>() {
+                Ok(x) => (x.saturating_sub(1)).to_string(),
+                Err(_) if is_identifier(size) => format!("{size}-1"),
+                Err(_) => format!("({size})-1"),
+            };
+            writer.start("array", &[]);
+            writer.element("left", "0");
+            writer.element("right", &right);
+            writer.end("array");
+        }
+        writer.end("arrays");
+    }
+    writer.end("port");
+}
+
+/// Generate IP-XACT (IEEE 1685-2014) component description of the module.
+/// `file` is the path of the emitted SystemVerilog relative to the output XML.
+pub fn export_component(
+    metadata: &Metadata,
+    symbol: &Symbol,
+    file: Option<&Path>,
+) -> Result<String, IpxactError> {
+    let SymbolKind::Module(ref property) = symbol.kind else {
+        return Err(IpxactError::MissingElement {
+            name: "module".to_string(),
+        });
+    };
+
+    let project = &metadata.project;
+    let name = symbol.token.to_string();
+    let vendor = metadata.ipxact.vendor.as_ref().unwrap_or(&project.name);
+    let library = metadata.ipxact.library.as_ref().unwrap_or(&project.name);
+    let version = project
+        .version
+        .as_ref()
+        .map(|x| x.to_string())
+        .unwrap_or_else(|| "0.0.0".to_string());
+    let module_name = if metadata.build.omit_project_prefix {
+        name.clone()
+    } else {
+        format!("{}_{name}", project.name)
+    };
+
+    let mut exporter = Exporter {
+        metadata,
+        ports: vec![],
+        bus_interfaces: vec![],
+    };
+    for port in &property.ports {
+        exporter.port(port);
+    }
+
+    let mut writer = XmlWriter::default();
+    let schema_location = format!("{IPXACT_NAMESPACE} {IPXACT_NAMESPACE}/index.xsd");
+    writer.start(
+        "component",
+        &[
+            ("xmlns:ipxact", IPXACT_NAMESPACE),
+            ("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"),
+            ("xsi:schemaLocation", &schema_location),
+        ],
+    );
+    writer.element("vendor", vendor);
+    writer.element("library", library);
+    writer.element("name", &name);
+    writer.element("version", &version);
+    if !symbol.doc_comment.is_empty() {
+        let text = symbol.doc_comment.format(false);
+        let lines: Vec<_> = text.lines().map(|x| x.trim()).collect();
+        writer.element("description", lines.join("\n").trim());
+    }
+
+    if !exporter.bus_interfaces.is_empty() {
+        writer.start("busInterfaces", &[]);
+        for x in &exporter.bus_interfaces {
+            write_bus_interface(&mut writer, x)?;
+        }
+        writer.end("busInterfaces");
+    }
+
+    writer.start("model", &[]);
+    writer.start("views", &[]);
+    writer.start("view", &[]);
+    writer.element("name", "rtl");
+    writer.element("componentInstantiationRef", "rtl");
+    writer.end("view");
+    writer.end("views");
+
+    writer.start("instantiations", &[]);
+    writer.start("componentInstantiation", &[]);
+    writer.element("name", "rtl");
+    writer.element("language", "systemverilog");
+    writer.element("moduleName", &module_name);
+    let parameters: Vec<_> = property
+        .parameters
+        .iter()
+        .filter(|x| matches!(x.property().kind, ParameterKind::Param))
+        .collect();
+    if !parameters.is_empty() {
+        writer.start("moduleParameters", &[]);
+        for x in parameters {
+            let name = x.name.to_string();
+            let value = x.property().value.as_ref().map(expression);
+            writer.start(
+                "moduleParameter",
+                &[("parameterId", &name), ("resolve", "user")],
+            );
+            writer.element("name", &name);
+            writer.element("value", value.as_deref().unwrap_or(""));
+            writer.end("moduleParameter");
+        }
+        writer.end("moduleParameters");
+    }
+    if file.is_some() {
+        writer.start("fileSetRef", &[]);
+        writer.element("localName", "rtl");
+        writer.end("fileSetRef");
+    }
+    writer.end("componentInstantiation");
+    writer.end("instantiations");
+
+    if !exporter.ports.is_empty() {
+        writer.start("ports", &[]);
+        for x in &exporter.ports {
+            write_port(&mut writer, x);
+        }
+        writer.end("ports");
+    }
+    writer.end("model");
+
+    if let Some(file) = file {
+        writer.start("fileSets", &[]);
+        writer.start("fileSet", &[]);
+        writer.element("name", "rtl");
+        writer.start("file", &[]);
+        writer.element("name", &file.to_string_lossy().replace('\\', "/"));
+        writer.element("fileType", "systemVerilogSource");
+        writer.end("file");
+        writer.end("fileSet");
+        writer.end("fileSets");
+    }
+
+    writer.end("component");
+    Ok(writer.finish())
+}
@@ -0,0 +1,366 @@
+use crate::ipxact::{Element, IpxactError};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+struct ImportedParameter {
+    name: String,
+    sv_name: String,
+    r#type: &'static str,
+    value: String,
+}
+
+struct ImportedPort {
+    name: String,
+    sv_name: String,
+    direction: &'static str,
+    width: Option<String>,
+    array: Vec<String>,
+}
+
+fn missing(name: &str) -> IpxactError {
+    IpxactError::MissingElement {
+        name: name.to_string(),
+    }
+}
+
+/// Convert to a valid identifier by replacing invalid characters
+fn sv_identifier(x: &str) -> String {
+    let mut ret: String = x
+        .chars()
+        .map(|x| {
+            if x.is_ascii_alphanumeric() || x == '_' {
+                x
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if ret.chars().next().is_none_or(|x| x.is_ascii_digit()) {
+        ret.insert(0, '_');
+    }
+    ret
+}
+
+/// Convert to a valid Veryl identifier.
+/// `__` prefix is reserved in Veryl, so it is removed.
+fn identifier(x: &str) -> String {
+    let ret = sv_identifier(x);
+    let trimmed = ret.trim_start_matches('_');
+    if trimmed.is_empty() || trimmed.starts_with(|x: char| x.is_ascii_digit()) {
+        format!("_{trimmed}")
+    } else if ret.starts_with("__") {
+        trimmed.to_string()
+    } else {
+        ret
+    }
+}
+
+fn connection(name: &str, sv_name: &str) -> String {
+    if name == sv_name {
+        name.to_string()
+    } else {
+        format!("{sv_name}: {name}")
+    }
+}
+
+fn is_simple(x: &str) -> bool {
+    x.chars().all(|x| x.is_ascii_alphanumeric() || x == '_')
+}
+
+fn parenthesize(x: &str) -> String {
+    if is_simple(x) {
+        x.to_string()
+    } else {
+        format!("({x})")
+    }
+}
+
+/// Replace parameter IDs in the expression by parameter names
+fn rename(x: &str, ids: &HashMap<String, String>) -> String {
+    // IEEE 1685-2009 refers other parameters by `id('ID')`
+    let mut x = x.to_string();
+    while let Some(start) = x.find("id('") {
+        let Some(len) = x[start + 4..].find("')") else {
+            break;
+        };
+        let id = x[start + 4..start + 4 + len].to_string();
+        x.replace_range(start..start + 4 + len + 2, &id);
+    }
+
+    let mut ret = String::new();
+    let mut word = String::new();
+    let flush = |word: &mut String, ret: &mut String| {
+        let id = std::mem::take(word);
+        ret.push_str(ids.get(&id).unwrap_or(&id));
+    };
+    for c in x.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '\'' {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut ret);
+            ret.push(c);
+        }
+    }
+    flush(&mut word, &mut ret);
+    ret.trim().to_string()
+}
+
+/// Width of `[left:right]`
+fn width(left: &str, right: &str) -> String {
+    if let (Ok(left), Ok(right)) = (left.parse::<i64>(), right.parse::<i64>()) {
+        return ((left - right).abs() + 1).to_string();
+    }
+
+    // arrays are often described as `[0:N-1]`
+    let (left, right) = if left == "0" {
+        (right, left)
+    } else {
+        (left, right)
+    };
+    if right == "0" {
+        let compact: String = left.chars().filter(|x| !x.is_whitespace()).collect();
+        if let Some(x) = compact.strip_suffix("-1") {
+            let x = x
+                .strip_prefix('(')
+                .and_then(|x| x.strip_suffix(')'))
+                .filter(|x| is_simple(x))
+                .unwrap_or(x);
+            return x.to_string();
+        }
+        format!("{}+1", parenthesize(left))
+    } else {
+        format!("{}-{}+1", parenthesize(left), parenthesize(right))
+    }
+}
+
+/// Value of the element.
+/// IEEE 1685-2009 describes expressions by `dependency` attribute.
+fn expression_of(x: &Element, ids: &HashMap<String, String>) -> String {
+    rename(x.attr("dependency").unwrap_or(x.text.trim()), ids)
+}
+
+fn range(x: &Element, ids: &HashMap<String, String>) -> Option<String> {
+    let left = expression_of(x.child("left")?, ids);
+    let right = expression_of(x.child("right")?, ids);
+    Some(width(&left, &right))
+}
+
+struct Importer {
+    /// Parameter IDs and names
+    ids: HashMap<String, String>,
+    parameters: Vec<ImportedParameter>,
+    ports: Vec<ImportedPort>,
+}
+
+impl Importer {
+    fn parameter_elements(component: &Element) -> Vec<&Element> {
+        // IEEE 1685-2014 / 2022
+        if let Some(instantiations) = component.path(&["model", "instantiations"]) {
+            let ret: Vec<_> = instantiations
+                .children("componentInstantiation")
+                .filter_map(|x| x.child("moduleParameters"))
+                .flat_map(|x| x.children("moduleParameter"))
+                .collect();
+            if !ret.is_empty() {
+                return ret;
+            }
+        }
+        // IEEE 1685-2009
+        if let Some(x) = component.path(&["model", "modelParameters"]) {
+            return x.children("modelParameter").collect();
+        }
+        component
+            .child("parameters")
+            .map(|x| x.children("parameter").collect())
+            .unwrap_or_default()
+    }
+
+    fn parameters(&mut self, component: &Element) {
+        let elements = Self::parameter_elements(component);
+        for x in &elements {
+            let Some(name) = x.text_of("name") else {
+                continue;
+            };
+            let name = identifier(name);
+            let ids = [
+                x.attr("parameterId"),
+                x.child("value").and_then(|x| x.attr("id")),
+            ];
+            for id in ids.into_iter().flatten() {
+                self.ids.insert(id.to_string(), name.clone());
+            }
+        }
+
+        for x in elements {
+            let Some(name) = x.text_of("name") else {
+                continue;
+            };
+            let Some(value) = x.child("value") else {
+                continue;
+            };
+            let text = value.text.trim();
+            let (r#type, value) = if text.starts_with('"') {
+                ("string", text.to_string())
+            } else if value.attr("format").is_some_and(|x| x == "string") {
+                ("string", format!("\"{text}\""))
+            } else {
+                ("u32", expression_of(value, &self.ids))
+            };
+            self.parameters.push(ImportedParameter {
+                name: identifier(name),
+                sv_name: sv_identifier(name),
+                r#type,
+                value,
+            });
+        }
+    }
+
+    fn ports(&mut self, component: &Element) {
+        let Some(ports) = component.path(&["model", "ports"]) else {
+            return;
+        };
+        for x in ports.children("port") {
+            let Some(name) = x.text_of("name") else {
+                continue;
+            };
+            // transactional ports can't be represented
+            let Some(wire) = x.child("wire") else {
+                continue;
+            };
+            let direction = match wire.text_of("direction") {
+                Some("in") => "input",
+                Some("out") => "output",
+                Some("inout") => "inout",
+                _ => continue,
+            };
+
+            let vector = wire
+                .child("vectors")
+                .and_then(|x| x.child("vector"))
+                .or_else(|| wire.child("vector"));
+            let width = vector
+                .and_then(|x| range(x, &self.ids))
+                .filter(|x| x != "1");
+
+            let array = x
+                .child("arrays")
+                .map(|x| {
+                    x.children("array")
+                        .filter_map(|x| range(x, &self.ids))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            self.ports.push(ImportedPort {
+                name: identifier(name),
+                sv_name: sv_identifier(name),
+                direction,
+                width,
+                array,
+            });
+        }
+    }
+
+    fn parameter_list(&self, ret: &mut String, value: bool) {
+        if self.parameters.is_empty() {
+            return;
+        }
+        writeln!(ret, " #(").unwrap();
+        for x in &self.parameters {
+            if value {
+                writeln!(ret, "param {}: {} = {},", x.name, x.r#type, x.value).unwrap();
+            } else {
+                writeln!(ret, "param {}: {},", x.name, x.r#type).unwrap();
+            }
+        }
+        write!(ret, ")").unwrap();
+    }
+
+    fn port_list(&self, ret: &mut String) {
+        if self.ports.is_empty() {
+            return;
+        }
+        writeln!(ret, " (").unwrap();
+        for x in &self.ports {
+            write!(ret, "{}: {} logic", x.name, x.direction).unwrap();
+            if let Some(width) = &x.width {
+                write!(ret, "<{width}>").unwrap();
+            }
+            if !x.array.is_empty() {
+                write!(ret, " [{}]", x.array.join(", ")).unwrap();
+            }
+            writeln!(ret, ",").unwrap();
+        }
+        write!(ret, ")").unwrap();
+    }
+}
+
+/// Generate Veryl source from IP-XACT component description.
+///
+/// The generated source has a proto module which has the same parameters and ports as the
+/// component, and a wrapper module implementing it by instantiating the SystemVerilog module.
+/// IEEE 1685-2009 (`spirit:`), 2014 and 2022 are supported.
+/// The returned text is not formatted.
+pub fn import_component(text: &str, source: &str) -> Result<String, IpxactError> {
+    let component = Element::parse(text)?;
+    if component.name != "component" {
+        return Err(missing("component"));
+    }
+
+    let name = component.text_of("name").ok_or_else(|| missing("name"))?;
+    let module_name = component
+        .path(&["model", "instantiations", "componentInstantiation"])
+        .and_then(|x| x.text_of("moduleName"))
+        .unwrap_or(name);
+    let name = identifier(name);
+    let module_name = sv_identifier(module_name);
+
+    let mut importer = Importer {
+        ids: HashMap::new(),
+        parameters: vec![],
+        ports: vec![],
+    };
+    importer.parameters(&component);
+    importer.ports(&component);
+
+    let mut ret = String::new();
+    writeln!(ret, "// Generated by `veryl ipxact` from {source}.").unwrap();
+    writeln!(ret, "// Do not edit this file directly.").unwrap();
+    writeln!(ret).unwrap();
+
+    if let Some(x) = component.text_of("description") {
+        for line in x.lines() {
+            writeln!(ret, "/// {}", line.trim()).unwrap();
+        }
+    }
+    write!(ret, "pub proto module {name}_proto").unwrap();
+    importer.parameter_list(&mut ret, false);
+    importer.port_list(&mut ret);
+    writeln!(ret, ";").unwrap();
+    writeln!(ret).unwrap();
+
+    writeln!(ret, "/// Wrapper of SystemVerilog module `{module_name}`").unwrap();
+    write!(ret, "pub module {name}_wrapper for {name}_proto").unwrap();
+    importer.parameter_list(&mut ret, true);
+    importer.port_list(&mut ret);
+    writeln!(ret, " {{").unwrap();
+    write!(ret, "inst u: $sv::{module_name}").unwrap();
+    if !importer.parameters.is_empty() {
+        writeln!(ret, " #(").unwrap();
+        for x in &importer.parameters {
+            writeln!(ret, "{},", connection(&x.name, &x.sv_name)).unwrap();
+        }
+        write!(ret, ")").unwrap();
+    }
+    if !importer.ports.is_empty() {
+        writeln!(ret, " (").unwrap();
+        for x in &importer.ports {
+            writeln!(ret, "{},", connection(&x.name, &x.sv_name)).unwrap();
+        }
+        write!(ret, ")").unwrap();
+    }
+    writeln!(ret, ";").unwrap();
+    writeln!(ret, "}}").unwrap();
+
+    Ok(ret)
+}
@@ -0,0 +1,181 @@
+use crate::ipxact::IpxactError;
+use quick_xml::XmlVersion;
+use quick_xml::escape::{escape, resolve_predefined_entity};
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+use std::fmt::Write;
+
+pub const IPXACT_NAMESPACE: &str = "http://www.accellera.org/XMLSchema/IPXACT/1685-2014";
+
+/// Writer of IP-XACT XML elements with `ipxact:` prefix
+pub struct XmlWriter {
+    buf: String,
+    depth: usize,
+}
+
+impl Default for XmlWriter {
+    fn default() -> Self {
+        let mut buf = String::new();
+        writeln!(buf, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+        Self { buf, depth: 0 }
+    }
+}
+
+impl XmlWriter {
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            self.buf.push_str("  ");
+        }
+    }
+
+    fn tag(&mut self, tag: &str, attrs: &[(&str, &str)]) {
+        write!(self.buf, "<ipxact:{tag}").unwrap();
+        for (key, value) in attrs {
+            write!(self.buf, " {key}=\"{}\"", escape(*value)).unwrap();
+        }
+    }
+
+    pub fn start(&mut self, tag: &str, attrs: &[(&str, &str)]) {
+        self.indent();
+        self.tag(tag, attrs);
+        writeln!(self.buf, ">").unwrap();
+        self.depth += 1;
+    }
+
+    pub fn end(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.indent();
+        writeln!(self.buf, "</ipxact:{tag}>").unwrap();
+    }
+
+    pub fn element(&mut self, tag: &str, text: &str) {
+        self.indent();
+        writeln!(self.buf, "<ipxact:{tag}>{}</ipxact:{tag}>", escape(text)).unwrap();
+    }
+
+    pub fn empty(&mut self, tag: &str, attrs: &[(&str, &str)]) {
+        self.indent();
+        self.tag(tag, attrs);
+        writeln!(self.buf, "/>").unwrap();
+    }
+
+    pub fn finish(self) -> String {
+        self.buf
+    }
+}
+
+/// Minimal DOM of XML.
+/// Namespace prefixes are dropped from element and attribute names,
+/// so IP-XACT 1685-2009 (`spirit:`), 2014 and 2022 can be handled uniformly.
+#[derive(Clone, Debug, Default)]
+pub struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub text: String,
+    pub children: Vec<Element>,
+}
+
+fn local_name(x: &str) -> String {
+    match x.rsplit_once(':') {
+        Some((_, x)) => x.to_string(),
+        None => x.to_string(),
+    }
+}
+
+fn xml_error<T: ToString>(x: T) -> IpxactError {
+    IpxactError::InvalidXml {
+        cause: x.to_string(),
+    }
+}
+
+impl Element {
+    pub fn parse(text: &str) -> Result<Self, IpxactError> {
+        let mut reader = Reader::from_str(text);
+
+        let mut stack = vec![Element::default()];
+        loop {
+            let event = reader.read_event().map_err(xml_error)?;
+            match event {
+                Event::Start(ref x) | Event::Empty(ref x) => {
+                    let mut element = Element {
+                        name: local_name(x.name().as_ref()),
+                        ..Default::default()
+                    };
+                    for attr in x.attributes() {
+                        let attr = attr.map_err(xml_error)?;
+                        let value = attr
+                            .normalized_value(XmlVersion::Implicit1_0)
+                            .map_err(xml_error)?;
+                        element
+                            .attrs
+                            .push((local_name(attr.key.as_ref()), value.to_string()));
+                    }
+                    if matches!(event, Event::Start(_)) {
+                        stack.push(element);
+                    } else {
+                        stack.last_mut().unwrap().children.push(element);
+                    }
+                }
+                Event::End(_) => {
+                    let mut element = stack.pop().unwrap();
+                    element.text = element.text.trim().to_string();
+                    let Some(parent) = stack.last_mut() else {
+                        return Err(xml_error("unbalanced end tag"));
+                    };
+                    parent.children.push(element);
+                }
+                Event::Text(x) => {
+                    let text = x.xml10_content();
+                    stack.last_mut().unwrap().text.push_str(&text);
+                }
+                Event::GeneralRef(x) => {
+                    let text = stack.last_mut().unwrap();
+                    if let Some(ch) = x.resolve_char_ref().map_err(xml_error)? {
+                        text.text.push(ch);
+                    } else if let Some(x) = resolve_predefined_entity(&x) {
+                        text.text.push_str(x);
+                    } else {
+                        return Err(xml_error(format!("unknown entity: &{};", &*x)));
+                    }
+                }
+                Event::CData(x) => {
+                    let text = x.xml10_content();
+                    stack.last_mut().unwrap().text.push_str(&text);
+                }
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+
+        let mut root = stack.pop().unwrap();
+        if !stack.is_empty() || root.children.len() != 1 {
+            return Err(xml_error("unexpected end of file"));
+        }
+        Ok(root.children.pop().unwrap())
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|x| x.name == name)
+    }
+
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |x| x.name == name)
+    }
+
+    /// Descendant element following `path`
+    pub fn path(&self, path: &[&str]) -> Option<&Element> {
+        path.iter().try_fold(self, |x, name| x.child(name))
+    }
+
+    /// Text of the child element
+    pub fn text_of(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|x| x.text.trim())
+    }
+
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(x, _)| x == name)
+            .map(|(_, x)| x.as_str())
+    }
+}
@@ -1,25 +1,38 @@
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
+use std::str::FromStr;
+use test_report::ReportTarget;
+use veryl_analyzer::wavedrom::CycleRange;
 
+pub mod build_cache;
 pub mod cmd_build;
 pub mod cmd_check;
 pub mod cmd_clean;
+pub mod cmd_debug;
 pub mod cmd_doc;
 pub mod cmd_dump;
 pub mod cmd_fmt;
 pub mod cmd_init;
+pub mod cmd_ipxact;
 pub mod cmd_metadata;
 pub mod cmd_migrate;
 pub mod cmd_new;
 pub mod cmd_publish;
+pub mod cmd_regmap;
+pub mod cmd_repl;
+pub mod cmd_sim;
 pub mod cmd_test;
 pub mod cmd_update;
 pub mod context;
 pub mod diff;
 pub mod doc;
+pub mod ipxact;
+pub mod regmap;
 pub mod runner;
 pub mod stopwatch;
+pub mod test_report;
 pub mod utils;
+pub mod watcher;
 pub use stopwatch::StopWatch;
 
 // ---------------------------------------------------------------------------------------------------------------------
@@ -74,9 +87,14 @@ pub enum Commands {
     Publish(OptPublish),
     Migrate(OptMigrate),
     Doc(OptDoc),
+    Regmap(OptRegmap),
+    Ipxact(OptIpxact),
     Metadata(OptMetadata),
     Dump(OptDump),
     Test(OptTest),
+    Debug(OptDebug),
+    Repl(OptRepl),
+    Sim(OptSim),
 }
 
 /// Create a new project
@@ -101,6 +119,42 @@ pub struct OptFmt {
     /// Run fmt in check mode
     #[arg(long)]
     pub check: bool,
+
+    /// Format only declarations intersecting the line range (e.g. src/a.veryl:10-40)
+    #[arg(long, value_name = "FILE:BEG-END")]
+    pub lines: Vec<LineRange>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LineRange {
+    pub path: PathBuf,
+    pub beg: u32,
+    pub end: u32,
+}
+
+impl FromStr for LineRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, lines) = s
+            .rsplit_once(':')
+            .ok_or_else(|| format!("line range is not specified: {s}"))?;
+        let (beg, end) = lines.split_once('-').unwrap_or((lines, lines));
+        let beg: u32 = beg
+            .parse()
+            .map_err(|_| format!("invalid line number: {beg}"))?;
+        let end: u32 = end
+            .parse()
+            .map_err(|_| format!("invalid line number: {end}"))?;
+        if beg == 0 || end < beg {
+            return Err(format!("invalid line range: {lines}"));
+        }
+        Ok(Self {
+            path: PathBuf::from(path),
+            beg,
+            end,
+        })
+    }
 }
 
 /// Analyze the current project
@@ -108,6 +162,10 @@ pub struct OptFmt {
 pub struct OptCheck {
     /// Target files
     pub files: Vec<PathBuf>,
+
+    /// Re-run check when source files are modified
+    #[arg(long)]
+    pub watch: bool,
 }
 
 /// Build the target codes corresponding to the current project
@@ -119,6 +177,50 @@ pub struct OptBuild {
     /// Run build in check mode
     #[arg(long)]
     pub check: bool,
+
+    /// Top modules to be built (overrides Veryl.toml)
+    #[arg(long)]
+    pub top: Vec<String>,
+
+    /// Filelist types to be generated (overrides Veryl.toml)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub filelist_type: Vec<FilelistType>,
+
+    /// Re-run build when source files are modified
+    #[arg(long)]
+    pub watch: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum FilelistType {
+    /// Absolute paths
+    Absolute,
+    /// Relative paths
+    Relative,
+    /// flgen
+    Flgen,
+    /// AMD Vivado TCL script
+    Vivado,
+    /// Intel Quartus settings fragment
+    Quartus,
+    /// Verilator/VCS filelist with include directories
+    Verilator,
+    /// FuseSoC core description
+    Fusesoc,
+}
+
+impl From<FilelistType> for veryl_metadata::FilelistType {
+    fn from(x: FilelistType) -> Self {
+        match x {
+            FilelistType::Absolute => veryl_metadata::FilelistType::Absolute,
+            FilelistType::Relative => veryl_metadata::FilelistType::Relative,
+            FilelistType::Flgen => veryl_metadata::FilelistType::Flgen,
+            FilelistType::Vivado => veryl_metadata::FilelistType::Vivado,
+            FilelistType::Quartus => veryl_metadata::FilelistType::Quartus,
+            FilelistType::Verilator => veryl_metadata::FilelistType::Verilator,
+            FilelistType::Fusesoc => veryl_metadata::FilelistType::Fusesoc,
+        }
+    }
 }
 
 /// Clean-up the current project
@@ -127,7 +229,11 @@ pub struct OptClean {}
 
 /// Update dependencies
 #[derive(Args)]
-pub struct OptUpdate {}
+pub struct OptUpdate {
+    /// Verify locked dependencies without updating them
+    #[arg(long)]
+    pub verify: bool,
+}
 
 /// Publish the current project
 #[derive(Args)]
@@ -153,6 +259,45 @@ pub struct OptMigrate {
 pub struct OptDoc {
     /// Target files
     pub files: Vec<PathBuf>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t)]
+    pub format: DocFormat,
+
+    /// Report documentation coverage instead of generating documents
+    #[arg(long)]
+    pub coverage: bool,
+}
+
+#[derive(Clone, Copy, Default, Debug, ValueEnum)]
+pub enum DocFormat {
+    /// HTML site by mdBook
+    #[default]
+    Html,
+    /// Machine-readable JSON
+    Json,
+}
+
+/// Generate register blocks from register map descriptions
+#[derive(Args)]
+pub struct OptRegmap {
+    /// Target register map descriptions (overrides Veryl.toml)
+    pub files: Vec<PathBuf>,
+
+    /// Run regmap in check mode
+    #[arg(long)]
+    pub check: bool,
+}
+
+/// Export or import IP-XACT component descriptions
+#[derive(Args)]
+pub struct OptIpxact {
+    /// Target files
+    pub files: Vec<PathBuf>,
+
+    /// IP-XACT component descriptions to be imported as proto modules
+    #[arg(long)]
+    pub import: Vec<PathBuf>,
 }
 
 /// Execute tests
@@ -173,6 +318,18 @@ pub struct OptTest {
     #[arg(long)]
     pub wave: bool,
 
+    /// Dump waveform of native tests and doc tests as WaveDrom JSON
+    #[arg(long)]
+    pub wavedrom: bool,
+
+    /// Signals written to WaveDrom JSON (e.g. clk,cnt)
+    #[arg(long, value_delimiter = ',')]
+    pub wavedrom_signals: Vec<String>,
+
+    /// Cycles written to WaveDrom JSON (e.g. 4..16)
+    #[arg(long, value_name = "BEG..END")]
+    pub wavedrom_cycles: Option<CycleRange>,
+
     /// Disable JIT compilation for native tests
     #[arg(long)]
     pub disable_jit: bool,
@@ -181,6 +338,58 @@ pub struct OptTest {
     #[arg(long)]
     pub disable_ff_opt: bool,
 
+    /// Simulate native tests with 4-state values
+    #[arg(long)]
+    pub four_state: bool,
+
+    /// Treatment of unknown conditions of `if` and `case` in 4-state simulation
+    #[arg(long, value_enum, default_value_t = XProp::Standard)]
+    pub xprop: XProp,
+
+    /// Initial value of registers
+    #[arg(long, value_enum, default_value_t = RegisterInit::Declared)]
+    pub register_init: RegisterInit,
+
+    /// Report registers which are still X after reset
+    #[arg(long, requires = "four_state")]
+    pub report_x: bool,
+
+    /// Disable the on-disk cache of JIT-compiled code at `.build/jit_cache`
+    #[arg(long)]
+    pub disable_jit_cache: bool,
+
+    /// Size limit of the on-disk JIT cache in MiB
+    #[arg(long, value_name = "MIB", default_value_t = 256)]
+    pub jit_cache_size: u64,
+
+    /// Number of threads simulating each native test
+    #[arg(long, default_value_t = 1)]
+    pub threads: usize,
+
+    /// Number of seeds (SEED..SEED+N) swept by native tests with `#[seed(var)]`
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub seeds: usize,
+
+    /// Seed of randomized native tests (`#[seed(var)]` variable and random register init)
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Wall-clock timeout of each native test in seconds; 0 disables it
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Maximum number of cycles of each clock in native tests, excluding reset
+    #[arg(long, value_name = "CYCLES")]
+    pub max_cycles: Option<u64>,
+
+    /// Maximum simulation time of each native test
+    #[arg(long, value_name = "TIME")]
+    pub max_time: Option<u64>,
+
+    /// Write test report (e.g. junit=report.xml, json=report.json)
+    #[arg(long, value_name = "FORMAT=PATH")]
+    pub report: Vec<ReportTarget>,
+
     /// Run only ignored tests
     #[arg(long)]
     pub ignored: bool,
@@ -188,6 +397,55 @@ pub struct OptTest {
     /// Run both ignored and non-ignored tests
     #[arg(long)]
     pub include_ignored: bool,
+
+    /// Re-run affected tests when source files are modified
+    #[arg(long)]
+    pub watch: bool,
+}
+
+/// Debug a native test through Debug Adapter Protocol over stdio
+#[derive(Args)]
+pub struct OptDebug {
+    /// Target files
+    pub files: Vec<PathBuf>,
+
+    /// Test name used if `launch` request doesn't specify it
+    #[arg(short = 't', long = "test")]
+    pub test: Option<String>,
+
+    /// Disable JIT compilation
+    #[arg(long)]
+    pub disable_jit: bool,
+}
+
+/// Simulate a module interactively
+#[derive(Args)]
+pub struct OptRepl {
+    /// Target files
+    pub files: Vec<PathBuf>,
+
+    /// Top module name
+    #[arg(long)]
+    pub top: String,
+
+    /// Disable JIT compilation
+    #[arg(long)]
+    pub disable_jit: bool,
+}
+
+/// Compile a module into a native simulation model
+#[derive(Args)]
+pub struct OptSim {
+    /// Target files
+    pub files: Vec<PathBuf>,
+
+    /// Top module name
+    #[arg(long)]
+    pub top: String,
+
+    /// Path of the native object. The C header and runtime are written to the same directory
+    #[arg(long, value_name = "PATH")]
+    pub emit_object: PathBuf,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -213,6 +471,53 @@ impl From<SimType> for veryl_metadata::SimType {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum XProp {
+    /// Take the false branch as SystemVerilog
+    Standard,
+    /// Take both branches and merge the results (T-merge)
+    Pessimistic,
+    /// Take the true branch
+    Optimistic,
+}
+
+impl From<XProp> for veryl_simulator::ir::XProp {
+    fn from(x: XProp) -> Self {
+        match x {
+            XProp::Standard => veryl_simulator::ir::XProp::Standard,
+            XProp::Pessimistic => veryl_simulator::ir::XProp::Pessimistic,
+            XProp::Optimistic => veryl_simulator::ir::XProp::Optimistic,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum RegisterInit {
+    /// Initial value of the declaration
+    Declared,
+    /// All bits are X
+    X,
+    /// All bits are 0
+    Zero,
+    /// All bits are 1
+    One,
+    /// Pseudo-random bits
+    Random,
+}
+
+impl RegisterInit {
+    /// Convert to the simulator setting; `seed` is used by `Random`
+    pub fn to_config(self, seed: u64) -> veryl_simulator::ir::RegisterInit {
+        match self {
+            RegisterInit::Declared => veryl_simulator::ir::RegisterInit::Declared,
+            RegisterInit::X => veryl_simulator::ir::RegisterInit::X,
+            RegisterInit::Zero => veryl_simulator::ir::RegisterInit::Zero,
+            RegisterInit::One => veryl_simulator::ir::RegisterInit::One,
+            RegisterInit::Random => veryl_simulator::ir::RegisterInit::Random(seed),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Default, Debug, ValueEnum)]
 pub enum BumpKind {
     /// Increment majoir version
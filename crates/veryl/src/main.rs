@@ -104,9 +104,14 @@ fn main() -> Result<ExitCode> {
         Commands::Publish(x) => cmd_publish::CmdPublish::new(x).exec(&mut metadata)?,
         Commands::Migrate(x) => cmd_migrate::CmdMigrate::new(x).exec(&mut metadata, opt.quiet)?,
         Commands::Doc(x) => cmd_doc::CmdDoc::new(x).exec(&mut metadata)?,
+        Commands::Regmap(x) => cmd_regmap::CmdRegmap::new(x).exec(&metadata, opt.quiet)?,
+        Commands::Ipxact(x) => cmd_ipxact::CmdIpxact::new(x).exec(&mut metadata)?,
         Commands::Metadata(x) => cmd_metadata::CmdMetadata::new(x).exec(&metadata)?,
         Commands::Dump(x) => cmd_dump::CmdDump::new(x).exec(&mut metadata)?,
         Commands::Test(x) => cmd_test::CmdTest::new(x).exec(&mut metadata)?,
+        Commands::Debug(x) => cmd_debug::CmdDebug::new(x).exec(&mut metadata)?,
+        Commands::Repl(x) => cmd_repl::CmdRepl::new(x).exec(&mut metadata)?,
+        Commands::Sim(x) => cmd_sim::CmdSim::new(x).exec(&mut metadata)?,
     };
 
     if let Some(dot_build_lock) = dot_build_lock {
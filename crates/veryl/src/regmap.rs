@@ -0,0 +1,30 @@
+mod c_header;
+mod description;
+mod rtl;
+pub use c_header::*;
+pub use description::*;
+pub use rtl::*;
+
+/// Convert `UpperCamelCase` to `snake_case`
+pub fn snake_case(x: &str) -> String {
+    let mut ret = String::new();
+    let mut prev_lower = false;
+    for c in x.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                ret.push('_');
+            }
+            ret.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else {
+            ret.push(c);
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        }
+    }
+    ret
+}
+
+/// Convert `UpperCamelCase` to `SCREAMING_SNAKE_CASE`
+pub fn screaming_snake_case(x: &str) -> String {
+    snake_case(x).to_ascii_uppercase()
+}
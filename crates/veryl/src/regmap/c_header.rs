@@ -0,0 +1,57 @@
+use crate::regmap::{Regmap, screaming_snake_case};
+use std::fmt::Write;
+
+fn hex(value: u64, bytes: u32) -> String {
+    let suffix = if value > u32::MAX as u64 { "ull" } else { "u" };
+    format!("0x{value:0width$x}{suffix}", width = bytes as usize * 2)
+}
+
+fn comment(ret: &mut String, text: &Option<String>) {
+    if let Some(text) = text {
+        for line in text.lines() {
+            // `*/` in descriptions would close the comment
+            let line = line.replace("*/", "* /");
+            writeln!(ret, "/* {line} */").unwrap();
+        }
+    }
+}
+
+/// Generate C header which defines offsets, bit positions, masks and reset values of registers.
+/// `source` is the name of the description file which is put in the header comment.
+pub fn generate_c_header(regmap: &Regmap, source: &str) -> String {
+    let prefix = screaming_snake_case(&regmap.name);
+    let guard = format!("{prefix}_H");
+    let bytes = regmap.data_bytes();
+    let mut ret = String::new();
+
+    writeln!(
+        ret,
+        "/* This file is generated by `veryl regmap` from {source}. */"
+    )
+    .unwrap();
+    writeln!(ret, "/* Do not edit it manually. */").unwrap();
+    writeln!(ret).unwrap();
+    writeln!(ret, "#ifndef {guard}").unwrap();
+    writeln!(ret, "#define {guard}").unwrap();
+    writeln!(ret).unwrap();
+    comment(&mut ret, &regmap.description);
+    writeln!(ret, "#define {prefix}_DATA_WIDTH {}u", regmap.data_width).unwrap();
+
+    for register in &regmap.registers {
+        let reg = format!("{prefix}_{}", register.name.to_uppercase());
+        writeln!(ret).unwrap();
+        comment(&mut ret, &register.description);
+        writeln!(ret, "#define {reg}_OFFSET {}", hex(register.offset, 0)).unwrap();
+        writeln!(ret, "#define {reg}_RESET {}", hex(register.reset(), bytes)).unwrap();
+        for field in &register.fields {
+            let name = format!("{reg}_{}", field.name.to_uppercase());
+            writeln!(ret, "#define {name}_SHIFT {}u", field.lsb()).unwrap();
+            writeln!(ret, "#define {name}_MASK {}", hex(field.mask(), bytes)).unwrap();
+            writeln!(ret, "#define {name}_RESET {}", hex(field.reset, 0)).unwrap();
+        }
+    }
+
+    writeln!(ret).unwrap();
+    writeln!(ret, "#endif /* {guard} */").unwrap();
+    ret
+}
@@ -0,0 +1,330 @@
+use miette::{self, Diagnostic, IntoDiagnostic, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Diagnostic, Debug)]
+pub enum RegmapError {
+    #[diagnostic(code(RegmapError::InvalidBits), help("use \"msb:lsb\" or \"bit\""))]
+    #[error("field \"{field}\" has invalid bits \"{bits}\"")]
+    InvalidBits { field: String, bits: String },
+
+    #[diagnostic(code(RegmapError::OutOfRange), help(""))]
+    #[error("field \"{field}\" exceeds data width {width}")]
+    OutOfRange { field: String, width: u32 },
+
+    #[diagnostic(code(RegmapError::Overlap), help(""))]
+    #[error("field \"{field0}\" overlaps field \"{field1}\"")]
+    Overlap { field0: String, field1: String },
+
+    #[diagnostic(code(RegmapError::Misaligned), help(""))]
+    #[error("register \"{register}\" offset {offset:#x} is not aligned to {bytes} bytes")]
+    Misaligned {
+        register: String,
+        offset: u64,
+        bytes: u32,
+    },
+
+    #[diagnostic(code(RegmapError::DuplicatedOffset), help(""))]
+    #[error("register \"{register0}\" and \"{register1}\" have the same offset {offset:#x}")]
+    DuplicatedOffset {
+        register0: String,
+        register1: String,
+        offset: u64,
+    },
+
+    #[diagnostic(code(RegmapError::ResetOverflow), help(""))]
+    #[error("reset value {reset:#x} of field \"{field}\" exceeds its width {width}")]
+    ResetOverflow {
+        field: String,
+        reset: u64,
+        width: u32,
+    },
+
+    #[diagnostic(code(RegmapError::InvalidDataWidth), help("use 8, 16, 32 or 64"))]
+    #[error("data width {width} is not supported")]
+    InvalidDataWidth { width: u32 },
+
+    #[diagnostic(code(RegmapError::AddressOverflow), help("increase address_width"))]
+    #[error("register \"{register}\" offset {offset:#x} exceeds address width {width}")]
+    AddressOverflow {
+        register: String,
+        offset: u64,
+        width: u32,
+    },
+
+    #[diagnostic(code(RegmapError::InvalidAddressWidth), help(""))]
+    #[error("address width {width} is too narrow for data width {data_width}")]
+    InvalidAddressWidth { width: u32, data_width: u32 },
+
+    #[diagnostic(code(RegmapError::InvalidName), help("use alphanumeric and \"_\""))]
+    #[error("\"{name}\" is not a valid identifier")]
+    InvalidName { name: String },
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Bus {
+    #[default]
+    Axi4Lite,
+    Apb,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    /// Read/write by software, read by hardware
+    Rw,
+    /// Read only by software, driven by hardware
+    Ro,
+    /// Write only by software, read by hardware
+    Wo,
+    /// Set by hardware, cleared by writing 1 from software
+    W1c,
+    /// Set by writing 1 from software, cleared by hardware
+    W1s,
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            Access::Rw => "rw",
+            Access::Ro => "ro",
+            Access::Wo => "wo",
+            Access::W1c => "w1c",
+            Access::W1s => "w1s",
+        };
+        text.fmt(f)
+    }
+}
+
+impl Access {
+    pub fn is_readable(&self) -> bool {
+        !matches!(self, Access::Wo)
+    }
+
+    pub fn is_writable(&self) -> bool {
+        !matches!(self, Access::Ro)
+    }
+
+    /// Whether the field has storage in the register block
+    pub fn has_storage(&self) -> bool {
+        !matches!(self, Access::Ro)
+    }
+}
+
+/// Register map described by a TOML file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Regmap {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub bus: Bus,
+    #[serde(default = "default_address_width")]
+    pub address_width: u32,
+    #[serde(default = "default_data_width")]
+    pub data_width: u32,
+    #[serde(default)]
+    pub registers: Vec<Register>,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Register {
+    pub name: String,
+    pub offset: u64,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Field {
+    pub name: String,
+    pub bits: String,
+    pub access: Access,
+    #[serde(default)]
+    pub reset: u64,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+fn default_address_width() -> u32 {
+    32
+}
+
+fn default_data_width() -> u32 {
+    32
+}
+
+impl Field {
+    /// MSB and LSB of the field
+    pub fn range(&self) -> Result<(u32, u32), RegmapError> {
+        let err = || RegmapError::InvalidBits {
+            field: self.name.clone(),
+            bits: self.bits.clone(),
+        };
+        let (msb, lsb) = self
+            .bits
+            .split_once(':')
+            .unwrap_or((&self.bits, &self.bits));
+        let msb: u32 = msb.trim().parse().map_err(|_| err())?;
+        let lsb: u32 = lsb.trim().parse().map_err(|_| err())?;
+        if msb < lsb {
+            return Err(err());
+        }
+        Ok((msb, lsb))
+    }
+
+    pub fn msb(&self) -> u32 {
+        self.range().map(|x| x.0).unwrap_or_default()
+    }
+
+    pub fn lsb(&self) -> u32 {
+        self.range().map(|x| x.1).unwrap_or_default()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.msb() - self.lsb() + 1
+    }
+
+    pub fn mask(&self) -> u64 {
+        let mask = if self.width() >= 64 {
+            u64::MAX
+        } else {
+            (1u64 << self.width()) - 1
+        };
+        mask << self.lsb()
+    }
+}
+
+impl Register {
+    pub fn reset(&self) -> u64 {
+        self.fields
+            .iter()
+            .fold(0, |acc, x| acc | ((x.reset << x.lsb()) & x.mask()))
+    }
+}
+
+fn check_name(name: &str) -> Result<(), RegmapError> {
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|x| x.is_ascii_alphabetic() || x == '_')
+        && name.chars().all(|x| x.is_ascii_alphanumeric() || x == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(RegmapError::InvalidName {
+            name: name.to_string(),
+        })
+    }
+}
+
+impl Regmap {
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .into_diagnostic()
+            .wrap_err(format!("{}", path.to_string_lossy()))?;
+        let mut ret: Self = text
+            .parse()
+            .wrap_err(format!("{}", path.to_string_lossy()))?;
+        ret.path = path.to_path_buf();
+        Ok(ret)
+    }
+
+    pub fn data_bytes(&self) -> u32 {
+        self.data_width / 8
+    }
+
+    fn check(&self) -> Result<(), RegmapError> {
+        check_name(&self.name)?;
+        if ![8, 16, 32, 64].contains(&self.data_width) {
+            return Err(RegmapError::InvalidDataWidth {
+                width: self.data_width,
+            });
+        }
+        if self.address_width <= self.data_bytes().trailing_zeros() || self.address_width > 64 {
+            return Err(RegmapError::InvalidAddressWidth {
+                width: self.address_width,
+                data_width: self.data_width,
+            });
+        }
+
+        for (i, register) in self.registers.iter().enumerate() {
+            check_name(&register.name)?;
+            if register.offset % self.data_bytes() as u64 != 0 {
+                return Err(RegmapError::Misaligned {
+                    register: register.name.clone(),
+                    offset: register.offset,
+                    bytes: self.data_bytes(),
+                });
+            }
+            if self.address_width < 64 && register.offset >> self.address_width != 0 {
+                return Err(RegmapError::AddressOverflow {
+                    register: register.name.clone(),
+                    offset: register.offset,
+                    width: self.address_width,
+                });
+            }
+            if let Some(x) = self.registers[..i]
+                .iter()
+                .find(|x| x.offset == register.offset)
+            {
+                return Err(RegmapError::DuplicatedOffset {
+                    register0: x.name.clone(),
+                    register1: register.name.clone(),
+                    offset: register.offset,
+                });
+            }
+
+            for (j, field) in register.fields.iter().enumerate() {
+                check_name(&field.name)?;
+                let (msb, _) = field.range()?;
+                if msb >= self.data_width {
+                    return Err(RegmapError::OutOfRange {
+                        field: field.name.clone(),
+                        width: self.data_width,
+                    });
+                }
+                if field.width() < 64 && field.reset >> field.width() != 0 {
+                    return Err(RegmapError::ResetOverflow {
+                        field: field.name.clone(),
+                        reset: field.reset,
+                        width: field.width(),
+                    });
+                }
+                if let Some(x) = register.fields[..j]
+                    .iter()
+                    .find(|x| x.mask() & field.mask() != 0)
+                {
+                    return Err(RegmapError::Overlap {
+                        field0: x.name.clone(),
+                        field1: field.name.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Regmap {
+    type Err = miette::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ret: Regmap = toml::from_str(s).into_diagnostic()?;
+        ret.check()?;
+        Ok(ret)
+    }
+}
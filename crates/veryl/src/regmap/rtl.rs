@@ -0,0 +1,424 @@
+use crate::regmap::{Access, Bus, Field, Register, Regmap};
+use std::fmt::Write;
+
+const OKAY: &str = "$std::axi4_lite_config::resp_variants::OKAY";
+const DECERR: &str = "$std::axi4_lite_config::resp_variants::DECERR";
+
+fn logic(width: u32) -> String {
+    if width == 1 {
+        "logic".to_string()
+    } else {
+        format!("logic<{width}>")
+    }
+}
+
+fn literal(width: u32, value: u64) -> String {
+    format!("{width}'h{value:x}")
+}
+
+fn slice(field: &Field) -> String {
+    format!("[{}:{}]", field.msb(), field.lsb())
+}
+
+fn port_name(register: &Register, field: &Field) -> String {
+    let name = format!("{}_{}", register.name, field.name).to_lowercase();
+    match field.access {
+        Access::Ro => format!("i_{name}"),
+        _ => format!("o_{name}"),
+    }
+}
+
+fn select_name(register: &Register) -> String {
+    format!("wr_{}_sel", register.name.to_lowercase())
+}
+
+fn doc_comment(text: &Option<String>, indent: &str) -> String {
+    let mut ret = String::new();
+    if let Some(text) = text {
+        for line in text.lines() {
+            writeln!(ret, "{indent}/// {line}").unwrap();
+        }
+    }
+    ret
+}
+
+struct Generator<'a> {
+    regmap: &'a Regmap,
+    /// LSB of the word address in the byte address
+    word_lsb: u32,
+}
+
+impl Generator<'_> {
+    fn word_width(&self) -> u32 {
+        self.regmap.address_width - self.word_lsb
+    }
+
+    fn word(&self, addr: &str) -> String {
+        if self.word_lsb == 0 {
+            addr.to_string()
+        } else {
+            format!(
+                "{addr}[{}:{}]",
+                self.regmap.address_width - 1,
+                self.word_lsb
+            )
+        }
+    }
+
+    fn word_literal(&self, register: &Register) -> String {
+        literal(self.word_width(), register.offset >> self.word_lsb)
+    }
+
+    fn fields(&self) -> impl Iterator<Item = (&Register, &Field)> {
+        self.regmap
+            .registers
+            .iter()
+            .flat_map(|r| r.fields.iter().map(move |f| (r, f)))
+    }
+
+    fn bus_ports(&self, ret: &mut String) {
+        let regmap = self.regmap;
+        match regmap.bus {
+            Bus::Axi4Lite => {
+                writeln!(
+                    ret,
+                    "    bus: modport $std::axi4_lite_if::<{}BusPkg>::slave,",
+                    regmap.name
+                )
+                .unwrap();
+            }
+            Bus::Apb => {
+                let aw = logic(regmap.address_width);
+                let dw = logic(regmap.data_width);
+                let sw = logic(regmap.data_bytes());
+                writeln!(ret, "    i_psel: input logic,").unwrap();
+                writeln!(ret, "    i_penable: input logic,").unwrap();
+                writeln!(ret, "    i_pwrite: input logic,").unwrap();
+                writeln!(ret, "    i_paddr: input {aw},").unwrap();
+                writeln!(ret, "    i_pwdata: input {dw},").unwrap();
+                writeln!(ret, "    i_pstrb: input {sw},").unwrap();
+                writeln!(ret, "    o_prdata: output {dw},").unwrap();
+                writeln!(ret, "    o_pready: output logic,").unwrap();
+                writeln!(ret, "    o_pslverr: output logic,").unwrap();
+            }
+        }
+    }
+
+    fn field_ports(&self, ret: &mut String) {
+        for (register, field) in self.fields() {
+            let typ = logic(field.width());
+            let comment = format!("{}.{}", register.name, field.name);
+            let comment = match &field.description {
+                Some(x) => Some(format!("{comment}: {}", x.lines().next().unwrap_or(""))),
+                None => Some(comment),
+            };
+            write!(ret, "{}", doc_comment(&comment, "    ")).unwrap();
+            match field.access {
+                Access::Rw | Access::Wo | Access::Ro => {
+                    let direction = if field.access == Access::Ro {
+                        "input"
+                    } else {
+                        "output"
+                    };
+                    let name = port_name(register, field);
+                    writeln!(ret, "    {name}: {direction} {typ},").unwrap();
+                }
+                Access::W1c => {
+                    let name = port_name(register, field);
+                    writeln!(ret, "    i_{}_set: input {typ},", &name[2..]).unwrap();
+                    writeln!(ret, "    {name}: output {typ},").unwrap();
+                }
+                Access::W1s => {
+                    let name = port_name(register, field);
+                    writeln!(ret, "    i_{}_clear: input {typ},", &name[2..]).unwrap();
+                    writeln!(ret, "    {name}: output {typ},").unwrap();
+                }
+            }
+        }
+    }
+
+    fn write_logic(&self, ret: &mut String) {
+        let regmap = self.regmap;
+        let dw = regmap.data_width;
+        let ww = self.word_width();
+
+        let (wr_en, wr_addr, wdata, wstrb) = match regmap.bus {
+            Bus::Axi4Lite => (
+                "bus.awvalid && bus.wvalid && !bus.bvalid",
+                "bus.awaddr",
+                "bus.wdata",
+                "bus.wstrb",
+            ),
+            Bus::Apb => (
+                "i_psel && i_penable && i_pwrite",
+                "i_paddr",
+                "i_pwdata",
+                "i_pstrb",
+            ),
+        };
+
+        let mask: Vec<_> = (0..regmap.data_bytes())
+            .rev()
+            .map(|i| format!("{wstrb}[{i}] repeat 8"))
+            .collect();
+        let writable: Vec<_> = regmap
+            .registers
+            .iter()
+            .filter(|x| x.fields.iter().any(|x| x.access.is_writable()))
+            .collect();
+        let hit: Vec<_> = regmap
+            .registers
+            .iter()
+            .map(|x| format!("wr_word == {}", self.word_literal(x)))
+            .collect();
+        let hit = if hit.is_empty() {
+            "1'b0".to_string()
+        } else {
+            hit.join(" || ")
+        };
+
+        if regmap.bus == Bus::Axi4Lite || !writable.is_empty() {
+            writeln!(ret, "    let wr_en: logic = {wr_en};").unwrap();
+        }
+        writeln!(
+            ret,
+            "    let wr_word: {} = {};",
+            logic(ww),
+            self.word(wr_addr)
+        )
+        .unwrap();
+        writeln!(ret, "    let wr_hit: logic = {hit};").unwrap();
+        if !writable.is_empty() {
+            writeln!(
+                ret,
+                "    let wr_mask: {} = {{{}}};",
+                logic(dw),
+                mask.join(", ")
+            )
+            .unwrap();
+            writeln!(ret, "    let wr_bits: {} = {wdata} & wr_mask;", logic(dw)).unwrap();
+        }
+        for register in &writable {
+            writeln!(
+                ret,
+                "    let {}: logic = wr_en && wr_word == {};",
+                select_name(register),
+                self.word_literal(register)
+            )
+            .unwrap();
+        }
+        writeln!(ret).unwrap();
+
+        if regmap.bus == Bus::Axi4Lite {
+            writeln!(ret, "    assign bus.awready = wr_en;").unwrap();
+            writeln!(ret, "    assign bus.wready = wr_en;").unwrap();
+            writeln!(ret).unwrap();
+            writeln!(ret, "    always_ff {{").unwrap();
+            writeln!(ret, "        if_reset {{").unwrap();
+            writeln!(ret, "            bus.bvalid = 1'b0;").unwrap();
+            writeln!(ret, "            bus.bresp = {OKAY};").unwrap();
+            writeln!(ret, "            bus.bid = 0;").unwrap();
+            writeln!(ret, "        }} else if wr_en {{").unwrap();
+            writeln!(ret, "            bus.bvalid = 1'b1;").unwrap();
+            writeln!(
+                ret,
+                "            bus.bresp = if wr_hit ? {OKAY} : {DECERR};"
+            )
+            .unwrap();
+            writeln!(ret, "            bus.bid = bus.awid;").unwrap();
+            writeln!(ret, "        }} else if bus.bready {{").unwrap();
+            writeln!(ret, "            bus.bvalid = 1'b0;").unwrap();
+            writeln!(ret, "        }}").unwrap();
+            writeln!(ret, "    }}").unwrap();
+            writeln!(ret).unwrap();
+        }
+
+        for (register, field) in self.fields() {
+            if !field.access.has_storage() {
+                continue;
+            }
+            let name = port_name(register, field);
+            let sel = select_name(register);
+            let slice = slice(field);
+            writeln!(ret, "    always_ff {{").unwrap();
+            writeln!(ret, "        if_reset {{").unwrap();
+            writeln!(
+                ret,
+                "            {name} = {};",
+                literal(field.width(), field.reset)
+            )
+            .unwrap();
+            match field.access {
+                Access::Rw | Access::Wo => {
+                    writeln!(ret, "        }} else if {sel} {{").unwrap();
+                    writeln!(
+                        ret,
+                        "            {name} = ({name} & ~wr_mask{slice}) | wr_bits{slice};"
+                    )
+                    .unwrap();
+                }
+                Access::W1c => {
+                    let set = format!("i_{}_set", &name[2..]);
+                    writeln!(ret, "        }} else if {sel} {{").unwrap();
+                    writeln!(
+                        ret,
+                        "            {name} = ({name} & ~wr_bits{slice}) | {set};"
+                    )
+                    .unwrap();
+                    writeln!(ret, "        }} else {{").unwrap();
+                    writeln!(ret, "            {name} = {name} | {set};").unwrap();
+                }
+                Access::W1s => {
+                    let clear = format!("i_{}_clear", &name[2..]);
+                    writeln!(ret, "        }} else if {sel} {{").unwrap();
+                    writeln!(
+                        ret,
+                        "            {name} = ({name} | wr_bits{slice}) & ~{clear};"
+                    )
+                    .unwrap();
+                    writeln!(ret, "        }} else {{").unwrap();
+                    writeln!(ret, "            {name} = {name} & ~{clear};").unwrap();
+                }
+                Access::Ro => unreachable!(),
+            }
+            writeln!(ret, "        }}").unwrap();
+            writeln!(ret, "    }}").unwrap();
+            writeln!(ret).unwrap();
+        }
+    }
+
+    fn read_logic(&self, ret: &mut String) {
+        let regmap = self.regmap;
+        let dw = regmap.data_width;
+        let ww = self.word_width();
+
+        let rd_addr = match regmap.bus {
+            Bus::Axi4Lite => "bus.araddr",
+            Bus::Apb => "i_paddr",
+        };
+
+        writeln!(
+            ret,
+            "    let rd_word: {} = {};",
+            logic(ww),
+            self.word(rd_addr)
+        )
+        .unwrap();
+        writeln!(ret, "    var rd_data: {};", logic(dw)).unwrap();
+        writeln!(ret, "    var rd_hit: logic;").unwrap();
+        writeln!(ret).unwrap();
+        writeln!(ret, "    always_comb {{").unwrap();
+        writeln!(ret, "        rd_data = 0;").unwrap();
+        writeln!(ret, "        rd_hit = 1'b1;").unwrap();
+        writeln!(ret, "        case rd_word {{").unwrap();
+        for register in &regmap.registers {
+            let readable: Vec<_> = register
+                .fields
+                .iter()
+                .filter(|x| x.access.is_readable())
+                .collect();
+            let word = self.word_literal(register);
+            if readable.is_empty() {
+                writeln!(ret, "            {word}: rd_data = 0;").unwrap();
+                continue;
+            }
+            writeln!(ret, "            {word}: {{").unwrap();
+            for field in readable {
+                let name = port_name(register, field);
+                writeln!(ret, "                rd_data{} = {name};", slice(field)).unwrap();
+            }
+            writeln!(ret, "            }}").unwrap();
+        }
+        writeln!(ret, "            default: rd_hit = 1'b0;").unwrap();
+        writeln!(ret, "        }}").unwrap();
+        writeln!(ret, "    }}").unwrap();
+        writeln!(ret).unwrap();
+
+        match regmap.bus {
+            Bus::Axi4Lite => {
+                writeln!(ret, "    let rd_en: logic = bus.arvalid && !bus.rvalid;").unwrap();
+                writeln!(ret).unwrap();
+                writeln!(ret, "    assign bus.arready = !bus.rvalid;").unwrap();
+                writeln!(ret).unwrap();
+                writeln!(ret, "    always_ff {{").unwrap();
+                writeln!(ret, "        if_reset {{").unwrap();
+                writeln!(ret, "            bus.rvalid = 1'b0;").unwrap();
+                writeln!(ret, "            bus.rdata = 0;").unwrap();
+                writeln!(ret, "            bus.rresp = {OKAY};").unwrap();
+                writeln!(ret, "            bus.rid = 0;").unwrap();
+                writeln!(ret, "        }} else if rd_en {{").unwrap();
+                writeln!(ret, "            bus.rvalid = 1'b1;").unwrap();
+                writeln!(ret, "            bus.rdata = rd_data;").unwrap();
+                writeln!(
+                    ret,
+                    "            bus.rresp = if rd_hit ? {OKAY} : {DECERR};"
+                )
+                .unwrap();
+                writeln!(ret, "            bus.rid = bus.arid;").unwrap();
+                writeln!(ret, "        }} else if bus.rready {{").unwrap();
+                writeln!(ret, "            bus.rvalid = 1'b0;").unwrap();
+                writeln!(ret, "        }}").unwrap();
+                writeln!(ret, "    }}").unwrap();
+            }
+            Bus::Apb => {
+                writeln!(ret, "    assign o_prdata = rd_data;").unwrap();
+                writeln!(ret, "    assign o_pready = 1'b1;").unwrap();
+                writeln!(
+                    ret,
+                    "    assign o_pslverr = i_psel && i_penable && !(if i_pwrite ? wr_hit : rd_hit);"
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn generate(&self, source: &str) -> String {
+        let regmap = self.regmap;
+        let mut ret = String::new();
+
+        writeln!(
+            ret,
+            "// This file is generated by `veryl regmap` from {source}."
+        )
+        .unwrap();
+        writeln!(ret, "// Do not edit it manually.").unwrap();
+        writeln!(ret).unwrap();
+
+        if regmap.bus == Bus::Axi4Lite {
+            writeln!(ret, "/// Bus package of {}", regmap.name).unwrap();
+            writeln!(
+                ret,
+                "pub alias package {}BusPkg = $std::axi4_lite_pkg::<{}, {}, 1>;",
+                regmap.name,
+                regmap.address_width,
+                regmap.data_bytes()
+            )
+            .unwrap();
+            writeln!(ret).unwrap();
+        }
+
+        write!(ret, "{}", doc_comment(&regmap.description, "")).unwrap();
+        writeln!(ret, "pub module {} (", regmap.name).unwrap();
+        writeln!(ret, "    i_clk: input clock,").unwrap();
+        writeln!(ret, "    i_rst: input reset,").unwrap();
+        self.bus_ports(&mut ret);
+        self.field_ports(&mut ret);
+        writeln!(ret, ") {{").unwrap();
+        self.write_logic(&mut ret);
+        self.read_logic(&mut ret);
+        writeln!(ret, "}}").unwrap();
+
+        ret
+    }
+}
+
+/// Generate Veryl source code of the register block.
+/// `source` is the name of the description file which is put in the header comment.
+/// The returned code is not formatted.
+pub fn generate_veryl(regmap: &Regmap, source: &str) -> String {
+    let generator = Generator {
+        regmap,
+        word_lsb: regmap.data_bytes().trailing_zeros(),
+    };
+    generator.generate(source)
+}
@@ -0,0 +1,313 @@
+use quick_xml::escape::escape;
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,
+    Json,
+}
+
+/// Output of `veryl test --report FORMAT=PATH`
+#[derive(Clone, Debug)]
+pub struct ReportTarget {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+impl FromStr for ReportTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((format, path)) = s.split_once('=') else {
+            return Err("expected FORMAT=PATH (e.g. junit=report.xml)".to_string());
+        };
+        let format = match format {
+            "junit" => ReportFormat::Junit,
+            "json" => ReportFormat::Json,
+            _ => return Err(format!("unknown report format '{format}' (junit or json)")),
+        };
+        if path.is_empty() {
+            return Err("report path is empty".to_string());
+        }
+        Ok(Self {
+            format,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestKind {
+    Native,
+    Doc,
+    External,
+}
+
+impl TestKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TestKind::Native => "native",
+            TestKind::Doc => "doc",
+            TestKind::External => "external",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TestCase {
+    pub name: String,
+    pub kind: TestKind,
+    pub status: TestStatus,
+    /// Duration in seconds
+    pub duration: f64,
+    pub message: Option<String>,
+    pub wave: Option<PathBuf>,
+    pub simulator: String,
+}
+
+impl TestCase {
+    pub fn new(name: &str, kind: TestKind, simulator: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            status: TestStatus::Ignored,
+            duration: 0.0,
+            message: None,
+            wave: None,
+            simulator: simulator.to_string(),
+        }
+    }
+
+    pub fn passed(mut self, duration: Duration, wave: Option<PathBuf>) -> Self {
+        self.status = TestStatus::Passed;
+        self.duration = duration.as_secs_f64();
+        self.wave = wave;
+        self
+    }
+
+    pub fn failed(mut self, duration: Duration, wave: Option<PathBuf>, message: String) -> Self {
+        self.status = TestStatus::Failed;
+        self.duration = duration.as_secs_f64();
+        self.wave = wave;
+        self.message = Some(message);
+        self
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TestReport {
+    pub project: String,
+    pub tests: Vec<TestCase>,
+}
+
+impl TestReport {
+    pub fn new(project: &str) -> Self {
+        Self {
+            project: project.to_string(),
+            tests: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, test: TestCase) {
+        self.tests.push(test);
+    }
+
+    pub fn write(&self, target: &ReportTarget) -> std::io::Result<()> {
+        let text = match target.format {
+            ReportFormat::Junit => self.to_junit(),
+            ReportFormat::Json => self.to_json(),
+        };
+        if let Some(parent) = target.path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&target.path, text)
+    }
+
+    fn to_json(&self) -> String {
+        let mut ret = serde_json::to_string_pretty(self).unwrap();
+        ret.push('\n');
+        ret
+    }
+
+    fn to_junit(&self) -> String {
+        let count = |status| self.tests.iter().filter(|x| x.status == status).count();
+        let tests = self.tests.len().to_string();
+        let failures = count(TestStatus::Failed).to_string();
+        let skipped = count(TestStatus::Ignored).to_string();
+        let time = format!("{:.6}", self.tests.iter().map(|x| x.duration).sum::<f64>());
+        let attrs = [
+            ("name", self.project.as_str()),
+            ("tests", &tests),
+            ("failures", &failures),
+            ("errors", "0"),
+            ("skipped", &skipped),
+            ("time", &time),
+        ];
+
+        let mut ret = String::new();
+        writeln!(ret, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+        writeln!(ret, "<testsuites{}>", xml_attrs(&attrs)).unwrap();
+        writeln!(ret, "  <testsuite{}>", xml_attrs(&attrs)).unwrap();
+
+        for test in &self.tests {
+            let time = format!("{:.6}", test.duration);
+            let attrs = [
+                ("name", test.name.as_str()),
+                ("classname", test.kind.as_str()),
+                ("time", &time),
+            ];
+            writeln!(ret, "    <testcase{}>", xml_attrs(&attrs)).unwrap();
+
+            let wave = test.wave.as_ref().map(|x| x.to_string_lossy());
+            writeln!(ret, "      <properties>").unwrap();
+            let mut properties = vec![("simulator", test.simulator.as_str())];
+            if let Some(wave) = &wave {
+                properties.push(("wave", wave));
+            }
+            for (name, value) in properties {
+                let attrs = [("name", name), ("value", value)];
+                writeln!(ret, "        <property{}/>", xml_attrs(&attrs)).unwrap();
+            }
+            writeln!(ret, "      </properties>").unwrap();
+
+            match test.status {
+                TestStatus::Passed => (),
+                TestStatus::Failed => {
+                    let message = test.message.as_deref().unwrap_or_default();
+                    let attrs = [("message", message)];
+                    writeln!(
+                        ret,
+                        "      <failure{}>{}</failure>",
+                        xml_attrs(&attrs),
+                        escape(message)
+                    )
+                    .unwrap();
+                }
+                TestStatus::Ignored => {
+                    writeln!(ret, "      <skipped/>").unwrap();
+                }
+            }
+            writeln!(ret, "    </testcase>").unwrap();
+        }
+
+        writeln!(ret, "  </testsuite>").unwrap();
+        writeln!(ret, "</testsuites>").unwrap();
+        ret
+    }
+}
+
+fn xml_attrs(attrs: &[(&str, &str)]) -> String {
+    let mut ret = String::new();
+    for (key, value) in attrs {
+        write!(ret, " {key}=\"{}\"", escape(*value)).unwrap();
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> TestReport {
+        let mut ret = TestReport::new("prj");
+        ret.push(
+            TestCase::new("test_a", TestKind::Native, "native")
+                .passed(Duration::from_millis(500), Some(PathBuf::from("a.vcd"))),
+        );
+        ret.push(
+            TestCase::new("test_b", TestKind::External, "verilator").failed(
+                Duration::from_millis(250),
+                None,
+                "x < 1 && \"y\" > 2".to_string(),
+            ),
+        );
+        ret.push(TestCase::new("test_c", TestKind::Doc, "native"));
+        ret
+    }
+
+    #[test]
+    fn report_target() {
+        let target: ReportTarget = "junit=out/report.xml".parse().unwrap();
+        assert_eq!(target.format, ReportFormat::Junit);
+        assert_eq!(target.path, PathBuf::from("out/report.xml"));
+
+        let target: ReportTarget = "json=a=b.json".parse().unwrap();
+        assert_eq!(target.format, ReportFormat::Json);
+        assert_eq!(target.path, PathBuf::from("a=b.json"));
+
+        assert!("report.xml".parse::<ReportTarget>().is_err());
+        assert!("xml=report.xml".parse::<ReportTarget>().is_err());
+        assert!("junit=".parse::<ReportTarget>().is_err());
+    }
+
+    #[test]
+    fn junit() {
+        let junit = report().to_junit();
+
+        let suite = r#"name="prj" tests="3" failures="1" errors="0" skipped="1" time="0.750000""#;
+        assert!(junit.contains(&format!("<testsuites {suite}>")));
+        assert!(junit.contains(&format!("<testsuite {suite}>")));
+        assert!(junit.contains(r#"<testcase name="test_a" classname="native" time="0.500000">"#));
+        assert!(junit.contains(r#"<property name="wave" value="a.vcd"/>"#));
+        assert!(junit.contains(
+            "<failure message=\"x &lt; 1 &amp;&amp; &quot;y&quot; &gt; 2\">\
+             x &lt; 1 &amp;&amp; &quot;y&quot; &gt; 2</failure>"
+        ));
+        assert!(junit.contains("<skipped/>"));
+        assert_eq!(junit.matches("<testcase ").count(), 3);
+        assert_eq!(junit.matches("</testcase>").count(), 3);
+    }
+
+    #[test]
+    fn json() {
+        let json: serde_json::Value = serde_json::from_str(&report().to_json()).unwrap();
+        let expected = serde_json::json!({
+            "project": "prj",
+            "tests": [
+                {
+                    "name": "test_a",
+                    "kind": "native",
+                    "status": "passed",
+                    "duration": 0.5,
+                    "message": null,
+                    "wave": "a.vcd",
+                    "simulator": "native",
+                },
+                {
+                    "name": "test_b",
+                    "kind": "external",
+                    "status": "failed",
+                    "duration": 0.25,
+                    "message": "x < 1 && \"y\" > 2",
+                    "wave": null,
+                    "simulator": "verilator",
+                },
+                {
+                    "name": "test_c",
+                    "kind": "doc",
+                    "status": "ignored",
+                    "duration": 0.0,
+                    "message": null,
+                    "wave": null,
+                    "simulator": "native",
+                },
+            ],
+        });
+        assert_eq!(json, expected);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::StopWatch;
+use log::{error, info};
+use miette::{IntoDiagnostic, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, channel};
+use std::time::Duration;
+use veryl_analyzer::type_dag;
+use veryl_metadata::{LockSource, Metadata};
+use veryl_parser::resource_table::{self, PathId};
+
+const DEBOUNCE_TIME: Duration = Duration::from_millis(200);
+
+pub struct Watcher {
+    rx: Receiver<notify::Result<notify::Event>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl Watcher {
+    pub fn new(metadata: &Metadata) -> Result<Self> {
+        let (tx, rx) = channel();
+        let mut watcher = notify::recommended_watcher(tx).into_diagnostic()?;
+
+        for path in Self::watch_paths(metadata) {
+            if path.exists() {
+                info!("Watching dir ({})", path.to_string_lossy());
+                watcher
+                    .watch(&path, RecursiveMode::Recursive)
+                    .into_diagnostic()?;
+            }
+        }
+
+        Ok(Self {
+            rx,
+            _watcher: watcher,
+        })
+    }
+
+    fn watch_paths(metadata: &Metadata) -> Vec<PathBuf> {
+        let base = metadata.project_path();
+        let mut ret: Vec<_> = metadata
+            .build
+            .sources
+            .iter()
+            .map(|x| base.join(x))
+            .collect();
+
+        // dependencies which can be modified locally
+        for locks in metadata.lockfile.lock_table.values() {
+            for lock in locks {
+                if let LockSource::Path(x) = &lock.source {
+                    ret.push(base.join(x));
+                }
+            }
+        }
+
+        ret
+    }
+
+    /// Block until `.veryl` files are modified, and return the modified files
+    pub fn wait(&self) -> HashSet<PathBuf> {
+        let mut ret = HashSet::new();
+
+        while ret.is_empty() {
+            let Ok(event) = self.rx.recv() else {
+                return ret;
+            };
+            push_event(&mut ret, event);
+
+            // gather succeeding events which are caused by the same modification
+            while let Ok(event) = self.rx.recv_timeout(DEBOUNCE_TIME) {
+                push_event(&mut ret, event);
+            }
+        }
+
+        ret
+    }
+}
+
+/// Add `.veryl` files modified by `event` to `paths`
+fn push_event(paths: &mut HashSet<PathBuf>, event: notify::Result<notify::Event>) {
+    let Ok(event) = event else {
+        return;
+    };
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+    for path in event.paths {
+        if path.extension().is_some_and(|x| x == "veryl") {
+            paths.insert(path);
+        }
+    }
+}
+
+/// Modified files and files depending on them
+pub fn affected_files(changed: &HashSet<PathBuf>) -> HashSet<PathId> {
+    let dependent_files = type_dag::dependent_files();
+
+    let mut ret = HashSet::new();
+    for path in changed {
+        let path = resource_table::insert_path(path);
+        ret.insert(path);
+        if let Some(dependents) = dependent_files.get(&path) {
+            ret.extend(dependents.iter().copied());
+        }
+    }
+    ret
+}
+
+/// Execute `f` each time when source files are modified.
+/// `f` receives `None` at the first execution, and modified files after that.
+///
+/// Each execution runs on a new thread because analyzer tables are thread local.
+pub fn watch<F>(metadata: &mut Metadata, f: F) -> Result<bool>
+where
+    F: Fn(&mut Metadata, Option<&HashSet<PathBuf>>) -> Result<bool> + Sync,
+{
+    let watcher = Watcher::new(metadata)?;
+    let mut changed: Option<HashSet<PathBuf>> = None;
+
+    loop {
+        let mut stopwatch = StopWatch::new();
+
+        let ret = std::thread::scope(|s| {
+            s.spawn(|| f(metadata, changed.as_ref()))
+                .join()
+                .unwrap_or_else(|_| Err(miette::miette!("panic occurred")))
+        });
+
+        let elapsed = stopwatch.lap();
+        let files = changed.as_ref().map(|x| x.len());
+        let files = files
+            .map(|x| format!("{x} changed files, "))
+            .unwrap_or_default();
+        match ret {
+            Ok(true) => info!("Finished cycle ({files}{elapsed} milliseconds)"),
+            Ok(false) => error!("Failed cycle ({files}{elapsed} milliseconds)"),
+            Err(x) => {
+                error!("{x:?}");
+                error!("Failed cycle ({files}{elapsed} milliseconds)");
+            }
+        }
+
+        metadata.build_info.veryl_version = Some(veryl_metadata::VERYL_VERSION.to_string());
+        metadata.save_build_info()?;
+
+        info!("Waiting for changes");
+        let paths = watcher.wait();
+        if paths.is_empty() {
+            // watcher is disconnected
+            return Ok(true);
+        }
+        for path in &paths {
+            info!("Detected change ({})", path.to_string_lossy());
+        }
+        changed = Some(paths);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind};
+    use veryl_analyzer::{Analyzer, Context, attribute_table, symbol_table};
+    use veryl_parser::Parser;
+
+    fn event(kind: EventKind, paths: &[&str]) -> notify::Result<notify::Event> {
+        let mut ret = notify::Event::new(kind);
+        for path in paths {
+            ret = ret.add_path(PathBuf::from(path));
+        }
+        Ok(ret)
+    }
+
+    #[test]
+    fn push_veryl_files() {
+        let mut paths = HashSet::new();
+
+        push_event(
+            &mut paths,
+            event(
+                EventKind::Modify(ModifyKind::Any),
+                &["src/a.veryl", "src/a.sv", "src/b.veryl.swp", "src/veryl"],
+            ),
+        );
+        push_event(
+            &mut paths,
+            event(EventKind::Create(CreateKind::File), &["src/b.veryl"]),
+        );
+        push_event(
+            &mut paths,
+            event(EventKind::Access(AccessKind::Any), &["src/c.veryl"]),
+        );
+        push_event(&mut paths, Err(notify::Error::generic("error")));
+
+        let expected: HashSet<_> = ["src/a.veryl", "src/b.veryl"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn affected_dependents() {
+        symbol_table::clear();
+        attribute_table::clear();
+        type_dag::clear();
+
+        let sources = [
+            ("package_a.veryl", "package PackageA { const A: u32 = 1; }"),
+            (
+                "module_a.veryl",
+                "module ModuleA { let _a: logic<32> = PackageA::A; }",
+            ),
+            ("module_b.veryl", "module ModuleB { inst u: ModuleA; }"),
+            ("module_c.veryl", "module ModuleC {}"),
+        ];
+
+        let metadata = Metadata::create_default("prj").unwrap();
+        let parsers: Vec<_> = sources
+            .iter()
+            .map(|(path, code)| Parser::parse(code, path).unwrap())
+            .collect();
+        let analyzer = Analyzer::new(&metadata);
+        let mut context = Context::default();
+        for parser in &parsers {
+            analyzer.analyze_pass1("prj", &parser.veryl);
+        }
+        Analyzer::analyze_post_pass1();
+        for parser in &parsers {
+            analyzer.analyze_pass2("prj", &parser.veryl, &mut context, None);
+        }
+        Analyzer::analyze_post_pass2();
+
+        let affected = |changed: &[&str]| -> HashSet<String> {
+            let changed = changed.iter().map(PathBuf::from).collect();
+            affected_files(&changed)
+                .into_iter()
+                .map(|x| x.to_string())
+                .collect()
+        };
+        let expected =
+            |paths: &[&str]| -> HashSet<String> { paths.iter().map(|x| x.to_string()).collect() };
+
+        assert_eq!(
+            affected(&["package_a.veryl"]),
+            expected(&["package_a.veryl", "module_a.veryl", "module_b.veryl"])
+        );
+        assert_eq!(
+            affected(&["module_a.veryl"]),
+            expected(&["module_a.veryl", "module_b.veryl"])
+        );
+        assert_eq!(affected(&["module_c.veryl"]), expected(&["module_c.veryl"]));
+    }
+}